@@ -0,0 +1,24 @@
+-- ログインセッション: リフレッシュトークンの family_id はセッションIDと一致する
+CREATE TABLE sessions (
+    id TEXT PRIMARY KEY NOT NULL,
+    user_id TEXT NOT NULL,
+    user_agent TEXT,
+    ip_address TEXT,
+    created_at TEXT NOT NULL,
+    last_seen_at TEXT NOT NULL,
+    revoked_at TEXT,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_sessions_user_id ON sessions(user_id);
+
+-- 既存のリフレッシュトークンのファミリーをセッションとして取り込む
+INSERT INTO sessions (id, user_id, created_at, last_seen_at, revoked_at)
+SELECT
+    family_id,
+    user_id,
+    MIN(created_at),
+    MAX(created_at),
+    CASE WHEN COUNT(*) = COUNT(revoked_at) THEN MAX(revoked_at) END
+FROM refresh_tokens
+GROUP BY family_id, user_id;
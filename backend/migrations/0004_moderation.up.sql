@@ -0,0 +1,32 @@
+-- ロールと利用停止（既存のユーザーは一般ユーザー）
+ALTER TABLE users ADD COLUMN role TEXT NOT NULL DEFAULT 'user';
+ALTER TABLE users ADD COLUMN suspended_at TEXT;
+
+-- ユーザーからの通報
+CREATE TABLE reports (
+    id TEXT PRIMARY KEY NOT NULL,
+    reporter_id TEXT NOT NULL,
+    target_type TEXT NOT NULL,
+    target_id TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    resolved_at TEXT,
+    resolved_by TEXT,
+    FOREIGN KEY (reporter_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (resolved_by) REFERENCES users(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_reports_resolved_at ON reports(resolved_at, created_at);
+
+-- 管理者・モデレーターの操作の監査ログ（対象が削除されても残す）
+CREATE TABLE moderation_actions (
+    id TEXT PRIMARY KEY NOT NULL,
+    actor_id TEXT NOT NULL,
+    action TEXT NOT NULL,
+    target_id TEXT NOT NULL,
+    details TEXT,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (actor_id) REFERENCES users(id)
+);
+
+CREATE INDEX idx_moderation_actions_created_at ON moderation_actions(created_at);
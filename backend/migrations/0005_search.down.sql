@@ -0,0 +1,13 @@
+DROP TRIGGER IF EXISTS users_fts_delete;
+DROP TRIGGER IF EXISTS users_fts_update;
+DROP TRIGGER IF EXISTS users_fts_insert;
+DROP TRIGGER IF EXISTS comments_fts_delete;
+DROP TRIGGER IF EXISTS comments_fts_update;
+DROP TRIGGER IF EXISTS comments_fts_insert;
+DROP TRIGGER IF EXISTS tweets_fts_delete;
+DROP TRIGGER IF EXISTS tweets_fts_update;
+DROP TRIGGER IF EXISTS tweets_fts_insert;
+DROP TABLE IF EXISTS users_fts;
+DROP TABLE IF EXISTS comments_fts;
+DROP TABLE IF EXISTS tweets_fts;
+DROP TABLE IF EXISTS search_ids;
@@ -0,0 +1,63 @@
+-- 全文検索の索引（trigram トークナイザで空白のない日本語も部分一致で検索できる）
+-- 外部コンテンツにせず本文の写しを持つ。索引の rowid は search_ids で ID と対応付け、
+-- INTEGER PRIMARY KEY なので VACUUM でも変わらず、トリガーからは rowid で引ける
+CREATE TABLE search_ids (
+    rowid INTEGER PRIMARY KEY,
+    id TEXT NOT NULL UNIQUE
+);
+CREATE VIRTUAL TABLE tweets_fts USING fts5(content, tokenize = 'trigram');
+CREATE VIRTUAL TABLE comments_fts USING fts5(content, tokenize = 'trigram');
+CREATE VIRTUAL TABLE users_fts USING fts5(username, tokenize = 'trigram');
+
+-- 本体のテーブルとトリガーで同期する
+CREATE TRIGGER tweets_fts_insert AFTER INSERT ON tweets BEGIN
+    INSERT INTO search_ids (id) VALUES (new.id);
+    INSERT INTO tweets_fts (rowid, content)
+    VALUES ((SELECT rowid FROM search_ids WHERE id = new.id), new.content);
+END;
+CREATE TRIGGER tweets_fts_update AFTER UPDATE OF content ON tweets BEGIN
+    UPDATE tweets_fts SET content = new.content
+    WHERE rowid = (SELECT rowid FROM search_ids WHERE id = old.id);
+END;
+CREATE TRIGGER tweets_fts_delete AFTER DELETE ON tweets BEGIN
+    DELETE FROM tweets_fts WHERE rowid = (SELECT rowid FROM search_ids WHERE id = old.id);
+    DELETE FROM search_ids WHERE id = old.id;
+END;
+
+CREATE TRIGGER comments_fts_insert AFTER INSERT ON comments BEGIN
+    INSERT INTO search_ids (id) VALUES (new.id);
+    INSERT INTO comments_fts (rowid, content)
+    VALUES ((SELECT rowid FROM search_ids WHERE id = new.id), new.content);
+END;
+CREATE TRIGGER comments_fts_update AFTER UPDATE OF content ON comments BEGIN
+    UPDATE comments_fts SET content = new.content
+    WHERE rowid = (SELECT rowid FROM search_ids WHERE id = old.id);
+END;
+CREATE TRIGGER comments_fts_delete AFTER DELETE ON comments BEGIN
+    DELETE FROM comments_fts WHERE rowid = (SELECT rowid FROM search_ids WHERE id = old.id);
+    DELETE FROM search_ids WHERE id = old.id;
+END;
+
+CREATE TRIGGER users_fts_insert AFTER INSERT ON users BEGIN
+    INSERT INTO search_ids (id) VALUES (new.id);
+    INSERT INTO users_fts (rowid, username)
+    VALUES ((SELECT rowid FROM search_ids WHERE id = new.id), new.username);
+END;
+CREATE TRIGGER users_fts_update AFTER UPDATE OF username ON users BEGIN
+    UPDATE users_fts SET username = new.username
+    WHERE rowid = (SELECT rowid FROM search_ids WHERE id = old.id);
+END;
+CREATE TRIGGER users_fts_delete AFTER DELETE ON users BEGIN
+    DELETE FROM users_fts WHERE rowid = (SELECT rowid FROM search_ids WHERE id = old.id);
+    DELETE FROM search_ids WHERE id = old.id;
+END;
+
+-- 既存のデータを索引に入れる
+INSERT INTO search_ids (id)
+SELECT id FROM tweets UNION ALL SELECT id FROM comments UNION ALL SELECT id FROM users;
+INSERT INTO tweets_fts (rowid, content)
+SELECT s.rowid, t.content FROM tweets t JOIN search_ids s ON s.id = t.id;
+INSERT INTO comments_fts (rowid, content)
+SELECT s.rowid, t.content FROM comments t JOIN search_ids s ON s.id = t.id;
+INSERT INTO users_fts (rowid, username)
+SELECT s.rowid, t.username FROM users t JOIN search_ids s ON s.id = t.id;
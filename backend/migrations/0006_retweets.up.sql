@@ -0,0 +1,16 @@
+-- 引用ツイートの引用元（引用元が削除されても ID は残し、取得時に見つからなければ表示しない）
+ALTER TABLE tweets ADD COLUMN quoted_tweet_id TEXT;
+
+CREATE INDEX idx_tweets_quoted_tweet_id ON tweets(quoted_tweet_id);
+
+-- リツイート（ユーザーごとに1ツイート1回）
+CREATE TABLE retweets (
+    user_id TEXT NOT NULL,
+    tweet_id TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (user_id, tweet_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (tweet_id) REFERENCES tweets(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_retweets_tweet_id ON retweets(tweet_id);
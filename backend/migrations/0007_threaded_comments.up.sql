@@ -0,0 +1,5 @@
+-- 返信先のコメント（NULL ならツイートへの直接のコメント）
+-- 返信先が削除されたときは返信もまとめて削除する（リポジトリで子孫ごと削除する）
+ALTER TABLE comments ADD COLUMN parent_id TEXT;
+
+CREATE INDEX idx_comments_parent_id ON comments(parent_id);
@@ -0,0 +1,20 @@
+-- ツイート・コメント本文の @username で言及されたユーザー（投稿時に存在したユーザーのみ）
+CREATE TABLE tweet_mentions (
+    tweet_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    PRIMARY KEY (tweet_id, user_id),
+    FOREIGN KEY (tweet_id) REFERENCES tweets(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_tweet_mentions_user_id ON tweet_mentions(user_id);
+
+CREATE TABLE comment_mentions (
+    comment_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    PRIMARY KEY (comment_id, user_id),
+    FOREIGN KEY (comment_id) REFERENCES comments(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_comment_mentions_user_id ON comment_mentions(user_id);
@@ -0,0 +1,27 @@
+-- 通知（同じユーザーから同じ group_key への通知は1行にまとめ、日時を更新して未読に戻す）
+CREATE TABLE notifications (
+    id TEXT PRIMARY KEY NOT NULL,
+    user_id TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    actor_id TEXT NOT NULL,
+    group_key TEXT NOT NULL,
+    tweet_id TEXT,
+    comment_id TEXT,
+    created_at TEXT NOT NULL,
+    read_at TEXT,
+    UNIQUE (user_id, group_key, actor_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (actor_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (tweet_id) REFERENCES tweets(id) ON DELETE CASCADE,
+    FOREIGN KEY (comment_id) REFERENCES comments(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_notifications_user_id_created_at ON notifications(user_id, created_at);
+
+-- 受け取らないことにした通知の種類
+CREATE TABLE disabled_notification_kinds (
+    user_id TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    PRIMARY KEY (user_id, kind),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
@@ -0,0 +1,34 @@
+-- 相互フォローでなくてもダイレクトメッセージを受け取るか
+ALTER TABLE users ADD COLUMN allow_open_dms BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- ダイレクトメッセージの会話（1対1と少人数のグループ）
+CREATE TABLE direct_conversations (
+    id TEXT PRIMARY KEY NOT NULL,
+    created_at TEXT NOT NULL,
+    last_message_at TEXT NOT NULL
+);
+
+-- 会話の参加者（last_read_at は既読にした日時）
+CREATE TABLE direct_conversation_members (
+    conversation_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    last_read_at TEXT,
+    PRIMARY KEY (conversation_id, user_id),
+    FOREIGN KEY (conversation_id) REFERENCES direct_conversations(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_direct_conversation_members_user_id ON direct_conversation_members(user_id);
+
+CREATE TABLE direct_messages (
+    id TEXT PRIMARY KEY NOT NULL,
+    conversation_id TEXT NOT NULL,
+    sender_id TEXT NOT NULL,
+    content TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (conversation_id) REFERENCES direct_conversations(id) ON DELETE CASCADE,
+    FOREIGN KEY (sender_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_direct_messages_conversation_id_created_at
+    ON direct_messages(conversation_id, created_at);
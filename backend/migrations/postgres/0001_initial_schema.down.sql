@@ -0,0 +1,7 @@
+DROP TABLE IF EXISTS follows;
+DROP TABLE IF EXISTS comments;
+DROP TABLE IF EXISTS tweet_hashtags;
+DROP TABLE IF EXISTS hashtags;
+DROP TABLE IF EXISTS likes;
+DROP TABLE IF EXISTS tweets;
+DROP TABLE IF EXISTS users;
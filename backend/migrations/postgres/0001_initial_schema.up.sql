@@ -0,0 +1,66 @@
+-- 初期スキーマ（PostgreSQL）: IDは UUID、日時は TIMESTAMPTZ で保持する
+
+CREATE TABLE users (
+    id UUID PRIMARY KEY,
+    username TEXT NOT NULL UNIQUE,
+    email TEXT NOT NULL UNIQUE,
+    password_hash TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE TABLE tweets (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id),
+    content TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE TABLE likes (
+    user_id UUID NOT NULL REFERENCES users(id),
+    tweet_id UUID NOT NULL REFERENCES tweets(id),
+    created_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (user_id, tweet_id)
+);
+
+CREATE INDEX idx_tweets_user_id ON tweets(user_id);
+CREATE INDEX idx_tweets_created_at ON tweets(created_at);
+CREATE INDEX idx_likes_tweet_id ON likes(tweet_id);
+CREATE INDEX idx_likes_user_id ON likes(user_id);
+
+-- ハッシュタグ
+CREATE TABLE hashtags (
+    id UUID PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE
+);
+
+-- ツイートとハッシュタグの中間テーブル（多対多）
+CREATE TABLE tweet_hashtags (
+    tweet_id UUID NOT NULL REFERENCES tweets(id) ON DELETE CASCADE,
+    hashtag_id UUID NOT NULL REFERENCES hashtags(id),
+    PRIMARY KEY (tweet_id, hashtag_id)
+);
+
+CREATE INDEX idx_tweet_hashtags_hashtag_id ON tweet_hashtags(hashtag_id);
+
+-- コメント
+CREATE TABLE comments (
+    id UUID PRIMARY KEY,
+    tweet_id UUID NOT NULL REFERENCES tweets(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id),
+    content TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_comments_tweet_id ON comments(tweet_id);
+CREATE INDEX idx_comments_user_id ON comments(user_id);
+CREATE INDEX idx_comments_created_at ON comments(created_at);
+
+-- フォロー
+CREATE TABLE follows (
+    follower_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    following_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (follower_id, following_id)
+);
+
+CREATE INDEX idx_follows_following_id ON follows(following_id);
@@ -0,0 +1,23 @@
+-- ログインセッション: リフレッシュトークンの family_id はセッションIDと一致する
+CREATE TABLE sessions (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    user_agent TEXT,
+    ip_address TEXT,
+    created_at TIMESTAMPTZ NOT NULL,
+    last_seen_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_sessions_user_id ON sessions(user_id);
+
+-- 既存のリフレッシュトークンのファミリーをセッションとして取り込む
+INSERT INTO sessions (id, user_id, created_at, last_seen_at, revoked_at)
+SELECT
+    family_id,
+    user_id,
+    MIN(created_at),
+    MAX(created_at),
+    CASE WHEN COUNT(*) = COUNT(revoked_at) THEN MAX(revoked_at) END
+FROM refresh_tokens
+GROUP BY family_id, user_id;
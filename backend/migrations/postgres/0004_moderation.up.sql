@@ -0,0 +1,30 @@
+-- ロールと利用停止（既存のユーザーは一般ユーザー）
+ALTER TABLE users ADD COLUMN role TEXT NOT NULL DEFAULT 'user'
+    CHECK (role IN ('user', 'moderator', 'admin'));
+ALTER TABLE users ADD COLUMN suspended_at TIMESTAMPTZ;
+
+-- ユーザーからの通報
+CREATE TABLE reports (
+    id UUID PRIMARY KEY,
+    reporter_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    target_type TEXT NOT NULL,
+    target_id UUID NOT NULL,
+    reason TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    resolved_at TIMESTAMPTZ,
+    resolved_by UUID REFERENCES users(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_reports_resolved_at ON reports(resolved_at, created_at);
+
+-- 管理者・モデレーターの操作の監査ログ（対象が削除されても残す）
+CREATE TABLE moderation_actions (
+    id UUID PRIMARY KEY,
+    actor_id UUID NOT NULL REFERENCES users(id),
+    action TEXT NOT NULL,
+    target_id UUID NOT NULL,
+    details TEXT,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_moderation_actions_created_at ON moderation_actions(created_at);
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_users_username_trgm;
+DROP INDEX IF EXISTS idx_comments_content_trgm;
+DROP INDEX IF EXISTS idx_tweets_content_trgm;
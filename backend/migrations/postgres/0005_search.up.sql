@@ -0,0 +1,6 @@
+-- 部分一致検索の索引（trigram で空白のない日本語も ILIKE で検索できる）
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX idx_tweets_content_trgm ON tweets USING gin (content gin_trgm_ops);
+CREATE INDEX idx_comments_content_trgm ON comments USING gin (content gin_trgm_ops);
+CREATE INDEX idx_users_username_trgm ON users USING gin (username gin_trgm_ops);
@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS retweets;
+DROP INDEX IF EXISTS idx_tweets_quoted_tweet_id;
+ALTER TABLE tweets DROP COLUMN IF EXISTS quoted_tweet_id;
@@ -0,0 +1,14 @@
+-- 引用ツイートの引用元（引用元が削除されても ID は残し、取得時に見つからなければ表示しない）
+ALTER TABLE tweets ADD COLUMN quoted_tweet_id UUID;
+
+CREATE INDEX idx_tweets_quoted_tweet_id ON tweets(quoted_tweet_id);
+
+-- リツイート（ユーザーごとに1ツイート1回）
+CREATE TABLE retweets (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    tweet_id UUID NOT NULL REFERENCES tweets(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (user_id, tweet_id)
+);
+
+CREATE INDEX idx_retweets_tweet_id ON retweets(tweet_id);
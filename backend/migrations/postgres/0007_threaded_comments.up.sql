@@ -0,0 +1,4 @@
+-- 返信先のコメント（NULL ならツイートへの直接のコメント、返信先が削除されると返信も削除される）
+ALTER TABLE comments ADD COLUMN parent_id UUID REFERENCES comments(id) ON DELETE CASCADE;
+
+CREATE INDEX idx_comments_parent_id ON comments(parent_id);
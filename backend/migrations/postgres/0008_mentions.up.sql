@@ -0,0 +1,16 @@
+-- ツイート・コメント本文の @username で言及されたユーザー（投稿時に存在したユーザーのみ）
+CREATE TABLE tweet_mentions (
+    tweet_id UUID NOT NULL REFERENCES tweets(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    PRIMARY KEY (tweet_id, user_id)
+);
+
+CREATE INDEX idx_tweet_mentions_user_id ON tweet_mentions(user_id);
+
+CREATE TABLE comment_mentions (
+    comment_id UUID NOT NULL REFERENCES comments(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    PRIMARY KEY (comment_id, user_id)
+);
+
+CREATE INDEX idx_comment_mentions_user_id ON comment_mentions(user_id);
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS disabled_notification_kinds;
+DROP TABLE IF EXISTS notifications;
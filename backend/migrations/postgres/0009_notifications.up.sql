@@ -0,0 +1,22 @@
+-- 通知（同じユーザーから同じ group_key への通知は1行にまとめ、日時を更新して未読に戻す）
+CREATE TABLE notifications (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    actor_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    group_key TEXT NOT NULL,
+    tweet_id UUID REFERENCES tweets(id) ON DELETE CASCADE,
+    comment_id UUID REFERENCES comments(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL,
+    read_at TIMESTAMPTZ,
+    UNIQUE (user_id, group_key, actor_id)
+);
+
+CREATE INDEX idx_notifications_user_id_created_at ON notifications(user_id, created_at);
+
+-- 受け取らないことにした通知の種類
+CREATE TABLE disabled_notification_kinds (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    PRIMARY KEY (user_id, kind)
+);
@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS direct_messages;
+DROP TABLE IF EXISTS direct_conversation_members;
+DROP TABLE IF EXISTS direct_conversations;
+ALTER TABLE users DROP COLUMN allow_open_dms;
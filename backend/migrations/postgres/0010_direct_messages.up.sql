@@ -0,0 +1,30 @@
+-- 相互フォローでなくてもダイレクトメッセージを受け取るか
+ALTER TABLE users ADD COLUMN allow_open_dms BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- ダイレクトメッセージの会話（1対1と少人数のグループ）
+CREATE TABLE direct_conversations (
+    id UUID PRIMARY KEY,
+    created_at TIMESTAMPTZ NOT NULL,
+    last_message_at TIMESTAMPTZ NOT NULL
+);
+
+-- 会話の参加者（last_read_at は既読にした日時）
+CREATE TABLE direct_conversation_members (
+    conversation_id UUID NOT NULL REFERENCES direct_conversations(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    last_read_at TIMESTAMPTZ,
+    PRIMARY KEY (conversation_id, user_id)
+);
+
+CREATE INDEX idx_direct_conversation_members_user_id ON direct_conversation_members(user_id);
+
+CREATE TABLE direct_messages (
+    id UUID PRIMARY KEY,
+    conversation_id UUID NOT NULL REFERENCES direct_conversations(id) ON DELETE CASCADE,
+    sender_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    content TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_direct_messages_conversation_id_created_at
+    ON direct_messages(conversation_id, created_at);
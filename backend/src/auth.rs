@@ -0,0 +1,219 @@
+use actix_web::dev::Payload;
+use actix_web::http::{StatusCode, header};
+use actix_web::{FromRequest, HttpRequest, HttpResponse, ResponseError, web};
+use async_graphql::ErrorExtensions;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use uuid::Uuid;
+
+use crate::error::{AppError, problem, problem_response};
+use crate::models::Role;
+use crate::repository::Repositories;
+use crate::roles::Actor;
+use crate::utils::verify_jwt;
+
+/// WWW-Authenticate ヘッダーの realm
+const REALM: &str = "play-with-actix-web";
+
+/// 認証エラー（RFC 6750 の WWW-Authenticate ヘッダー付きで 401 を返す）
+#[derive(Debug)]
+pub enum AuthError {
+    /// Authorization ヘッダーがない
+    MissingToken,
+    /// ヘッダーの形式が不正、署名が不正、セッションが失効しているなど
+    InvalidToken(String),
+    /// トークンの有効期限切れ
+    ExpiredToken,
+    /// 検証中のサーバーエラー
+    Internal(AppError),
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthError::MissingToken => write!(f, "Missing authorization header"),
+            AuthError::InvalidToken(msg) => write!(f, "{}", msg),
+            AuthError::ExpiredToken => write!(f, "Token has expired"),
+            AuthError::Internal(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl ResponseError for AuthError {
+    fn error_response(&self) -> HttpResponse {
+        if let AuthError::Internal(e) = self {
+            return e.error_response();
+        }
+
+        let challenge = match self {
+            AuthError::MissingToken => format!("Bearer realm=\"{}\"", REALM),
+            _ => format!(
+                "Bearer realm=\"{}\", error=\"invalid_token\", error_description=\"{}\"",
+                REALM,
+                self.to_string().replace('"', "'")
+            ),
+        };
+
+        problem_response(self.status_code())
+            .insert_header((header::WWW_AUTHENTICATE, challenge))
+            .json(problem(
+                self.status_code(),
+                "UNAUTHENTICATED",
+                &self.to_string(),
+            ))
+    }
+
+    fn status_code(&self) -> StatusCode {
+        match self {
+            AuthError::Internal(e) => e.status_code(),
+            _ => StatusCode::UNAUTHORIZED,
+        }
+    }
+}
+
+// GraphQL（WebSocket の connection_init など）では UNAUTHENTICATED として返す
+impl ErrorExtensions for AuthError {
+    fn extend(&self) -> async_graphql::Error {
+        match self {
+            AuthError::Internal(e) => e.extend(),
+            other => AppError::Unauthorized(other.to_string()).extend(),
+        }
+    }
+}
+
+// セッション検証のエラーは認証エラーとして扱い、DBエラーなどはそのまま返す
+impl From<AppError> for AuthError {
+    fn from(err: AppError) -> Self {
+        match err {
+            AppError::Unauthorized(msg) => AuthError::InvalidToken(msg),
+            other => AuthError::Internal(other),
+        }
+    }
+}
+
+/// 認証済みユーザー（トークンがない・不正な場合は 401）
+#[derive(Debug, Clone, Copy)]
+pub struct AuthenticatedUser {
+    pub user_id: Uuid,
+    pub session_id: Uuid,
+    pub role: Role,
+}
+
+impl AuthenticatedUser {
+    pub fn actor(&self) -> Actor {
+        Actor {
+            user_id: self.user_id,
+            role: self.role,
+        }
+    }
+}
+
+/// 任意認証のユーザー（トークンがなければ None、不正なトークンは 401）
+#[derive(Debug, Clone, Copy)]
+pub struct OptionalUser(pub Option<AuthenticatedUser>);
+
+/// AuthorizationヘッダーからBearerトークンを抽出する（ヘッダーがなければ None）
+fn extract_bearer_token(req: &HttpRequest) -> Result<Option<String>, AuthError> {
+    let Some(value) = req.headers().get(header::AUTHORIZATION) else {
+        return Ok(None);
+    };
+
+    value
+        .to_str()
+        .ok()
+        .and_then(parse_bearer)
+        .map(Some)
+        .ok_or_else(invalid_format)
+}
+
+/// `Bearer <token>` 形式の値からトークンを取り出す
+fn parse_bearer(value: &str) -> Option<String> {
+    value
+        .strip_prefix("Bearer ")
+        .map(|token| token.trim().to_string())
+}
+
+fn invalid_format() -> AuthError {
+    AuthError::InvalidToken("Invalid authorization format".to_string())
+}
+
+/// トークンを検証して認証済みユーザーを返す
+async fn authenticate_token(
+    repos: &Repositories,
+    token: &str,
+) -> Result<AuthenticatedUser, AuthError> {
+    let claims = verify_jwt(repos, token).await?;
+
+    Ok(AuthenticatedUser {
+        user_id: claims.user_id,
+        session_id: claims.sid,
+        role: claims.role,
+    })
+}
+
+/// リクエストを認証する（ヘッダーがなければ Ok(None)）
+pub async fn authenticate_optional(
+    req: &HttpRequest,
+) -> Result<Option<AuthenticatedUser>, AuthError> {
+    let Some(token) = extract_bearer_token(req)? else {
+        return Ok(None);
+    };
+
+    let repos = req.app_data::<web::Data<Repositories>>().ok_or_else(|| {
+        AuthError::Internal(AppError::Internal(
+            "Repositories not configured".to_string(),
+        ))
+    })?;
+
+    authenticate_token(repos.as_ref(), &token).await.map(Some)
+}
+
+/// WebSocketの connection_init ペイロードを認証する
+///
+/// ペイロードは `{"Authorization": "Bearer <token>"}` の形式（キーの大文字小文字は問わない）で、
+/// トークンがなければ Ok(None)
+pub async fn authenticate_connection(
+    repos: &Repositories,
+    payload: &serde_json::Value,
+) -> Result<Option<AuthenticatedUser>, AuthError> {
+    let value = payload.as_object().and_then(|params| {
+        params
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(header::AUTHORIZATION.as_str()))
+            .map(|(_, value)| value)
+    });
+    let Some(value) = value else {
+        return Ok(None);
+    };
+
+    let token = value
+        .as_str()
+        .and_then(parse_bearer)
+        .ok_or_else(invalid_format)?;
+    authenticate_token(repos, &token).await.map(Some)
+}
+
+impl FromRequest for AuthenticatedUser {
+    type Error = AuthError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let req = req.clone();
+        Box::pin(async move {
+            authenticate_optional(&req)
+                .await?
+                .ok_or(AuthError::MissingToken)
+        })
+    }
+}
+
+impl FromRequest for OptionalUser {
+    type Error = AuthError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let req = req.clone();
+        Box::pin(async move { authenticate_optional(&req).await.map(OptionalUser) })
+    }
+}
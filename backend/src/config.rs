@@ -0,0 +1,451 @@
+use jsonwebtoken::Algorithm;
+use serde::Deserialize;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::keys::DEV_SECRET;
+use crate::store::MEMORY_URL;
+
+/// 設定ファイルのデフォルトパス（APP_CONFIG で上書き可能）
+const DEFAULT_CONFIG_PATH: &str = "config.toml";
+
+/// config.example.toml に載せているシークレットの例（公開されているため本番では使えない）
+const EXAMPLE_SECRET: &str = "change-me-to-a-random-string-of-32-bytes";
+
+/// アプリケーション全体の設定
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub cors: CorsConfig,
+    pub jwt: JwtConfig,
+    pub limits: LimitsConfig,
+    pub graphql: GraphqlConfig,
+}
+
+/// HTTPサーバーの待ち受け設定
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub host: String,
+    pub port: u16,
+    pub environment: Environment,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            host: "127.0.0.1".to_string(),
+            port: 8080,
+            environment: Environment::Production,
+        }
+    }
+}
+
+/// 実行環境（開発モードでのみ安全でない既定値を許可する）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Environment {
+    Development,
+    Production,
+}
+
+impl FromStr for Environment {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "development" => Ok(Environment::Development),
+            "production" => Ok(Environment::Production),
+            other => Err(format!(
+                "unknown environment {} (expected development or production)",
+                other
+            )),
+        }
+    }
+}
+
+/// データベース接続設定
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    pub url: String,
+    pub max_connections: u32,
+}
+
+/// database.url の既定値とスキーム（`postgres` フィーチャーで切り替わる）
+#[cfg(not(feature = "postgres"))]
+const DEFAULT_DATABASE_URL: &str = "sqlite:./app.db?mode=rwc";
+#[cfg(not(feature = "postgres"))]
+const DATABASE_SCHEMES: &[&str] = &["sqlite:"];
+#[cfg(feature = "postgres")]
+const DEFAULT_DATABASE_URL: &str = "postgres://localhost/play_with_actix_web";
+#[cfg(feature = "postgres")]
+const DATABASE_SCHEMES: &[&str] = &["postgres://", "postgresql://"];
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            url: DEFAULT_DATABASE_URL.to_string(),
+            max_connections: 5,
+        }
+    }
+}
+
+/// CORS設定
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CorsConfig {
+    pub allowed_origins: Vec<String>,
+    pub max_age: usize,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: vec!["http://localhost:3000".to_string()],
+            max_age: 3600,
+        }
+    }
+}
+
+/// JWT設定
+///
+/// HS256 は `secret`、RS256/EdDSA は PEM 形式の鍵ペアを使う
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JwtConfig {
+    pub algorithm: Algorithm,
+    pub secret: Option<String>,
+    /// 署名鍵のID（JWTヘッダーの kid）
+    pub kid: Option<String>,
+    pub private_key_path: Option<String>,
+    pub public_key_path: Option<String>,
+    /// アクセストークンの有効期間（分）
+    pub access_token_minutes: i64,
+    /// リフレッシュトークンの有効期間（日）
+    pub refresh_token_days: i64,
+    /// ローテーション中の旧鍵（検証のみに使う）
+    pub verification_keys: Vec<VerificationKeyConfig>,
+}
+
+impl Default for JwtConfig {
+    fn default() -> Self {
+        Self {
+            algorithm: Algorithm::HS256,
+            secret: None,
+            kid: None,
+            private_key_path: None,
+            public_key_path: None,
+            access_token_minutes: 15,
+            refresh_token_days: 30,
+            verification_keys: Vec::new(),
+        }
+    }
+}
+
+/// 検証専用の鍵
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VerificationKeyConfig {
+    pub kid: String,
+    pub algorithm: Algorithm,
+    pub secret: Option<String>,
+    pub public_key_path: Option<String>,
+}
+
+/// リクエストボディなどの上限設定
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LimitsConfig {
+    pub json_body_bytes: usize,
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        Self {
+            json_body_bytes: 4096,
+        }
+    }
+}
+
+/// GraphQLエンドポイントの制限と永続化クエリの設定
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GraphqlConfig {
+    /// クエリのネストの深さの上限
+    pub max_depth: usize,
+    /// クエリの複雑さ（フィールドごとのコストの合計）の上限
+    pub max_complexity: usize,
+    /// 自動永続化クエリ（APQ）として登録しておくクエリ数（超えたら最近使われていないものから消す）
+    pub apq_cache_size: usize,
+    /// 許可リスト（sha256 ハッシュ → クエリ本文の JSON）
+    pub persisted_queries_path: Option<String>,
+    /// 本番モード: 許可リストのクエリだけを受け付け、イントロスペクションと /graphiql を無効にする
+    pub persisted_only: bool,
+}
+
+impl Default for GraphqlConfig {
+    fn default() -> Self {
+        Self {
+            max_depth: 15,
+            max_complexity: 2000,
+            apq_cache_size: 1000,
+            persisted_queries_path: None,
+            persisted_only: false,
+        }
+    }
+}
+
+/// 設定の読み込み・検証エラー
+#[derive(Debug)]
+pub enum ConfigError {
+    /// 設定ファイルを読み込めない
+    Io(String, std::io::Error),
+    /// 設定ファイルの構文・型エラー
+    Parse(String, toml::de::Error),
+    /// 環境変数の値が不正
+    Env(&'static str, String),
+    /// 値の検証エラー
+    Invalid(&'static str, String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, e) => write!(f, "Failed to read {}: {}", path, e),
+            ConfigError::Parse(path, e) => write!(f, "Failed to parse {}: {}", path, e),
+            ConfigError::Env(var, msg) => {
+                write!(f, "Invalid environment variable {}: {}", var, msg)
+            }
+            ConfigError::Invalid(key, msg) => write!(f, "Invalid config value {}: {}", key, msg),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    /// 設定ファイル（存在すれば）と環境変数から設定を読み込み、検証する
+    ///
+    /// APP_CONFIG が指定された場合、そのファイルは必須となる
+    pub fn load() -> Result<Self, ConfigError> {
+        let (path, required) = match std::env::var("APP_CONFIG") {
+            Ok(path) => (path, true),
+            Err(_) => (DEFAULT_CONFIG_PATH.to_string(), false),
+        };
+
+        let mut config = match std::fs::read_to_string(&path) {
+            Ok(text) => Self::from_toml(&text).map_err(|e| ConfigError::Parse(path, e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && !required => Self::default(),
+            Err(e) => return Err(ConfigError::Io(path, e)),
+        };
+
+        config.apply_env()?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(text)
+    }
+
+    /// 環境変数で設定を上書きする
+    fn apply_env(&mut self) -> Result<(), ConfigError> {
+        override_from_env("APP_HOST", &mut self.server.host)?;
+        override_from_env("APP_PORT", &mut self.server.port)?;
+        override_from_env("APP_ENV", &mut self.server.environment)?;
+        override_from_env("DATABASE_URL", &mut self.database.url)?;
+        override_from_env(
+            "DATABASE_MAX_CONNECTIONS",
+            &mut self.database.max_connections,
+        )?;
+        override_from_env("CORS_MAX_AGE", &mut self.cors.max_age)?;
+        override_from_env("JWT_ALGORITHM", &mut self.jwt.algorithm)?;
+        override_option_from_env("JWT_SECRET", &mut self.jwt.secret);
+        override_option_from_env("JWT_KID", &mut self.jwt.kid);
+        override_option_from_env("JWT_PRIVATE_KEY_PATH", &mut self.jwt.private_key_path);
+        override_option_from_env("JWT_PUBLIC_KEY_PATH", &mut self.jwt.public_key_path);
+        override_from_env(
+            "JWT_ACCESS_TOKEN_MINUTES",
+            &mut self.jwt.access_token_minutes,
+        )?;
+        override_from_env("JWT_REFRESH_TOKEN_DAYS", &mut self.jwt.refresh_token_days)?;
+        override_from_env("JSON_BODY_LIMIT", &mut self.limits.json_body_bytes)?;
+        override_from_env("GRAPHQL_MAX_DEPTH", &mut self.graphql.max_depth)?;
+        override_from_env("GRAPHQL_MAX_COMPLEXITY", &mut self.graphql.max_complexity)?;
+        override_option_from_env(
+            "GRAPHQL_PERSISTED_QUERIES",
+            &mut self.graphql.persisted_queries_path,
+        );
+        override_from_env("GRAPHQL_PERSISTED_ONLY", &mut self.graphql.persisted_only)?;
+
+        // カンマ区切りで複数指定可能
+        if let Ok(origins) = std::env::var("CORS_ALLOWED_ORIGINS") {
+            self.cors.allowed_origins = origins
+                .split(',')
+                .map(|o| o.trim().to_string())
+                .filter(|o| !o.is_empty())
+                .collect();
+        }
+
+        Ok(())
+    }
+
+    /// 起動前に設定値を検証する
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.server.host.is_empty() {
+            return Err(invalid("server.host", "must not be empty"));
+        }
+        if self.server.port == 0 {
+            return Err(invalid("server.port", "must be between 1 and 65535"));
+        }
+        let url = &self.database.url;
+        if url != MEMORY_URL && !DATABASE_SCHEMES.iter().any(|s| url.starts_with(s)) {
+            return Err(invalid(
+                "database.url",
+                &format!(
+                    "must start with {} (or be memory: for an in-memory store)",
+                    DATABASE_SCHEMES.join(" or ")
+                ),
+            ));
+        }
+        if self.database.max_connections == 0 {
+            return Err(invalid("database.max_connections", "must be at least 1"));
+        }
+        for origin in &self.cors.allowed_origins {
+            if !(origin.starts_with("http://") || origin.starts_with("https://"))
+                || origin.ends_with('/')
+            {
+                return Err(invalid(
+                    "cors.allowed_origins",
+                    &format!("{} must be a scheme://host[:port] origin", origin),
+                ));
+            }
+        }
+        self.validate_jwt()?;
+        if self.jwt.access_token_minutes <= 0 {
+            return Err(invalid("jwt.access_token_minutes", "must be positive"));
+        }
+        if self.jwt.refresh_token_days <= 0 {
+            return Err(invalid("jwt.refresh_token_days", "must be positive"));
+        }
+        if self.limits.json_body_bytes == 0 {
+            return Err(invalid("limits.json_body_bytes", "must be positive"));
+        }
+        let graphql = &self.graphql;
+        if graphql.max_depth == 0 || graphql.max_complexity == 0 {
+            return Err(invalid(
+                "graphql",
+                "max_depth and max_complexity must be positive",
+            ));
+        }
+        if graphql.persisted_only && graphql.persisted_queries_path.is_none() {
+            return Err(invalid(
+                "graphql.persisted_queries_path",
+                "is required when persisted_only is enabled",
+            ));
+        }
+        Ok(())
+    }
+
+    /// JWT設定を検証する（本番環境では既定のシークレットを拒否する）
+    fn validate_jwt(&self) -> Result<(), ConfigError> {
+        let jwt = &self.jwt;
+        let production = self.server.environment == Environment::Production;
+
+        match jwt.algorithm {
+            Algorithm::HS256 => {
+                if let Some(problem) = secret_problem(jwt.secret.as_deref(), production) {
+                    return Err(invalid("jwt.secret", problem));
+                }
+            }
+            Algorithm::RS256 | Algorithm::EdDSA => {
+                if jwt.private_key_path.is_none() || jwt.public_key_path.is_none() {
+                    return Err(invalid(
+                        "jwt",
+                        "private_key_path and public_key_path are required for RS256/EdDSA",
+                    ));
+                }
+                if jwt.kid.is_none() {
+                    return Err(invalid("jwt.kid", "is required for RS256/EdDSA"));
+                }
+            }
+            _ => return Err(invalid("jwt.algorithm", "must be HS256, RS256 or EdDSA")),
+        }
+
+        let mut kids: Vec<&str> = jwt.kid.iter().map(String::as_str).collect();
+        for key in &jwt.verification_keys {
+            if key.kid.is_empty() || kids.contains(&key.kid.as_str()) {
+                return Err(invalid(
+                    "jwt.verification_keys.kid",
+                    &format!("{:?} must be non-empty and unique", key.kid),
+                ));
+            }
+            kids.push(&key.kid);
+
+            let ok = match key.algorithm {
+                Algorithm::HS256 => key.secret.is_some(),
+                Algorithm::RS256 | Algorithm::EdDSA => key.public_key_path.is_some(),
+                _ => false,
+            };
+            if !ok {
+                return Err(invalid(
+                    "jwt.verification_keys",
+                    &format!(
+                        "{} needs a secret (HS256) or public_key_path (RS256/EdDSA)",
+                        key.kid
+                    ),
+                ));
+            }
+            // 検証専用の鍵でも、本番環境では署名鍵と同じシークレットの条件を満たす必要がある
+            if key.algorithm == Algorithm::HS256
+                && let Some(problem) = secret_problem(key.secret.as_deref(), production)
+            {
+                return Err(invalid(
+                    "jwt.verification_keys.secret",
+                    &format!("{} {}", key.kid, problem),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// HS256 のシークレットの問題点（本番環境では未設定・既定値・例の値・短い値を拒否する）
+fn secret_problem(secret: Option<&str>, production: bool) -> Option<&'static str> {
+    match secret {
+        None if production => Some("must be set (or run with APP_ENV=development)"),
+        Some("") => Some("must not be empty"),
+        Some(DEV_SECRET) if production => Some("the default secret is only allowed in development"),
+        Some(EXAMPLE_SECRET) if production => Some("must be changed from the example value"),
+        Some(secret) if production && secret.len() < 32 => Some("must be at least 32 bytes"),
+        _ => None,
+    }
+}
+
+fn invalid(key: &'static str, msg: &str) -> ConfigError {
+    ConfigError::Invalid(key, msg.to_string())
+}
+
+/// 環境変数が設定されていればパースして上書きする
+fn override_from_env<T>(var: &'static str, target: &mut T) -> Result<(), ConfigError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    if let Ok(value) = std::env::var(var) {
+        *target = value
+            .parse()
+            .map_err(|e: T::Err| ConfigError::Env(var, e.to_string()))?;
+    }
+    Ok(())
+}
+
+fn override_option_from_env(var: &'static str, target: &mut Option<String>) {
+    if let Ok(value) = std::env::var(var) {
+        *target = Some(value);
+    }
+}
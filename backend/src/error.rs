@@ -1,28 +1,84 @@
+use actix_web::error::InternalError;
 use actix_web::{HttpResponse, ResponseError, http::StatusCode};
+use async_graphql::ErrorExtensions;
 use std::fmt;
 
 /// アプリケーション全体で使用するエラー型
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum AppError {
     /// データベースエラー
     Database(String),
     /// 認証エラー（401）
     Unauthorized(String),
+    /// 権限がない（403）
+    Forbidden(String),
     /// リクエストエラー（400）
     BadRequest(String),
+    /// 入力値の検証エラー（400、field は入力のパス）
+    Validation { field: String, message: String },
     /// リソースが見つからない（404）
     NotFound(String),
+    /// 既存のデータと競合する（409）
+    Conflict(String),
+    /// リクエストが多すぎる（429）
+    #[allow(dead_code)]
+    RateLimited(String),
     /// 内部サーバーエラー（500）
     Internal(String),
 }
 
+impl AppError {
+    /// 入力値の検証エラー
+    pub fn validation(field: &str, message: impl Into<String>) -> Self {
+        AppError::Validation {
+            field: field.to_string(),
+            message: message.into(),
+        }
+    }
+
+    /// クライアントに返すメッセージ（種別の接頭辞なし）
+    pub fn message(&self) -> &str {
+        match self {
+            AppError::Database(msg)
+            | AppError::Unauthorized(msg)
+            | AppError::Forbidden(msg)
+            | AppError::BadRequest(msg)
+            | AppError::Validation { message: msg, .. }
+            | AppError::NotFound(msg)
+            | AppError::Conflict(msg)
+            | AppError::RateLimited(msg)
+            | AppError::Internal(msg) => msg,
+        }
+    }
+
+    /// クライアントが分岐に使うエラーコード（GraphQL と REST で共通）
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::Database(_) | AppError::Internal(_) => "INTERNAL_SERVER_ERROR",
+            AppError::Unauthorized(_) => "UNAUTHENTICATED",
+            AppError::Forbidden(_) => "FORBIDDEN",
+            AppError::BadRequest(_) => "BAD_REQUEST",
+            AppError::Validation { .. } => "VALIDATION_FAILED",
+            AppError::NotFound(_) => "NOT_FOUND",
+            AppError::Conflict(_) => "CONFLICT",
+            AppError::RateLimited(_) => "RATE_LIMITED",
+        }
+    }
+}
+
 impl fmt::Display for AppError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             AppError::Database(msg) => write!(f, "Database error: {}", msg),
             AppError::Unauthorized(msg) => write!(f, "Unauthorized: {}", msg),
+            AppError::Forbidden(msg) => write!(f, "Forbidden: {}", msg),
             AppError::BadRequest(msg) => write!(f, "Bad request: {}", msg),
+            AppError::Validation { field, message } => {
+                write!(f, "Validation failed: {}: {}", field, message)
+            }
             AppError::NotFound(msg) => write!(f, "Not found: {}", msg),
+            AppError::Conflict(msg) => write!(f, "Conflict: {}", msg),
+            AppError::RateLimited(msg) => write!(f, "Rate limited: {}", msg),
             AppError::Internal(msg) => write!(f, "Internal error: {}", msg),
         }
     }
@@ -30,30 +86,64 @@ impl fmt::Display for AppError {
 
 impl ResponseError for AppError {
     fn error_response(&self) -> HttpResponse {
-        let (status, message) = match self {
-            AppError::Database(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg.as_str()),
-            AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg.as_str()),
-            AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg.as_str()),
-            AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg.as_str()),
-            AppError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg.as_str()),
-        };
-
-        HttpResponse::build(status).json(serde_json::json!({
-            "error": message
-        }))
+        let mut body = problem(self.status_code(), self.code(), self.message());
+        if let AppError::Validation { field, .. } = self {
+            body["field"] = field.as_str().into();
+        }
+        problem_response(self.status_code()).json(body)
     }
 
     fn status_code(&self) -> StatusCode {
         match self {
             AppError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
             AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
-            AppError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::BadRequest(_) | AppError::Validation { .. } => StatusCode::BAD_REQUEST,
             AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
             AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
 }
 
+// GraphQLエラーの extensions に code（検証エラーでは field も）を付ける
+impl ErrorExtensions for AppError {
+    fn extend(&self) -> async_graphql::Error {
+        async_graphql::Error::new(self.message()).extend_with(|_, e| {
+            e.set("code", self.code());
+            if let AppError::Validation { field, .. } = self {
+                e.set("field", field.as_str());
+            }
+        })
+    }
+}
+
+/// RFC 7807 の problem+json 本文
+pub fn problem(status: StatusCode, code: &str, detail: &str) -> serde_json::Value {
+    serde_json::json!({
+        "type": "about:blank",
+        "title": status.canonical_reason().unwrap_or_default(),
+        "status": status.as_u16(),
+        "detail": detail,
+        "code": code,
+    })
+}
+
+/// problem+json を返すレスポンスビルダー
+pub fn problem_response(status: StatusCode) -> actix_web::HttpResponseBuilder {
+    let mut builder = HttpResponse::build(status);
+    builder.content_type("application/problem+json");
+    builder
+}
+
+/// リクエストの抽出エラー（JSON・パスの不正など）を problem+json で返す
+pub fn extractor_error(err: impl ResponseError + 'static) -> actix_web::Error {
+    let status = err.status_code();
+    let response = problem_response(status).json(problem(status, "BAD_REQUEST", &err.to_string()));
+    InternalError::from_response(err, response).into()
+}
+
 // sqlx::Error から AppError への変換
 impl From<sqlx::Error> for AppError {
     fn from(err: sqlx::Error) -> Self {
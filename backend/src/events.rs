@@ -0,0 +1,51 @@
+//! アプリ内のイベントバス
+//!
+//! サービス層が発行したイベントをGraphQLサブスクリプションの購読者に配信する。
+//! 単一プロセス内の配信のため、複数インスタンス構成では各インスタンスの購読者にしか届かない
+
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use crate::models::{Comment, Tweet};
+
+/// 購読者ごとに保持する未配信イベントの上限（超えた分は古いものから捨てる）
+const CHANNEL_CAPACITY: usize = 256;
+
+/// 配信するイベント
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// ツイートが投稿された
+    TweetPosted(Tweet),
+    /// ツイートにいいねされた（author_id はツイートの投稿者）
+    TweetLiked { tweet_id: Uuid, author_id: Uuid },
+    /// コメントが投稿された
+    CommentAdded(Comment),
+}
+
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<Event>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self { sender }
+    }
+
+    /// イベントを発行する（購読者がいなければ捨てる）
+    pub fn publish(&self, event: Event) {
+        let _ = self.sender.send(event);
+    }
+
+    /// 以降に発行されるイベントを受け取る
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
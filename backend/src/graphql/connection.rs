@@ -0,0 +1,180 @@
+use async_graphql::connection::{
+    self, Connection, ConnectionNameType, CursorType, Edge, EdgeNameType, EmptyFields,
+};
+use async_graphql::dataloader::DataLoader;
+use async_graphql::{Context, Object, ObjectType, OutputType, Result};
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::graphql::FETCH_COST;
+use crate::graphql::gql_error;
+use crate::graphql::loaders::FollowCountsLoader;
+use crate::pagination::{Cursor, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE, Page, PageQuery};
+use crate::services::Services;
+
+/// totalCount を持つ Relay 形式の Connection
+pub type CountedConnection<Node> = Connection<Cursor, Node, TotalCount>;
+
+impl CursorType for Cursor {
+    type Error = String;
+
+    fn decode_cursor(s: &str) -> std::result::Result<Self, Self::Error> {
+        Cursor::decode(s).map_err(|e| e.message().to_string())
+    }
+
+    fn encode_cursor(&self) -> String {
+        self.encode()
+    }
+}
+
+/// Connection の totalCount（要求されたときだけ数える）
+pub enum TotalCount {
+    Timeline(Uuid),
+    Comments {
+        tweet_id: Uuid,
+        viewer: Option<Uuid>,
+    },
+    Followers(Uuid),
+    Following(Uuid),
+    Hashtag(String),
+    Mentions(Uuid),
+    Conversations(Uuid),
+    Messages(Uuid),
+    Thread {
+        tweet_id: Uuid,
+        parent_id: Option<Uuid>,
+        depth: i64,
+        viewer: Option<Uuid>,
+    },
+}
+
+#[Object]
+impl TotalCount {
+    /// ページに関係なく一覧全体の件数
+    #[graphql(complexity = "FETCH_COST")]
+    async fn total_count(&self, ctx: &Context<'_>) -> Result<i64> {
+        let services = ctx.data::<Services>()?;
+
+        let count = match self {
+            TotalCount::Timeline(user_id) => services.tweets.timeline_count(*user_id).await,
+            TotalCount::Comments { tweet_id, viewer } => {
+                services.tweets.comment_count(*tweet_id, *viewer).await
+            }
+            TotalCount::Followers(user_id) | TotalCount::Following(user_id) => {
+                let loader = ctx.data::<DataLoader<FollowCountsLoader>>()?;
+                let counts = loader
+                    .load_one(*user_id)
+                    .await
+                    .map(Option::unwrap_or_default);
+                counts.map(|c| match self {
+                    TotalCount::Followers(_) => c.followers,
+                    _ => c.following,
+                })
+            }
+            TotalCount::Hashtag(name) => services
+                .hashtags
+                .find(name)
+                .await
+                .map(|h| h.map_or(0, |h| h.stats.tweet_count)),
+            TotalCount::Mentions(user_id) => services.tweets.mention_count(*user_id).await,
+            TotalCount::Conversations(user_id) => {
+                services.direct_messages.conversation_count(*user_id).await
+            }
+            TotalCount::Messages(conversation_id) => {
+                services
+                    .direct_messages
+                    .message_count(*conversation_id)
+                    .await
+            }
+            TotalCount::Thread {
+                tweet_id,
+                parent_id,
+                depth,
+                viewer,
+            } => {
+                services
+                    .tweets
+                    .thread_count(*tweet_id, *parent_id, *depth, *viewer)
+                    .await
+            }
+        };
+        count.map_err(gql_error)
+    }
+}
+
+/// 一覧フィールドの複雑さ（要素のフィールドのコストをページの件数倍で数える）
+pub fn page_complexity(first: Option<i32>, last: Option<i32>, child_complexity: usize) -> usize {
+    let size = first
+        .or(last)
+        .map_or(DEFAULT_PAGE_SIZE, |n| n.max(0) as usize)
+        .min(MAX_PAGE_SIZE);
+    FETCH_COST + size * child_complexity
+}
+
+/// first/after/last/before を検証してページを取得し、Connection に変換する
+pub async fn paginate<T, Node, F, Fut>(
+    after: Option<String>,
+    before: Option<String>,
+    first: Option<i32>,
+    last: Option<i32>,
+    total_count: TotalCount,
+    fetch: F,
+) -> Result<CountedConnection<Node>>
+where
+    Node: OutputType + From<T>,
+    F: FnOnce(PageQuery) -> Fut,
+    Fut: Future<Output = std::result::Result<Page<T>, AppError>>,
+{
+    paginate_with_edges(after, before, first, last, total_count, fetch, |item| {
+        (Node::from(item), EmptyFields)
+    })
+    .await
+}
+
+/// paginate と同じだが、要素ごとにノードとエッジのフィールドを作る（fields は Connection に付けるフィールド）
+pub async fn paginate_with_edges<T, Node, ConnectionFields, EdgeFields, Name, EdgeName, F, Fut, M>(
+    after: Option<String>,
+    before: Option<String>,
+    first: Option<i32>,
+    last: Option<i32>,
+    fields: ConnectionFields,
+    fetch: F,
+    to_edge: M,
+) -> Result<Connection<Cursor, Node, ConnectionFields, EdgeFields, Name, EdgeName>>
+where
+    Node: OutputType,
+    ConnectionFields: ObjectType,
+    EdgeFields: ObjectType,
+    Name: ConnectionNameType,
+    EdgeName: EdgeNameType,
+    F: FnOnce(PageQuery) -> Fut,
+    Fut: Future<Output = std::result::Result<Page<T>, AppError>>,
+    M: Fn(T) -> (Node, EdgeFields),
+{
+    connection::query(
+        after,
+        before,
+        first,
+        last,
+        |after, before, first, last| async move {
+            let query = PageQuery::new(first, after, last, before).map_err(gql_error)?;
+            let page = fetch(query).await.map_err(gql_error)?;
+
+            let mut connection = Connection::with_additional_fields(
+                page.has_previous_page,
+                page.has_next_page,
+                fields,
+            );
+            connection.edges = page
+                .items
+                .into_iter()
+                .map(|(cursor, item)| {
+                    let (node, fields) = to_edge(item);
+                    Edge::with_additional_fields(cursor, node, fields)
+                })
+                .collect();
+            Ok::<_, async_graphql::Error>(connection)
+        },
+    )
+    .await
+}
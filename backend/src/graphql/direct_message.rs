@@ -0,0 +1,137 @@
+use async_graphql::dataloader::DataLoader;
+use async_graphql::{Context, Object, Result};
+use uuid::Uuid;
+
+use crate::graphql::loaders::{
+    ConversationMembersLoader, LastMessageLoader, UnreadMessageCountLoader, UserLoader, ViewerKey,
+};
+use crate::graphql::query::{UserType, load_user};
+use crate::graphql::{FETCH_COST, gql_error};
+use crate::models::{DirectConversation, DirectConversationMember, DirectMessage};
+
+/// ダイレクトメッセージの会話（参加者にだけ返す）
+pub struct DirectConversationType(pub DirectConversation);
+
+#[Object]
+impl DirectConversationType {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    async fn created_at(&self) -> &str {
+        &self.0.created_at
+    }
+
+    /// 最後のメッセージの日時（メッセージがなければ作成日時）
+    async fn last_message_at(&self) -> &str {
+        &self.0.last_message_at
+    }
+
+    /// 自分を含む参加者
+    #[graphql(complexity = "FETCH_COST * 2 + child_complexity")]
+    async fn members(&self, ctx: &Context<'_>) -> Result<Vec<UserType>> {
+        let members = load_members(ctx, self.0.id).await?;
+        load_users(ctx, members.into_iter().map(|m| m.user_id).collect()).await
+    }
+
+    #[graphql(complexity = "FETCH_COST + child_complexity")]
+    async fn last_message(&self, ctx: &Context<'_>) -> Result<Option<DirectMessageType>> {
+        let loader = ctx.data::<DataLoader<LastMessageLoader>>()?;
+        let message = loader.load_one(self.0.id).await.map_err(gql_error)?;
+        Ok(message.map(DirectMessageType::from))
+    }
+
+    /// 現在のユーザーがまだ読んでいない、他の参加者のメッセージの数
+    #[graphql(complexity = "FETCH_COST")]
+    async fn unread_count(&self, ctx: &Context<'_>) -> Result<i64> {
+        let Ok(viewer) = ctx.data::<Uuid>() else {
+            return Ok(0);
+        };
+        let loader = ctx.data::<DataLoader<UnreadMessageCountLoader>>()?;
+
+        let key = ViewerKey {
+            viewer: *viewer,
+            id: self.0.id,
+        };
+        let count = loader.load_one(key).await.map_err(gql_error)?;
+        Ok(count.unwrap_or(0))
+    }
+}
+
+impl From<DirectConversation> for DirectConversationType {
+    fn from(conversation: DirectConversation) -> Self {
+        Self(conversation)
+    }
+}
+
+pub struct DirectMessageType(pub DirectMessage);
+
+#[Object]
+impl DirectMessageType {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    async fn conversation_id(&self) -> Uuid {
+        self.0.conversation_id
+    }
+
+    async fn content(&self) -> &str {
+        &self.0.content
+    }
+
+    async fn created_at(&self) -> &str {
+        &self.0.created_at
+    }
+
+    #[graphql(complexity = "FETCH_COST + child_complexity")]
+    async fn sender(&self, ctx: &Context<'_>) -> Result<Option<UserType>> {
+        load_user(ctx, self.0.sender_id).await
+    }
+
+    /// 既読にした送信者以外の参加者（既読の通知）
+    #[graphql(complexity = "FETCH_COST * 2 + child_complexity")]
+    async fn read_by(&self, ctx: &Context<'_>) -> Result<Vec<UserType>> {
+        let members = load_members(ctx, self.0.conversation_id).await?;
+        let readers = members
+            .into_iter()
+            .filter(|m| {
+                m.user_id != self.0.sender_id
+                    && m.last_read_at
+                        .as_ref()
+                        .is_some_and(|read| *read >= self.0.created_at)
+            })
+            .map(|m| m.user_id)
+            .collect();
+        load_users(ctx, readers).await
+    }
+}
+
+impl From<DirectMessage> for DirectMessageType {
+    fn from(message: DirectMessage) -> Self {
+        Self(message)
+    }
+}
+
+async fn load_members(
+    ctx: &Context<'_>,
+    conversation_id: Uuid,
+) -> Result<Vec<DirectConversationMember>> {
+    let loader = ctx.data::<DataLoader<ConversationMembersLoader>>()?;
+    let members = loader.load_one(conversation_id).await.map_err(gql_error)?;
+    Ok(members.unwrap_or_default())
+}
+
+/// ユーザーを user_ids の順に取得する
+async fn load_users(ctx: &Context<'_>, user_ids: Vec<Uuid>) -> Result<Vec<UserType>> {
+    let loader = ctx.data::<DataLoader<UserLoader>>()?;
+    let mut users = loader
+        .load_many(user_ids.clone())
+        .await
+        .map_err(gql_error)?;
+    Ok(user_ids
+        .iter()
+        .filter_map(|id| users.remove(id))
+        .map(UserType::from)
+        .collect())
+}
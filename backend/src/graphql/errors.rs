@@ -0,0 +1,62 @@
+//! GraphQLエラーのコード
+//!
+//! サービス層のエラーは `AppError` の `ErrorExtensions` で code を付ける。
+//! 構文・検証・複雑さの制限など、スキーマの実行前に起きるエラーにはここで BAD_REQUEST を付ける
+
+use async_graphql::extensions::{
+    Extension, ExtensionContext, ExtensionFactory, NextRequest, NextSubscribe,
+};
+use async_graphql::futures_util::StreamExt;
+use async_graphql::futures_util::stream::BoxStream;
+use async_graphql::{ErrorExtensionValues, Response, ServerError};
+use std::sync::Arc;
+
+/// code のないエラーに付けるコード
+const DEFAULT_CODE: &str = "BAD_REQUEST";
+
+/// コード付きのリクエストエラー
+pub(crate) fn request_error(message: impl Into<String>, code: &str) -> ServerError {
+    let mut extensions = ErrorExtensionValues::default();
+    extensions.set("code", code);
+    ServerError {
+        extensions: Some(extensions),
+        ..ServerError::new(message, None)
+    }
+}
+
+/// すべてのエラーに code を付ける拡張
+pub struct ErrorCodes;
+
+impl ExtensionFactory for ErrorCodes {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(ErrorCodesExtension)
+    }
+}
+
+struct ErrorCodesExtension;
+
+#[async_trait::async_trait]
+impl Extension for ErrorCodesExtension {
+    async fn request(&self, ctx: &ExtensionContext<'_>, next: NextRequest<'_>) -> Response {
+        with_codes(next.run(ctx).await)
+    }
+
+    fn subscribe<'s>(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        stream: BoxStream<'s, Response>,
+        next: NextSubscribe<'_>,
+    ) -> BoxStream<'s, Response> {
+        next.run(ctx, stream).map(with_codes).boxed()
+    }
+}
+
+fn with_codes(mut resp: Response) -> Response {
+    for err in &mut resp.errors {
+        let extensions = err.extensions.get_or_insert_with(Default::default);
+        if extensions.get("code").is_none() {
+            extensions.set("code", DEFAULT_CODE);
+        }
+    }
+    resp
+}
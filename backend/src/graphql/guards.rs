@@ -0,0 +1,65 @@
+//! フィールドの認可ガード
+//!
+//! ガードを満たさないフィールドはエラーになる。公開される型の一部だけを隠すときは `redact` を使う
+
+use async_graphql::{Context, Guard, Result};
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::graphql::{current_role, current_user, gql_error};
+use crate::roles::{Permission, RoleGuard};
+
+/// ログイン中のユーザーのみ（未ログインなら UNAUTHENTICATED）
+pub struct LoginGuard;
+
+impl Guard for LoginGuard {
+    async fn check(&self, ctx: &Context<'_>) -> Result<()> {
+        current_user(ctx).map(|_| ())
+    }
+}
+
+/// 権限を持つロールのみ（未ログインなら UNAUTHENTICATED、権限がなければ FORBIDDEN）
+impl Guard for RoleGuard {
+    async fn check(&self, ctx: &Context<'_>) -> Result<()> {
+        current_user(ctx)?;
+        RoleGuard::check(self, current_role(ctx)).map_err(gql_error)
+    }
+}
+
+/// 本人と管理者のみ（他のユーザーや未ログインなら FORBIDDEN）
+pub struct SelfGuard {
+    user_id: Uuid,
+    /// ログイン・登録の結果として本人に返すユーザー（トークンの発行前なので閲覧者がいない）
+    session_owner: bool,
+}
+
+impl SelfGuard {
+    pub fn new(user_id: Uuid, session_owner: bool) -> Self {
+        Self {
+            user_id,
+            session_owner,
+        }
+    }
+}
+
+impl Guard for SelfGuard {
+    async fn check(&self, ctx: &Context<'_>) -> Result<()> {
+        if self.session_owner
+            || ctx.data_opt::<Uuid>() == Some(&self.user_id)
+            || current_role(ctx).can(Permission::ViewPrivateFields)
+        {
+            return Ok(());
+        }
+        Err(gql_error(AppError::Forbidden(
+            "Not authorized to view this field".to_string(),
+        )))
+    }
+}
+
+/// ガードを満たさなければ null にする
+///
+/// フィールドのガードが失敗すると親のオブジェクトごと null になるため、
+/// 一部のフィールドだけを隠すときはリゾルバの中でガードを確認する
+pub async fn redact<T>(ctx: &Context<'_>, guard: impl Guard, value: T) -> Option<T> {
+    guard.check(ctx).await.ok().map(|_| value)
+}
@@ -0,0 +1,382 @@
+//! ネストしたフィールドをまとめて取得する DataLoader
+//!
+//! 一覧の各要素が個別にクエリを発行しないよう、同時に要求されたキーを1回の取得にまとめる
+
+use async_graphql::dataloader::{DataLoader, Loader};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::models::{Comment, DirectConversationMember, DirectMessage, Tweet, User};
+use crate::repository::FollowCounts;
+use crate::services::{MAX_NOTIFICATION_ACTORS, Services};
+
+type Result<T> = std::result::Result<T, AppError>;
+
+/// バッチの取得を tokio のタスクで実行する DataLoader を作る
+pub fn loader<T>(loader: T) -> DataLoader<T> {
+    DataLoader::new(loader, tokio::spawn)
+}
+
+/// 閲覧者によって値が変わるフィールドのキー
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ViewerKey {
+    pub viewer: Uuid,
+    pub id: Uuid,
+}
+
+/// ユーザー
+pub struct UserLoader(pub Services);
+
+impl Loader<Uuid> for UserLoader {
+    type Value = User;
+    type Error = AppError;
+
+    async fn load(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, User>> {
+        self.0.users.find_many(ids).await
+    }
+}
+
+/// ユーザーのフォロワー数とフォロー中の数
+pub struct FollowCountsLoader(pub Services);
+
+impl Loader<Uuid> for FollowCountsLoader {
+    type Value = FollowCounts;
+    type Error = AppError;
+
+    async fn load(&self, user_ids: &[Uuid]) -> Result<HashMap<Uuid, FollowCounts>> {
+        self.0.social.counts(user_ids).await
+    }
+}
+
+/// 閲覧者がユーザーをフォローしているか
+pub struct FollowingLoader(pub Services);
+
+impl Loader<ViewerKey> for FollowingLoader {
+    type Value = bool;
+    type Error = AppError;
+
+    async fn load(&self, keys: &[ViewerKey]) -> Result<HashMap<ViewerKey, bool>> {
+        let mut result = HashMap::new();
+        for (viewer, user_ids) in group_by_viewer(keys) {
+            let following_set = self.0.social.following_set(viewer, &user_ids).await?;
+            result.extend(user_ids.into_iter().map(|id| {
+                // 自分自身はフォローできないので常に false
+                let is_following = id != viewer && following_set.contains(&id);
+                (ViewerKey { viewer, id }, is_following)
+            }));
+        }
+        Ok(result)
+    }
+}
+
+/// ツイート
+pub struct TweetLoader(pub Services);
+
+impl Loader<Uuid> for TweetLoader {
+    type Value = Tweet;
+    type Error = AppError;
+
+    async fn load(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, Tweet>> {
+        self.0.tweets.find_many(ids).await
+    }
+}
+
+/// コメント
+pub struct CommentLoader(pub Services);
+
+impl Loader<Uuid> for CommentLoader {
+    type Value = Comment;
+    type Error = AppError;
+
+    async fn load(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, Comment>> {
+        self.0.tweets.find_comments(ids).await
+    }
+}
+
+/// ツイートのいいね数
+pub struct LikeCountLoader(pub Services);
+
+impl Loader<Uuid> for LikeCountLoader {
+    type Value = i64;
+    type Error = AppError;
+
+    async fn load(&self, tweet_ids: &[Uuid]) -> Result<HashMap<Uuid, i64>> {
+        self.0.tweets.like_counts(tweet_ids).await
+    }
+}
+
+/// ツイートへの直接のコメント数
+///
+/// 未ログインなら ID、ログイン中なら閲覧者から見えないユーザーを除くため ViewerKey で取得する
+pub struct TweetReplyCountLoader(pub Services);
+
+impl Loader<Uuid> for TweetReplyCountLoader {
+    type Value = i64;
+    type Error = AppError;
+
+    async fn load(&self, tweet_ids: &[Uuid]) -> Result<HashMap<Uuid, i64>> {
+        self.0.tweets.tweet_reply_counts(tweet_ids, None).await
+    }
+}
+
+impl Loader<ViewerKey> for TweetReplyCountLoader {
+    type Value = i64;
+    type Error = AppError;
+
+    async fn load(&self, keys: &[ViewerKey]) -> Result<HashMap<ViewerKey, i64>> {
+        let mut result = HashMap::new();
+        for (viewer, tweet_ids) in group_by_viewer(keys) {
+            let counts = self
+                .0
+                .tweets
+                .tweet_reply_counts(&tweet_ids, Some(viewer))
+                .await?;
+            result.extend(
+                counts
+                    .into_iter()
+                    .map(|(id, count)| (ViewerKey { viewer, id }, count)),
+            );
+        }
+        Ok(result)
+    }
+}
+
+/// コメントへの直接の返信数（キーは TweetReplyCountLoader と同じ）
+pub struct CommentReplyCountLoader(pub Services);
+
+impl Loader<Uuid> for CommentReplyCountLoader {
+    type Value = i64;
+    type Error = AppError;
+
+    async fn load(&self, comment_ids: &[Uuid]) -> Result<HashMap<Uuid, i64>> {
+        self.0.tweets.comment_reply_counts(comment_ids, None).await
+    }
+}
+
+impl Loader<ViewerKey> for CommentReplyCountLoader {
+    type Value = i64;
+    type Error = AppError;
+
+    async fn load(&self, keys: &[ViewerKey]) -> Result<HashMap<ViewerKey, i64>> {
+        let mut result = HashMap::new();
+        for (viewer, comment_ids) in group_by_viewer(keys) {
+            let counts = self
+                .0
+                .tweets
+                .comment_reply_counts(&comment_ids, Some(viewer))
+                .await?;
+            result.extend(
+                counts
+                    .into_iter()
+                    .map(|(id, count)| (ViewerKey { viewer, id }, count)),
+            );
+        }
+        Ok(result)
+    }
+}
+
+/// ツイートで言及されたユーザーID
+pub struct TweetMentionsLoader(pub Services);
+
+impl Loader<Uuid> for TweetMentionsLoader {
+    type Value = Vec<Uuid>;
+    type Error = AppError;
+
+    async fn load(&self, tweet_ids: &[Uuid]) -> Result<HashMap<Uuid, Vec<Uuid>>> {
+        self.0.tweets.tweet_mentions(tweet_ids).await
+    }
+}
+
+/// コメントで言及されたユーザーID
+pub struct CommentMentionsLoader(pub Services);
+
+impl Loader<Uuid> for CommentMentionsLoader {
+    type Value = Vec<Uuid>;
+    type Error = AppError;
+
+    async fn load(&self, comment_ids: &[Uuid]) -> Result<HashMap<Uuid, Vec<Uuid>>> {
+        self.0.tweets.comment_mentions(comment_ids).await
+    }
+}
+
+/// 閲覧者がツイートにいいねしているか
+pub struct LikedLoader(pub Services);
+
+impl Loader<ViewerKey> for LikedLoader {
+    type Value = bool;
+    type Error = AppError;
+
+    async fn load(&self, keys: &[ViewerKey]) -> Result<HashMap<ViewerKey, bool>> {
+        let mut result = HashMap::new();
+        for (viewer, tweet_ids) in group_by_viewer(keys) {
+            let liked = self.0.tweets.liked_by(viewer, &tweet_ids).await?;
+            result.extend(
+                tweet_ids
+                    .into_iter()
+                    .map(|id| (ViewerKey { viewer, id }, liked.contains(&id))),
+            );
+        }
+        Ok(result)
+    }
+}
+
+/// ツイートのリツイート数
+pub struct RetweetCountLoader(pub Services);
+
+impl Loader<Uuid> for RetweetCountLoader {
+    type Value = i64;
+    type Error = AppError;
+
+    async fn load(&self, tweet_ids: &[Uuid]) -> Result<HashMap<Uuid, i64>> {
+        self.0.tweets.retweet_counts(tweet_ids).await
+    }
+}
+
+/// ツイートの引用数
+pub struct QuoteCountLoader(pub Services);
+
+impl Loader<Uuid> for QuoteCountLoader {
+    type Value = i64;
+    type Error = AppError;
+
+    async fn load(&self, tweet_ids: &[Uuid]) -> Result<HashMap<Uuid, i64>> {
+        self.0.tweets.quote_counts(tweet_ids).await
+    }
+}
+
+/// 閲覧者がツイートをリツイートしているか
+pub struct RetweetedLoader(pub Services);
+
+impl Loader<ViewerKey> for RetweetedLoader {
+    type Value = bool;
+    type Error = AppError;
+
+    async fn load(&self, keys: &[ViewerKey]) -> Result<HashMap<ViewerKey, bool>> {
+        let mut result = HashMap::new();
+        for (viewer, tweet_ids) in group_by_viewer(keys) {
+            let retweeted = self.0.tweets.retweeted_by(viewer, &tweet_ids).await?;
+            result.extend(
+                tweet_ids
+                    .into_iter()
+                    .map(|id| (ViewerKey { viewer, id }, retweeted.contains(&id))),
+            );
+        }
+        Ok(result)
+    }
+}
+
+/// ツイートのハッシュタグ
+pub struct HashtagLoader(pub Services);
+
+impl Loader<Uuid> for HashtagLoader {
+    type Value = Vec<String>;
+    type Error = AppError;
+
+    async fn load(&self, tweet_ids: &[Uuid]) -> Result<HashMap<Uuid, Vec<String>>> {
+        self.0.tweets.hashtags(tweet_ids).await
+    }
+}
+
+/// 会話の参加者と既読日時
+pub struct ConversationMembersLoader(pub Services);
+
+impl Loader<Uuid> for ConversationMembersLoader {
+    type Value = Vec<DirectConversationMember>;
+    type Error = AppError;
+
+    async fn load(
+        &self,
+        conversation_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, Vec<DirectConversationMember>>> {
+        self.0.direct_messages.members(conversation_ids).await
+    }
+}
+
+/// 会話の最後のメッセージ
+pub struct LastMessageLoader(pub Services);
+
+impl Loader<Uuid> for LastMessageLoader {
+    type Value = DirectMessage;
+    type Error = AppError;
+
+    async fn load(&self, conversation_ids: &[Uuid]) -> Result<HashMap<Uuid, DirectMessage>> {
+        self.0.direct_messages.last_messages(conversation_ids).await
+    }
+}
+
+/// 閲覧者の会話ごとの未読メッセージ数
+pub struct UnreadMessageCountLoader(pub Services);
+
+impl Loader<ViewerKey> for UnreadMessageCountLoader {
+    type Value = i64;
+    type Error = AppError;
+
+    async fn load(&self, keys: &[ViewerKey]) -> Result<HashMap<ViewerKey, i64>> {
+        let mut result = HashMap::new();
+        for (viewer, conversation_ids) in group_by_viewer(keys) {
+            let counts = self
+                .0
+                .direct_messages
+                .unread_counts(viewer, &conversation_ids)
+                .await?;
+            result.extend(conversation_ids.into_iter().map(|id| {
+                let count = counts.get(&id).copied().unwrap_or(0);
+                (ViewerKey { viewer, id }, count)
+            }));
+        }
+        Ok(result)
+    }
+}
+
+/// ユーザーが受け取ったまとめた通知のキー
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NotificationGroupKey {
+    pub user_id: Uuid,
+    pub group_key: String,
+}
+
+/// まとめた通知の最近操作したユーザー（新しい順）
+pub struct NotificationActorsLoader(pub Services);
+
+impl Loader<NotificationGroupKey> for NotificationActorsLoader {
+    type Value = Vec<Uuid>;
+    type Error = AppError;
+
+    async fn load(
+        &self,
+        keys: &[NotificationGroupKey],
+    ) -> Result<HashMap<NotificationGroupKey, Vec<Uuid>>> {
+        let mut groups: HashMap<Uuid, Vec<String>> = HashMap::new();
+        for key in keys {
+            groups
+                .entry(key.user_id)
+                .or_default()
+                .push(key.group_key.clone());
+        }
+
+        let mut result = HashMap::new();
+        for (user_id, group_keys) in groups {
+            let actors = self
+                .0
+                .notifications
+                .recent_actors(user_id, &group_keys, MAX_NOTIFICATION_ACTORS)
+                .await?;
+            result.extend(
+                actors
+                    .into_iter()
+                    .map(|(group_key, ids)| (NotificationGroupKey { user_id, group_key }, ids)),
+            );
+        }
+        Ok(result)
+    }
+}
+
+/// キーを閲覧者ごとにまとめる（閲覧者ごとに1回ずつ取得する）
+fn group_by_viewer(keys: &[ViewerKey]) -> HashMap<Uuid, Vec<Uuid>> {
+    let mut groups: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    for key in keys {
+        groups.entry(key.viewer).or_default().push(key.id);
+    }
+    groups
+}
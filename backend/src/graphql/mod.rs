@@ -1,16 +1,103 @@
+mod connection;
+mod direct_message;
+mod errors;
+mod guards;
+mod loaders;
 mod mutation;
+mod notification;
+mod persisted;
 pub mod query;
+mod search;
+mod subscription;
 
-use async_graphql::{EmptySubscription, Schema};
+use async_graphql::{Context, ErrorExtensions, Schema};
+use errors::ErrorCodes;
+use loaders::{
+    CommentLoader, CommentMentionsLoader, CommentReplyCountLoader, ConversationMembersLoader,
+    FollowCountsLoader, FollowingLoader, HashtagLoader, LastMessageLoader, LikeCountLoader,
+    LikedLoader, NotificationActorsLoader, QuoteCountLoader, RetweetCountLoader, RetweetedLoader,
+    TweetLoader, TweetMentionsLoader, TweetReplyCountLoader, UnreadMessageCountLoader, UserLoader,
+    loader,
+};
 use mutation::MutationRoot;
 use query::QueryRoot;
+use subscription::SubscriptionRoot;
+use uuid::Uuid;
 
-use crate::store::Db;
+pub(crate) use errors::request_error;
+pub use persisted::PersistedQueries;
 
-pub type AppSchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
+use crate::config::GraphqlConfig;
+use crate::error::AppError;
+use crate::models::Role;
+use crate::roles::Actor;
+use crate::services::Services;
 
-pub fn create_schema(db: Db) -> AppSchema {
-    Schema::build(QueryRoot, MutationRoot, EmptySubscription)
-        .data(db)
-        .finish()
+pub type AppSchema = Schema<QueryRoot, MutationRoot, SubscriptionRoot>;
+
+/// DataLoader やクエリで取得するフィールドの複雑さのコスト（通常のフィールドは 1）
+pub(crate) const FETCH_COST: usize = 2;
+
+pub fn create_schema(
+    services: Services,
+    config: &GraphqlConfig,
+    persisted: PersistedQueries,
+) -> AppSchema {
+    let mut builder = Schema::build(QueryRoot, MutationRoot, SubscriptionRoot)
+        .limit_depth(config.max_depth)
+        .limit_complexity(config.max_complexity)
+        // 他の拡張のエラーにも code を付けるため最初に登録する
+        .extension(ErrorCodes)
+        .extension(persisted)
+        .data(loader(UserLoader(services.clone())))
+        .data(loader(TweetLoader(services.clone())))
+        .data(loader(FollowCountsLoader(services.clone())))
+        .data(loader(FollowingLoader(services.clone())))
+        .data(loader(LikeCountLoader(services.clone())))
+        .data(loader(LikedLoader(services.clone())))
+        .data(loader(RetweetCountLoader(services.clone())))
+        .data(loader(QuoteCountLoader(services.clone())))
+        .data(loader(RetweetedLoader(services.clone())))
+        .data(loader(TweetReplyCountLoader(services.clone())))
+        .data(loader(CommentReplyCountLoader(services.clone())))
+        .data(loader(TweetMentionsLoader(services.clone())))
+        .data(loader(CommentMentionsLoader(services.clone())))
+        .data(loader(HashtagLoader(services.clone())))
+        .data(loader(CommentLoader(services.clone())))
+        .data(loader(NotificationActorsLoader(services.clone())))
+        .data(loader(ConversationMembersLoader(services.clone())))
+        .data(loader(LastMessageLoader(services.clone())))
+        .data(loader(UnreadMessageCountLoader(services.clone())))
+        .data(services);
+    if config.persisted_only {
+        builder = builder.disable_introspection();
+    }
+    builder.finish()
+}
+
+/// サービス層のエラーをGraphQLエラーに変換する
+pub(crate) fn gql_error(err: AppError) -> async_graphql::Error {
+    err.extend()
+}
+
+/// ログイン中のユーザーID（未ログインなら UNAUTHENTICATED）
+pub(crate) fn current_user<'a>(ctx: &Context<'a>) -> async_graphql::Result<&'a Uuid> {
+    ctx.data_opt::<Uuid>().ok_or_else(|| {
+        gql_error(AppError::Unauthorized(
+            "Authentication required".to_string(),
+        ))
+    })
+}
+
+/// ログイン中のユーザーとロール（未ログインなら UNAUTHENTICATED）
+pub(crate) fn current_actor(ctx: &Context<'_>) -> async_graphql::Result<Actor> {
+    Ok(Actor {
+        user_id: *current_user(ctx)?,
+        role: current_role(ctx),
+    })
+}
+
+/// 閲覧中のユーザーのロール（未ログインなら一般ユーザー）
+pub(crate) fn current_role(ctx: &Context<'_>) -> Role {
+    ctx.data_opt::<Role>().copied().unwrap_or_default()
 }
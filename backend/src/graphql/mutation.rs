@@ -1,299 +1,516 @@
 use async_graphql::{Context, InputObject, Object, Result};
-use chrono::Utc;
 use uuid::Uuid;
 
-use crate::graphql::query::{CommentType, TweetType, UserType};
-use crate::models::User;
-use crate::store::Db;
-use crate::utils::{create_jwt, extract_hashtags, hash_password, verify_password};
+use crate::graphql::direct_message::DirectMessageType;
+use crate::graphql::guards::LoginGuard;
+use crate::graphql::notification::NotificationPreferenceType;
+use crate::graphql::query::{CommentType, ReportType, TweetType, UserType};
+use crate::graphql::{current_actor, current_user, gql_error};
+use crate::models::{NotificationKind, ReportTarget, Role};
+use crate::roles::{Permission, RoleGuard};
+use crate::services::{AuthSession, Services};
+use crate::sessions::{ClientInfo, CurrentSession};
 
 pub struct MutationRoot;
 
 #[Object]
 impl MutationRoot {
     async fn register(&self, ctx: &Context<'_>, input: RegisterInput) -> Result<AuthPayload> {
-        let db = ctx.data::<Db>()?;
+        let services = ctx.data::<Services>()?;
+        let client = ctx.data_opt::<ClientInfo>().cloned().unwrap_or_default();
 
-        let exists: Option<(i32,)> = sqlx::query_as("SELECT 1 FROM users WHERE email = ?")
-            .bind(&input.email)
-            .fetch_optional(db)
-            .await?;
+        let session = services
+            .users
+            .register(&input.username, &input.email, &input.password, &client)
+            .await
+            .map_err(gql_error)?;
 
-        if exists.is_some() {
-            return Err(async_graphql::Error::new("Email already exists"));
-        }
-
-        let password_hash =
-            hash_password(&input.password).map_err(|e| async_graphql::Error::new(e.to_string()))?;
-        let user_id = Uuid::new_v4();
-        let created_at = Utc::now().to_rfc3339();
-
-        sqlx::query(
-            "INSERT INTO users (id, username, email, password_hash, created_at) VALUES (?, ?, ?, ?, ?)",
-        )
-        .bind(user_id)
-        .bind(&input.username)
-        .bind(&input.email)
-        .bind(&password_hash)
-        .bind(&created_at)
-        .execute(db)
-        .await?;
-
-        let user = User {
-            id: user_id,
-            username: input.username.clone(),
-            email: input.email.clone(),
-            password_hash,
-            created_at,
-        };
-
-        let token = create_jwt(user_id).map_err(|e| async_graphql::Error::new(e.to_string()))?;
-
-        Ok(AuthPayload {
-            token,
-            user: UserType::from(user),
-        })
+        Ok(AuthPayload::from(session))
     }
 
     async fn login(&self, ctx: &Context<'_>, input: LoginInput) -> Result<AuthPayload> {
-        let db = ctx.data::<Db>()?;
+        let services = ctx.data::<Services>()?;
+        let client = ctx.data_opt::<ClientInfo>().cloned().unwrap_or_default();
 
-        let user: User = sqlx::query_as("SELECT * FROM users WHERE email = ?")
-            .bind(&input.email)
-            .fetch_optional(db)
-            .await?
-            .ok_or_else(|| async_graphql::Error::new("Invalid email or password"))?;
+        let session = services
+            .users
+            .login(&input.email, &input.password, &client)
+            .await
+            .map_err(gql_error)?;
 
-        let valid = verify_password(&input.password, &user.password_hash)
-            .map_err(|e| async_graphql::Error::new(e.to_string()))?;
+        Ok(AuthPayload::from(session))
+    }
 
-        if !valid {
-            return Err(async_graphql::Error::new("Invalid email or password"));
-        }
+    /// リフレッシュトークンをローテーションし、新しいトークンの組を発行する
+    async fn refresh_token(&self, ctx: &Context<'_>, refresh_token: String) -> Result<AuthPayload> {
+        let services = ctx.data::<Services>()?;
+
+        let session = services
+            .users
+            .refresh(&refresh_token)
+            .await
+            .map_err(gql_error)?;
+
+        Ok(AuthPayload::from(session))
+    }
+
+    /// ログアウト: リフレッシュトークンのファミリーとセッションを失効させる
+    async fn logout(&self, ctx: &Context<'_>, refresh_token: String) -> Result<bool> {
+        let services = ctx.data::<Services>()?;
 
-        let token = create_jwt(user.id).map_err(|e| async_graphql::Error::new(e.to_string()))?;
+        services
+            .users
+            .logout(&refresh_token)
+            .await
+            .map_err(gql_error)?;
 
-        Ok(AuthPayload {
-            token,
-            user: UserType::from(user),
-        })
+        Ok(true)
+    }
+
+    /// 指定したセッションを失効させる（他の端末からのログアウト）
+    #[graphql(guard = "LoginGuard")]
+    async fn revoke_session(&self, ctx: &Context<'_>, id: Uuid) -> Result<bool> {
+        let services = ctx.data::<Services>()?;
+        let user_id = current_user(ctx)?;
+
+        services
+            .users
+            .revoke_session(*user_id, id)
+            .await
+            .map_err(gql_error)?;
+
+        Ok(true)
     }
 
+    /// 現在のセッション以外をすべて失効させ、失効させた件数を返す
+    #[graphql(guard = "LoginGuard")]
+    async fn revoke_all_other_sessions(&self, ctx: &Context<'_>) -> Result<u64> {
+        let services = ctx.data::<Services>()?;
+        let user_id = current_user(ctx)?;
+        let current = ctx.data::<CurrentSession>()?;
+
+        let revoked = services
+            .users
+            .revoke_other_sessions(*user_id, current.0)
+            .await
+            .map_err(gql_error)?;
+
+        Ok(revoked)
+    }
+
+    #[graphql(guard = "LoginGuard")]
     async fn create_tweet(&self, ctx: &Context<'_>, content: String) -> Result<TweetType> {
-        let db = ctx.data::<Db>()?;
-        let user_id = ctx.data::<Uuid>()?;
+        let services = ctx.data::<Services>()?;
+        let user_id = current_user(ctx)?;
 
-        if content.is_empty() || content.len() > 280 {
-            return Err("Tweet content must be between 1 and 280 characters".into());
-        }
+        let tweet = services
+            .tweets
+            .create(*user_id, &content)
+            .await
+            .map_err(gql_error)?;
 
-        let tweet_id = Uuid::new_v4();
-        let created_at = Utc::now().to_rfc3339();
-
-        sqlx::query("INSERT INTO tweets (id, user_id, content, created_at) VALUES (?, ?, ?, ?)")
-            .bind(tweet_id)
-            .bind(user_id)
-            .bind(&content)
-            .bind(&created_at)
-            .execute(db)
-            .await?;
-
-        let hashtag_names = extract_hashtags(&content);
-        for tag_name in &hashtag_names {
-            sqlx::query("INSERT OR IGNORE INTO hashtags (id, name) VALUES (?, ?)")
-                .bind(Uuid::new_v4())
-                .bind(tag_name)
-                .execute(db)
-                .await?;
-
-            let (hashtag_id,): (Uuid,) = sqlx::query_as("SELECT id FROM hashtags WHERE name = ?")
-                .bind(tag_name)
-                .fetch_one(db)
-                .await?;
-
-            sqlx::query("INSERT INTO tweet_hashtags (tweet_id, hashtag_id) VALUES (?, ?)")
-                .bind(tweet_id)
-                .bind(hashtag_id)
-                .execute(db)
-                .await?;
-        }
+        Ok(TweetType::from(tweet.tweet))
+    }
 
-        Ok(TweetType {
-            id: tweet_id,
-            user_id: *user_id,
-            content,
-            created_at,
-            like_count: 0,
-            is_liked: false,
-            hashtags: hashtag_names,
-        })
+    /// ツイートを引用してコメント付きで投稿する
+    #[graphql(guard = "LoginGuard")]
+    async fn quote_tweet(
+        &self,
+        ctx: &Context<'_>,
+        tweet_id: Uuid,
+        content: String,
+    ) -> Result<TweetType> {
+        let services = ctx.data::<Services>()?;
+        let user_id = current_user(ctx)?;
+
+        let tweet = services
+            .tweets
+            .quote(*user_id, tweet_id, &content)
+            .await
+            .map_err(gql_error)?;
+
+        Ok(TweetType::from(tweet.tweet))
     }
 
+    /// ツイートを削除する（モデレーター以上は他のユーザーのツイートも削除できる）
+    #[graphql(guard = "LoginGuard")]
     async fn delete_tweet(&self, ctx: &Context<'_>, id: Uuid) -> Result<bool> {
-        let db = ctx.data::<Db>()?;
-        let user_id = ctx.data::<Uuid>()?;
+        let services = ctx.data::<Services>()?;
+        let actor = current_actor(ctx)?;
 
-        let result = sqlx::query("DELETE FROM tweets WHERE id = ? AND user_id = ?")
-            .bind(id)
-            .bind(user_id)
-            .execute(db)
-            .await?;
-
-        if result.rows_affected() == 0 {
-            return Err("Tweet not found or not authorized".into());
-        }
+        services.tweets.delete(actor, id).await.map_err(gql_error)?;
 
         Ok(true)
     }
 
+    #[graphql(guard = "LoginGuard")]
     async fn like_tweet(&self, ctx: &Context<'_>, tweet_id: Uuid) -> Result<bool> {
-        let db = ctx.data::<Db>()?;
-        let user_id = ctx.data::<Uuid>()?;
-
-        sqlx::query_as::<_, (i32,)>("SELECT 1 FROM tweets WHERE id = ?")
-            .bind(tweet_id)
-            .fetch_optional(db)
-            .await?
-            .ok_or("Tweet not found")?;
-
-        let result = sqlx::query(
-            "INSERT OR IGNORE INTO likes (user_id, tweet_id, created_at) VALUES (?, ?, ?)",
-        )
-        .bind(user_id)
-        .bind(tweet_id)
-        .bind(Utc::now().to_rfc3339())
-        .execute(db)
-        .await?;
-
-        if result.rows_affected() == 0 {
-            return Err("Already liked".into());
-        }
+        let services = ctx.data::<Services>()?;
+        let user_id = current_user(ctx)?;
+
+        services
+            .tweets
+            .like(*user_id, tweet_id)
+            .await
+            .map_err(gql_error)?;
 
         Ok(true)
     }
 
+    #[graphql(guard = "LoginGuard")]
     async fn unlike_tweet(&self, ctx: &Context<'_>, tweet_id: Uuid) -> Result<bool> {
-        let db = ctx.data::<Db>()?;
-        let user_id = ctx.data::<Uuid>()?;
+        let services = ctx.data::<Services>()?;
+        let user_id = current_user(ctx)?;
 
-        let result = sqlx::query("DELETE FROM likes WHERE tweet_id = ? AND user_id = ?")
-            .bind(tweet_id)
-            .bind(user_id)
-            .execute(db)
-            .await?;
+        services
+            .tweets
+            .unlike(*user_id, tweet_id)
+            .await
+            .map_err(gql_error)?;
 
-        if result.rows_affected() == 0 {
-            return Err("Like not found".into());
-        }
+        Ok(true)
+    }
+
+    /// リツイートする（フォロワーのタイムラインに載る）
+    #[graphql(guard = "LoginGuard")]
+    async fn retweet(&self, ctx: &Context<'_>, tweet_id: Uuid) -> Result<bool> {
+        let services = ctx.data::<Services>()?;
+        let user_id = current_user(ctx)?;
+
+        services
+            .tweets
+            .retweet(*user_id, tweet_id)
+            .await
+            .map_err(gql_error)?;
+
+        Ok(true)
+    }
+
+    #[graphql(guard = "LoginGuard")]
+    async fn undo_retweet(&self, ctx: &Context<'_>, tweet_id: Uuid) -> Result<bool> {
+        let services = ctx.data::<Services>()?;
+        let user_id = current_user(ctx)?;
+
+        services
+            .tweets
+            .undo_retweet(*user_id, tweet_id)
+            .await
+            .map_err(gql_error)?;
 
         Ok(true)
     }
 
+    /// コメントする（parentId を指定すれば同じツイートのコメントへの返信になる）
+    #[graphql(guard = "LoginGuard")]
     async fn create_comment(
         &self,
         ctx: &Context<'_>,
         tweet_id: Uuid,
         content: String,
+        parent_id: Option<Uuid>,
     ) -> Result<CommentType> {
-        let db = ctx.data::<Db>()?;
-        let user_id = ctx.data::<Uuid>()?;
+        let services = ctx.data::<Services>()?;
+        let user_id = current_user(ctx)?;
 
-        if content.is_empty() || content.len() > 280 {
-            return Err("Comment content must be between 1 and 280 characters".into());
-        }
+        let comment = services
+            .tweets
+            .create_comment(*user_id, tweet_id, parent_id, &content)
+            .await
+            .map_err(gql_error)?;
 
-        sqlx::query_as::<_, (i32,)>("SELECT 1 FROM tweets WHERE id = ?")
-            .bind(tweet_id)
-            .fetch_optional(db)
-            .await?
-            .ok_or("Tweet not found")?;
-
-        let comment_id = Uuid::new_v4();
-        let created_at = Utc::now().to_rfc3339();
-
-        sqlx::query(
-            "INSERT INTO comments (id, tweet_id, user_id, content, created_at) VALUES (?, ?, ?, ?, ?)",
-        )
-        .bind(comment_id)
-        .bind(tweet_id)
-        .bind(user_id)
-        .bind(&content)
-        .bind(&created_at)
-        .execute(db)
-        .await?;
-
-        Ok(CommentType {
-            id: comment_id,
-            tweet_id,
-            user_id: *user_id,
-            content,
-            created_at,
-        })
+        Ok(CommentType::from(comment))
     }
 
+    /// コメントを削除する（モデレーター以上は他のユーザーのコメントも削除できる）
+    #[graphql(guard = "LoginGuard")]
     async fn delete_comment(&self, ctx: &Context<'_>, id: Uuid) -> Result<bool> {
-        let db = ctx.data::<Db>()?;
-        let user_id = ctx.data::<Uuid>()?;
-
-        let result = sqlx::query("DELETE FROM comments WHERE id = ? AND user_id = ?")
-            .bind(id)
-            .bind(user_id)
-            .execute(db)
-            .await?;
+        let services = ctx.data::<Services>()?;
+        let actor = current_actor(ctx)?;
 
-        if result.rows_affected() == 0 {
-            return Err("Comment not found or not authorized".into());
-        }
+        services
+            .tweets
+            .delete_comment(actor, id)
+            .await
+            .map_err(gql_error)?;
 
         Ok(true)
     }
 
+    #[graphql(guard = "LoginGuard")]
     async fn follow_user(&self, ctx: &Context<'_>, target_id: Uuid) -> Result<Uuid> {
-        let db = ctx.data::<Db>()?;
-        let current_user_id = ctx.data::<Uuid>()?;
-
-        if *current_user_id == target_id {
-            return Err("Cannot follow yourself".into());
-        }
+        let services = ctx.data::<Services>()?;
+        let current_user_id = current_user(ctx)?;
 
-        sqlx::query_as::<_, (i32,)>("SELECT 1 FROM users WHERE id = ?")
-            .bind(target_id)
-            .fetch_optional(db)
-            .await?
-            .ok_or("User not found")?;
-
-        let result = sqlx::query(
-            "INSERT OR IGNORE INTO follows (follower_id, following_id, created_at) VALUES (?, ?, ?)",
-        )
-        .bind(current_user_id)
-        .bind(target_id)
-        .bind(Utc::now().to_rfc3339())
-        .execute(db)
-        .await?;
-
-        if result.rows_affected() == 0 {
-            return Err("Already following this user".into());
-        }
+        services
+            .social
+            .follow(*current_user_id, target_id)
+            .await
+            .map_err(gql_error)?;
 
         Ok(target_id)
     }
 
+    #[graphql(guard = "LoginGuard")]
     async fn unfollow_user(&self, ctx: &Context<'_>, target_id: Uuid) -> Result<Uuid> {
-        let db = ctx.data::<Db>()?;
-        let current_user_id = ctx.data::<Uuid>()?;
+        let services = ctx.data::<Services>()?;
+        let current_user_id = current_user(ctx)?;
 
-        let result = sqlx::query("DELETE FROM follows WHERE follower_id = ? AND following_id = ?")
-            .bind(current_user_id)
-            .bind(target_id)
-            .execute(db)
-            .await?;
+        services
+            .social
+            .unfollow(*current_user_id, target_id)
+            .await
+            .map_err(gql_error)?;
 
-        if result.rows_affected() == 0 {
-            return Err("Not following this user".into());
-        }
+        Ok(target_id)
+    }
+
+    /// ブロックする（お互いのフォローは解除され、相手からフォロー・いいね・コメントができなくなる）
+    #[graphql(guard = "LoginGuard")]
+    async fn block_user(&self, ctx: &Context<'_>, target_id: Uuid) -> Result<Uuid> {
+        let services = ctx.data::<Services>()?;
+        let current_user_id = current_user(ctx)?;
+
+        services
+            .social
+            .block(*current_user_id, target_id)
+            .await
+            .map_err(gql_error)?;
+
+        Ok(target_id)
+    }
+
+    /// ブロックを解除する（解除したフォローは戻らない）
+    #[graphql(guard = "LoginGuard")]
+    async fn unblock_user(&self, ctx: &Context<'_>, target_id: Uuid) -> Result<Uuid> {
+        let services = ctx.data::<Services>()?;
+        let current_user_id = current_user(ctx)?;
+
+        services
+            .social
+            .unblock(*current_user_id, target_id)
+            .await
+            .map_err(gql_error)?;
+
+        Ok(target_id)
+    }
+
+    /// ミュートする（相手の投稿と通知を自分に表示しない）
+    #[graphql(guard = "LoginGuard")]
+    async fn mute_user(&self, ctx: &Context<'_>, target_id: Uuid) -> Result<Uuid> {
+        let services = ctx.data::<Services>()?;
+        let current_user_id = current_user(ctx)?;
+
+        services
+            .social
+            .mute(*current_user_id, target_id)
+            .await
+            .map_err(gql_error)?;
 
         Ok(target_id)
     }
+
+    /// ミュートを解除する
+    #[graphql(guard = "LoginGuard")]
+    async fn unmute_user(&self, ctx: &Context<'_>, target_id: Uuid) -> Result<Uuid> {
+        let services = ctx.data::<Services>()?;
+        let current_user_id = current_user(ctx)?;
+
+        services
+            .social
+            .unmute(*current_user_id, target_id)
+            .await
+            .map_err(gql_error)?;
+
+        Ok(target_id)
+    }
+
+    /// 未読の通知をすべて既読にする（既読にした件数を返す）
+    #[graphql(guard = "LoginGuard")]
+    async fn mark_notifications_read(&self, ctx: &Context<'_>) -> Result<u64> {
+        let services = ctx.data::<Services>()?;
+        let user_id = current_user(ctx)?;
+
+        let count = services
+            .notifications
+            .mark_read(*user_id)
+            .await
+            .map_err(gql_error)?;
+
+        Ok(count)
+    }
+
+    /// 種類ごとに通知を受け取るかを設定する
+    #[graphql(guard = "LoginGuard")]
+    async fn set_notification_preference(
+        &self,
+        ctx: &Context<'_>,
+        kind: NotificationKind,
+        enabled: bool,
+    ) -> Result<NotificationPreferenceType> {
+        let services = ctx.data::<Services>()?;
+        let user_id = current_user(ctx)?;
+
+        let preference = services
+            .notifications
+            .set_preference(*user_id, kind, enabled)
+            .await
+            .map_err(gql_error)?;
+
+        Ok(NotificationPreferenceType(preference))
+    }
+
+    /// ダイレクトメッセージを送る（既存の会話なら conversationId、新しい相手なら recipientIds を指定）
+    #[graphql(guard = "LoginGuard")]
+    async fn send_direct_message(
+        &self,
+        ctx: &Context<'_>,
+        conversation_id: Option<Uuid>,
+        recipient_ids: Option<Vec<Uuid>>,
+        content: String,
+    ) -> Result<DirectMessageType> {
+        let services = ctx.data::<Services>()?;
+        let user_id = current_user(ctx)?;
+
+        let message = services
+            .direct_messages
+            .send(
+                *user_id,
+                conversation_id,
+                recipient_ids.as_deref(),
+                &content,
+            )
+            .await
+            .map_err(gql_error)?;
+
+        Ok(DirectMessageType::from(message))
+    }
+
+    /// 会話のこれまでのメッセージを既読にする
+    #[graphql(guard = "LoginGuard")]
+    async fn mark_conversation_read(
+        &self,
+        ctx: &Context<'_>,
+        conversation_id: Uuid,
+    ) -> Result<bool> {
+        let services = ctx.data::<Services>()?;
+        let user_id = current_user(ctx)?;
+
+        services
+            .direct_messages
+            .mark_read(*user_id, conversation_id)
+            .await
+            .map_err(gql_error)?;
+
+        Ok(true)
+    }
+
+    /// 相互フォローでないユーザーからもダイレクトメッセージを受け取るかを設定する
+    #[graphql(guard = "LoginGuard")]
+    async fn set_allow_open_direct_messages(&self, ctx: &Context<'_>, allow: bool) -> Result<bool> {
+        let services = ctx.data::<Services>()?;
+        let user_id = current_user(ctx)?;
+
+        services
+            .direct_messages
+            .set_allow_open_dms(*user_id, allow)
+            .await
+            .map_err(gql_error)?;
+
+        Ok(allow)
+    }
+
+    /// ツイート・コメント・ユーザーを通報する
+    #[graphql(guard = "LoginGuard")]
+    async fn report_content(&self, ctx: &Context<'_>, input: ReportInput) -> Result<ReportType> {
+        let services = ctx.data::<Services>()?;
+        let user_id = current_user(ctx)?;
+
+        let report = services
+            .moderation
+            .report(*user_id, input.target_type, input.target_id, &input.reason)
+            .await
+            .map_err(gql_error)?;
+
+        Ok(ReportType { report })
+    }
+
+    /// 通報を対応済みにする
+    #[graphql(guard = "RoleGuard::new(Permission::ViewReports)")]
+    async fn resolve_report(&self, ctx: &Context<'_>, id: Uuid) -> Result<bool> {
+        let services = ctx.data::<Services>()?;
+        let actor = current_actor(ctx)?;
+
+        services
+            .moderation
+            .resolve_report(actor, id)
+            .await
+            .map_err(gql_error)?;
+
+        Ok(true)
+    }
+
+    /// ユーザーを利用停止にし、ログイン中のセッションをすべて失効させる
+    #[graphql(guard = "RoleGuard::new(Permission::SuspendUsers)")]
+    async fn suspend_user(
+        &self,
+        ctx: &Context<'_>,
+        user_id: Uuid,
+        reason: Option<String>,
+    ) -> Result<bool> {
+        let services = ctx.data::<Services>()?;
+        let actor = current_actor(ctx)?;
+
+        services
+            .moderation
+            .suspend(actor, user_id, reason.as_deref())
+            .await
+            .map_err(gql_error)?;
+
+        Ok(true)
+    }
+
+    /// 利用停止を解除する
+    #[graphql(guard = "RoleGuard::new(Permission::SuspendUsers)")]
+    async fn unsuspend_user(&self, ctx: &Context<'_>, user_id: Uuid) -> Result<bool> {
+        let services = ctx.data::<Services>()?;
+        let actor = current_actor(ctx)?;
+
+        services
+            .moderation
+            .unsuspend(actor, user_id)
+            .await
+            .map_err(gql_error)?;
+
+        Ok(true)
+    }
+
+    /// ユーザーのロールを変更する（対象ユーザーのセッションはすべて失効する）
+    #[graphql(guard = "RoleGuard::new(Permission::ManageRoles)")]
+    async fn set_user_role(
+        &self,
+        ctx: &Context<'_>,
+        user_id: Uuid,
+        role: Role,
+    ) -> Result<UserType> {
+        let services = ctx.data::<Services>()?;
+        let actor = current_actor(ctx)?;
+
+        let user = services
+            .moderation
+            .set_role(actor, user_id, role)
+            .await
+            .map_err(gql_error)?;
+
+        Ok(UserType::from(user))
+    }
+}
+
+/// 通報入力
+#[derive(InputObject)]
+pub struct ReportInput {
+    pub target_type: ReportTarget,
+    pub target_id: Uuid,
+    pub reason: String,
 }
 
 /// 登録入力
@@ -314,6 +531,7 @@ pub struct LoginInput {
 /// 認証レスポンス
 pub struct AuthPayload {
     pub token: String,
+    pub refresh_token: String,
     pub user: UserType,
 }
 
@@ -323,7 +541,24 @@ impl AuthPayload {
         &self.token
     }
 
+    async fn refresh_token(&self) -> &str {
+        &self.refresh_token
+    }
+
     async fn user(&self) -> UserType {
         self.user.clone()
     }
 }
+
+impl From<AuthSession> for AuthPayload {
+    fn from(session: AuthSession) -> Self {
+        Self {
+            token: session.token,
+            refresh_token: session.refresh_token,
+            user: UserType {
+                session_owner: true,
+                ..UserType::from(session.user)
+            },
+        }
+    }
+}
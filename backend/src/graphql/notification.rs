@@ -0,0 +1,163 @@
+use async_graphql::connection::Connection;
+use async_graphql::dataloader::DataLoader;
+use async_graphql::{Context, Object, Result};
+use uuid::Uuid;
+
+use crate::graphql::loaders::{
+    CommentLoader, NotificationActorsLoader, NotificationGroupKey, TweetLoader, UserLoader,
+};
+use crate::graphql::query::{CommentType, TweetType, UserType, load_user};
+use crate::graphql::{FETCH_COST, gql_error};
+use crate::models::NotificationKind;
+use crate::pagination::Cursor;
+use crate::repository::NotificationGroup;
+use crate::services::{NotificationPreference, Services};
+
+/// 通知の Connection（totalCount と unreadCount を持つ）
+pub type NotificationConnection = Connection<Cursor, NotificationType, NotificationCounts>;
+
+/// 通知の Connection の件数（要求されたときだけ数える）
+pub struct NotificationCounts(pub Uuid);
+
+#[Object]
+impl NotificationCounts {
+    /// まとめた通知の数
+    #[graphql(complexity = "FETCH_COST")]
+    async fn total_count(&self, ctx: &Context<'_>) -> Result<i64> {
+        let services = ctx.data::<Services>()?;
+        services
+            .notifications
+            .group_count(self.0)
+            .await
+            .map_err(gql_error)
+    }
+
+    /// 未読の通知を含むまとまりの数
+    #[graphql(complexity = "FETCH_COST")]
+    async fn unread_count(&self, ctx: &Context<'_>) -> Result<i64> {
+        let services = ctx.data::<Services>()?;
+        services
+            .notifications
+            .unread_count(self.0)
+            .await
+            .map_err(gql_error)
+    }
+}
+
+/// 同じ対象への同じ種類の通知をまとめたもの（内容は最新の通知）
+pub struct NotificationType(pub NotificationGroup);
+
+#[Object]
+impl NotificationType {
+    /// 最新の通知の ID
+    async fn id(&self) -> Uuid {
+        self.0.latest.id
+    }
+
+    async fn kind(&self) -> NotificationKind {
+        self.0.latest.kind
+    }
+
+    /// 最新の通知の日時
+    async fn created_at(&self) -> &str {
+        &self.0.latest.created_at
+    }
+
+    /// まとめた通知がすべて既読か
+    async fn is_read(&self) -> bool {
+        !self.0.unread
+    }
+
+    /// 操作したユーザーの数
+    async fn actor_count(&self) -> i64 {
+        self.0.actor_count
+    }
+
+    /// 最近操作したユーザー（新しい順に最大3人）
+    #[graphql(complexity = "FETCH_COST * 2 + child_complexity")]
+    async fn actors(&self, ctx: &Context<'_>) -> Result<Vec<UserType>> {
+        let loader = ctx.data::<DataLoader<NotificationActorsLoader>>()?;
+        let key = NotificationGroupKey {
+            user_id: self.0.latest.user_id,
+            group_key: self.0.latest.group_key.clone(),
+        };
+        let user_ids = loader
+            .load_one(key)
+            .await
+            .map_err(gql_error)?
+            .unwrap_or_default();
+
+        let loader = ctx.data::<DataLoader<UserLoader>>()?;
+        let mut users = loader
+            .load_many(user_ids.clone())
+            .await
+            .map_err(gql_error)?;
+        Ok(user_ids
+            .iter()
+            .filter_map(|id| users.remove(id))
+            .map(UserType::from)
+            .collect())
+    }
+
+    /// 「alice and 3 others liked your tweet」のような通知の文面
+    #[graphql(complexity = "FETCH_COST * 2")]
+    async fn summary(&self, ctx: &Context<'_>) -> Result<String> {
+        let actor = load_user(ctx, self.0.latest.actor_id).await?;
+        let name = actor.map_or_else(|| "Someone".to_string(), |u| u.username);
+        let others = match self.0.actor_count - 1 {
+            0 => String::new(),
+            1 => " and 1 other".to_string(),
+            n => format!(" and {} others", n),
+        };
+        let action = match self.0.latest.kind {
+            NotificationKind::Like => "liked your tweet",
+            NotificationKind::Follow => "followed you",
+            NotificationKind::Comment => "commented on your tweet",
+            NotificationKind::Mention => "mentioned you",
+        };
+        Ok(format!("{}{} {}", name, others, action))
+    }
+
+    /// 対象のツイート（フォローの通知では null）
+    #[graphql(complexity = "FETCH_COST + child_complexity")]
+    async fn tweet(&self, ctx: &Context<'_>) -> Result<Option<TweetType>> {
+        let Some(tweet_id) = self.0.latest.tweet_id else {
+            return Ok(None);
+        };
+        let loader = ctx.data::<DataLoader<TweetLoader>>()?;
+        let tweet = loader.load_one(tweet_id).await.map_err(gql_error)?;
+        Ok(tweet.map(TweetType::from))
+    }
+
+    /// 最新の通知のコメント（コメントでの言及とコメントの通知のみ）
+    #[graphql(complexity = "FETCH_COST + child_complexity")]
+    async fn comment(&self, ctx: &Context<'_>) -> Result<Option<CommentType>> {
+        let Some(comment_id) = self.0.latest.comment_id else {
+            return Ok(None);
+        };
+        let loader = ctx.data::<DataLoader<CommentLoader>>()?;
+        let comment = loader.load_one(comment_id).await.map_err(gql_error)?;
+        Ok(comment.map(CommentType::from))
+    }
+}
+
+impl From<NotificationGroup> for NotificationType {
+    fn from(group: NotificationGroup) -> Self {
+        Self(group)
+    }
+}
+
+/// 通知の種類ごとの受け取り設定
+pub struct NotificationPreferenceType(pub NotificationPreference);
+
+#[Object]
+impl NotificationPreferenceType {
+    async fn kind(&self) -> NotificationKind {
+        self.0.kind
+    }
+
+    /// この種類の通知を受け取るか
+    async fn enabled(&self) -> bool {
+        self.0.enabled
+    }
+}
@@ -0,0 +1,228 @@
+//! 永続化クエリ（Automatic Persisted Queries）
+//!
+//! クライアントはクエリ本文の代わりに `extensions.persistedQuery.sha256Hash` を送れる。
+//! 未登録のハッシュには `PersistedQueryNotFound` を返し、クライアントが本文付きで送り直すと、
+//! 検証と実行がエラーなく終わった場合に登録する。
+//! 本番モードでは許可リストのクエリしか受け付けず、登録もしない
+
+use async_graphql::extensions::{
+    Extension, ExtensionContext, ExtensionFactory, NextPrepareRequest, NextRequest,
+};
+use async_graphql::{Request, Response, ServerResult};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::config::{ConfigError, GraphqlConfig};
+use crate::graphql::request_error;
+
+/// 登録済みのクエリ（ハッシュ → 本文）
+#[derive(Clone)]
+pub struct PersistedQueries {
+    /// 許可リストから読み込んだクエリ
+    allowlist: Arc<HashMap<String, String>>,
+    /// APQ で登録されたクエリ
+    registered: Arc<Mutex<LruCache>>,
+    persisted_only: bool,
+}
+
+impl PersistedQueries {
+    pub fn new(allowlist: HashMap<String, String>, config: &GraphqlConfig) -> Self {
+        Self {
+            allowlist: Arc::new(allowlist),
+            registered: Arc::new(Mutex::new(LruCache::new(config.apq_cache_size))),
+            persisted_only: config.persisted_only,
+        }
+    }
+
+    /// 設定された許可リストを読み込む（指定がなければ空）
+    pub fn from_config(config: &GraphqlConfig) -> Result<Self, ConfigError> {
+        let Some(path) = &config.persisted_queries_path else {
+            return Ok(Self::new(HashMap::new(), config));
+        };
+
+        let text = std::fs::read_to_string(path).map_err(|e| ConfigError::Io(path.clone(), e))?;
+        let allowlist: HashMap<String, String> = serde_json::from_str(&text)
+            .map_err(|e| ConfigError::Invalid("graphql.persisted_queries_path", e.to_string()))?;
+
+        // ハッシュと本文が食い違う許可リストは起動時に拒否する
+        if let Some(hash) = allowlist
+            .keys()
+            .find(|hash| **hash != sha256(&allowlist[*hash]))
+        {
+            return Err(ConfigError::Invalid(
+                "graphql.persisted_queries_path",
+                format!("{} does not match the sha256 of its query", hash),
+            ));
+        }
+
+        Ok(Self::new(allowlist, config))
+    }
+
+    fn get(&self, hash: &str) -> Option<String> {
+        if let Some(query) = self.allowlist.get(hash) {
+            return Some(query.clone());
+        }
+        self.registered.lock().unwrap().get(hash)
+    }
+
+    /// APQ で登録する（上限に達したら最近使われていないクエリを消す）
+    fn register(&self, hash: String, query: String) {
+        self.registered.lock().unwrap().insert(hash, query);
+    }
+
+    /// リクエストのクエリ本文と、成功したら APQ で登録するハッシュを決める
+    fn resolve(&self, mut request: Request) -> ServerResult<(Request, Option<String>)> {
+        let persisted = match request.extensions.remove("persistedQuery") {
+            Some(value) => Some(
+                async_graphql::from_value::<PersistedQuery>(value)
+                    .ok()
+                    .filter(|p| p.version == 1)
+                    .ok_or_else(|| {
+                        request_error("Invalid persistedQuery extension", "BAD_REQUEST")
+                    })?,
+            ),
+            None => None,
+        };
+
+        let mut register = None;
+        match persisted {
+            // ハッシュのみ: 登録済みのクエリを使う
+            Some(persisted) if request.query.is_empty() => {
+                request.query = self.get(&persisted.sha256_hash).ok_or_else(|| {
+                    request_error("PersistedQueryNotFound", "PERSISTED_QUERY_NOT_FOUND")
+                })?;
+            }
+            // ハッシュと本文: 一致を確認し、未登録なら実行に成功した後で登録する
+            Some(persisted) => {
+                let hash = sha256(&request.query);
+                if persisted.sha256_hash != hash {
+                    return Err(request_error(
+                        "provided sha does not match query",
+                        "BAD_REQUEST",
+                    ));
+                }
+                if self.persisted_only {
+                    if !self.allowlist.contains_key(&hash) {
+                        return Err(request_error(
+                            "PersistedQueryNotFound",
+                            "PERSISTED_QUERY_NOT_FOUND",
+                        ));
+                    }
+                } else if self.get(&hash).is_none() {
+                    register = Some(hash);
+                }
+            }
+            // 本文のみ: 本番モードでは許可リストにある本文だけを受け付ける
+            None => {
+                if self.persisted_only && !self.allowlist.contains_key(&sha256(&request.query)) {
+                    return Err(request_error(
+                        "Only persisted queries are allowed",
+                        "PERSISTED_QUERY_NOT_ALLOWED",
+                    ));
+                }
+            }
+        }
+
+        Ok((request, register))
+    }
+}
+
+/// 件数に上限のあるキャッシュ（上限に達したら最後に使われたのが最も古いものを消す）
+struct LruCache {
+    /// ハッシュ → (本文, 最後に使われた時刻)
+    entries: HashMap<String, (String, u64)>,
+    capacity: usize,
+    clock: u64,
+}
+
+impl LruCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            capacity,
+            clock: 0,
+        }
+    }
+
+    fn get(&mut self, key: &str) -> Option<String> {
+        self.clock += 1;
+        let (value, used_at) = self.entries.get_mut(key)?;
+        *used_at = self.clock;
+        Some(value.clone())
+    }
+
+    fn insert(&mut self, key: String, value: String) {
+        if self.capacity == 0 {
+            return;
+        }
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.capacity {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, used_at))| *used_at)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.clock += 1;
+        self.entries.insert(key, (value, self.clock));
+    }
+}
+
+#[derive(Deserialize)]
+struct PersistedQuery {
+    version: i32,
+    #[serde(rename = "sha256Hash")]
+    sha256_hash: String,
+}
+
+fn sha256(query: &str) -> String {
+    format!("{:x}", Sha256::digest(query.as_bytes()))
+}
+
+impl ExtensionFactory for PersistedQueries {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(PersistedQueryExtension {
+            queries: self.clone(),
+            pending: Mutex::default(),
+        })
+    }
+}
+
+/// リクエストごとに作られる拡張
+struct PersistedQueryExtension {
+    queries: PersistedQueries,
+    /// 実行に成功したら登録するクエリ（ハッシュ, 本文）
+    pending: Mutex<Option<(String, String)>>,
+}
+
+#[async_trait::async_trait]
+impl Extension for PersistedQueryExtension {
+    /// 検証エラーや実行時のエラーになったクエリは登録しない
+    async fn request(&self, ctx: &ExtensionContext<'_>, next: NextRequest<'_>) -> Response {
+        let response = next.run(ctx).await;
+        let pending = self.pending.lock().unwrap().take();
+        if let Some((hash, query)) = pending
+            && response.is_ok()
+        {
+            self.queries.register(hash, query);
+        }
+        response
+    }
+
+    async fn prepare_request(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        request: Request,
+        next: NextPrepareRequest<'_>,
+    ) -> ServerResult<Request> {
+        let (request, register) = self.queries.resolve(request)?;
+        if let Some(hash) = register {
+            *self.pending.lock().unwrap() = Some((hash, request.query.clone()));
+        }
+        next.run(ctx, request).await
+    }
+}
@@ -1,340 +1,441 @@
-use async_graphql::{Context, Object, Result};
-use std::collections::HashSet;
+use async_graphql::connection::{Connection, ConnectionNameType, EdgeNameType, EmptyFields};
+use async_graphql::dataloader::DataLoader;
+use async_graphql::{Context, Object, OutputType, Result};
 use uuid::Uuid;
 
-use crate::models::{Comment, HashtagName, LikeTweetId, Tweet, User};
-use crate::store::Db;
+use crate::graphql::connection::{
+    CountedConnection, TotalCount, page_complexity, paginate, paginate_with_edges,
+};
+use crate::graphql::direct_message::{DirectConversationType, DirectMessageType};
+use crate::graphql::guards::{LoginGuard, SelfGuard, redact};
+use crate::graphql::loaders::{
+    CommentMentionsLoader, CommentReplyCountLoader, FollowCountsLoader, FollowingLoader,
+    HashtagLoader, LikeCountLoader, LikedLoader, QuoteCountLoader, RetweetCountLoader,
+    RetweetedLoader, TweetLoader, TweetMentionsLoader, TweetReplyCountLoader, UserLoader,
+    ViewerKey,
+};
+use crate::graphql::notification::{
+    NotificationConnection, NotificationCounts, NotificationPreferenceType, NotificationType,
+};
+use crate::graphql::search::{SearchConnection, search};
+use crate::graphql::{FETCH_COST, current_user, gql_error};
+use crate::models::{
+    Comment, ModerationAction, ModerationActionKind, Report, ReportTarget, Role, Session, Tweet,
+    User,
+};
+use crate::pagination::{Cursor, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
+use crate::repository::FollowCounts;
+use crate::roles::{Permission, RoleGuard};
+use crate::search::SearchType;
+use crate::services::{
+    Conversation, DEFAULT_THREAD_DEPTH, DEFAULT_TRENDING_LIMIT, Hashtag, Services, TrendWindow,
+    TrendingHashtag,
+};
+use crate::sessions::CurrentSession;
+use crate::utils::extract_mentions;
 
 pub struct QueryRoot;
 
 #[Object]
 impl QueryRoot {
-    /// 現在のユーザーのタイムラインを取得（自分 + フォロー中のユーザーのツイート）
-    async fn timeline(&self, ctx: &Context<'_>) -> Result<Vec<TweetType>> {
-        let db = ctx.data::<Db>()?;
-        let user_id = ctx.data::<Uuid>()?;
-
-        // 自分とフォロー中のユーザーのツイートを取得
-        let tweets: Vec<Tweet> = sqlx::query_as(
-            r#"
-            SELECT t.* FROM tweets t
-            WHERE t.user_id = ?
-               OR t.user_id IN (SELECT following_id FROM follows WHERE follower_id = ?)
-            ORDER BY t.created_at DESC
-            "#,
+    /// 現在のユーザーのタイムラインを取得（自分 + フォロー中のユーザーのツイートとリツイート、新しい順）
+    ///
+    /// 同じツイートは最も新しく載ったときの1件だけになる
+    #[graphql(
+        guard = "LoginGuard",
+        complexity = "page_complexity(first, last, child_complexity)"
+    )]
+    async fn timeline(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> Result<TimelineConnection> {
+        let services = ctx.data::<Services>()?;
+        let user_id = *current_user(ctx)?;
+
+        paginate_with_edges(
+            after,
+            before,
+            first,
+            last,
+            TotalCount::Timeline(user_id),
+            |query| async move { services.tweets.timeline_page(user_id, &query).await },
+            |entry| {
+                let retweet = entry.retweeted_by.map(|user_id| TimelineRetweet {
+                    user_id,
+                    created_at: entry.activity_at,
+                });
+                (TweetType::from(entry.tweet), TimelineEdgeFields { retweet })
+            },
         )
-        .bind(user_id)
-        .bind(user_id)
-        .fetch_all(db)
-        .await?;
+        .await
+    }
 
-        if tweets.is_empty() {
-            return Ok(Vec::new());
-        }
+    async fn tweet(&self, ctx: &Context<'_>, id: Uuid) -> Result<Option<TweetType>> {
+        let services = ctx.data::<Services>()?;
 
-        let tweet_ids: Vec<Uuid> = tweets.iter().map(|t| t.id).collect();
+        let tweet = services.tweets.find(id).await.map_err(gql_error)?;
 
-        // SQLiteでIN句を使うため、プレースホルダを動的に生成
-        let placeholders = tweet_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
-        let like_count_query = format!(
-            "SELECT tweet_id, COUNT(*) as count FROM likes WHERE tweet_id IN ({}) GROUP BY tweet_id",
-            placeholders
-        );
+        Ok(tweet.map(TweetType::from))
+    }
 
-        let mut query = sqlx::query_as::<_, (Uuid, i64)>(&like_count_query);
-        for id in &tweet_ids {
-            query = query.bind(id);
-        }
-        let like_counts: Vec<(Uuid, i64)> = query.fetch_all(db).await?;
-        let like_count_map: std::collections::HashMap<Uuid, i64> =
-            like_counts.into_iter().collect();
-
-        // 現在のユーザーがいいねしたツイートを取得
-        let user_likes_query = format!(
-            "SELECT tweet_id FROM likes WHERE tweet_id IN ({}) AND user_id = ?",
-            placeholders
-        );
-        let mut query = sqlx::query_as::<_, LikeTweetId>(&user_likes_query);
-        for id in &tweet_ids {
-            query = query.bind(id);
-        }
-        query = query.bind(user_id);
-        let user_likes: Vec<LikeTweetId> = query.fetch_all(db).await?;
-        let liked_tweet_ids: HashSet<Uuid> = user_likes.into_iter().map(|l| l.tweet_id).collect();
-
-        // 各ツイートのハッシュタグを取得
-        let hashtags_query = format!(
-            r#"
-            SELECT th.tweet_id, h.name 
-            FROM tweet_hashtags th 
-            JOIN hashtags h ON th.hashtag_id = h.id 
-            WHERE th.tweet_id IN ({})
-            "#,
-            placeholders
-        );
-        let mut query = sqlx::query_as::<_, (Uuid, String)>(&hashtags_query);
-        for id in &tweet_ids {
-            query = query.bind(id);
-        }
-        let tweet_hashtags: Vec<(Uuid, String)> = query.fetch_all(db).await?;
+    /// 現在のユーザー情報を取得
+    async fn me(&self, ctx: &Context<'_>) -> Result<Option<UserType>> {
+        let services = ctx.data::<Services>()?;
+        let Ok(user_id) = ctx.data::<Uuid>() else {
+            return Ok(None);
+        };
 
-        // ツイートIDごとにハッシュタグをグループ化
-        let mut hashtag_map: std::collections::HashMap<Uuid, Vec<String>> =
-            std::collections::HashMap::new();
-        for (tweet_id, tag_name) in tweet_hashtags {
-            hashtag_map.entry(tweet_id).or_default().push(tag_name);
-        }
+        let user = services.users.find(*user_id).await.map_err(gql_error)?;
+        Ok(user.map(UserType::from))
+    }
+
+    /// 現在のユーザーのログイン中セッション（端末）一覧を取得
+    #[graphql(guard = "LoginGuard")]
+    async fn sessions(&self, ctx: &Context<'_>) -> Result<Vec<SessionType>> {
+        let services = ctx.data::<Services>()?;
+        let user_id = current_user(ctx)?;
+        let current = ctx.data::<CurrentSession>()?;
 
-        let result: Vec<TweetType> = tweets
+        let sessions = services.users.sessions(*user_id).await.map_err(gql_error)?;
+
+        Ok(sessions
             .into_iter()
-            .map(|tweet| {
-                let like_count = *like_count_map.get(&tweet.id).unwrap_or(&0);
-                let is_liked = liked_tweet_ids.contains(&tweet.id);
-                let hashtags = hashtag_map.remove(&tweet.id).unwrap_or_default();
-                TweetType::from_tweet(tweet, like_count, is_liked, hashtags)
+            .map(|session| SessionType {
+                is_current: session.id == current.0,
+                session,
             })
-            .collect();
+            .collect())
+    }
 
-        Ok(result)
+    /// 現在のユーザーを言及したツイートを取得（新しい順）
+    #[graphql(
+        guard = "LoginGuard",
+        complexity = "page_complexity(first, last, child_complexity)"
+    )]
+    async fn mentions_timeline(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> Result<CountedConnection<TweetType>> {
+        let services = ctx.data::<Services>()?;
+        let user_id = *current_user(ctx)?;
+
+        paginate(
+            after,
+            before,
+            first,
+            last,
+            TotalCount::Mentions(user_id),
+            |query| async move { services.tweets.mentions_page(user_id, &query).await },
+        )
+        .await
     }
 
-    async fn tweet(&self, ctx: &Context<'_>, id: Uuid) -> Result<Option<TweetType>> {
-        let db = ctx.data::<Db>()?;
-        let user_id = ctx.data::<Uuid>().ok();
-
-        let tweet: Option<Tweet> = sqlx::query_as("SELECT * FROM tweets WHERE id = ?")
-            .bind(id)
-            .fetch_optional(db)
-            .await?;
-
-        if let Some(tweet) = tweet {
-            // いいね数を取得
-            let (like_count,): (i64,) =
-                sqlx::query_as("SELECT COUNT(*) FROM likes WHERE tweet_id = ?")
-                    .bind(tweet.id)
-                    .fetch_one(db)
-                    .await?;
-
-            let is_liked = if let Some(uid) = user_id {
-                let exists: Option<(i32,)> =
-                    sqlx::query_as("SELECT 1 FROM likes WHERE tweet_id = ? AND user_id = ?")
-                        .bind(tweet.id)
-                        .bind(uid)
-                        .fetch_optional(db)
-                        .await?;
-                exists.is_some()
-            } else {
-                false
-            };
-
-            // ハッシュタグを取得
-            let hashtags: Vec<HashtagName> = sqlx::query_as(
-                r#"
-                SELECT h.name 
-                FROM tweet_hashtags th 
-                JOIN hashtags h ON th.hashtag_id = h.id 
-                WHERE th.tweet_id = ?
-                "#,
-            )
-            .bind(tweet.id)
-            .fetch_all(db)
-            .await?;
-            let hashtag_names: Vec<String> = hashtags.into_iter().map(|h| h.name).collect();
-
-            Ok(Some(TweetType::from_tweet(
-                tweet,
-                like_count,
-                is_liked,
-                hashtag_names,
-            )))
-        } else {
-            Ok(None)
-        }
+    /// 現在のユーザーへの通知を取得（同じ対象への同じ種類の通知は1件にまとめ、最新の通知の新しい順）
+    #[graphql(
+        guard = "LoginGuard",
+        complexity = "page_complexity(first, last, child_complexity)"
+    )]
+    async fn notifications(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> Result<NotificationConnection> {
+        let services = ctx.data::<Services>()?;
+        let user_id = *current_user(ctx)?;
+
+        paginate_with_edges(
+            after,
+            before,
+            first,
+            last,
+            NotificationCounts(user_id),
+            |query| async move { services.notifications.page(user_id, &query).await },
+            |group| (NotificationType::from(group), EmptyFields),
+        )
+        .await
     }
 
-    /// 現在のユーザー情報を取得
-    async fn me(&self, ctx: &Context<'_>) -> Result<Option<UserType>> {
-        let db = ctx.data::<Db>()?;
-        let user_id = ctx.data::<Uuid>().ok();
-
-        if let Some(id) = user_id {
-            let user: Option<User> = sqlx::query_as("SELECT * FROM users WHERE id = ?")
-                .bind(id)
-                .fetch_optional(db)
-                .await?;
-            Ok(user.map(UserType::from))
-        } else {
-            Ok(None)
-        }
+    /// 現在のユーザーの通知の種類ごとの受け取り設定
+    #[graphql(guard = "LoginGuard")]
+    async fn notification_preferences(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<Vec<NotificationPreferenceType>> {
+        let services = ctx.data::<Services>()?;
+        let user_id = current_user(ctx)?;
+
+        let preferences = services
+            .notifications
+            .preferences(*user_id)
+            .await
+            .map_err(gql_error)?;
+        Ok(preferences
+            .into_iter()
+            .map(NotificationPreferenceType)
+            .collect())
+    }
+
+    /// 現在のユーザーが参加しているダイレクトメッセージの会話を取得（最後のメッセージの新しい順）
+    #[graphql(
+        guard = "LoginGuard",
+        complexity = "page_complexity(first, last, child_complexity)"
+    )]
+    async fn conversations(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> Result<CountedConnection<DirectConversationType>> {
+        let services = ctx.data::<Services>()?;
+        let user_id = *current_user(ctx)?;
+
+        paginate(
+            after,
+            before,
+            first,
+            last,
+            TotalCount::Conversations(user_id),
+            |query| async move {
+                services
+                    .direct_messages
+                    .conversations(user_id, &query)
+                    .await
+            },
+        )
+        .await
     }
 
-    /// ツイートへのコメント一覧を取得
-    async fn comments(&self, ctx: &Context<'_>, tweet_id: Uuid) -> Result<Vec<CommentType>> {
-        let db = ctx.data::<Db>()?;
+    /// 会話のメッセージを取得（新しい順、参加者のみ）
+    #[graphql(
+        guard = "LoginGuard",
+        complexity = "page_complexity(first, last, child_complexity)"
+    )]
+    async fn messages(
+        &self,
+        ctx: &Context<'_>,
+        conversation_id: Uuid,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> Result<CountedConnection<DirectMessageType>> {
+        let services = ctx.data::<Services>()?;
+        let user_id = *current_user(ctx)?;
+
+        paginate(
+            after,
+            before,
+            first,
+            last,
+            TotalCount::Messages(conversation_id),
+            |query| async move {
+                services
+                    .direct_messages
+                    .messages(user_id, conversation_id, &query)
+                    .await
+            },
+        )
+        .await
+    }
 
-        let comments: Vec<Comment> =
-            sqlx::query_as("SELECT * FROM comments WHERE tweet_id = ? ORDER BY created_at ASC")
-                .bind(tweet_id)
-                .fetch_all(db)
-                .await?;
+    /// ツイートへのコメント一覧を取得（古い順、ブロック・ミュートしたユーザーのコメントは除く）
+    #[graphql(complexity = "page_complexity(first, last, child_complexity)")]
+    async fn comments(
+        &self,
+        ctx: &Context<'_>,
+        tweet_id: Uuid,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> Result<CountedConnection<CommentType>> {
+        let services = ctx.data::<Services>()?;
+        let viewer = ctx.data_opt::<Uuid>().copied();
+
+        paginate(
+            after,
+            before,
+            first,
+            last,
+            TotalCount::Comments { tweet_id, viewer },
+            |query| async move { services.tweets.comments(tweet_id, viewer, &query).await },
+        )
+        .await
+    }
 
-        Ok(comments.into_iter().map(CommentType::from).collect())
+    /// ツイート（commentId を指定すればそのコメント）を起点にした返信スレッド
+    ///
+    /// ツイートがない場合・コメントがそのツイートのものでない場合は null
+    #[graphql(complexity = "FETCH_COST + child_complexity")]
+    async fn conversation(
+        &self,
+        ctx: &Context<'_>,
+        tweet_id: Uuid,
+        comment_id: Option<Uuid>,
+    ) -> Result<Option<ConversationType>> {
+        let services = ctx.data::<Services>()?;
+
+        let conversation = services
+            .tweets
+            .conversation(tweet_id, comment_id)
+            .await
+            .map_err(gql_error)?;
+        Ok(conversation.map(ConversationType))
     }
 
-    async fn user(&self, ctx: &Context<'_>, id: Uuid) -> Result<Option<UserType>> {
-        let db = ctx.data::<Db>()?;
-        let current_user_id = ctx.data::<Uuid>().ok();
-
-        let user: Option<User> = sqlx::query_as("SELECT * FROM users WHERE id = ?")
-            .bind(id)
-            .fetch_optional(db)
-            .await?;
-
-        if let Some(user) = user {
-            // フォロワー数を取得
-            let (followers_count,): (i64,) =
-                sqlx::query_as("SELECT COUNT(*) FROM follows WHERE following_id = ?")
-                    .bind(id)
-                    .fetch_one(db)
-                    .await?;
-
-            // フォロー中の数を取得
-            let (following_count,): (i64,) =
-                sqlx::query_as("SELECT COUNT(*) FROM follows WHERE follower_id = ?")
-                    .bind(id)
-                    .fetch_one(db)
-                    .await?;
-
-            // 現在のユーザーがこのユーザーをフォローしているか
-            let is_following = if let Some(current_id) = current_user_id {
-                if *current_id == id {
-                    false // 自分自身の場合はフォロー不可
-                } else {
-                    let exists: Option<(i32,)> = sqlx::query_as(
-                        "SELECT 1 FROM follows WHERE follower_id = ? AND following_id = ?",
-                    )
-                    .bind(current_id)
-                    .bind(id)
-                    .fetch_optional(db)
-                    .await?;
-                    exists.is_some()
-                }
-            } else {
-                false
-            };
-
-            Ok(Some(UserType {
-                id: user.id,
-                username: user.username,
-                email: user.email,
-                followers_count,
-                following_count,
-                is_following,
-            }))
-        } else {
-            Ok(None)
-        }
+    /// ハッシュタグ（`#` の有無・大文字小文字は問わない、どのツイートにも付いていなければ null）
+    #[graphql(complexity = "FETCH_COST + child_complexity")]
+    async fn hashtag(&self, ctx: &Context<'_>, name: String) -> Result<Option<HashtagType>> {
+        let services = ctx.data::<Services>()?;
+
+        let hashtag = services.hashtags.find(&name).await.map_err(gql_error)?;
+        Ok(hashtag.map(HashtagType))
     }
 
-    async fn followers(&self, ctx: &Context<'_>, user_id: Uuid) -> Result<Vec<UserType>> {
-        let db = ctx.data::<Db>()?;
-        let current_user_id = ctx.data::<Uuid>()?;
-
-        let rows: Vec<(Uuid, String, String, i64, i64)> = sqlx::query_as(
-            r#"
-            SELECT 
-                u.id, u.username, u.email,
-                (SELECT COUNT(*) FROM follows WHERE following_id = u.id) as followers_count,
-                (SELECT COUNT(*) FROM follows WHERE follower_id = u.id) as following_count
-            FROM users u
-            JOIN follows f ON u.id = f.follower_id
-            WHERE f.following_id = ?
-            ORDER BY f.created_at DESC
-            "#,
-        )
-        .bind(user_id)
-        .fetch_all(db)
-        .await?;
+    /// 直近の期間に勢いのあるハッシュタグ（その前の期間と比べた伸びの大きい順）
+    #[graphql(complexity = "FETCH_COST + limit.min(MAX_PAGE_SIZE) * child_complexity")]
+    async fn trending_hashtags(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default_with = "TrendWindow::Day")] window: TrendWindow,
+        #[graphql(default_with = "DEFAULT_TRENDING_LIMIT")] limit: usize,
+    ) -> Result<Vec<TrendingHashtagType>> {
+        let services = ctx.data::<Services>()?;
+
+        let trending = services
+            .hashtags
+            .trending(window, limit)
+            .await
+            .map_err(gql_error)?;
+        Ok(trending.into_iter().map(TrendingHashtagType).collect())
+    }
 
-        let user_ids: Vec<Uuid> = rows.iter().map(|(id, ..)| *id).collect();
-        let following_set = fetch_following_set(db, *current_user_id, &user_ids).await?;
+    async fn user(&self, ctx: &Context<'_>, id: Uuid) -> Result<Option<UserType>> {
+        let services = ctx.data::<Services>()?;
 
-        Ok(rows
-            .into_iter()
-            .map(
-                |(id, username, email, followers_count, following_count)| UserType {
-                    id,
-                    username,
-                    email,
-                    followers_count,
-                    following_count,
-                    is_following: id != *current_user_id && following_set.contains(&id),
-                },
-            )
-            .collect())
+        let user = services.users.find(id).await.map_err(gql_error)?;
+        Ok(user.map(UserType::from))
     }
 
-    async fn following(&self, ctx: &Context<'_>, user_id: Uuid) -> Result<Vec<UserType>> {
-        let db = ctx.data::<Db>()?;
-        let current_user_id = ctx.data::<Uuid>()?;
-
-        let rows: Vec<(Uuid, String, String, i64, i64)> = sqlx::query_as(
-            r#"
-            SELECT 
-                u.id, u.username, u.email,
-                (SELECT COUNT(*) FROM follows WHERE following_id = u.id) as followers_count,
-                (SELECT COUNT(*) FROM follows WHERE follower_id = u.id) as following_count
-            FROM users u
-            JOIN follows f ON u.id = f.following_id
-            WHERE f.follower_id = ?
-            ORDER BY f.created_at DESC
-            "#,
+    /// user_id のフォロワー一覧を取得（フォローの新しい順）
+    #[graphql(
+        guard = "LoginGuard",
+        complexity = "page_complexity(first, last, child_complexity)"
+    )]
+    async fn followers(
+        &self,
+        ctx: &Context<'_>,
+        user_id: Uuid,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> Result<CountedConnection<UserType>> {
+        let services = ctx.data::<Services>()?;
+
+        paginate(
+            after,
+            before,
+            first,
+            last,
+            TotalCount::Followers(user_id),
+            |query| async move { services.social.followers(user_id, &query).await },
         )
-        .bind(user_id)
-        .fetch_all(db)
-        .await?;
+        .await
+    }
+
+    /// user_id がフォローしているユーザー一覧を取得（フォローの新しい順）
+    #[graphql(
+        guard = "LoginGuard",
+        complexity = "page_complexity(first, last, child_complexity)"
+    )]
+    async fn following(
+        &self,
+        ctx: &Context<'_>,
+        user_id: Uuid,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> Result<CountedConnection<UserType>> {
+        let services = ctx.data::<Services>()?;
+
+        paginate(
+            after,
+            before,
+            first,
+            last,
+            TotalCount::Following(user_id),
+            |query| async move { services.social.following(user_id, &query).await },
+        )
+        .await
+    }
+
+    /// 未対応の通報一覧（古い順）
+    #[graphql(guard = "RoleGuard::new(Permission::ViewReports)")]
+    async fn reports(&self, ctx: &Context<'_>) -> Result<Vec<ReportType>> {
+        let services = ctx.data::<Services>()?;
 
-        let user_ids: Vec<Uuid> = rows.iter().map(|(id, ..)| *id).collect();
-        let following_set = fetch_following_set(db, *current_user_id, &user_ids).await?;
+        let reports = services
+            .moderation
+            .open_reports()
+            .await
+            .map_err(gql_error)?;
 
-        Ok(rows
+        Ok(reports
             .into_iter()
-            .map(
-                |(id, username, email, followers_count, following_count)| UserType {
-                    id,
-                    username,
-                    email,
-                    followers_count,
-                    following_count,
-                    is_following: id != *current_user_id && following_set.contains(&id),
-                },
-            )
+            .map(|report| ReportType { report })
             .collect())
     }
-}
 
-async fn fetch_following_set(
-    db: &Db,
-    current_user_id: Uuid,
-    target_ids: &[Uuid],
-) -> Result<HashSet<Uuid>> {
-    if target_ids.is_empty() {
-        return Ok(HashSet::new());
-    }
+    /// 最近のモデレーション操作（新しい順）
+    #[graphql(guard = "RoleGuard::new(Permission::ViewAuditLog)")]
+    async fn moderation_log(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default_with = "DEFAULT_PAGE_SIZE")] limit: usize,
+    ) -> Result<Vec<ModerationActionType>> {
+        let services = ctx.data::<Services>()?;
 
-    let placeholders = target_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
-    let query = format!(
-        "SELECT following_id FROM follows WHERE follower_id = ? AND following_id IN ({})",
-        placeholders
-    );
+        let actions = services.moderation.log(limit).await.map_err(gql_error)?;
 
-    let mut q = sqlx::query_as::<_, (Uuid,)>(&query).bind(current_user_id);
-    for id in target_ids {
-        q = q.bind(id);
+        Ok(actions
+            .into_iter()
+            .map(|action| ModerationActionType { action })
+            .collect())
     }
 
-    let rows: Vec<(Uuid,)> = q.fetch_all(db).await?;
-    Ok(rows.into_iter().map(|(id,)| id).collect())
+    /// ツイート・コメント・ユーザーを検索（新しい順）
+    ///
+    /// query は空白区切りの語（すべてを含むもの）と `from:username`・`#hashtag`・
+    /// `since:YYYY-MM-DD`・`until:YYYY-MM-DD` の絞り込み
+    #[graphql(complexity = "page_complexity(first, None, child_complexity)")]
+    async fn search(
+        &self,
+        ctx: &Context<'_>,
+        query: String,
+        #[graphql(name = "type", default_with = "SearchType::Tweets")] search_type: SearchType,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<SearchConnection> {
+        let services = ctx.data::<Services>()?;
+        let viewer = ctx.data_opt::<Uuid>().copied();
+
+        search(services, query, search_type, viewer, first, after).await
+    }
 }
 
 #[derive(Clone)]
@@ -342,9 +443,11 @@ pub struct UserType {
     pub id: Uuid,
     pub username: String,
     pub email: String,
-    pub followers_count: i64,
-    pub following_count: i64,
-    pub is_following: bool,
+    pub role: Role,
+    pub suspended_at: Option<String>,
+    pub allow_open_dms: bool,
+    /// ログイン・登録の結果として本人に返すユーザーか
+    pub session_owner: bool,
 }
 
 #[Object]
@@ -357,20 +460,61 @@ impl UserType {
         &self.username
     }
 
-    async fn email(&self) -> &str {
-        &self.email
+    /// メールアドレス（本人と ViewPrivateFields 権限を持つユーザー以外には null）
+    async fn email(&self, ctx: &Context<'_>) -> Option<&str> {
+        let guard = SelfGuard::new(self.id, self.session_owner);
+        redact(ctx, guard, self.email.as_str()).await
     }
 
-    async fn followers_count(&self) -> i64 {
-        self.followers_count
+    async fn role(&self) -> Role {
+        self.role
     }
 
-    async fn following_count(&self) -> i64 {
-        self.following_count
+    /// 利用停止された日時（モデレーター以外には null）
+    async fn suspended_at(&self, ctx: &Context<'_>) -> Option<&str> {
+        let guard = RoleGuard::new(Permission::SuspendUsers);
+        redact(ctx, guard, self.suspended_at.as_deref())
+            .await
+            .flatten()
     }
 
-    async fn is_following(&self) -> bool {
-        self.is_following
+    /// 相互フォローでなくてもダイレクトメッセージを始められるか
+    async fn allows_open_direct_messages(&self) -> bool {
+        self.allow_open_dms
+    }
+
+    #[graphql(complexity = "FETCH_COST")]
+    async fn followers_count(&self, ctx: &Context<'_>) -> Result<i64> {
+        Ok(self.follow_counts(ctx).await?.followers)
+    }
+
+    #[graphql(complexity = "FETCH_COST")]
+    async fn following_count(&self, ctx: &Context<'_>) -> Result<i64> {
+        Ok(self.follow_counts(ctx).await?.following)
+    }
+
+    /// 現在のユーザーがこのユーザーをフォローしているか（未ログインなら false）
+    #[graphql(complexity = "FETCH_COST")]
+    async fn is_following(&self, ctx: &Context<'_>) -> Result<bool> {
+        let Ok(viewer) = ctx.data::<Uuid>() else {
+            return Ok(false);
+        };
+        let loader = ctx.data::<DataLoader<FollowingLoader>>()?;
+
+        let key = ViewerKey {
+            viewer: *viewer,
+            id: self.id,
+        };
+        let is_following = loader.load_one(key).await.map_err(gql_error)?;
+        Ok(is_following.unwrap_or(false))
+    }
+}
+
+impl UserType {
+    async fn follow_counts(&self, ctx: &Context<'_>) -> Result<FollowCounts> {
+        let loader = ctx.data::<DataLoader<FollowCountsLoader>>()?;
+        let counts = loader.load_one(self.id).await.map_err(gql_error)?;
+        Ok(counts.unwrap_or_default())
     }
 }
 
@@ -380,22 +524,74 @@ impl From<User> for UserType {
             id: user.id,
             username: user.username,
             email: user.email,
-            followers_count: 0,
-            following_count: 0,
-            is_following: false,
+            role: user.role,
+            suspended_at: user.suspended_at,
+            allow_open_dms: user.allow_open_dms,
+            session_owner: false,
         }
     }
 }
 
+/// タイムラインの Connection（エッジにリツイートの情報を持つ）
+pub type TimelineConnection = Connection<
+    Cursor,
+    TweetType,
+    TotalCount,
+    TimelineEdgeFields,
+    TimelineConnectionName,
+    TimelineEdgeName,
+>;
+
+pub struct TimelineConnectionName;
+
+impl ConnectionNameType for TimelineConnectionName {
+    fn type_name<T: OutputType>() -> String {
+        "TimelineConnection".to_string()
+    }
+}
+
+pub struct TimelineEdgeName;
+
+impl EdgeNameType for TimelineEdgeName {
+    fn type_name<T: OutputType>() -> String {
+        "TimelineEdge".to_string()
+    }
+}
+
+/// フォロー中のユーザーのリツイートとしてタイムラインに載ったときの情報
+struct TimelineRetweet {
+    user_id: Uuid,
+    created_at: String,
+}
+
+pub struct TimelineEdgeFields {
+    retweet: Option<TimelineRetweet>,
+}
+
+#[Object]
+impl TimelineEdgeFields {
+    /// リツイートとして載った場合のリツイートしたユーザー（投稿として載った場合は null）
+    #[graphql(complexity = "FETCH_COST + child_complexity")]
+    async fn retweeted_by(&self, ctx: &Context<'_>) -> Result<Option<UserType>> {
+        match &self.retweet {
+            Some(retweet) => load_user(ctx, retweet.user_id).await,
+            None => Ok(None),
+        }
+    }
+
+    /// リツイートした日時
+    async fn retweeted_at(&self) -> Option<&str> {
+        self.retweet.as_ref().map(|r| r.created_at.as_str())
+    }
+}
+
 #[derive(Clone)]
 pub struct TweetType {
     pub id: Uuid,
     pub user_id: Uuid,
     pub content: String,
     pub created_at: String,
-    pub like_count: i64,
-    pub is_liked: bool,
-    pub hashtags: Vec<String>,
+    pub quoted_tweet_id: Option<Uuid>,
 }
 
 #[Object]
@@ -416,54 +612,120 @@ impl TweetType {
         &self.created_at
     }
 
-    async fn like_count(&self) -> i64 {
-        self.like_count
+    #[graphql(complexity = "FETCH_COST")]
+    async fn like_count(&self, ctx: &Context<'_>) -> Result<i64> {
+        let loader = ctx.data::<DataLoader<LikeCountLoader>>()?;
+        let count = loader.load_one(self.id).await.map_err(gql_error)?;
+        Ok(count.unwrap_or(0))
     }
 
-    async fn is_liked(&self) -> bool {
-        self.is_liked
+    /// 現在のユーザーがいいねしているか（未ログインなら false）
+    #[graphql(complexity = "FETCH_COST")]
+    async fn is_liked(&self, ctx: &Context<'_>) -> Result<bool> {
+        let Ok(viewer) = ctx.data::<Uuid>() else {
+            return Ok(false);
+        };
+        let loader = ctx.data::<DataLoader<LikedLoader>>()?;
+
+        let key = ViewerKey {
+            viewer: *viewer,
+            id: self.id,
+        };
+        let is_liked = loader.load_one(key).await.map_err(gql_error)?;
+        Ok(is_liked.unwrap_or(false))
     }
 
-    async fn hashtags(&self) -> &[String] {
-        &self.hashtags
+    /// 直接のコメント数（コメントへの返信と、現在のユーザーから見えないユーザーのコメントは含まない）
+    #[graphql(complexity = "FETCH_COST")]
+    async fn reply_count(&self, ctx: &Context<'_>) -> Result<i64> {
+        let loader = ctx.data::<DataLoader<TweetReplyCountLoader>>()?;
+        let count = match ctx.data_opt::<Uuid>() {
+            Some(viewer) => {
+                let key = ViewerKey {
+                    viewer: *viewer,
+                    id: self.id,
+                };
+                loader.load_one(key).await
+            }
+            None => loader.load_one(self.id).await,
+        };
+        Ok(count.map_err(gql_error)?.unwrap_or(0))
     }
 
-    async fn user(&self, ctx: &Context<'_>) -> Result<Option<UserType>> {
-        let db = ctx.data::<Db>()?;
-        let user: Option<User> = sqlx::query_as("SELECT * FROM users WHERE id = ?")
-            .bind(self.user_id)
-            .fetch_optional(db)
-            .await?;
+    #[graphql(complexity = "FETCH_COST")]
+    async fn retweet_count(&self, ctx: &Context<'_>) -> Result<i64> {
+        let loader = ctx.data::<DataLoader<RetweetCountLoader>>()?;
+        let count = loader.load_one(self.id).await.map_err(gql_error)?;
+        Ok(count.unwrap_or(0))
+    }
 
-        Ok(user.map(UserType::from))
+    #[graphql(complexity = "FETCH_COST")]
+    async fn quote_count(&self, ctx: &Context<'_>) -> Result<i64> {
+        let loader = ctx.data::<DataLoader<QuoteCountLoader>>()?;
+        let count = loader.load_one(self.id).await.map_err(gql_error)?;
+        Ok(count.unwrap_or(0))
+    }
+
+    /// 現在のユーザーがリツイートしているか（未ログインなら false）
+    #[graphql(complexity = "FETCH_COST")]
+    async fn is_retweeted(&self, ctx: &Context<'_>) -> Result<bool> {
+        let Ok(viewer) = ctx.data::<Uuid>() else {
+            return Ok(false);
+        };
+        let loader = ctx.data::<DataLoader<RetweetedLoader>>()?;
+
+        let key = ViewerKey {
+            viewer: *viewer,
+            id: self.id,
+        };
+        let is_retweeted = loader.load_one(key).await.map_err(gql_error)?;
+        Ok(is_retweeted.unwrap_or(false))
+    }
+
+    /// 引用しているツイート（引用ツイートでない場合・引用元が削除された場合は null）
+    #[graphql(complexity = "FETCH_COST + child_complexity")]
+    async fn quoted_tweet(&self, ctx: &Context<'_>) -> Result<Option<TweetType>> {
+        let Some(quoted_tweet_id) = self.quoted_tweet_id else {
+            return Ok(None);
+        };
+        let loader = ctx.data::<DataLoader<TweetLoader>>()?;
+        let tweet = loader.load_one(quoted_tweet_id).await.map_err(gql_error)?;
+        Ok(tweet.map(TweetType::from))
+    }
+
+    #[graphql(complexity = "FETCH_COST")]
+    async fn hashtags(&self, ctx: &Context<'_>) -> Result<Vec<String>> {
+        let loader = ctx.data::<DataLoader<HashtagLoader>>()?;
+        let hashtags = loader.load_one(self.id).await.map_err(gql_error)?;
+        Ok(hashtags.unwrap_or_default())
+    }
+
+    /// 本文で言及されたユーザー（本文での出現順）
+    #[graphql(complexity = "FETCH_COST + child_complexity")]
+    async fn mentions(&self, ctx: &Context<'_>) -> Result<Vec<UserType>> {
+        let loader = ctx.data::<DataLoader<TweetMentionsLoader>>()?;
+        let user_ids = loader.load_one(self.id).await.map_err(gql_error)?;
+        load_mentions(ctx, user_ids.unwrap_or_default(), &self.content).await
+    }
+
+    #[graphql(complexity = "FETCH_COST + child_complexity")]
+    async fn user(&self, ctx: &Context<'_>) -> Result<Option<UserType>> {
+        load_user(ctx, self.user_id).await
     }
 }
 
-impl TweetType {
-    pub fn from_tweet(
-        tweet: Tweet,
-        like_count: i64,
-        is_liked: bool,
-        hashtags: Vec<String>,
-    ) -> Self {
+impl From<Tweet> for TweetType {
+    fn from(tweet: Tweet) -> Self {
         Self {
             id: tweet.id,
             user_id: tweet.user_id,
             content: tweet.content,
             created_at: tweet.created_at,
-            like_count,
-            is_liked,
-            hashtags,
+            quoted_tweet_id: tweet.quoted_tweet_id,
         }
     }
 }
 
-impl From<Tweet> for TweetType {
-    fn from(tweet: Tweet) -> Self {
-        Self::from_tweet(tweet, 0, false, Vec::new())
-    }
-}
-
 #[derive(Clone)]
 pub struct CommentType {
     pub id: Uuid,
@@ -471,6 +733,7 @@ pub struct CommentType {
     pub user_id: Uuid,
     pub content: String,
     pub created_at: String,
+    pub parent_id: Option<Uuid>,
 }
 
 #[Object]
@@ -495,13 +758,39 @@ impl CommentType {
         &self.created_at
     }
 
+    /// 返信先のコメント ID（ツイートへの直接のコメントなら null）
+    async fn parent_id(&self) -> Option<Uuid> {
+        self.parent_id
+    }
+
+    /// 直接の返信数（現在のユーザーから見えないユーザーの返信は含まない）
+    #[graphql(complexity = "FETCH_COST")]
+    async fn reply_count(&self, ctx: &Context<'_>) -> Result<i64> {
+        let loader = ctx.data::<DataLoader<CommentReplyCountLoader>>()?;
+        let count = match ctx.data_opt::<Uuid>() {
+            Some(viewer) => {
+                let key = ViewerKey {
+                    viewer: *viewer,
+                    id: self.id,
+                };
+                loader.load_one(key).await
+            }
+            None => loader.load_one(self.id).await,
+        };
+        Ok(count.map_err(gql_error)?.unwrap_or(0))
+    }
+
+    /// 本文で言及されたユーザー（本文での出現順）
+    #[graphql(complexity = "FETCH_COST + child_complexity")]
+    async fn mentions(&self, ctx: &Context<'_>) -> Result<Vec<UserType>> {
+        let loader = ctx.data::<DataLoader<CommentMentionsLoader>>()?;
+        let user_ids = loader.load_one(self.id).await.map_err(gql_error)?;
+        load_mentions(ctx, user_ids.unwrap_or_default(), &self.content).await
+    }
+
+    #[graphql(complexity = "FETCH_COST + child_complexity")]
     async fn user(&self, ctx: &Context<'_>) -> Result<Option<UserType>> {
-        let db = ctx.data::<Db>()?;
-        let user: Option<User> = sqlx::query_as("SELECT * FROM users WHERE id = ?")
-            .bind(self.user_id)
-            .fetch_optional(db)
-            .await?;
-        Ok(user.map(UserType::from))
+        load_user(ctx, self.user_id).await
     }
 }
 
@@ -513,6 +802,276 @@ impl From<Comment> for CommentType {
             user_id: comment.user_id,
             content: comment.content,
             created_at: comment.created_at,
+            parent_id: comment.parent_id,
         }
     }
 }
+
+/// 投稿者などの関連ユーザーを DataLoader でまとめて取得する
+pub(crate) async fn load_user(ctx: &Context<'_>, user_id: Uuid) -> Result<Option<UserType>> {
+    let loader = ctx.data::<DataLoader<UserLoader>>()?;
+    let user = loader.load_one(user_id).await.map_err(gql_error)?;
+    Ok(user.map(UserType::from))
+}
+
+/// 言及されたユーザーを取得し、本文での出現順に並べる
+async fn load_mentions(
+    ctx: &Context<'_>,
+    user_ids: Vec<Uuid>,
+    content: &str,
+) -> Result<Vec<UserType>> {
+    let loader = ctx.data::<DataLoader<UserLoader>>()?;
+    let users = loader.load_many(user_ids).await.map_err(gql_error)?;
+
+    let usernames = extract_mentions(content);
+    let mut users: Vec<User> = users.into_values().collect();
+    users.sort_by_key(|u| usernames.iter().position(|name| *name == u.username));
+    Ok(users.into_iter().map(UserType::from).collect())
+}
+
+pub struct ConversationType(Conversation);
+
+#[Object]
+impl ConversationType {
+    async fn tweet(&self) -> TweetType {
+        TweetType::from(self.0.tweet.clone())
+    }
+
+    /// 起点のコメント（ツイートを起点にした場合は null）
+    async fn comment(&self) -> Option<CommentType> {
+        self.0.comment.clone().map(CommentType::from)
+    }
+
+    /// 起点のコメントの返信先をたどったコメント（ツイートへの直接のコメントから順）
+    async fn ancestors(&self) -> Vec<CommentType> {
+        self.0
+            .ancestors
+            .iter()
+            .cloned()
+            .map(CommentType::from)
+            .collect()
+    }
+
+    /// 起点から depth 階層までの返信（古い順、返信先は必ず返信より前に並ぶ）
+    #[graphql(complexity = "page_complexity(first, last, child_complexity)")]
+    async fn replies(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default_with = "DEFAULT_THREAD_DEPTH")] depth: i64,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> Result<CountedConnection<CommentType>> {
+        let services = ctx.data::<Services>()?;
+        let viewer = ctx.data_opt::<Uuid>().copied();
+        let tweet_id = self.0.tweet.id;
+        let parent_id = self.0.comment.as_ref().map(|c| c.id);
+
+        paginate(
+            after,
+            before,
+            first,
+            last,
+            TotalCount::Thread {
+                tweet_id,
+                parent_id,
+                depth,
+                viewer,
+            },
+            |query| async move {
+                services
+                    .tweets
+                    .thread(tweet_id, parent_id, depth, viewer, &query)
+                    .await
+            },
+        )
+        .await
+    }
+}
+
+pub struct HashtagType(Hashtag);
+
+#[Object]
+impl HashtagType {
+    /// 小文字・`#` なしの名前
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    async fn tweet_count(&self) -> i64 {
+        self.0.stats.tweet_count
+    }
+
+    /// 最初に付けられたツイートの投稿日時
+    async fn first_used_at(&self) -> &str {
+        &self.0.stats.first_used_at
+    }
+
+    /// 最後に付けられたツイートの投稿日時
+    async fn last_used_at(&self) -> &str {
+        &self.0.stats.last_used_at
+    }
+
+    /// このハッシュタグの付いたツイート（新しい順）
+    #[graphql(complexity = "page_complexity(first, last, child_complexity)")]
+    async fn tweets(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> Result<CountedConnection<TweetType>> {
+        let services = ctx.data::<Services>()?;
+        let name = &self.0.name;
+
+        paginate(
+            after,
+            before,
+            first,
+            last,
+            TotalCount::Hashtag(name.clone()),
+            |query| async move { services.hashtags.tweets(name, &query).await },
+        )
+        .await
+    }
+}
+
+pub struct TrendingHashtagType(TrendingHashtag);
+
+#[Object]
+impl TrendingHashtagType {
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    /// 直近の期間に付けられたツイート数
+    async fn recent_count(&self) -> i64 {
+        self.0.recent_count
+    }
+
+    /// 基準（直前の期間）での1期間あたりの平均ツイート数
+    async fn baseline(&self) -> f64 {
+        self.0.baseline
+    }
+
+    /// 基準からの伸び（大きいほど急に使われ始めている）
+    async fn score(&self) -> f64 {
+        self.0.score
+    }
+
+    /// ハッシュタグの使用状況とツイート
+    #[graphql(complexity = "FETCH_COST + child_complexity")]
+    async fn hashtag(&self, ctx: &Context<'_>) -> Result<Option<HashtagType>> {
+        let services = ctx.data::<Services>()?;
+
+        let hashtag = services
+            .hashtags
+            .find(&self.0.name)
+            .await
+            .map_err(gql_error)?;
+        Ok(hashtag.map(HashtagType))
+    }
+}
+
+pub struct SessionType {
+    pub session: Session,
+    pub is_current: bool,
+}
+
+#[Object]
+impl SessionType {
+    async fn id(&self) -> Uuid {
+        self.session.id
+    }
+
+    async fn user_agent(&self) -> Option<&str> {
+        self.session.user_agent.as_deref()
+    }
+
+    async fn ip_address(&self) -> Option<&str> {
+        self.session.ip_address.as_deref()
+    }
+
+    async fn created_at(&self) -> &str {
+        &self.session.created_at
+    }
+
+    async fn last_seen_at(&self) -> &str {
+        &self.session.last_seen_at
+    }
+
+    /// このリクエストで使われているセッションか
+    async fn is_current(&self) -> bool {
+        self.is_current
+    }
+}
+
+pub struct ReportType {
+    pub report: Report,
+}
+
+#[Object]
+impl ReportType {
+    async fn id(&self) -> Uuid {
+        self.report.id
+    }
+
+    async fn target_type(&self) -> ReportTarget {
+        self.report.target_type
+    }
+
+    async fn target_id(&self) -> Uuid {
+        self.report.target_id
+    }
+
+    async fn reason(&self) -> &str {
+        &self.report.reason
+    }
+
+    async fn created_at(&self) -> &str {
+        &self.report.created_at
+    }
+
+    /// 通報したユーザー
+    #[graphql(complexity = "FETCH_COST + child_complexity")]
+    async fn reporter(&self, ctx: &Context<'_>) -> Result<Option<UserType>> {
+        load_user(ctx, self.report.reporter_id).await
+    }
+}
+
+pub struct ModerationActionType {
+    pub action: ModerationAction,
+}
+
+#[Object]
+impl ModerationActionType {
+    async fn id(&self) -> Uuid {
+        self.action.id
+    }
+
+    async fn action(&self) -> ModerationActionKind {
+        self.action.action
+    }
+
+    /// 操作対象（ツイート・コメント・ユーザー・通報）のID
+    async fn target_id(&self) -> Uuid {
+        self.action.target_id
+    }
+
+    /// 理由や変更後のロールなど
+    async fn details(&self) -> Option<&str> {
+        self.action.details.as_deref()
+    }
+
+    async fn created_at(&self) -> &str {
+        &self.action.created_at
+    }
+
+    /// 操作した管理者・モデレーター
+    #[graphql(complexity = "FETCH_COST + child_complexity")]
+    async fn actor(&self, ctx: &Context<'_>) -> Result<Option<UserType>> {
+        load_user(ctx, self.action.actor_id).await
+    }
+}
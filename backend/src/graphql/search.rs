@@ -0,0 +1,87 @@
+use async_graphql::connection::{self, Connection, Edge, EmptyFields};
+use async_graphql::{Object, Result, Union};
+use uuid::Uuid;
+
+use crate::graphql::gql_error;
+use crate::graphql::query::{CommentType, TweetType, UserType};
+use crate::pagination::{Cursor, PageQuery};
+use crate::search::{Fragment, SearchHit, SearchItem, SearchType};
+use crate::services::Services;
+
+/// 検索結果の Connection（エッジにスニペットを持つ）
+pub type SearchConnection = Connection<Cursor, SearchResult, EmptyFields, SearchHighlight>;
+
+/// 検索結果の要素
+#[derive(Union)]
+pub enum SearchResult {
+    Tweet(TweetType),
+    Comment(CommentType),
+    User(UserType),
+}
+
+/// エッジに付けるハイライト
+pub struct SearchHighlight {
+    snippet: Vec<Fragment>,
+}
+
+#[Object]
+impl SearchHighlight {
+    /// 本文（ユーザーはユーザー名）の検索語の周辺。一致した部分は highlighted
+    async fn snippet(&self) -> Vec<SnippetFragment> {
+        self.snippet.iter().cloned().map(SnippetFragment).collect()
+    }
+}
+
+pub struct SnippetFragment(Fragment);
+
+#[Object]
+impl SnippetFragment {
+    async fn text(&self) -> &str {
+        &self.0.text
+    }
+
+    async fn highlighted(&self) -> bool {
+        self.0.highlighted
+    }
+}
+
+/// first/after を検証して検索し、Connection に変換する
+pub async fn search(
+    services: &Services,
+    query: String,
+    search_type: SearchType,
+    viewer: Option<Uuid>,
+    first: Option<i32>,
+    after: Option<String>,
+) -> Result<SearchConnection> {
+    connection::query(
+        after,
+        None,
+        first,
+        None,
+        |after, _before, first, _last| async move {
+            let page_query = PageQuery::new(first, after, None, None).map_err(gql_error)?;
+            let page = services
+                .search
+                .search(&query, search_type, viewer, &page_query)
+                .await
+                .map_err(gql_error)?;
+
+            let mut connection = Connection::new(page.has_previous_page, page.has_next_page);
+            connection.edges = page
+                .items
+                .into_iter()
+                .map(|(cursor, SearchHit { item, snippet })| {
+                    let node = match item {
+                        SearchItem::Tweet(tweet) => SearchResult::Tweet(tweet.into()),
+                        SearchItem::Comment(comment) => SearchResult::Comment(comment.into()),
+                        SearchItem::User(user) => SearchResult::User(user.into()),
+                    };
+                    Edge::with_additional_fields(cursor, node, SearchHighlight { snippet })
+                })
+                .collect();
+            Ok::<_, async_graphql::Error>(connection)
+        },
+    )
+    .await
+}
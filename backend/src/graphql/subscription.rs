@@ -0,0 +1,173 @@
+use async_graphql::futures_util::{Stream, StreamExt, stream};
+use async_graphql::{Context, Result, Subscription};
+use tokio::sync::broadcast::error::RecvError;
+use uuid::Uuid;
+
+use crate::events::Event;
+use crate::graphql::current_user;
+use crate::graphql::guards::LoginGuard;
+use crate::graphql::query::{CommentType, TweetType};
+use crate::services::Services;
+use crate::sessions::CurrentSession;
+
+pub struct SubscriptionRoot;
+
+#[Subscription]
+impl SubscriptionRoot {
+    /// 現在のユーザーのタイムラインに投稿されたツイート（自分 + フォロー中のユーザー）
+    #[graphql(guard = "LoginGuard")]
+    async fn tweet_posted(&self, ctx: &Context<'_>) -> Result<impl Stream<Item = TweetType>> {
+        let services = ctx.data::<Services>()?.clone();
+        let viewer = *current_user(ctx)?;
+
+        Ok(while_active(
+            services.clone(),
+            session(ctx),
+            events(&services).filter_map(move |event| {
+                let services = services.clone();
+                async move {
+                    let Event::TweetPosted(tweet) = event else {
+                        return None;
+                    };
+                    if !on_timeline(&services, viewer, tweet.user_id).await {
+                        return None;
+                    }
+                    Some(TweetType::from(tweet))
+                }
+            }),
+        ))
+    }
+
+    /// タイムライン上のツイートへのいいね（tweetId を指定すればそのツイートのみ）
+    ///
+    /// いいね数を更新したツイートを返す
+    #[graphql(guard = "LoginGuard")]
+    async fn tweet_liked(
+        &self,
+        ctx: &Context<'_>,
+        tweet_id: Option<Uuid>,
+    ) -> Result<impl Stream<Item = TweetType>> {
+        let services = ctx.data::<Services>()?.clone();
+        let viewer = *current_user(ctx)?;
+
+        Ok(while_active(
+            services.clone(),
+            session(ctx),
+            events(&services).filter_map(move |event| {
+                let services = services.clone();
+                async move {
+                    let Event::TweetLiked {
+                        tweet_id: liked,
+                        author_id,
+                    } = event
+                    else {
+                        return None;
+                    };
+                    if tweet_id.is_some_and(|id| id != liked)
+                        || !on_timeline(&services, viewer, author_id).await
+                    {
+                        return None;
+                    }
+                    // 削除済みのツイートや取得に失敗したイベントは配信しない
+                    let tweet = services.tweets.find(liked).await.ok()??;
+                    Some(TweetType::from(tweet))
+                }
+            }),
+        ))
+    }
+
+    /// ツイートに追加されたコメント（ログイン中ならブロック・ミュートした相手のものは除く）
+    async fn comment_added(
+        &self,
+        ctx: &Context<'_>,
+        tweet_id: Uuid,
+    ) -> Result<impl Stream<Item = CommentType>> {
+        let services = ctx.data::<Services>()?.clone();
+        let viewer = ctx.data_opt::<Uuid>().copied();
+
+        Ok(while_active(
+            services.clone(),
+            session(ctx),
+            events(&services).filter_map(move |event| {
+                let services = services.clone();
+                async move {
+                    let Event::CommentAdded(comment) = event else {
+                        return None;
+                    };
+                    if comment.tweet_id != tweet_id {
+                        return None;
+                    }
+                    if let Some(viewer) = viewer
+                        && is_hidden(&services, viewer, comment.user_id).await
+                    {
+                        return None;
+                    }
+                    Some(CommentType::from(comment))
+                }
+            }),
+        ))
+    }
+}
+
+/// イベントバスの購読をストリームにする（取りこぼしたイベントは読み飛ばす）
+fn events(services: &Services) -> impl Stream<Item = Event> + use<> {
+    stream::unfold(services.events.subscribe(), |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => return Some((event, receiver)),
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    })
+}
+
+/// 配信のたびにセッションを確認し、ログアウト・失効・利用停止の後は購読を終える（未ログインなら確認しない）
+fn while_active<S: Stream>(
+    services: Services,
+    session: Option<(Uuid, Uuid)>,
+    stream: S,
+) -> impl Stream<Item = S::Item> {
+    stream.take_while(move |_| {
+        let services = services.clone();
+        async move {
+            let Some((user_id, session_id)) = session else {
+                return true;
+            };
+            services
+                .users
+                .ensure_session(user_id, session_id)
+                .await
+                .is_ok()
+        }
+    })
+}
+
+/// 購読したユーザーとセッション（未ログインなら None）
+fn session(ctx: &Context<'_>) -> Option<(Uuid, Uuid)> {
+    let user_id = ctx.data_opt::<Uuid>()?;
+    let session = ctx.data_opt::<CurrentSession>()?;
+    Some((*user_id, session.0))
+}
+
+/// author_id のツイートが viewer のタイムラインに載るか
+async fn on_timeline(services: &Services, viewer: Uuid, author_id: Uuid) -> bool {
+    if author_id == viewer {
+        return true;
+    }
+    services
+        .social
+        .is_following(viewer, author_id)
+        .await
+        .unwrap_or(false)
+        && !is_hidden(services, viewer, author_id).await
+}
+
+/// user_id が viewer から隠されているか（確認に失敗したときも配信しない）
+async fn is_hidden(services: &Services, viewer: Uuid, user_id: Uuid) -> bool {
+    services
+        .social
+        .is_hidden(viewer, user_id)
+        .await
+        .unwrap_or(true)
+}
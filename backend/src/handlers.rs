@@ -1,39 +1,37 @@
+use crate::auth::{AuthError, AuthenticatedUser, OptionalUser, authenticate_connection};
 use crate::error::AppError;
-use crate::graphql::AppSchema;
+use crate::graphql::{AppSchema, request_error};
 use crate::models::*;
-use crate::store::Db;
-use crate::utils::{authenticate, create_jwt, hash_password, verify_jwt, verify_password};
+use crate::repository::Repositories;
+use crate::roles::{Permission, RoleGuard};
+use crate::services::Services;
+use crate::sessions::{ClientInfo, CurrentSession};
+use crate::utils::jwks;
 use actix_web::{HttpRequest, HttpResponse, web};
+use async_graphql::ErrorExtensions;
 use async_graphql::http::GraphiQLSource;
-use async_graphql_actix_web::{GraphQLRequest, GraphQLResponse};
-use chrono::Utc;
+use async_graphql_actix_web::{GraphQLRequest, GraphQLResponse, GraphQLSubscription};
 use uuid::Uuid;
 
 type Result<T> = std::result::Result<T, AppError>;
 
 // GraphQLハンドラー
 
-/// Authorizationヘッダーからユーザーを認証し、リクエストにユーザーIDを追加
-fn authenticate_request(
+/// 認証済みユーザーと端末情報をGraphQLリクエストのコンテキストに追加
+fn with_auth(
     req: &HttpRequest,
+    user: OptionalUser,
     mut request: async_graphql::Request,
 ) -> async_graphql::Request {
-    match req
-        .headers()
-        .get("Authorization")
-        .and_then(|h| h.to_str().ok())
-        .and_then(|s| s.strip_prefix("Bearer "))
-        .map(verify_jwt)
-    {
-        Some(Ok(user_id)) => {
-            request = request.data(user_id);
-        }
-        Some(Err(e)) => {
-            eprintln!("JWT verification failed: {}", e);
-        }
-        None => {
-            // Authorization ヘッダーがない場合は認証不要なリクエストとして続行
-        }
+    // ログイン系ミューテーションでセッションを作成するために端末情報を渡す
+    request = request.data(ClientInfo::from_request(req));
+
+    // トークンがない場合は認証不要なリクエストとして続行（不正なトークンは抽出時に401）
+    if let OptionalUser(Some(user)) = user {
+        request = request
+            .data(user.user_id)
+            .data(user.role)
+            .data(CurrentSession(user.session_id));
     }
     request
 }
@@ -42,9 +40,10 @@ fn authenticate_request(
 pub async fn graphql_handler(
     schema: web::Data<AppSchema>,
     req: HttpRequest,
+    user: OptionalUser,
     gql_req: GraphQLRequest,
 ) -> GraphQLResponse {
-    let request = authenticate_request(&req, gql_req.into_inner());
+    let request = with_auth(&req, user, gql_req.into_inner());
     schema.execute(request).await.into()
 }
 
@@ -52,6 +51,7 @@ pub async fn graphql_handler(
 pub async fn graphql_handler_get(
     schema: web::Data<AppSchema>,
     req: HttpRequest,
+    user: OptionalUser,
     query: web::Query<GraphQLQueryParams>,
 ) -> GraphQLResponse {
     let mut request = async_graphql::Request::new(&query.query);
@@ -63,28 +63,65 @@ pub async fn graphql_handler_get(
     if let Some(ref vars) = query.variables {
         match serde_json::from_str(vars) {
             Ok(variables) => request = request.variables(variables),
-            Err(e) => {
-                return async_graphql::Response::from_errors(vec![
-                    async_graphql::ServerError::new(
-                        format!("Failed to parse GraphQL variables: {}", e),
-                        None,
-                    ),
-                ])
-                .into();
-            }
+            Err(e) => return parse_error("variables", e),
         }
     }
 
-    let request = authenticate_request(&req, request);
+    // 永続化クエリはハッシュを extensions で受け取る
+    if let Some(ref extensions) = query.extensions {
+        match serde_json::from_str(extensions) {
+            Ok(extensions) => request.extensions = extensions,
+            Err(e) => return parse_error("extensions", e),
+        }
+    }
+
+    let request = with_auth(&req, user, request);
     schema.execute(request).await.into()
 }
 
+/// GraphQLサブスクリプションエンドポイント (WebSocket)
+///
+/// connection_init のペイロードに含まれるトークンで認証する（トークンがなければ未ログイン）
+pub async fn graphql_ws_handler(
+    schema: web::Data<AppSchema>,
+    repos: web::Data<Repositories>,
+    req: HttpRequest,
+    payload: web::Payload,
+) -> actix_web::Result<HttpResponse> {
+    let repos = repos.get_ref().clone();
+
+    GraphQLSubscription::new(schema.get_ref().clone())
+        .on_connection_init(move |params| async move {
+            let mut data = async_graphql::Data::default();
+            let user = authenticate_connection(&repos, &params)
+                .await
+                .map_err(|e| e.extend())?;
+            if let Some(user) = user {
+                data.insert(user.user_id);
+                data.insert(user.role);
+                data.insert(CurrentSession(user.session_id));
+            }
+            Ok(data)
+        })
+        .start(&req, payload)
+}
+
 #[derive(serde::Deserialize)]
 pub struct GraphQLQueryParams {
+    #[serde(default)]
     query: String,
     #[serde(rename = "operationName")]
     operation_name: Option<String>,
     variables: Option<String>,
+    extensions: Option<String>,
+}
+
+fn parse_error(field: &str, e: serde_json::Error) -> GraphQLResponse {
+    async_graphql::Response::from_errors(vec![request_error(
+        format!("Failed to parse GraphQL {}: {}", field, e),
+        "BAD_REQUEST",
+    )])
+    .into()
 }
 
 /// GraphQL Playgroundエンドポイント
@@ -94,132 +131,97 @@ pub async fn graphiql_handler() -> HttpResponse {
         .body(GraphiQLSource::build().endpoint("/graphql").finish())
 }
 
-async fn register_user(
-    db: &Db,
-    username: &str,
-    email: &str,
-    password: &str,
-) -> Result<(User, String)> {
-    let exists: Option<(i32,)> = sqlx::query_as("SELECT 1 FROM users WHERE email = ?")
-        .bind(email)
-        .fetch_optional(db)
-        .await?;
-
-    if exists.is_some() {
-        return Err(AppError::BadRequest("Email already exists".to_string()));
-    }
-
-    let password_hash = hash_password(password)?;
-    let user_id = Uuid::new_v4();
-    let created_at = Utc::now().to_rfc3339();
-
-    sqlx::query(
-        "INSERT INTO users (id, username, email, password_hash, created_at) VALUES (?, ?, ?, ?, ?)",
-    )
-    .bind(user_id)
-    .bind(username)
-    .bind(email)
-    .bind(&password_hash)
-    .bind(&created_at)
-    .execute(db)
-    .await?;
-
-    let user = User {
-        id: user_id,
-        username: username.to_string(),
-        email: email.to_string(),
-        password_hash,
-        created_at,
-    };
-
-    let token = create_jwt(user_id)?;
-
-    Ok((user, token))
+/// JWKSエンドポイント: 他サービスがトークンを検証するための公開鍵一覧
+pub async fn jwks_handler() -> HttpResponse {
+    HttpResponse::Ok().json(jwks())
 }
 
-async fn login_user(db: &Db, email: &str, password: &str) -> Result<(User, String)> {
-    let user: User = sqlx::query_as("SELECT * FROM users WHERE email = ?")
-        .bind(email)
-        .fetch_optional(db)
-        .await?
-        .ok_or_else(|| AppError::Unauthorized("Invalid email or password".to_string()))?;
-
-    if !verify_password(password, &user.password_hash)? {
-        return Err(AppError::Unauthorized(
-            "Invalid email or password".to_string(),
-        ));
-    }
-
-    // user.id は既に Uuid 型なので parse 不要
-    let token = create_jwt(user.id)?;
+pub async fn register(
+    req_http: HttpRequest,
+    services: web::Data<Services>,
+    req: web::Json<RegisterRequest>,
+) -> Result<HttpResponse> {
+    let session = services
+        .users
+        .register(
+            &req.username,
+            &req.email,
+            &req.password,
+            &ClientInfo::from_request(&req_http),
+        )
+        .await?;
 
-    Ok((user, token))
+    Ok(HttpResponse::Ok().json(AuthResponse::from(session)))
 }
 
-async fn create_tweet_internal(db: &Db, user_id: Uuid, content: &str) -> Result<TweetResponse> {
-    if content.is_empty() || content.len() > 280 {
-        return Err(AppError::BadRequest(
-            "Tweet content must be between 1 and 280 characters".to_string(),
-        ));
-    }
-
-    let tweet_id = Uuid::new_v4();
-    let created_at = Utc::now();
-
-    sqlx::query("INSERT INTO tweets (id, user_id, content, created_at) VALUES (?, ?, ?, ?)")
-        .bind(tweet_id)
-        .bind(user_id)
-        .bind(content)
-        .bind(created_at.to_rfc3339())
-        .execute(db)
+pub async fn login(
+    req_http: HttpRequest,
+    services: web::Data<Services>,
+    req: web::Json<LoginRequest>,
+) -> Result<HttpResponse> {
+    let session = services
+        .users
+        .login(
+            &req.email,
+            &req.password,
+            &ClientInfo::from_request(&req_http),
+        )
         .await?;
 
-    Ok(TweetResponse {
-        id: tweet_id,
-        user_id,
-        content: content.to_string(),
-        created_at,
-    })
+    Ok(HttpResponse::Ok().json(AuthResponse::from(session)))
 }
 
-pub async fn register(db: web::Data<Db>, req: web::Json<RegisterRequest>) -> Result<HttpResponse> {
-    let (user, token) =
-        register_user(db.as_ref(), &req.username, &req.email, &req.password).await?;
+/// リフレッシュトークンをローテーションし、新しいアクセストークンを発行する
+pub async fn refresh_token(
+    services: web::Data<Services>,
+    req: web::Json<RefreshTokenRequest>,
+) -> Result<HttpResponse> {
+    let session = services.users.refresh(&req.refresh_token).await?;
 
-    Ok(HttpResponse::Ok().json(AuthResponse {
-        token,
-        user: UserResponse::from(user),
-    }))
+    Ok(HttpResponse::Ok().json(AuthResponse::from(session)))
 }
 
-pub async fn login(db: web::Data<Db>, req: web::Json<LoginRequest>) -> Result<HttpResponse> {
-    let (user, token) = login_user(db.as_ref(), &req.email, &req.password).await?;
-
-    Ok(HttpResponse::Ok().json(AuthResponse {
-        token,
-        user: UserResponse::from(user),
-    }))
-}
+/// ログアウト: リフレッシュトークンのファミリーとセッションを失効させる
+///
+/// 本文がなければ、アクセストークンのセッションを失効させる
+pub async fn logout(
+    user: OptionalUser,
+    services: web::Data<Services>,
+    req: Option<web::Json<RefreshTokenRequest>>,
+) -> actix_web::Result<HttpResponse> {
+    match (req, user) {
+        (Some(req), _) => services.users.logout(&req.refresh_token).await?,
+        (None, OptionalUser(Some(user))) => {
+            services
+                .users
+                .revoke_session(user.user_id, user.session_id)
+                .await?
+        }
+        (None, OptionalUser(None)) => return Err(AuthError::MissingToken.into()),
+    }
 
-pub async fn logout() -> Result<HttpResponse> {
     Ok(HttpResponse::Ok().json(serde_json::json!({ "message": "Logged out successfully" })))
 }
 
 pub async fn create_tweet(
-    req_http: HttpRequest,
-    db: web::Data<Db>,
+    user: AuthenticatedUser,
+    services: web::Data<Services>,
     req: web::Json<CreateTweetRequest>,
 ) -> Result<HttpResponse> {
-    let user_id = authenticate(&req_http)?;
-    let tweet = create_tweet_internal(db.as_ref(), user_id, &req.content).await?;
+    let tweet = services.tweets.create(user.user_id, &req.content).await?;
 
-    Ok(HttpResponse::Created().json(tweet))
+    Ok(HttpResponse::Created().json(TweetResponse::from(tweet)))
 }
 
-pub async fn get_tweet(db: web::Data<Db>, path: web::Path<Uuid>) -> Result<HttpResponse> {
-    let tweet: Tweet = sqlx::query_as("SELECT * FROM tweets WHERE id = ?")
-        .bind(*path)
-        .fetch_optional(db.as_ref())
+pub async fn get_tweet(
+    user: OptionalUser,
+    services: web::Data<Services>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse> {
+    let viewer = user.0.map(|u| u.user_id);
+    let tweet = services
+        .tweets
+        .get(*path, viewer)
         .await?
         .ok_or_else(|| AppError::NotFound("Tweet not found".to_string()))?;
 
@@ -227,37 +229,54 @@ pub async fn get_tweet(db: web::Data<Db>, path: web::Path<Uuid>) -> Result<HttpR
 }
 
 pub async fn delete_tweet(
-    req_http: HttpRequest,
-    db: web::Data<Db>,
+    user: AuthenticatedUser,
+    services: web::Data<Services>,
     path: web::Path<Uuid>,
 ) -> Result<HttpResponse> {
-    let user_id = authenticate(&req_http)?;
-
-    let result = sqlx::query("DELETE FROM tweets WHERE id = ? AND user_id = ?")
-        .bind(*path)
-        .bind(user_id)
-        .execute(db.as_ref())
-        .await?;
-
-    if result.rows_affected() == 0 {
-        return Err(AppError::NotFound(
-            "Tweet not found or not authorized".to_string(),
-        ));
-    }
+    services.tweets.delete(user.actor(), *path).await?;
 
     Ok(HttpResponse::NoContent().finish())
 }
 
-pub async fn get_timeline(req_http: HttpRequest, db: web::Data<Db>) -> Result<HttpResponse> {
-    let user_id = authenticate(&req_http)?;
-
-    let tweets: Vec<Tweet> =
-        sqlx::query_as("SELECT * FROM tweets WHERE user_id = ? ORDER BY created_at DESC")
-            .bind(user_id)
-            .fetch_all(db.as_ref())
-            .await?;
+/// タイムライン（自分 + フォロー中のユーザーのツイート）
+pub async fn get_timeline(
+    user: AuthenticatedUser,
+    services: web::Data<Services>,
+) -> Result<HttpResponse> {
+    let tweets = services.tweets.timeline(user.user_id).await?;
 
     let timeline: Vec<TweetResponse> = tweets.into_iter().map(TweetResponse::from).collect();
 
     Ok(HttpResponse::Ok().json(timeline))
 }
+
+/// 未対応の通報一覧（モデレーター以上）
+pub async fn get_reports(
+    user: AuthenticatedUser,
+    services: web::Data<Services>,
+) -> Result<HttpResponse> {
+    RoleGuard::new(Permission::ViewReports).check(user.role)?;
+
+    let reports = services.moderation.open_reports().await?;
+
+    let reports: Vec<ReportResponse> = reports.into_iter().map(ReportResponse::from).collect();
+
+    Ok(HttpResponse::Ok().json(reports))
+}
+
+/// ユーザーを利用停止にする（モデレーター以上）
+pub async fn suspend_user(
+    user: AuthenticatedUser,
+    services: web::Data<Services>,
+    path: web::Path<Uuid>,
+    req: web::Json<SuspendUserRequest>,
+) -> Result<HttpResponse> {
+    RoleGuard::new(Permission::SuspendUsers).check(user.role)?;
+
+    services
+        .moderation
+        .suspend(user.actor(), *path, req.reason.as_deref())
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,253 @@
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use jsonwebtoken::jwk::{
+    AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm,
+    OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header};
+use simple_asn1::ASN1Block;
+
+use crate::config::{ConfigError, Environment, JwtConfig, VerificationKeyConfig};
+
+/// 開発モードでシークレット未設定の場合に使う既定のHMACシークレット
+pub const DEV_SECRET: &str = "your-secret-key";
+
+/// JWTの署名鍵と検証鍵の集合
+pub struct JwtKeys {
+    signing: SigningKey,
+    /// 先頭は署名鍵と対になる検証鍵、以降はローテーション中の旧鍵
+    verifying: Vec<VerifyingKey>,
+    pub access_token_minutes: i64,
+    pub refresh_token_days: i64,
+}
+
+struct SigningKey {
+    kid: Option<String>,
+    algorithm: Algorithm,
+    key: EncodingKey,
+}
+
+pub struct VerifyingKey {
+    kid: Option<String>,
+    pub algorithm: Algorithm,
+    pub key: DecodingKey,
+    /// 公開可能な鍵のJWK表現（HMACの場合はNone）
+    jwk: Option<Jwk>,
+}
+
+impl JwtKeys {
+    /// 設定から鍵を読み込む（鍵ファイルの読み込み・パースに失敗した場合はエラー）
+    pub fn from_config(config: &JwtConfig, environment: Environment) -> Result<Self, ConfigError> {
+        let kid = config.kid.clone();
+
+        let (signing, active) = match config.algorithm {
+            Algorithm::HS256 => {
+                let secret = match (&config.secret, environment) {
+                    (Some(secret), _) => secret.clone(),
+                    (None, Environment::Development) => DEV_SECRET.to_string(),
+                    (None, Environment::Production) => {
+                        return Err(invalid("jwt.secret", "must be set outside development"));
+                    }
+                };
+                (
+                    EncodingKey::from_secret(secret.as_bytes()),
+                    hmac_key(kid.clone(), &secret),
+                )
+            }
+            algorithm => {
+                let private_pem = read_key("jwt.private_key_path", &config.private_key_path)?;
+                let encoding = match algorithm {
+                    Algorithm::RS256 => EncodingKey::from_rsa_pem(&private_pem),
+                    _ => EncodingKey::from_ed_pem(&private_pem),
+                }
+                .map_err(|e| invalid("jwt.private_key_path", &e.to_string()))?;
+
+                let public_pem = read_key("jwt.public_key_path", &config.public_key_path)?;
+                let verifying =
+                    public_key(kid.clone(), algorithm, &public_pem, "jwt.public_key_path")?;
+                (encoding, verifying)
+            }
+        };
+
+        let mut verifying = vec![active];
+        for key in &config.verification_keys {
+            verifying.push(rotated_key(key)?);
+        }
+
+        Ok(Self {
+            signing: SigningKey {
+                kid,
+                algorithm: config.algorithm,
+                key: signing,
+            },
+            verifying,
+            access_token_minutes: config.access_token_minutes,
+            refresh_token_days: config.refresh_token_days,
+        })
+    }
+
+    /// 署名に使うJWTヘッダー（kidを含む）
+    pub fn header(&self) -> Header {
+        let mut header = Header::new(self.signing.algorithm);
+        header.kid = self.signing.kid.clone();
+        header
+    }
+
+    pub fn encoding_key(&self) -> &EncodingKey {
+        &self.signing.key
+    }
+
+    /// kidに対応する検証鍵を探す（kidなしのトークンは現在の署名鍵で検証する）
+    pub fn find(&self, kid: Option<&str>) -> Option<&VerifyingKey> {
+        match kid {
+            Some(kid) => self
+                .verifying
+                .iter()
+                .find(|k| k.kid.as_deref() == Some(kid)),
+            None => self.verifying.first(),
+        }
+    }
+
+    /// 公開鍵のJWKセット（/.well-known/jwks.json 用）
+    pub fn jwks(&self) -> JwkSet {
+        JwkSet {
+            keys: self
+                .verifying
+                .iter()
+                .filter_map(|k| k.jwk.clone())
+                .collect(),
+        }
+    }
+}
+
+fn invalid(key: &'static str, msg: &str) -> ConfigError {
+    ConfigError::Invalid(key, msg.to_string())
+}
+
+fn read_key(key: &'static str, path: &Option<String>) -> Result<Vec<u8>, ConfigError> {
+    let path = path.as_ref().ok_or_else(|| invalid(key, "must be set"))?;
+    std::fs::read(path).map_err(|e| ConfigError::Io(path.clone(), e))
+}
+
+fn hmac_key(kid: Option<String>, secret: &str) -> VerifyingKey {
+    VerifyingKey {
+        kid,
+        algorithm: Algorithm::HS256,
+        key: DecodingKey::from_secret(secret.as_bytes()),
+        jwk: None,
+    }
+}
+
+/// ローテーション中の旧鍵を読み込む
+fn rotated_key(config: &VerificationKeyConfig) -> Result<VerifyingKey, ConfigError> {
+    let kid = Some(config.kid.clone());
+    match config.algorithm {
+        Algorithm::HS256 => {
+            let secret = config
+                .secret
+                .as_ref()
+                .ok_or_else(|| invalid("jwt.verification_keys.secret", "must be set"))?;
+            Ok(hmac_key(kid, secret))
+        }
+        algorithm => {
+            let pem = read_key(
+                "jwt.verification_keys.public_key_path",
+                &config.public_key_path,
+            )?;
+            public_key(
+                kid,
+                algorithm,
+                &pem,
+                "jwt.verification_keys.public_key_path",
+            )
+        }
+    }
+}
+
+/// PEM形式の公開鍵から検証鍵とJWKを作る
+fn public_key(
+    kid: Option<String>,
+    algorithm: Algorithm,
+    pem: &[u8],
+    key: &'static str,
+) -> Result<VerifyingKey, ConfigError> {
+    let decoding = match algorithm {
+        Algorithm::RS256 => DecodingKey::from_rsa_pem(pem),
+        _ => DecodingKey::from_ed_pem(pem),
+    }
+    .map_err(|e| invalid(key, &e.to_string()))?;
+
+    let parsed = pem::parse(pem).map_err(|e| invalid(key, &e.to_string()))?;
+    let algorithm_params = match algorithm {
+        Algorithm::RS256 => rsa_jwk_params(parsed.tag(), parsed.contents()),
+        _ => ed25519_jwk_params(parsed.contents()),
+    }
+    .ok_or_else(|| invalid(key, "unsupported public key encoding"))?;
+
+    let jwk = Jwk {
+        common: CommonParameters {
+            public_key_use: Some(PublicKeyUse::Signature),
+            key_algorithm: Some(match algorithm {
+                Algorithm::RS256 => KeyAlgorithm::RS256,
+                _ => KeyAlgorithm::EdDSA,
+            }),
+            key_id: kid.clone(),
+            ..Default::default()
+        },
+        algorithm: algorithm_params,
+    };
+
+    Ok(VerifyingKey {
+        kid,
+        algorithm,
+        key: decoding,
+        jwk: Some(jwk),
+    })
+}
+
+/// SubjectPublicKeyInfo から公開鍵本体（BIT STRING）を取り出す
+fn spki_public_key(der: &[u8]) -> Option<Vec<u8>> {
+    match simple_asn1::from_der(der).ok()?.first()? {
+        ASN1Block::Sequence(_, items) => match items.get(1)? {
+            ASN1Block::BitString(_, _, bytes) => Some(bytes.clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// RSA公開鍵（SPKI "PUBLIC KEY" または PKCS#1 "RSA PUBLIC KEY"）から n, e を取り出す
+fn rsa_jwk_params(tag: &str, der: &[u8]) -> Option<AlgorithmParameters> {
+    let pkcs1 = match tag {
+        "RSA PUBLIC KEY" => der.to_vec(),
+        _ => spki_public_key(der)?,
+    };
+
+    match simple_asn1::from_der(&pkcs1).ok()?.first()? {
+        ASN1Block::Sequence(_, items) => match (items.first()?, items.get(1)?) {
+            (ASN1Block::Integer(_, n), ASN1Block::Integer(_, e)) => {
+                Some(AlgorithmParameters::RSA(RSAKeyParameters {
+                    key_type: RSAKeyType::RSA,
+                    n: URL_SAFE_NO_PAD.encode(n.to_bytes_be().1),
+                    e: URL_SAFE_NO_PAD.encode(e.to_bytes_be().1),
+                }))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Ed25519公開鍵（SPKI）からJWKの x を作る
+fn ed25519_jwk_params(der: &[u8]) -> Option<AlgorithmParameters> {
+    let key = spki_public_key(der)?;
+    if key.len() != 32 {
+        return None;
+    }
+
+    Some(AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+        key_type: OctetKeyPairType::OctetKeyPair,
+        curve: EllipticCurve::Ed25519,
+        x: URL_SAFE_NO_PAD.encode(key),
+    }))
+}
@@ -1,22 +1,77 @@
+mod auth;
+mod config;
 mod error;
+mod events;
 mod graphql;
 mod handlers;
+mod keys;
+mod migrate;
 mod models;
+mod pagination;
+mod repository;
+mod roles;
+mod search;
+mod services;
+mod sessions;
 mod store;
+#[cfg(test)]
+mod tests;
+mod tokens;
 mod utils;
 
 use actix_cors::Cors;
-use actix_web::{App, HttpServer, http::header, web};
-use graphql::create_schema;
-use store::init_db;
+use actix_web::{App, HttpServer, guard, http::header, web};
+use config::{Config, Environment};
+use error::extractor_error;
+use graphql::{PersistedQueries, create_schema};
+use keys::JwtKeys;
+use models::Role;
+use repository::Repositories;
+use services::Services;
+use store::{MEMORY_URL, connect, init_repositories};
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    // データベース接続プールを初期化
-    let db = match init_db().await {
-        Ok(pool) => {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    // 設定を読み込み、不正な値があれば起動しない
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Invalid configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    match args.first().map(String::as_str) {
+        None | Some("serve") => {}
+        Some(command @ ("migrate" | "rollback" | "status")) => {
+            run_migration_command(&config, command, args.get(1).map(String::as_str)).await;
+            return Ok(());
+        }
+        Some("grant-role") => {
+            run_grant_role(
+                &config,
+                args.get(1).map(String::as_str),
+                args.get(2).map(String::as_str),
+            )
+            .await;
+            return Ok(());
+        }
+        Some(other) => {
+            eprintln!("Unknown command: {}", other);
+            eprintln!(
+                "Usage: play-with-actix-web [serve | migrate | rollback [steps] | status | grant-role <email> <role>]"
+            );
+            std::process::exit(2);
+        }
+    }
+
+    // リポジトリを初期化（SQLiteの場合は未適用のマイグレーションもここで適用）
+    let repos = match init_repositories(&config.database).await {
+        Ok(repos) => {
             println!("Database initialized successfully");
-            pool
+            repos
         }
         Err(e) => {
             eprintln!("Failed to initialize database: {}", e);
@@ -24,37 +79,208 @@ async fn main() -> std::io::Result<()> {
         }
     };
 
-    // GraphQLスキーマを作成
-    let schema = create_schema(db.clone());
+    // JWT鍵を読み込む（鍵ファイルが不正なら起動しない）
+    match JwtKeys::from_config(&config.jwt, config.server.environment) {
+        Ok(keys) => utils::init_jwt(keys),
+        Err(e) => {
+            eprintln!("Invalid JWT keys: {}", e);
+            std::process::exit(1);
+        }
+    }
+    if config.server.environment == Environment::Development
+        && config.jwt.algorithm == jsonwebtoken::Algorithm::HS256
+        && config.jwt.secret.is_none()
+    {
+        eprintln!("Warning: JWT_SECRET is not set; using the insecure development secret");
+    }
+
+    // 永続化クエリの許可リストを読み込む
+    let persisted = match PersistedQueries::from_config(&config.graphql) {
+        Ok(persisted) => persisted,
+        Err(e) => {
+            eprintln!("Invalid persisted queries: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    serve(config, repos, persisted).await
+}
+
+/// migrate / rollback / status サブコマンドを実行する
+async fn run_migration_command(config: &Config, command: &str, arg: Option<&str>) {
+    if config.database.url == MEMORY_URL {
+        eprintln!("The in-memory store has no migrations");
+        std::process::exit(2);
+    }
+
+    let db = match connect(&config.database).await {
+        Ok(pool) => pool,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let result = match command {
+        "migrate" => migrate::migrate(&db).await.map(|versions| {
+            if versions.is_empty() {
+                println!("Database is up to date");
+            }
+            for version in versions {
+                println!("Applied migration {:04}", version);
+            }
+        }),
+        "rollback" => {
+            let steps = match arg.map(str::parse::<usize>) {
+                None => 1,
+                Some(Ok(steps)) => steps,
+                Some(Err(_)) => {
+                    eprintln!("rollback expects a number of steps");
+                    std::process::exit(2);
+                }
+            };
+            migrate::rollback(&db, steps).await.map(|versions| {
+                for version in versions {
+                    println!("Rolled back migration {:04}", version);
+                }
+            })
+        }
+        _ => migrate::status(&db).await.map(|statuses| {
+            for s in statuses {
+                match s.applied_at {
+                    Some(at) => println!("{:04}_{}  applied at {}", s.version, s.name, at),
+                    None => println!("{:04}_{}  pending", s.version, s.name),
+                }
+            }
+        }),
+    };
+
+    if let Err(e) = result {
+        eprintln!("Migration failed: {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// grant-role サブコマンド: 最初の管理者など、ユーザーのロールを設定する
+async fn run_grant_role(config: &Config, email: Option<&str>, role: Option<&str>) {
+    let (Some(email), Some(Ok(role))) = (email, role.map(|r| Role::try_from(r.to_string()))) else {
+        eprintln!("Usage: play-with-actix-web grant-role <email> <user | moderator | admin>");
+        std::process::exit(2);
+    };
+    if config.database.url == MEMORY_URL {
+        eprintln!("The in-memory store does not persist roles");
+        std::process::exit(2);
+    }
+
+    let repos = match init_repositories(&config.database).await {
+        Ok(repos) => repos,
+        Err(e) => {
+            eprintln!("Failed to initialize database: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let result = match repos.users.find_by_email(email).await {
+        Ok(Some(user)) => repos.users.set_role(user.id, role).await,
+        Ok(None) => {
+            eprintln!("User not found: {}", email);
+            std::process::exit(1);
+        }
+        Err(e) => Err(e),
+    };
+
+    match result {
+        // 新しいロールは次のログイン・トークン更新から有効
+        Ok(_) => println!("Granted role {} to {}", role.as_str(), email),
+        Err(e) => {
+            eprintln!("Failed to grant role: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+async fn serve(
+    config: Config,
+    repos: Repositories,
+    persisted: PersistedQueries,
+) -> std::io::Result<()> {
+    let app = configure_app(repos, &config, persisted);
+    let bind_address = (config.server.host.clone(), config.server.port);
 
     HttpServer::new(move || {
-        // CORS設定: Next.jsフロントエンド（localhost:3000）からのアクセスを許可
-        let cors = Cors::default()
-            .allowed_origin("http://localhost:3000")
+        // CORS設定: 設定された許可オリジン（既定はNext.jsのlocalhost:3000）からのアクセスを許可
+        let mut cors = Cors::default()
             .allowed_methods(vec!["GET", "POST", "DELETE"])
             .allowed_headers(vec![header::AUTHORIZATION, header::CONTENT_TYPE])
             .supports_credentials()
-            .max_age(3600);
+            .max_age(config.cors.max_age);
+        for origin in &config.cors.allowed_origins {
+            cors = cors.allowed_origin(origin);
+        }
 
-        App::new()
-            .wrap(cors)
-            .app_data(web::Data::new(db.clone()))
+        App::new().wrap(cors).configure(app.clone())
+    })
+    .bind(bind_address)?
+    .run()
+    .await
+}
+
+/// 共有する状態とルートを登録する（テストも同じ構成でアプリを起動する）
+fn configure_app(
+    repos: Repositories,
+    config: &Config,
+    persisted: PersistedQueries,
+) -> impl Fn(&mut web::ServiceConfig) + Clone + Send + 'static {
+    // RESTとGraphQLで共有するサービス層
+    let services = Services::new(repos.clone());
+    // GraphQLスキーマを作成
+    let schema = create_schema(services.clone(), &config.graphql, persisted);
+    let json_body_bytes = config.limits.json_body_bytes;
+    // 本番モードではスキーマを探索できるUIを公開しない
+    let graphiql = !config.graphql.persisted_only;
+
+    move |cfg: &mut web::ServiceConfig| {
+        cfg.app_data(web::Data::new(repos.clone()))
+            .app_data(web::Data::new(services.clone()))
             .app_data(web::Data::new(schema.clone()))
-            .app_data(web::JsonConfig::default().limit(4096))
+            .app_data(
+                web::JsonConfig::default()
+                    .limit(json_body_bytes)
+                    .error_handler(|err, _| extractor_error(err)),
+            )
+            .app_data(web::PathConfig::default().error_handler(|err, _| extractor_error(err)))
             // GraphQLエンドポイント
             .route("/graphql", web::post().to(handlers::graphql_handler))
+            .route(
+                "/graphql",
+                web::get()
+                    .guard(guard::Header("upgrade", "websocket"))
+                    .to(handlers::graphql_ws_handler),
+            )
             .route("/graphql", web::get().to(handlers::graphql_handler_get))
-            .route("/graphiql", web::get().to(handlers::graphiql_handler))
+            .route(
+                "/.well-known/jwks.json",
+                web::get().to(handlers::jwks_handler),
+            )
             // REST APIエンドポイント（後方互換性のため残す）
             .route("/api/register", web::post().to(handlers::register))
             .route("/api/login", web::post().to(handlers::login))
             .route("/api/logout", web::post().to(handlers::logout))
+            .route(
+                "/api/token/refresh",
+                web::post().to(handlers::refresh_token),
+            )
             .route("/api/tweets", web::post().to(handlers::create_tweet))
             .route("/api/tweets/{id}", web::get().to(handlers::get_tweet))
             .route("/api/tweets/{id}", web::delete().to(handlers::delete_tweet))
             .route("/api/timeline", web::get().to(handlers::get_timeline))
-    })
-    .bind(("127.0.0.1", 8080))?
-    .run()
-    .await
+            .route("/api/reports", web::get().to(handlers::get_reports))
+            .route(
+                "/api/users/{id}/suspend",
+                web::post().to(handlers::suspend_user),
+            );
+        if graphiql {
+            cfg.route("/graphiql", web::get().to(handlers::graphiql_handler));
+        }
+    }
 }
@@ -0,0 +1,298 @@
+use chrono::Utc;
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+use crate::store::Db;
+
+/// 方言ごとのマイグレーションSQLを読み込む（PostgreSQLは migrations/postgres/ 以下）
+#[cfg(not(feature = "postgres"))]
+macro_rules! migration_sql {
+    ($file:literal) => {
+        include_str!(concat!("../migrations/", $file))
+    };
+}
+#[cfg(feature = "postgres")]
+macro_rules! migration_sql {
+    ($file:literal) => {
+        include_str!(concat!("../migrations/postgres/", $file))
+    };
+}
+
+/// 番号付きマイグレーション（up/downスクリプトの組）
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub up: &'static str,
+    pub down: &'static str,
+}
+
+impl Migration {
+    /// upスクリプトのSHA-256チェックサム（適用後の改変検知に使う）
+    pub fn checksum(&self) -> String {
+        format!("{:x}", Sha256::digest(self.up.as_bytes()))
+    }
+}
+
+/// 登録済みマイグレーション一覧（バージョン昇順）
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial_schema",
+        up: migration_sql!("0001_initial_schema.up.sql"),
+        down: migration_sql!("0001_initial_schema.down.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "refresh_tokens",
+        up: migration_sql!("0002_refresh_tokens.up.sql"),
+        down: migration_sql!("0002_refresh_tokens.down.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "sessions",
+        up: migration_sql!("0003_sessions.up.sql"),
+        down: migration_sql!("0003_sessions.down.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "moderation",
+        up: migration_sql!("0004_moderation.up.sql"),
+        down: migration_sql!("0004_moderation.down.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "search",
+        up: migration_sql!("0005_search.up.sql"),
+        down: migration_sql!("0005_search.down.sql"),
+    },
+    Migration {
+        version: 6,
+        name: "retweets",
+        up: migration_sql!("0006_retweets.up.sql"),
+        down: migration_sql!("0006_retweets.down.sql"),
+    },
+    Migration {
+        version: 7,
+        name: "threaded_comments",
+        up: migration_sql!("0007_threaded_comments.up.sql"),
+        down: migration_sql!("0007_threaded_comments.down.sql"),
+    },
+    Migration {
+        version: 8,
+        name: "mentions",
+        up: migration_sql!("0008_mentions.up.sql"),
+        down: migration_sql!("0008_mentions.down.sql"),
+    },
+    Migration {
+        version: 9,
+        name: "notifications",
+        up: migration_sql!("0009_notifications.up.sql"),
+        down: migration_sql!("0009_notifications.down.sql"),
+    },
+    Migration {
+        version: 10,
+        name: "direct_messages",
+        up: migration_sql!("0010_direct_messages.up.sql"),
+        down: migration_sql!("0010_direct_messages.down.sql"),
+    },
+    Migration {
+        version: 11,
+        name: "blocks_and_mutes",
+        up: migration_sql!("0011_blocks_and_mutes.up.sql"),
+        down: migration_sql!("0011_blocks_and_mutes.down.sql"),
+    },
+];
+
+/// マイグレーション処理のエラー
+#[derive(Debug)]
+pub enum MigrateError {
+    /// データベースエラー
+    Database(sqlx::Error),
+    /// 適用済みスクリプトが変更されている
+    ChecksumMismatch { version: i64, name: String },
+    /// DBに記録されているがコードに存在しないマイグレーション
+    Unknown { version: i64, name: String },
+}
+
+impl fmt::Display for MigrateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrateError::Database(e) => write!(f, "Database error: {}", e),
+            MigrateError::ChecksumMismatch { version, name } => write!(
+                f,
+                "Checksum mismatch for applied migration {:04}_{}",
+                version, name
+            ),
+            MigrateError::Unknown { version, name } => write!(
+                f,
+                "Applied migration {:04}_{} is not known to this build",
+                version, name
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MigrateError {}
+
+impl From<sqlx::Error> for MigrateError {
+    fn from(err: sqlx::Error) -> Self {
+        MigrateError::Database(err)
+    }
+}
+
+/// schema_migrations に記録された適用済みマイグレーション
+#[derive(Debug, sqlx::FromRow)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub name: String,
+    pub checksum: String,
+    pub applied_at: String,
+}
+
+/// status コマンド用の各マイグレーションの状態
+pub struct MigrationStatus {
+    pub version: i64,
+    pub name: &'static str,
+    pub applied_at: Option<String>,
+}
+
+/// schema_migrations を操作するSQL（方言ごと）
+#[cfg(not(feature = "postgres"))]
+mod sql {
+    pub const CREATE_TABLE: &str = r#"
+        CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY NOT NULL,
+            name TEXT NOT NULL,
+            checksum TEXT NOT NULL,
+            applied_at TEXT NOT NULL
+        )
+        "#;
+    pub const SELECT_APPLIED: &str = "SELECT * FROM schema_migrations ORDER BY version ASC";
+    pub const INSERT: &str =
+        "INSERT INTO schema_migrations (version, name, checksum, applied_at) VALUES (?, ?, ?, ?)";
+    pub const DELETE: &str = "DELETE FROM schema_migrations WHERE version = ?";
+}
+
+#[cfg(feature = "postgres")]
+mod sql {
+    pub const CREATE_TABLE: &str = r#"
+        CREATE TABLE IF NOT EXISTS schema_migrations (
+            version BIGINT PRIMARY KEY,
+            name TEXT NOT NULL,
+            checksum TEXT NOT NULL,
+            applied_at TIMESTAMPTZ NOT NULL
+        )
+        "#;
+    pub const SELECT_APPLIED: &str = r#"
+        SELECT version, name, checksum, applied_at::TEXT AS applied_at
+        FROM schema_migrations ORDER BY version ASC
+        "#;
+    pub const INSERT: &str = r#"
+        INSERT INTO schema_migrations (version, name, checksum, applied_at)
+        VALUES ($1, $2, $3, $4::TIMESTAMPTZ)
+        "#;
+    pub const DELETE: &str = "DELETE FROM schema_migrations WHERE version = $1";
+}
+
+/// 管理テーブルを作成する
+async fn ensure_table(db: &Db) -> Result<(), MigrateError> {
+    sqlx::query(sql::CREATE_TABLE).execute(db).await?;
+    Ok(())
+}
+
+/// 適用済みマイグレーションを取得し、チェックサムを検証する
+async fn applied(db: &Db) -> Result<Vec<AppliedMigration>, MigrateError> {
+    ensure_table(db).await?;
+
+    let rows: Vec<AppliedMigration> = sqlx::query_as(sql::SELECT_APPLIED).fetch_all(db).await?;
+
+    for row in &rows {
+        let migration = MIGRATIONS
+            .iter()
+            .find(|m| m.version == row.version)
+            .ok_or_else(|| MigrateError::Unknown {
+                version: row.version,
+                name: row.name.clone(),
+            })?;
+
+        if migration.checksum() != row.checksum {
+            return Err(MigrateError::ChecksumMismatch {
+                version: row.version,
+                name: row.name.clone(),
+            });
+        }
+    }
+
+    Ok(rows)
+}
+
+/// 未適用のマイグレーションを順に適用し、適用したバージョンを返す
+pub async fn migrate(db: &Db) -> Result<Vec<i64>, MigrateError> {
+    let applied = applied(db).await?;
+    let mut newly_applied = Vec::new();
+
+    for migration in MIGRATIONS {
+        if applied.iter().any(|a| a.version == migration.version) {
+            continue;
+        }
+
+        // スクリプトと記録を同一トランザクションで適用する
+        let mut tx = db.begin().await?;
+        sqlx::raw_sql(migration.up).execute(&mut *tx).await?;
+        sqlx::query(sql::INSERT)
+            .bind(migration.version)
+            .bind(migration.name)
+            .bind(migration.checksum())
+            .bind(Utc::now().to_rfc3339())
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        newly_applied.push(migration.version);
+    }
+
+    Ok(newly_applied)
+}
+
+/// 直近に適用したマイグレーションを `steps` 件ロールバックし、戻したバージョンを返す
+pub async fn rollback(db: &Db, steps: usize) -> Result<Vec<i64>, MigrateError> {
+    let applied = applied(db).await?;
+    let mut rolled_back = Vec::new();
+
+    for row in applied.iter().rev().take(steps) {
+        // applied() で存在確認済み
+        let migration = MIGRATIONS
+            .iter()
+            .find(|m| m.version == row.version)
+            .expect("applied migration must be known");
+
+        let mut tx = db.begin().await?;
+        sqlx::raw_sql(migration.down).execute(&mut *tx).await?;
+        sqlx::query(sql::DELETE)
+            .bind(migration.version)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        rolled_back.push(migration.version);
+    }
+
+    Ok(rolled_back)
+}
+
+/// 全マイグレーションの適用状況を取得する
+pub async fn status(db: &Db) -> Result<Vec<MigrationStatus>, MigrateError> {
+    let applied = applied(db).await?;
+
+    Ok(MIGRATIONS
+        .iter()
+        .map(|m| MigrationStatus {
+            version: m.version,
+            name: m.name,
+            applied_at: applied
+                .iter()
+                .find(|a| a.version == m.version)
+                .map(|a| a.applied_at.clone()),
+        })
+        .collect())
+}
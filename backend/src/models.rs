@@ -3,6 +3,86 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 
+use crate::services::{AuthSession, TweetDetails};
+
+/// TEXT 列に名前で保存する列挙型（GraphQL の enum と JSON にも同じ名前で出す）
+macro_rules! text_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident => $text:literal,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, async_graphql::Enum)]
+        #[serde(rename_all = "snake_case")]
+        pub enum $name {
+            $($(#[$variant_meta])* $variant,)*
+        }
+
+        impl $name {
+            pub fn as_str(self) -> &'static str {
+                match self {
+                    $(Self::$variant => $text,)*
+                }
+            }
+        }
+
+        impl TryFrom<String> for $name {
+            type Error = String;
+
+            fn try_from(value: String) -> Result<Self, Self::Error> {
+                match value.as_str() {
+                    $($text => Ok(Self::$variant),)*
+                    _ => Err(format!("Unknown {}: {}", stringify!($name), value)),
+                }
+            }
+        }
+    };
+}
+
+text_enum! {
+    /// ユーザーのロール（権限は `roles` で定義する、並び順は権限の強さ）
+    #[derive(Default, PartialOrd, Ord)]
+    pub enum Role {
+        #[default]
+        User => "user",
+        Moderator => "moderator",
+        Admin => "admin",
+    }
+}
+
+text_enum! {
+    /// 通報の対象
+    pub enum ReportTarget {
+        Tweet => "tweet",
+        Comment => "comment",
+        User => "user",
+    }
+}
+
+text_enum! {
+    /// 監査ログに記録するモデレーション操作
+    pub enum ModerationActionKind {
+        DeleteTweet => "delete_tweet",
+        DeleteComment => "delete_comment",
+        SuspendUser => "suspend_user",
+        UnsuspendUser => "unsuspend_user",
+        ChangeRole => "change_role",
+        ResolveReport => "resolve_report",
+    }
+}
+
+text_enum! {
+    /// 通知の種類（種類ごとに受け取らない設定にできる）
+    pub enum NotificationKind {
+        Like => "like",
+        Follow => "follow",
+        Comment => "comment",
+        Mention => "mention",
+    }
+}
+
 #[derive(Debug, Clone, FromRow)]
 pub struct User {
     pub id: Uuid,
@@ -11,6 +91,12 @@ pub struct User {
     pub password_hash: String,
     #[allow(dead_code)]
     pub created_at: String,
+    #[sqlx(try_from = "String")]
+    pub role: Role,
+    /// 利用停止された日時（停止中でなければ None）
+    pub suspended_at: Option<String>,
+    /// 相互フォローでなくてもダイレクトメッセージを受け取るか
+    pub allow_open_dms: bool,
 }
 
 #[derive(Debug, Clone, FromRow)]
@@ -19,25 +105,124 @@ pub struct Tweet {
     pub user_id: Uuid,
     pub content: String,
     pub created_at: String,
+    /// 引用ツイートの引用元
+    pub quoted_tweet_id: Option<Uuid>,
 }
 
-/// いいね済みツイートIDのみ取得用
+/// タイムラインの1件（同じツイートは1回だけ、最後に載った理由で表示する）
+#[derive(Debug, Clone)]
+pub struct TimelineEntry {
+    pub tweet: Tweet,
+    /// リツイートしたユーザー（投稿として載った場合は None）
+    pub retweeted_by: Option<Uuid>,
+    /// タイムラインに載った日時（投稿日時またはリツイート日時）
+    pub activity_at: String,
+}
+
+/// コメント
 #[derive(Debug, Clone, FromRow)]
-pub struct LikeTweetId {
+pub struct Comment {
+    pub id: Uuid,
     pub tweet_id: Uuid,
+    pub user_id: Uuid,
+    pub content: String,
+    pub created_at: String,
+    /// 返信先のコメント（ツイートへの直接のコメントなら None）
+    pub parent_id: Option<Uuid>,
 }
 
+/// リフレッシュトークン（token_hash はトークンのSHA-256）
 #[derive(Debug, Clone, FromRow)]
-pub struct HashtagName {
-    pub name: String,
+pub struct RefreshToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub family_id: Uuid,
+    pub token_hash: String,
+    pub created_at: String,
+    pub expires_at: String,
+    pub revoked_at: Option<String>,
+    pub replaced_by: Option<Uuid>,
 }
 
-/// コメント
+/// ログインセッション（端末ごと）
 #[derive(Debug, Clone, FromRow)]
-pub struct Comment {
+pub struct Session {
     pub id: Uuid,
-    pub tweet_id: Uuid,
     pub user_id: Uuid,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: String,
+    pub last_seen_at: String,
+    pub revoked_at: Option<String>,
+}
+
+/// ユーザーからの通報
+#[derive(Debug, Clone, FromRow)]
+pub struct Report {
+    pub id: Uuid,
+    pub reporter_id: Uuid,
+    #[sqlx(try_from = "String")]
+    pub target_type: ReportTarget,
+    pub target_id: Uuid,
+    pub reason: String,
+    pub created_at: String,
+    pub resolved_at: Option<String>,
+    pub resolved_by: Option<Uuid>,
+}
+
+/// 管理者・モデレーターの操作の監査ログ
+#[derive(Debug, Clone, FromRow)]
+pub struct ModerationAction {
+    pub id: Uuid,
+    pub actor_id: Uuid,
+    #[sqlx(try_from = "String")]
+    pub action: ModerationActionKind,
+    /// 操作対象（ツイート・コメント・ユーザー・通報）のID
+    pub target_id: Uuid,
+    /// 理由や変更後のロールなど
+    pub details: Option<String>,
+    pub created_at: String,
+}
+
+/// 通知（user_id が受け取るユーザー、actor_id が操作したユーザー）
+#[derive(Debug, Clone, FromRow)]
+pub struct Notification {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    #[sqlx(try_from = "String")]
+    pub kind: NotificationKind,
+    pub actor_id: Uuid,
+    /// まとめて表示する単位（種類と対象から作る）
+    pub group_key: String,
+    pub tweet_id: Option<Uuid>,
+    pub comment_id: Option<Uuid>,
+    pub created_at: String,
+    pub read_at: Option<String>,
+}
+
+/// ダイレクトメッセージの会話
+#[derive(Debug, Clone, FromRow)]
+pub struct DirectConversation {
+    pub id: Uuid,
+    pub created_at: String,
+    /// 最後のメッセージの日時（メッセージがなければ作成日時）
+    pub last_message_at: String,
+}
+
+/// 会話の参加者
+#[derive(Debug, Clone, FromRow)]
+pub struct DirectConversationMember {
+    pub conversation_id: Uuid,
+    pub user_id: Uuid,
+    /// 既読にした日時（この日時までのメッセージを読んだ）
+    pub last_read_at: Option<String>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct DirectMessage {
+    pub id: Uuid,
+    pub conversation_id: Uuid,
+    pub sender_id: Uuid,
     pub content: String,
     pub created_at: String,
 }
@@ -56,11 +241,21 @@ pub struct LoginRequest {
     pub password: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CreateTweetRequest {
     pub content: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SuspendUserRequest {
+    pub reason: Option<String>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct UserResponse {
     pub id: Uuid,
@@ -74,11 +269,27 @@ pub struct TweetResponse {
     pub user_id: Uuid,
     pub content: String,
     pub created_at: DateTime<Utc>,
+    pub like_count: i64,
+    /// 閲覧中のユーザーがいいねしているか（未ログインなら false）
+    pub is_liked: bool,
+    pub hashtags: Vec<String>,
+    pub quoted_tweet_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReportResponse {
+    pub id: Uuid,
+    pub reporter_id: Uuid,
+    pub target_type: ReportTarget,
+    pub target_id: Uuid,
+    pub reason: String,
+    pub created_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct AuthResponse {
     pub token: String,
+    pub refresh_token: String,
     pub user: UserResponse,
 }
 
@@ -92,8 +303,19 @@ impl From<User> for UserResponse {
     }
 }
 
-impl From<Tweet> for TweetResponse {
-    fn from(tweet: Tweet) -> Self {
+impl From<AuthSession> for AuthResponse {
+    fn from(session: AuthSession) -> Self {
+        Self {
+            token: session.token,
+            refresh_token: session.refresh_token,
+            user: UserResponse::from(session.user),
+        }
+    }
+}
+
+impl From<TweetDetails> for TweetResponse {
+    fn from(details: TweetDetails) -> Self {
+        let tweet = details.tweet;
         Self {
             id: tweet.id,
             user_id: tweet.user_id,
@@ -101,6 +323,25 @@ impl From<Tweet> for TweetResponse {
             created_at: DateTime::parse_from_rfc3339(&tweet.created_at)
                 .expect("Invalid date format")
                 .with_timezone(&Utc),
+            like_count: details.like_count,
+            is_liked: details.is_liked,
+            hashtags: details.hashtags,
+            quoted_tweet_id: tweet.quoted_tweet_id,
+        }
+    }
+}
+
+impl From<Report> for ReportResponse {
+    fn from(report: Report) -> Self {
+        Self {
+            id: report.id,
+            reporter_id: report.reporter_id,
+            target_type: report.target_type,
+            target_id: report.target_id,
+            reason: report.reason,
+            created_at: DateTime::parse_from_rfc3339(&report.created_at)
+                .expect("Invalid date format")
+                .with_timezone(&Utc),
         }
     }
 }
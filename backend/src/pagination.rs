@@ -0,0 +1,133 @@
+//! カーソルによるページネーション（Relay の Connection 仕様に対応）
+//!
+//! カーソルは並び順のキー（作成日時 + ID）で、同時刻の要素があっても位置が一意に決まる
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+/// first/last を省略した場合の件数
+pub const DEFAULT_PAGE_SIZE: usize = 20;
+/// 1ページの最大件数
+pub const MAX_PAGE_SIZE: usize = 100;
+
+/// 一覧内の位置（作成日時 + ID）
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Cursor {
+    pub created_at: String,
+    pub id: Uuid,
+}
+
+impl Cursor {
+    pub fn new(created_at: &str, id: Uuid) -> Self {
+        Self {
+            created_at: created_at.to_string(),
+            id,
+        }
+    }
+
+    /// クライアントに渡す不透明な文字列にする
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(format!("{}|{}", self.created_at, self.id))
+    }
+
+    pub fn decode(s: &str) -> Result<Self, AppError> {
+        let invalid = || AppError::BadRequest("Invalid cursor".to_string());
+
+        let bytes = URL_SAFE_NO_PAD.decode(s).map_err(|_| invalid())?;
+        let text = String::from_utf8(bytes).map_err(|_| invalid())?;
+        let (created_at, id) = text.rsplit_once('|').ok_or_else(invalid)?;
+        chrono::DateTime::parse_from_rfc3339(created_at).map_err(|_| invalid())?;
+
+        Ok(Self::new(created_at, id.parse().map_err(|_| invalid())?))
+    }
+}
+
+/// ページの取得条件
+#[derive(Debug, Clone)]
+pub struct PageQuery {
+    /// このカーソルより後ろ（一覧の並び順で）
+    pub after: Option<Cursor>,
+    /// このカーソルより前
+    pub before: Option<Cursor>,
+    pub limit: usize,
+    /// 末尾から数える（last 指定）
+    pub from_end: bool,
+}
+
+impl PageQuery {
+    /// first/after/last/before から取得条件を作る
+    pub fn new(
+        first: Option<usize>,
+        after: Option<Cursor>,
+        last: Option<usize>,
+        before: Option<Cursor>,
+    ) -> Result<Self, AppError> {
+        let (limit, from_end) = match (first, last) {
+            (Some(_), Some(_)) => {
+                return Err(AppError::BadRequest(
+                    "Passing both first and last is not supported".to_string(),
+                ));
+            }
+            (Some(first), None) => (first, false),
+            (None, Some(last)) => (last, true),
+            (None, None) => (DEFAULT_PAGE_SIZE, false),
+        };
+        if limit > MAX_PAGE_SIZE {
+            return Err(AppError::validation(
+                if from_end { "last" } else { "first" },
+                format!("first/last must be at most {}", MAX_PAGE_SIZE),
+            ));
+        }
+
+        Ok(Self {
+            after,
+            before,
+            limit,
+            from_end,
+        })
+    }
+
+    /// 指定されたカーソル（after, before の順）
+    pub fn cursors(&self) -> impl Iterator<Item = &Cursor> {
+        self.after.iter().chain(self.before.iter())
+    }
+
+    /// 次のページの有無を判定するため1件多く取得する
+    pub fn fetch_limit(&self) -> i64 {
+        self.limit as i64 + 1
+    }
+
+    /// 取得順（from_end なら一覧の逆順）に並んだ最大 fetch_limit 件の行からページを作る
+    pub fn page<T>(&self, mut rows: Vec<(Cursor, T)>) -> Page<T> {
+        let has_more = rows.len() > self.limit;
+        rows.truncate(self.limit);
+        if self.from_end {
+            rows.reverse();
+        }
+
+        Page {
+            items: rows,
+            has_previous_page: if self.from_end {
+                has_more
+            } else {
+                self.after.is_some()
+            },
+            has_next_page: if self.from_end {
+                self.before.is_some()
+            } else {
+                has_more
+            },
+        }
+    }
+}
+
+/// 一覧の1ページ（要素は一覧の並び順）
+#[derive(Debug)]
+pub struct Page<T> {
+    pub items: Vec<(Cursor, T)>,
+    pub has_previous_page: bool,
+    pub has_next_page: bool,
+}
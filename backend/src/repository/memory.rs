@@ -0,0 +1,1510 @@
+use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, MutexGuard};
+use uuid::Uuid;
+
+use super::{
+    BlockRepository, CommentRepository, DirectMessageRepository, FollowCounts, FollowRepository,
+    HashtagRepository, HashtagStats, LikeRepository, MentionRepository, ModerationLogRepository,
+    NotificationGroup, NotificationRepository, RefreshTokenRepository, ReportRepository, Result,
+    RetweetRepository, SearchFilter, SearchRepository, SessionRepository, TweetRepository,
+    UserRepository,
+};
+use crate::error::AppError;
+use crate::models::{
+    Comment, DirectConversation, DirectConversationMember, DirectMessage, ModerationAction,
+    Notification, NotificationKind, RefreshToken, Report, Role, Session, TimelineEntry, Tweet,
+    User,
+};
+use crate::pagination::{Cursor, Page, PageQuery};
+
+/// インメモリのリポジトリ実装（テストやデータベースなしでの起動用）
+///
+/// SQLite実装と同じ制約（一意制約・削除時のカスケード・並び順）を再現する
+#[derive(Default)]
+pub struct MemoryRepository {
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    users: Vec<User>,
+    tweets: Vec<Tweet>,
+    /// (tweet_id, ハッシュタグ名)
+    tweet_hashtags: Vec<(Uuid, String)>,
+    /// (tweet_id, 言及されたユーザーID)
+    tweet_mentions: Vec<(Uuid, Uuid)>,
+    /// (comment_id, 言及されたユーザーID)
+    comment_mentions: Vec<(Uuid, Uuid)>,
+    likes: Vec<Like>,
+    retweets: Vec<Retweet>,
+    follows: Vec<Follow>,
+    /// (blocker_id, blocked_id)
+    blocks: Vec<(Uuid, Uuid)>,
+    /// (muter_id, muted_id)
+    mutes: Vec<(Uuid, Uuid)>,
+    comments: Vec<Comment>,
+    sessions: Vec<Session>,
+    refresh_tokens: Vec<RefreshToken>,
+    reports: Vec<Report>,
+    moderation_actions: Vec<ModerationAction>,
+    notifications: Vec<Notification>,
+    /// (user_id, 受け取らない通知の種類)
+    disabled_notification_kinds: Vec<(Uuid, NotificationKind)>,
+    direct_conversations: Vec<DirectConversation>,
+    direct_conversation_members: Vec<DirectConversationMember>,
+    direct_messages: Vec<DirectMessage>,
+}
+
+struct Like {
+    user_id: Uuid,
+    tweet_id: Uuid,
+}
+
+struct Retweet {
+    user_id: Uuid,
+    tweet_id: Uuid,
+    created_at: String,
+}
+
+struct Follow {
+    follower_id: Uuid,
+    following_id: Uuid,
+    created_at: String,
+}
+
+impl MemoryRepository {
+    fn state(&self) -> MutexGuard<'_, State> {
+        // パニックしたスレッドがあってもデータ自体は一貫しているので使い続ける
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn unique_violation(column: &str) -> AppError {
+    AppError::Database(format!("UNIQUE constraint failed: {}", column))
+}
+
+/// SQLの (key, id) によるキーセットページネーションを再現する（descending は一覧の並び順）
+fn paginate<T>(mut items: Vec<(Cursor, T)>, descending: bool, query: &PageQuery) -> Page<T> {
+    // 取得順（from_end なら一覧の逆順）に並べる
+    items.sort_by(|a, b| a.0.cmp(&b.0));
+    if descending != query.from_end {
+        items.reverse();
+    }
+
+    // 一覧の並び順で a が b より後ろにあるか
+    let later = |a: &Cursor, b: &Cursor| if descending { a < b } else { a > b };
+    items.retain(|(cursor, _)| {
+        query
+            .after
+            .as_ref()
+            .is_none_or(|after| later(cursor, after))
+            && query
+                .before
+                .as_ref()
+                .is_none_or(|before| later(before, cursor))
+    });
+    items.truncate(query.fetch_limit() as usize);
+
+    query.page(items)
+}
+
+/// 語をすべて含むか（大文字小文字は区別しない）
+fn contains_all(text: &str, terms: &[String]) -> bool {
+    let text = text.to_lowercase();
+    terms.iter().all(|term| text.contains(&term.to_lowercase()))
+}
+
+/// 投稿者と日時の条件に合うか
+fn matches_filter(filter: &SearchFilter, user_id: Uuid, created_at: &str) -> bool {
+    filter.author_id.is_none_or(|author| author == user_id)
+        && filter
+            .since
+            .as_deref()
+            .is_none_or(|since| created_at >= since)
+        && filter
+            .until
+            .as_deref()
+            .is_none_or(|until| created_at < until)
+}
+
+impl State {
+    /// pick が返すユーザーIDを、フォローの新しい順にページングする
+    fn users_by_follow<F>(&self, pick: F, query: &PageQuery) -> Page<User>
+    where
+        F: Fn(&Follow) -> Option<Uuid>,
+    {
+        let items = self
+            .follows
+            .iter()
+            .filter_map(|f| {
+                let id = pick(f)?;
+                let user = self.users.iter().find(|u| u.id == id)?;
+                Some((Cursor::new(&f.created_at, id), user.clone()))
+            })
+            .collect();
+        paginate(items, true, query)
+    }
+
+    /// ハッシュタグの付いたツイート（順不同）
+    fn tagged_tweets<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Tweet> {
+        self.tweets.iter().filter(move |t| {
+            self.tweet_hashtags
+                .iter()
+                .any(|(tweet_id, tag)| *tweet_id == t.id && tag == name)
+        })
+    }
+
+    /// コメントと、そのコメントでの言及・通知を削除する
+    fn remove_comments(&mut self, ids: &HashSet<Uuid>) {
+        self.comments.retain(|c| !ids.contains(&c.id));
+        self.comment_mentions
+            .retain(|(comment_id, _)| !ids.contains(comment_id));
+        self.notifications
+            .retain(|n| n.comment_id.is_none_or(|id| !ids.contains(&id)));
+    }
+
+    /// ユーザーの通知を group_key ごとにまとめる（順不同）
+    fn notification_groups(&self, user_id: Uuid) -> HashMap<&str, Vec<&Notification>> {
+        let mut groups: HashMap<&str, Vec<&Notification>> = HashMap::new();
+        for n in self.notifications.iter().filter(|n| n.user_id == user_id) {
+            groups.entry(&n.group_key).or_default().push(n);
+        }
+        // 各まとまりを新しい順に並べる
+        for group in groups.values_mut() {
+            group.sort_by(|a, b| (&b.created_at, b.id).cmp(&(&a.created_at, a.id)));
+        }
+        groups
+    }
+
+    /// parent_id（None ならツイート）から max_depth 階層までの返信（順不同）
+    fn thread(
+        &self,
+        tweet_id: Uuid,
+        parent_id: Option<Uuid>,
+        max_depth: i64,
+        hidden: &HashSet<Uuid>,
+    ) -> impl Iterator<Item = &Comment> {
+        let mut ids: HashSet<Uuid> = HashSet::new();
+        let mut level: Vec<Option<Uuid>> = vec![parent_id];
+        for _ in 0..max_depth {
+            let children: Vec<Option<Uuid>> = self
+                .comments
+                .iter()
+                .filter(|c| {
+                    c.tweet_id == tweet_id
+                        && level.contains(&c.parent_id)
+                        && !hidden.contains(&c.user_id)
+                })
+                .map(|c| Some(c.id))
+                .collect();
+            if children.is_empty() {
+                break;
+            }
+            ids.extend(children.iter().flatten().copied());
+            level = children;
+        }
+        self.comments.iter().filter(move |c| ids.contains(&c.id))
+    }
+
+    /// viewer から見えないユーザー（viewer がブロック・ミュートしたか、viewer をブロックした）
+    fn hidden_users(&self, viewer: Uuid) -> HashSet<Uuid> {
+        let blocks = self.blocks.iter().filter_map(|&(blocker, blocked)| {
+            if blocker == viewer {
+                Some(blocked)
+            } else if blocked == viewer {
+                Some(blocker)
+            } else {
+                None
+            }
+        });
+        let mutes = self
+            .mutes
+            .iter()
+            .filter(|(muter, _)| *muter == viewer)
+            .map(|(_, muted)| *muted);
+        blocks.chain(mutes).collect()
+    }
+
+    /// 自分とフォロー中のユーザー（見えないユーザーは除く）
+    fn timeline_authors(&self, user_id: Uuid) -> HashSet<Uuid> {
+        let hidden = self.hidden_users(user_id);
+        self.follows
+            .iter()
+            .filter(|f| f.follower_id == user_id)
+            .map(|f| f.following_id)
+            .chain(std::iter::once(user_id))
+            .filter(|id| !hidden.contains(id))
+            .collect()
+    }
+
+    /// 自分とフォロー中のユーザーのツイート（順不同）
+    fn timeline_tweets(&self, user_id: Uuid) -> impl Iterator<Item = &Tweet> {
+        let authors = self.timeline_authors(user_id);
+        self.tweets
+            .iter()
+            .filter(move |t| authors.contains(&t.user_id))
+    }
+
+    /// 自分とフォロー中のユーザーの投稿・リツイート（ツイートごとに最後に載った1件、順不同）
+    ///
+    /// 見えないユーザーのツイートのリツイートは除く
+    fn timeline_entries(&self, user_id: Uuid) -> Vec<TimelineEntry> {
+        let authors = self.timeline_authors(user_id);
+        let hidden = self.hidden_users(user_id);
+        let posts = self
+            .tweets
+            .iter()
+            .filter(|t| authors.contains(&t.user_id))
+            .map(|t| (t, None, &t.created_at));
+        let retweets = self
+            .retweets
+            .iter()
+            .filter(|r| authors.contains(&r.user_id))
+            .filter_map(|r| {
+                let tweet = self.tweets.iter().find(|t| t.id == r.tweet_id)?;
+                if hidden.contains(&tweet.user_id) {
+                    return None;
+                }
+                Some((tweet, Some(r.user_id), &r.created_at))
+            });
+
+        let mut latest: HashMap<Uuid, TimelineEntry> = HashMap::new();
+        for (tweet, retweeted_by, activity_at) in posts.chain(retweets) {
+            if latest
+                .get(&tweet.id)
+                .is_some_and(|e| e.activity_at >= *activity_at)
+            {
+                continue;
+            }
+            latest.insert(
+                tweet.id,
+                TimelineEntry {
+                    tweet: tweet.clone(),
+                    retweeted_by,
+                    activity_at: activity_at.clone(),
+                },
+            );
+        }
+        latest.into_values().collect()
+    }
+}
+
+#[async_trait]
+impl UserRepository for MemoryRepository {
+    async fn insert(&self, user: &User) -> Result<()> {
+        let mut state = self.state();
+        if state.users.iter().any(|u| u.email == user.email) {
+            return Err(unique_violation("users.email"));
+        }
+        if state.users.iter().any(|u| u.username == user.username) {
+            return Err(unique_violation("users.username"));
+        }
+        state.users.push(user.clone());
+        Ok(())
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<User>> {
+        Ok(self.state().users.iter().find(|u| u.id == id).cloned())
+    }
+
+    async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<User>> {
+        Ok(self
+            .state()
+            .users
+            .iter()
+            .filter(|u| ids.contains(&u.id))
+            .cloned()
+            .collect())
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<User>> {
+        Ok(self
+            .state()
+            .users
+            .iter()
+            .find(|u| u.email == email)
+            .cloned())
+    }
+
+    async fn find_by_username(&self, username: &str) -> Result<Option<User>> {
+        Ok(self
+            .state()
+            .users
+            .iter()
+            .find(|u| u.username == username)
+            .cloned())
+    }
+
+    async fn find_by_usernames(&self, usernames: &[String]) -> Result<Vec<User>> {
+        Ok(self
+            .state()
+            .users
+            .iter()
+            .filter(|u| usernames.contains(&u.username))
+            .cloned()
+            .collect())
+    }
+
+    async fn set_role(&self, id: Uuid, role: Role) -> Result<bool> {
+        match self.state().users.iter_mut().find(|u| u.id == id) {
+            Some(user) => {
+                user.role = role;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn set_suspended_at(&self, id: Uuid, at: Option<&str>) -> Result<bool> {
+        match self.state().users.iter_mut().find(|u| u.id == id) {
+            Some(user) => {
+                user.suspended_at = at.map(str::to_string);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn set_allow_open_dms(&self, id: Uuid, allow: bool) -> Result<bool> {
+        match self.state().users.iter_mut().find(|u| u.id == id) {
+            Some(user) => {
+                user.allow_open_dms = allow;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+#[async_trait]
+impl TweetRepository for MemoryRepository {
+    async fn insert(&self, tweet: &Tweet, hashtags: &[String], mentions: &[Uuid]) -> Result<()> {
+        let mut state = self.state();
+        state.tweets.push(tweet.clone());
+        state
+            .tweet_hashtags
+            .extend(hashtags.iter().map(|name| (tweet.id, name.clone())));
+        state
+            .tweet_mentions
+            .extend(mentions.iter().map(|user_id| (tweet.id, *user_id)));
+        Ok(())
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<Tweet>> {
+        Ok(self.state().tweets.iter().find(|t| t.id == id).cloned())
+    }
+
+    async fn find_many(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, Tweet>> {
+        Ok(self
+            .state()
+            .tweets
+            .iter()
+            .filter(|t| ids.contains(&t.id))
+            .map(|t| (t.id, t.clone()))
+            .collect())
+    }
+
+    async fn delete(&self, id: Uuid, user_id: Option<Uuid>) -> Result<bool> {
+        let mut state = self.state();
+        let before = state.tweets.len();
+        state
+            .tweets
+            .retain(|t| !(t.id == id && user_id.is_none_or(|u| t.user_id == u)));
+        if state.tweets.len() == before {
+            return Ok(false);
+        }
+
+        state.likes.retain(|l| l.tweet_id != id);
+        state.retweets.retain(|r| r.tweet_id != id);
+        state.tweet_hashtags.retain(|(tweet_id, _)| *tweet_id != id);
+        state.tweet_mentions.retain(|(tweet_id, _)| *tweet_id != id);
+        state.notifications.retain(|n| n.tweet_id != Some(id));
+        let removed: HashSet<Uuid> = state
+            .comments
+            .iter()
+            .filter(|c| c.tweet_id == id)
+            .map(|c| c.id)
+            .collect();
+        state.remove_comments(&removed);
+        Ok(true)
+    }
+
+    async fn timeline(&self, user_id: Uuid) -> Result<Vec<Tweet>> {
+        let mut tweets: Vec<Tweet> = self.state().timeline_tweets(user_id).cloned().collect();
+        tweets.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(tweets)
+    }
+
+    async fn timeline_page(&self, user_id: Uuid, query: &PageQuery) -> Result<Page<TimelineEntry>> {
+        let items = self
+            .state()
+            .timeline_entries(user_id)
+            .into_iter()
+            .map(|e| (Cursor::new(&e.activity_at, e.tweet.id), e))
+            .collect();
+        Ok(paginate(items, true, query))
+    }
+
+    async fn timeline_count(&self, user_id: Uuid) -> Result<i64> {
+        Ok(self.state().timeline_entries(user_id).len() as i64)
+    }
+
+    async fn quote_counts(&self, tweet_ids: &[Uuid]) -> Result<HashMap<Uuid, i64>> {
+        let mut counts = HashMap::new();
+        for tweet in &self.state().tweets {
+            if let Some(quoted) = tweet.quoted_tweet_id.filter(|id| tweet_ids.contains(id)) {
+                *counts.entry(quoted).or_insert(0) += 1;
+            }
+        }
+        Ok(counts)
+    }
+}
+
+#[async_trait]
+impl RetweetRepository for MemoryRepository {
+    async fn insert(&self, user_id: Uuid, tweet_id: Uuid, created_at: &str) -> Result<bool> {
+        let mut state = self.state();
+        if state
+            .retweets
+            .iter()
+            .any(|r| r.user_id == user_id && r.tweet_id == tweet_id)
+        {
+            return Ok(false);
+        }
+        state.retweets.push(Retweet {
+            user_id,
+            tweet_id,
+            created_at: created_at.to_string(),
+        });
+        Ok(true)
+    }
+
+    async fn delete(&self, user_id: Uuid, tweet_id: Uuid) -> Result<bool> {
+        let mut state = self.state();
+        let before = state.retweets.len();
+        state
+            .retweets
+            .retain(|r| !(r.user_id == user_id && r.tweet_id == tweet_id));
+        Ok(state.retweets.len() < before)
+    }
+
+    async fn counts(&self, tweet_ids: &[Uuid]) -> Result<HashMap<Uuid, i64>> {
+        let mut counts = HashMap::new();
+        for retweet in &self.state().retweets {
+            if tweet_ids.contains(&retweet.tweet_id) {
+                *counts.entry(retweet.tweet_id).or_insert(0) += 1;
+            }
+        }
+        Ok(counts)
+    }
+
+    async fn retweeted_by(&self, user_id: Uuid, tweet_ids: &[Uuid]) -> Result<HashSet<Uuid>> {
+        Ok(self
+            .state()
+            .retweets
+            .iter()
+            .filter(|r| r.user_id == user_id && tweet_ids.contains(&r.tweet_id))
+            .map(|r| r.tweet_id)
+            .collect())
+    }
+}
+
+#[async_trait]
+impl LikeRepository for MemoryRepository {
+    async fn insert(&self, user_id: Uuid, tweet_id: Uuid, _created_at: &str) -> Result<bool> {
+        let mut state = self.state();
+        if state
+            .likes
+            .iter()
+            .any(|l| l.user_id == user_id && l.tweet_id == tweet_id)
+        {
+            return Ok(false);
+        }
+        state.likes.push(Like { user_id, tweet_id });
+        Ok(true)
+    }
+
+    async fn delete(&self, user_id: Uuid, tweet_id: Uuid) -> Result<bool> {
+        let mut state = self.state();
+        let before = state.likes.len();
+        state
+            .likes
+            .retain(|l| !(l.user_id == user_id && l.tweet_id == tweet_id));
+        Ok(state.likes.len() < before)
+    }
+
+    async fn counts(&self, tweet_ids: &[Uuid]) -> Result<HashMap<Uuid, i64>> {
+        let mut counts = HashMap::new();
+        for like in &self.state().likes {
+            if tweet_ids.contains(&like.tweet_id) {
+                *counts.entry(like.tweet_id).or_insert(0) += 1;
+            }
+        }
+        Ok(counts)
+    }
+
+    async fn liked_by(&self, user_id: Uuid, tweet_ids: &[Uuid]) -> Result<HashSet<Uuid>> {
+        Ok(self
+            .state()
+            .likes
+            .iter()
+            .filter(|l| l.user_id == user_id && tweet_ids.contains(&l.tweet_id))
+            .map(|l| l.tweet_id)
+            .collect())
+    }
+}
+
+#[async_trait]
+impl FollowRepository for MemoryRepository {
+    async fn insert(
+        &self,
+        follower_id: Uuid,
+        following_id: Uuid,
+        created_at: &str,
+    ) -> Result<bool> {
+        let mut state = self.state();
+        if state
+            .follows
+            .iter()
+            .any(|f| f.follower_id == follower_id && f.following_id == following_id)
+        {
+            return Ok(false);
+        }
+        state.follows.push(Follow {
+            follower_id,
+            following_id,
+            created_at: created_at.to_string(),
+        });
+        Ok(true)
+    }
+
+    async fn delete(&self, follower_id: Uuid, following_id: Uuid) -> Result<bool> {
+        let mut state = self.state();
+        let before = state.follows.len();
+        state
+            .follows
+            .retain(|f| !(f.follower_id == follower_id && f.following_id == following_id));
+        Ok(state.follows.len() < before)
+    }
+
+    async fn counts(&self, user_ids: &[Uuid]) -> Result<HashMap<Uuid, FollowCounts>> {
+        let state = self.state();
+        Ok(user_ids
+            .iter()
+            .filter(|id| state.users.iter().any(|u| u.id == **id))
+            .map(|id| {
+                let counts = FollowCounts {
+                    followers: state
+                        .follows
+                        .iter()
+                        .filter(|f| f.following_id == *id)
+                        .count() as i64,
+                    following: state
+                        .follows
+                        .iter()
+                        .filter(|f| f.follower_id == *id)
+                        .count() as i64,
+                };
+                (*id, counts)
+            })
+            .collect())
+    }
+
+    async fn following_set(&self, follower_id: Uuid, target_ids: &[Uuid]) -> Result<HashSet<Uuid>> {
+        Ok(self
+            .state()
+            .follows
+            .iter()
+            .filter(|f| f.follower_id == follower_id && target_ids.contains(&f.following_id))
+            .map(|f| f.following_id)
+            .collect())
+    }
+
+    async fn followers(&self, user_id: Uuid, query: &PageQuery) -> Result<Page<User>> {
+        Ok(self.state().users_by_follow(
+            |f| (f.following_id == user_id).then_some(f.follower_id),
+            query,
+        ))
+    }
+
+    async fn following(&self, user_id: Uuid, query: &PageQuery) -> Result<Page<User>> {
+        Ok(self.state().users_by_follow(
+            |f| (f.follower_id == user_id).then_some(f.following_id),
+            query,
+        ))
+    }
+}
+
+#[async_trait]
+impl BlockRepository for MemoryRepository {
+    async fn block(&self, blocker_id: Uuid, blocked_id: Uuid, _created_at: &str) -> Result<bool> {
+        let mut state = self.state();
+        if state.blocks.contains(&(blocker_id, blocked_id)) {
+            return Ok(false);
+        }
+        state.blocks.push((blocker_id, blocked_id));
+
+        let between = |a: Uuid, b: Uuid| {
+            (a == blocker_id && b == blocked_id) || (a == blocked_id && b == blocker_id)
+        };
+        state
+            .follows
+            .retain(|f| !between(f.follower_id, f.following_id));
+        state
+            .notifications
+            .retain(|n| !between(n.user_id, n.actor_id));
+        let authors: HashMap<Uuid, Uuid> = state.tweets.iter().map(|t| (t.id, t.user_id)).collect();
+        state.retweets.retain(|r| {
+            authors
+                .get(&r.tweet_id)
+                .is_none_or(|&author| !between(r.user_id, author))
+        });
+        Ok(true)
+    }
+
+    async fn unblock(&self, blocker_id: Uuid, blocked_id: Uuid) -> Result<bool> {
+        let mut state = self.state();
+        let before = state.blocks.len();
+        state.blocks.retain(|b| *b != (blocker_id, blocked_id));
+        Ok(state.blocks.len() < before)
+    }
+
+    async fn mute(&self, muter_id: Uuid, muted_id: Uuid, _created_at: &str) -> Result<bool> {
+        let mut state = self.state();
+        if state.mutes.contains(&(muter_id, muted_id)) {
+            return Ok(false);
+        }
+        state.mutes.push((muter_id, muted_id));
+        state
+            .notifications
+            .retain(|n| !(n.user_id == muter_id && n.actor_id == muted_id));
+        Ok(true)
+    }
+
+    async fn unmute(&self, muter_id: Uuid, muted_id: Uuid) -> Result<bool> {
+        let mut state = self.state();
+        let before = state.mutes.len();
+        state.mutes.retain(|m| *m != (muter_id, muted_id));
+        Ok(state.mutes.len() < before)
+    }
+
+    async fn is_blocked(&self, a: Uuid, b: Uuid) -> Result<bool> {
+        Ok(self
+            .state()
+            .blocks
+            .iter()
+            .any(|&block| block == (a, b) || block == (b, a)))
+    }
+
+    async fn hidden_set(&self, viewer: Uuid, user_ids: &[Uuid]) -> Result<HashSet<Uuid>> {
+        let state = self.state();
+        let hidden = state.hidden_users(viewer);
+        Ok(user_ids
+            .iter()
+            .filter(|id| hidden.contains(id) && state.users.iter().any(|u| u.id == **id))
+            .copied()
+            .collect())
+    }
+}
+
+#[async_trait]
+impl CommentRepository for MemoryRepository {
+    async fn insert(&self, comment: &Comment, mentions: &[Uuid]) -> Result<()> {
+        let mut state = self.state();
+        state.comments.push(comment.clone());
+        state
+            .comment_mentions
+            .extend(mentions.iter().map(|user_id| (comment.id, *user_id)));
+        Ok(())
+    }
+
+    async fn list_for_tweet(
+        &self,
+        tweet_id: Uuid,
+        viewer: Option<Uuid>,
+        query: &PageQuery,
+    ) -> Result<Page<Comment>> {
+        let state = self.state();
+        let hidden = viewer.map(|v| state.hidden_users(v)).unwrap_or_default();
+        let items = state
+            .comments
+            .iter()
+            .filter(|c| c.tweet_id == tweet_id && !hidden.contains(&c.user_id))
+            .map(|c| (Cursor::new(&c.created_at, c.id), c.clone()))
+            .collect();
+        Ok(paginate(items, false, query))
+    }
+
+    async fn count_for_tweet(&self, tweet_id: Uuid, viewer: Option<Uuid>) -> Result<i64> {
+        let state = self.state();
+        let hidden = viewer.map(|v| state.hidden_users(v)).unwrap_or_default();
+        Ok(state
+            .comments
+            .iter()
+            .filter(|c| c.tweet_id == tweet_id && !hidden.contains(&c.user_id))
+            .count() as i64)
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<Comment>> {
+        Ok(self.state().comments.iter().find(|c| c.id == id).cloned())
+    }
+
+    async fn find_many(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, Comment>> {
+        Ok(self
+            .state()
+            .comments
+            .iter()
+            .filter(|c| ids.contains(&c.id))
+            .map(|c| (c.id, c.clone()))
+            .collect())
+    }
+
+    async fn delete(&self, id: Uuid, user_id: Option<Uuid>) -> Result<bool> {
+        let mut state = self.state();
+        let Some(comment) = state
+            .comments
+            .iter()
+            .find(|c| c.id == id && user_id.is_none_or(|u| c.user_id == u))
+        else {
+            return Ok(false);
+        };
+
+        let mut removed: HashSet<Uuid> = state
+            .thread(comment.tweet_id, Some(id), i64::MAX, &HashSet::new())
+            .map(|c| c.id)
+            .collect();
+        removed.insert(id);
+        state.remove_comments(&removed);
+        Ok(true)
+    }
+
+    async fn thread_page(
+        &self,
+        tweet_id: Uuid,
+        parent_id: Option<Uuid>,
+        max_depth: i64,
+        viewer: Option<Uuid>,
+        query: &PageQuery,
+    ) -> Result<Page<Comment>> {
+        let state = self.state();
+        let hidden = viewer.map(|v| state.hidden_users(v)).unwrap_or_default();
+        let items = state
+            .thread(tweet_id, parent_id, max_depth, &hidden)
+            .map(|c| (Cursor::new(&c.created_at, c.id), c.clone()))
+            .collect();
+        Ok(paginate(items, false, query))
+    }
+
+    async fn thread_count(
+        &self,
+        tweet_id: Uuid,
+        parent_id: Option<Uuid>,
+        max_depth: i64,
+        viewer: Option<Uuid>,
+    ) -> Result<i64> {
+        let state = self.state();
+        let hidden = viewer.map(|v| state.hidden_users(v)).unwrap_or_default();
+        Ok(state
+            .thread(tweet_id, parent_id, max_depth, &hidden)
+            .count() as i64)
+    }
+
+    async fn ancestors(&self, id: Uuid) -> Result<Vec<Comment>> {
+        let state = self.state();
+        let find = |id: Uuid| state.comments.iter().find(|c| c.id == id);
+
+        let mut ancestors = Vec::new();
+        let mut parent_id = find(id).and_then(|c| c.parent_id);
+        while let Some(parent) = parent_id.and_then(find) {
+            ancestors.push(parent.clone());
+            parent_id = parent.parent_id;
+        }
+        ancestors.reverse();
+        Ok(ancestors)
+    }
+
+    async fn reply_counts(
+        &self,
+        comment_ids: &[Uuid],
+        viewer: Option<Uuid>,
+    ) -> Result<HashMap<Uuid, i64>> {
+        let state = self.state();
+        let hidden = viewer.map(|v| state.hidden_users(v)).unwrap_or_default();
+        let mut counts = HashMap::new();
+        for comment in state
+            .comments
+            .iter()
+            .filter(|c| !hidden.contains(&c.user_id))
+        {
+            if let Some(parent) = comment.parent_id.filter(|id| comment_ids.contains(id)) {
+                *counts.entry(parent).or_insert(0) += 1;
+            }
+        }
+        Ok(counts)
+    }
+
+    async fn top_level_counts(
+        &self,
+        tweet_ids: &[Uuid],
+        viewer: Option<Uuid>,
+    ) -> Result<HashMap<Uuid, i64>> {
+        let state = self.state();
+        let hidden = viewer.map(|v| state.hidden_users(v)).unwrap_or_default();
+        let mut counts = HashMap::new();
+        for comment in state
+            .comments
+            .iter()
+            .filter(|c| !hidden.contains(&c.user_id))
+        {
+            if comment.parent_id.is_none() && tweet_ids.contains(&comment.tweet_id) {
+                *counts.entry(comment.tweet_id).or_insert(0) += 1;
+            }
+        }
+        Ok(counts)
+    }
+}
+
+#[async_trait]
+impl HashtagRepository for MemoryRepository {
+    async fn for_tweets(&self, tweet_ids: &[Uuid]) -> Result<HashMap<Uuid, Vec<String>>> {
+        let mut hashtag_map: HashMap<Uuid, Vec<String>> = HashMap::new();
+        for (tweet_id, name) in &self.state().tweet_hashtags {
+            if tweet_ids.contains(tweet_id) {
+                hashtag_map.entry(*tweet_id).or_default().push(name.clone());
+            }
+        }
+        Ok(hashtag_map)
+    }
+
+    async fn stats(&self, name: &str) -> Result<Option<HashtagStats>> {
+        let state = self.state();
+        let created_at: Vec<&str> = state
+            .tagged_tweets(name)
+            .map(|t| t.created_at.as_str())
+            .collect();
+
+        Ok(created_at.iter().min().zip(created_at.iter().max()).map(
+            |(first_used_at, last_used_at)| HashtagStats {
+                tweet_count: created_at.len() as i64,
+                first_used_at: first_used_at.to_string(),
+                last_used_at: last_used_at.to_string(),
+            },
+        ))
+    }
+
+    async fn tweets_page(&self, name: &str, query: &PageQuery) -> Result<Page<Tweet>> {
+        let items = self
+            .state()
+            .tagged_tweets(name)
+            .map(|t| (Cursor::new(&t.created_at, t.id), t.clone()))
+            .collect();
+        Ok(paginate(items, true, query))
+    }
+
+    async fn counts_between(&self, since: &str, until: &str) -> Result<HashMap<String, i64>> {
+        let state = self.state();
+        let mut counts: HashMap<String, i64> = HashMap::new();
+        for (tweet_id, name) in &state.tweet_hashtags {
+            let in_range = state.tweets.iter().any(|t| {
+                t.id == *tweet_id && t.created_at.as_str() >= since && t.created_at.as_str() < until
+            });
+            if in_range {
+                *counts.entry(name.clone()).or_default() += 1;
+            }
+        }
+        Ok(counts)
+    }
+}
+
+#[async_trait]
+impl MentionRepository for MemoryRepository {
+    async fn for_tweets(&self, tweet_ids: &[Uuid]) -> Result<HashMap<Uuid, Vec<Uuid>>> {
+        Ok(group_mentions(&self.state().tweet_mentions, tweet_ids))
+    }
+
+    async fn for_comments(&self, comment_ids: &[Uuid]) -> Result<HashMap<Uuid, Vec<Uuid>>> {
+        Ok(group_mentions(&self.state().comment_mentions, comment_ids))
+    }
+
+    async fn tweets_page(&self, user_id: Uuid, query: &PageQuery) -> Result<Page<Tweet>> {
+        let state = self.state();
+        let items = state
+            .tweets
+            .iter()
+            .filter(|t| state.tweet_mentions.contains(&(t.id, user_id)))
+            .map(|t| (Cursor::new(&t.created_at, t.id), t.clone()))
+            .collect();
+        Ok(paginate(items, true, query))
+    }
+
+    async fn tweet_count(&self, user_id: Uuid) -> Result<i64> {
+        Ok(self
+            .state()
+            .tweet_mentions
+            .iter()
+            .filter(|(_, u)| *u == user_id)
+            .count() as i64)
+    }
+}
+
+/// (ID, 言及されたユーザーID) の組を ids の ID ごとにまとめる
+fn group_mentions(mentions: &[(Uuid, Uuid)], ids: &[Uuid]) -> HashMap<Uuid, Vec<Uuid>> {
+    let mut mention_map: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    for (id, user_id) in mentions {
+        if ids.contains(id) {
+            mention_map.entry(*id).or_default().push(*user_id);
+        }
+    }
+    mention_map
+}
+
+#[async_trait]
+impl SessionRepository for MemoryRepository {
+    async fn insert(&self, session: &Session) -> Result<()> {
+        self.state().sessions.push(session.clone());
+        Ok(())
+    }
+
+    async fn find(&self, id: Uuid, user_id: Uuid) -> Result<Option<Session>> {
+        Ok(self
+            .state()
+            .sessions
+            .iter()
+            .find(|s| s.id == id && s.user_id == user_id)
+            .cloned())
+    }
+
+    async fn touch(&self, id: Uuid, at: &str) -> Result<()> {
+        if let Some(session) = self.state().sessions.iter_mut().find(|s| s.id == id) {
+            session.last_seen_at = at.to_string();
+        }
+        Ok(())
+    }
+
+    async fn list_active(&self, user_id: Uuid) -> Result<Vec<Session>> {
+        let mut sessions: Vec<Session> = self
+            .state()
+            .sessions
+            .iter()
+            .filter(|s| s.user_id == user_id && s.revoked_at.is_none())
+            .cloned()
+            .collect();
+        sessions.sort_by(|a, b| b.last_seen_at.cmp(&a.last_seen_at));
+        Ok(sessions)
+    }
+
+    async fn revoke(&self, id: Uuid, user_id: Option<Uuid>, at: &str) -> Result<bool> {
+        let mut state = self.state();
+        let session = state.sessions.iter_mut().find(|s| {
+            s.id == id && user_id.is_none_or(|u| s.user_id == u) && s.revoked_at.is_none()
+        });
+        match session {
+            Some(session) => {
+                session.revoked_at = Some(at.to_string());
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn revoke_others(&self, user_id: Uuid, except: Uuid, at: &str) -> Result<u64> {
+        let mut revoked = 0;
+        for session in self.state().sessions.iter_mut() {
+            if session.user_id == user_id && session.id != except && session.revoked_at.is_none() {
+                session.revoked_at = Some(at.to_string());
+                revoked += 1;
+            }
+        }
+        Ok(revoked)
+    }
+}
+
+#[async_trait]
+impl RefreshTokenRepository for MemoryRepository {
+    async fn insert(&self, token: &RefreshToken) -> Result<()> {
+        let mut state = self.state();
+        if state
+            .refresh_tokens
+            .iter()
+            .any(|t| t.token_hash == token.token_hash)
+        {
+            return Err(unique_violation("refresh_tokens.token_hash"));
+        }
+        state.refresh_tokens.push(token.clone());
+        Ok(())
+    }
+
+    async fn find_by_hash(&self, token_hash: &str) -> Result<Option<RefreshToken>> {
+        Ok(self
+            .state()
+            .refresh_tokens
+            .iter()
+            .find(|t| t.token_hash == token_hash)
+            .cloned())
+    }
+
+    async fn revoke_family(&self, family_id: Uuid, at: &str) -> Result<()> {
+        for token in self.state().refresh_tokens.iter_mut() {
+            if token.family_id == family_id && token.revoked_at.is_none() {
+                token.revoked_at = Some(at.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    async fn revoke_other_families(&self, user_id: Uuid, except: Uuid, at: &str) -> Result<()> {
+        for token in self.state().refresh_tokens.iter_mut() {
+            if token.user_id == user_id && token.family_id != except && token.revoked_at.is_none() {
+                token.revoked_at = Some(at.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    async fn mark_replaced(&self, id: Uuid, replaced_by: Uuid, at: &str) -> Result<bool> {
+        let mut state = self.state();
+        match state
+            .refresh_tokens
+            .iter_mut()
+            .find(|t| t.id == id && t.revoked_at.is_none())
+        {
+            Some(token) => {
+                token.revoked_at = Some(at.to_string());
+                token.replaced_by = Some(replaced_by);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+#[async_trait]
+impl ReportRepository for MemoryRepository {
+    async fn insert(&self, report: &Report) -> Result<()> {
+        self.state().reports.push(report.clone());
+        Ok(())
+    }
+
+    async fn list_open(&self) -> Result<Vec<Report>> {
+        let mut reports: Vec<Report> = self
+            .state()
+            .reports
+            .iter()
+            .filter(|r| r.resolved_at.is_none())
+            .cloned()
+            .collect();
+        reports.sort_by(|a, b| (&a.created_at, a.id).cmp(&(&b.created_at, b.id)));
+        Ok(reports)
+    }
+
+    async fn resolve(&self, id: Uuid, resolved_by: Uuid, at: &str) -> Result<bool> {
+        let mut state = self.state();
+        let report = state
+            .reports
+            .iter_mut()
+            .find(|r| r.id == id && r.resolved_at.is_none());
+        match report {
+            Some(report) => {
+                report.resolved_at = Some(at.to_string());
+                report.resolved_by = Some(resolved_by);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+#[async_trait]
+impl ModerationLogRepository for MemoryRepository {
+    async fn insert(&self, action: &ModerationAction) -> Result<()> {
+        self.state().moderation_actions.push(action.clone());
+        Ok(())
+    }
+
+    async fn recent(&self, limit: i64) -> Result<Vec<ModerationAction>> {
+        let mut actions = self.state().moderation_actions.clone();
+        actions.sort_by(|a, b| (&b.created_at, b.id).cmp(&(&a.created_at, a.id)));
+        actions.truncate(limit.max(0) as usize);
+        Ok(actions)
+    }
+}
+
+#[async_trait]
+impl NotificationRepository for MemoryRepository {
+    async fn upsert(&self, notification: &Notification) -> Result<()> {
+        let mut state = self.state();
+        let existing = state.notifications.iter_mut().find(|n| {
+            n.user_id == notification.user_id
+                && n.group_key == notification.group_key
+                && n.actor_id == notification.actor_id
+        });
+        match existing {
+            Some(n) => {
+                n.tweet_id = notification.tweet_id;
+                n.comment_id = notification.comment_id;
+                n.created_at = notification.created_at.clone();
+                n.read_at = None;
+            }
+            None => state.notifications.push(notification.clone()),
+        }
+        Ok(())
+    }
+
+    async fn groups_page(
+        &self,
+        user_id: Uuid,
+        query: &PageQuery,
+    ) -> Result<Page<NotificationGroup>> {
+        let items = self
+            .state()
+            .notification_groups(user_id)
+            .into_values()
+            .map(|group| {
+                let latest = group[0].clone();
+                let group = NotificationGroup {
+                    actor_count: group.len() as i64,
+                    unread: group.iter().any(|n| n.read_at.is_none()),
+                    latest,
+                };
+                (
+                    Cursor::new(&group.latest.created_at, group.latest.id),
+                    group,
+                )
+            })
+            .collect();
+        Ok(paginate(items, true, query))
+    }
+
+    async fn group_count(&self, user_id: Uuid) -> Result<i64> {
+        Ok(self.state().notification_groups(user_id).len() as i64)
+    }
+
+    async fn unread_count(&self, user_id: Uuid) -> Result<i64> {
+        Ok(self
+            .state()
+            .notification_groups(user_id)
+            .values()
+            .filter(|group| group.iter().any(|n| n.read_at.is_none()))
+            .count() as i64)
+    }
+
+    async fn recent_actors(
+        &self,
+        user_id: Uuid,
+        group_keys: &[String],
+        limit: i64,
+    ) -> Result<HashMap<String, Vec<Uuid>>> {
+        Ok(self
+            .state()
+            .notification_groups(user_id)
+            .into_iter()
+            .filter(|(key, _)| group_keys.iter().any(|k| k == key))
+            .map(|(key, group)| {
+                let actors = group
+                    .iter()
+                    .take(limit.max(0) as usize)
+                    .map(|n| n.actor_id)
+                    .collect();
+                (key.to_string(), actors)
+            })
+            .collect())
+    }
+
+    async fn mark_read(&self, user_id: Uuid, at: &str) -> Result<u64> {
+        let mut count = 0;
+        for n in self
+            .state()
+            .notifications
+            .iter_mut()
+            .filter(|n| n.user_id == user_id && n.read_at.is_none())
+        {
+            n.read_at = Some(at.to_string());
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    async fn disabled_kinds(&self, user_id: Uuid) -> Result<Vec<NotificationKind>> {
+        Ok(self
+            .state()
+            .disabled_notification_kinds
+            .iter()
+            .filter(|(id, _)| *id == user_id)
+            .map(|(_, kind)| *kind)
+            .collect())
+    }
+
+    async fn set_kind_enabled(
+        &self,
+        user_id: Uuid,
+        kind: NotificationKind,
+        enabled: bool,
+    ) -> Result<()> {
+        let mut state = self.state();
+        let disabled = &mut state.disabled_notification_kinds;
+        disabled.retain(|entry| *entry != (user_id, kind));
+        if !enabled {
+            disabled.push((user_id, kind));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl DirectMessageRepository for MemoryRepository {
+    async fn create_conversation(
+        &self,
+        conversation: &DirectConversation,
+        member_ids: &[Uuid],
+    ) -> Result<()> {
+        let mut state = self.state();
+        state.direct_conversations.push(conversation.clone());
+        state
+            .direct_conversation_members
+            .extend(member_ids.iter().map(|user_id| DirectConversationMember {
+                conversation_id: conversation.id,
+                user_id: *user_id,
+                last_read_at: None,
+            }));
+        Ok(())
+    }
+
+    async fn find_by_members(&self, member_ids: &[Uuid]) -> Result<Option<DirectConversation>> {
+        let state = self.state();
+        let wanted: HashSet<Uuid> = member_ids.iter().copied().collect();
+        Ok(state
+            .direct_conversations
+            .iter()
+            .find(|c| {
+                let members: HashSet<Uuid> = state
+                    .direct_conversation_members
+                    .iter()
+                    .filter(|m| m.conversation_id == c.id)
+                    .map(|m| m.user_id)
+                    .collect();
+                members == wanted
+            })
+            .cloned())
+    }
+
+    async fn members(
+        &self,
+        conversation_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, Vec<DirectConversationMember>>> {
+        let mut members: HashMap<Uuid, Vec<DirectConversationMember>> = HashMap::new();
+        for member in self
+            .state()
+            .direct_conversation_members
+            .iter()
+            .filter(|m| conversation_ids.contains(&m.conversation_id))
+        {
+            members
+                .entry(member.conversation_id)
+                .or_default()
+                .push(member.clone());
+        }
+        Ok(members)
+    }
+
+    async fn conversations_page(
+        &self,
+        user_id: Uuid,
+        query: &PageQuery,
+    ) -> Result<Page<DirectConversation>> {
+        let state = self.state();
+        let items = state
+            .direct_conversations
+            .iter()
+            .filter(|c| {
+                state
+                    .direct_conversation_members
+                    .iter()
+                    .any(|m| m.conversation_id == c.id && m.user_id == user_id)
+            })
+            .map(|c| (Cursor::new(&c.last_message_at, c.id), c.clone()))
+            .collect();
+        Ok(paginate(items, true, query))
+    }
+
+    async fn conversation_count(&self, user_id: Uuid) -> Result<i64> {
+        Ok(self
+            .state()
+            .direct_conversation_members
+            .iter()
+            .filter(|m| m.user_id == user_id)
+            .count() as i64)
+    }
+
+    async fn insert_message(&self, message: &DirectMessage) -> Result<()> {
+        let mut state = self.state();
+        state.direct_messages.push(message.clone());
+        if let Some(conversation) = state
+            .direct_conversations
+            .iter_mut()
+            .find(|c| c.id == message.conversation_id)
+        {
+            conversation.last_message_at = message.created_at.clone();
+        }
+        if let Some(member) = state.direct_conversation_members.iter_mut().find(|m| {
+            m.conversation_id == message.conversation_id && m.user_id == message.sender_id
+        }) {
+            member.last_read_at = Some(message.created_at.clone());
+        }
+        Ok(())
+    }
+
+    async fn messages_page(
+        &self,
+        conversation_id: Uuid,
+        query: &PageQuery,
+    ) -> Result<Page<DirectMessage>> {
+        let items = self
+            .state()
+            .direct_messages
+            .iter()
+            .filter(|m| m.conversation_id == conversation_id)
+            .map(|m| (Cursor::new(&m.created_at, m.id), m.clone()))
+            .collect();
+        Ok(paginate(items, true, query))
+    }
+
+    async fn message_count(&self, conversation_id: Uuid) -> Result<i64> {
+        Ok(self
+            .state()
+            .direct_messages
+            .iter()
+            .filter(|m| m.conversation_id == conversation_id)
+            .count() as i64)
+    }
+
+    async fn last_messages(
+        &self,
+        conversation_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, DirectMessage>> {
+        let mut last: HashMap<Uuid, DirectMessage> = HashMap::new();
+        for message in self
+            .state()
+            .direct_messages
+            .iter()
+            .filter(|m| conversation_ids.contains(&m.conversation_id))
+        {
+            if last
+                .get(&message.conversation_id)
+                .is_some_and(|l| (&l.created_at, l.id) >= (&message.created_at, message.id))
+            {
+                continue;
+            }
+            last.insert(message.conversation_id, message.clone());
+        }
+        Ok(last)
+    }
+
+    async fn unread_counts(
+        &self,
+        user_id: Uuid,
+        conversation_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, i64>> {
+        let state = self.state();
+        let mut counts = HashMap::new();
+        for member in state
+            .direct_conversation_members
+            .iter()
+            .filter(|m| m.user_id == user_id && conversation_ids.contains(&m.conversation_id))
+        {
+            let count = state
+                .direct_messages
+                .iter()
+                .filter(|d| {
+                    d.conversation_id == member.conversation_id
+                        && d.sender_id != user_id
+                        && member
+                            .last_read_at
+                            .as_ref()
+                            .is_none_or(|read| d.created_at > *read)
+                })
+                .count() as i64;
+            if count > 0 {
+                counts.insert(member.conversation_id, count);
+            }
+        }
+        Ok(counts)
+    }
+
+    async fn mark_read(&self, conversation_id: Uuid, user_id: Uuid, at: &str) -> Result<bool> {
+        match self
+            .state()
+            .direct_conversation_members
+            .iter_mut()
+            .find(|m| m.conversation_id == conversation_id && m.user_id == user_id)
+        {
+            Some(member) => {
+                member.last_read_at = Some(at.to_string());
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+#[async_trait]
+impl SearchRepository for MemoryRepository {
+    async fn search_tweets(&self, filter: &SearchFilter, query: &PageQuery) -> Result<Page<Tweet>> {
+        let state = self.state();
+        let hidden = filter
+            .viewer
+            .map(|v| state.hidden_users(v))
+            .unwrap_or_default();
+        let items = state
+            .tweets
+            .iter()
+            .filter(|t| {
+                contains_all(&t.content, &filter.terms)
+                    && matches_filter(filter, t.user_id, &t.created_at)
+                    && !hidden.contains(&t.user_id)
+                    && filter.hashtags.iter().all(|tag| {
+                        state
+                            .tweet_hashtags
+                            .iter()
+                            .any(|(tweet_id, name)| *tweet_id == t.id && name == tag)
+                    })
+            })
+            .map(|t| (Cursor::new(&t.created_at, t.id), t.clone()))
+            .collect();
+        Ok(paginate(items, true, query))
+    }
+
+    async fn search_comments(
+        &self,
+        filter: &SearchFilter,
+        query: &PageQuery,
+    ) -> Result<Page<Comment>> {
+        let state = self.state();
+        let hidden = filter
+            .viewer
+            .map(|v| state.hidden_users(v))
+            .unwrap_or_default();
+        let items = state
+            .comments
+            .iter()
+            .filter(|c| {
+                contains_all(&c.content, &filter.terms)
+                    && matches_filter(filter, c.user_id, &c.created_at)
+                    && !hidden.contains(&c.user_id)
+            })
+            .map(|c| (Cursor::new(&c.created_at, c.id), c.clone()))
+            .collect();
+        Ok(paginate(items, true, query))
+    }
+
+    async fn search_users(&self, terms: &[String], query: &PageQuery) -> Result<Page<User>> {
+        let items = self
+            .state()
+            .users
+            .iter()
+            .filter(|u| contains_all(&u.username, terms))
+            .map(|u| (Cursor::new(&u.created_at, u.id), u.clone()))
+            .collect();
+        Ok(paginate(items, true, query))
+    }
+}
@@ -0,0 +1,456 @@
+//! データアクセスのトレイト定義
+//!
+//! サービス層はこれらのトレイト経由でのみデータを読み書きする。
+//! 実装は SQLite（`sqlite`）または PostgreSQL（`postgres` フィーチャー）と、インメモリ（`memory`）
+
+mod memory;
+#[cfg(feature = "postgres")]
+mod postgres;
+#[cfg(not(feature = "postgres"))]
+mod sqlite;
+
+pub use memory::MemoryRepository;
+#[cfg(feature = "postgres")]
+pub use postgres::PostgresRepository;
+#[cfg(not(feature = "postgres"))]
+pub use sqlite::SqliteRepository;
+
+use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::models::{
+    Comment, DirectConversation, DirectConversationMember, DirectMessage, ModerationAction,
+    Notification, NotificationKind, RefreshToken, Report, Role, Session, TimelineEntry, Tweet,
+    User,
+};
+use crate::pagination::{Page, PageQuery};
+use crate::store::Db;
+
+pub type Result<T> = std::result::Result<T, AppError>;
+
+/// フォロワー数とフォロー中の数
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FollowCounts {
+    pub followers: i64,
+    pub following: i64,
+}
+
+#[async_trait]
+pub trait UserRepository: Send + Sync {
+    async fn insert(&self, user: &User) -> Result<()>;
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<User>>;
+    /// 複数のユーザーをまとめて取得する（存在しない ID は含まれない）
+    async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<User>>;
+    async fn find_by_email(&self, email: &str) -> Result<Option<User>>;
+    async fn find_by_username(&self, username: &str) -> Result<Option<User>>;
+    /// ユーザー名で複数のユーザーをまとめて取得する（存在しないユーザー名は含まれない）
+    async fn find_by_usernames(&self, usernames: &[String]) -> Result<Vec<User>>;
+    /// ロールを変更する（ユーザーが存在しなければ false）
+    async fn set_role(&self, id: Uuid, role: Role) -> Result<bool>;
+    /// 利用停止の日時を設定・解除する（ユーザーが存在しなければ false）
+    async fn set_suspended_at(&self, id: Uuid, at: Option<&str>) -> Result<bool>;
+    /// 相互フォロー以外からのダイレクトメッセージを受け取るかを設定する（ユーザーが存在しなければ false）
+    async fn set_allow_open_dms(&self, id: Uuid, allow: bool) -> Result<bool>;
+}
+
+#[async_trait]
+pub trait TweetRepository: Send + Sync {
+    /// ツイートとハッシュタグ・言及したユーザーの関連付けを1つのトランザクションで保存する
+    async fn insert(&self, tweet: &Tweet, hashtags: &[String], mentions: &[Uuid]) -> Result<()>;
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<Tweet>>;
+    /// ID で複数のツイートを取得する（存在しないIDは含まない）
+    async fn find_many(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, Tweet>>;
+    /// ツイートを削除する（user_id が指定されれば本人のものに限る、削除した場合 true）
+    async fn delete(&self, id: Uuid, user_id: Option<Uuid>) -> Result<bool>;
+    /// 自分とフォロー中のユーザーのツイート（新しい順、ブロック・ミュートしたユーザーのものは除く）
+    async fn timeline(&self, user_id: Uuid) -> Result<Vec<Tweet>>;
+    /// 自分とフォロー中のユーザーの投稿・リツイートの1ページ
+    ///
+    /// 同じツイートは最後に載った1件にまとめる（カーソルは載った日時 + ツイートID）。
+    /// ブロック・ミュートしたユーザーの投稿とリツイート、そのユーザーのツイートのリツイートは除く
+    async fn timeline_page(&self, user_id: Uuid, query: &PageQuery) -> Result<Page<TimelineEntry>>;
+    /// timeline_page の総件数
+    async fn timeline_count(&self, user_id: Uuid) -> Result<i64>;
+    /// ツイートごとの引用数（引用されていないツイートは含まない）
+    async fn quote_counts(&self, tweet_ids: &[Uuid]) -> Result<HashMap<Uuid, i64>>;
+}
+
+#[async_trait]
+pub trait LikeRepository: Send + Sync {
+    /// いいねを追加する（既にいいね済みなら false）
+    async fn insert(&self, user_id: Uuid, tweet_id: Uuid, created_at: &str) -> Result<bool>;
+    /// いいねを取り消す（いいねしていなければ false）
+    async fn delete(&self, user_id: Uuid, tweet_id: Uuid) -> Result<bool>;
+    /// ツイートごとのいいね数（いいねがないツイートは含まない）
+    async fn counts(&self, tweet_ids: &[Uuid]) -> Result<HashMap<Uuid, i64>>;
+    /// tweet_ids のうち user_id がいいねしたもの
+    async fn liked_by(&self, user_id: Uuid, tweet_ids: &[Uuid]) -> Result<HashSet<Uuid>>;
+}
+
+#[async_trait]
+pub trait RetweetRepository: Send + Sync {
+    /// リツイートする（既にリツイート済みなら false）
+    async fn insert(&self, user_id: Uuid, tweet_id: Uuid, created_at: &str) -> Result<bool>;
+    /// リツイートを取り消す（リツイートしていなければ false）
+    async fn delete(&self, user_id: Uuid, tweet_id: Uuid) -> Result<bool>;
+    /// ツイートごとのリツイート数（リツイートがないツイートは含まない）
+    async fn counts(&self, tweet_ids: &[Uuid]) -> Result<HashMap<Uuid, i64>>;
+    /// tweet_ids のうち user_id がリツイートしたもの
+    async fn retweeted_by(&self, user_id: Uuid, tweet_ids: &[Uuid]) -> Result<HashSet<Uuid>>;
+}
+
+#[async_trait]
+pub trait FollowRepository: Send + Sync {
+    /// フォローを追加する（既にフォロー済みなら false）
+    async fn insert(&self, follower_id: Uuid, following_id: Uuid, created_at: &str)
+    -> Result<bool>;
+    /// フォローを解除する（フォローしていなければ false）
+    async fn delete(&self, follower_id: Uuid, following_id: Uuid) -> Result<bool>;
+    /// ユーザーごとのフォロー数
+    async fn counts(&self, user_ids: &[Uuid]) -> Result<HashMap<Uuid, FollowCounts>>;
+    /// target_ids のうち follower_id がフォローしているユーザー
+    async fn following_set(&self, follower_id: Uuid, target_ids: &[Uuid]) -> Result<HashSet<Uuid>>;
+    /// user_id のフォロワー（フォローの新しい順、カーソルはフォロー日時 + ユーザーID）
+    async fn followers(&self, user_id: Uuid, query: &PageQuery) -> Result<Page<User>>;
+    /// user_id がフォローしているユーザー（フォローの新しい順、カーソルはフォロー日時 + ユーザーID）
+    async fn following(&self, user_id: Uuid, query: &PageQuery) -> Result<Page<User>>;
+}
+
+#[async_trait]
+pub trait BlockRepository: Send + Sync {
+    /// ブロックし、2人の間のフォロー・通知・相手のツイートのリツイートを1つのトランザクションで削除する（既にブロック済みなら false）
+    async fn block(&self, blocker_id: Uuid, blocked_id: Uuid, created_at: &str) -> Result<bool>;
+    /// ブロックを解除する（ブロックしていなければ false）
+    async fn unblock(&self, blocker_id: Uuid, blocked_id: Uuid) -> Result<bool>;
+    /// ミュートし、muted_id から muter_id への通知を1つのトランザクションで削除する（既にミュート済みなら false）
+    async fn mute(&self, muter_id: Uuid, muted_id: Uuid, created_at: &str) -> Result<bool>;
+    /// ミュートを解除する（ミュートしていなければ false）
+    async fn unmute(&self, muter_id: Uuid, muted_id: Uuid) -> Result<bool>;
+    /// どちらかがもう一方をブロックしているか
+    async fn is_blocked(&self, a: Uuid, b: Uuid) -> Result<bool>;
+    /// user_ids のうち viewer から見えないユーザー（viewer がブロック・ミュートしたか、viewer をブロックした）
+    async fn hidden_set(&self, viewer: Uuid, user_ids: &[Uuid]) -> Result<HashSet<Uuid>>;
+}
+
+#[async_trait]
+pub trait CommentRepository: Send + Sync {
+    /// コメントと言及したユーザーの関連付けを1つのトランザクションで保存する
+    async fn insert(&self, comment: &Comment, mentions: &[Uuid]) -> Result<()>;
+    /// ツイートへのコメント（古い順、カーソルはコメントの作成日時 + ID）
+    ///
+    /// viewer から見えないユーザー（`BlockRepository::hidden_set` を参照）のコメントは除く
+    async fn list_for_tweet(
+        &self,
+        tweet_id: Uuid,
+        viewer: Option<Uuid>,
+        query: &PageQuery,
+    ) -> Result<Page<Comment>>;
+    async fn count_for_tweet(&self, tweet_id: Uuid, viewer: Option<Uuid>) -> Result<i64>;
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<Comment>>;
+    /// ID で複数のコメントを取得する（存在しないIDは含まない）
+    async fn find_many(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, Comment>>;
+    /// コメントを返信ごと削除する（user_id が指定されれば本人のものに限る、削除した場合 true）
+    async fn delete(&self, id: Uuid, user_id: Option<Uuid>) -> Result<bool>;
+    /// parent_id（None ならツイート）から max_depth 階層までの返信（古い順、カーソルはコメントの作成日時 + ID）
+    ///
+    /// 返信は返信先より後に作られるため、返信先は必ず返信より前に並ぶ。
+    /// viewer から見えないユーザーのコメントは、その下の返信ごと除く
+    async fn thread_page(
+        &self,
+        tweet_id: Uuid,
+        parent_id: Option<Uuid>,
+        max_depth: i64,
+        viewer: Option<Uuid>,
+        query: &PageQuery,
+    ) -> Result<Page<Comment>>;
+    async fn thread_count(
+        &self,
+        tweet_id: Uuid,
+        parent_id: Option<Uuid>,
+        max_depth: i64,
+        viewer: Option<Uuid>,
+    ) -> Result<i64>;
+    /// コメントの返信先をたどったコメント（ツイートへの直接のコメントから順、自身は含まない）
+    async fn ancestors(&self, id: Uuid) -> Result<Vec<Comment>>;
+    /// 各コメントへの直接の返信数（返信のないコメントは含まれない、viewer から見えないユーザーの返信は数えない）
+    async fn reply_counts(
+        &self,
+        comment_ids: &[Uuid],
+        viewer: Option<Uuid>,
+    ) -> Result<HashMap<Uuid, i64>>;
+    /// 各ツイートへの直接のコメント数（コメントのないツイートは含まれない、viewer から見えないユーザーのコメントは数えない）
+    async fn top_level_counts(
+        &self,
+        tweet_ids: &[Uuid],
+        viewer: Option<Uuid>,
+    ) -> Result<HashMap<Uuid, i64>>;
+}
+
+#[async_trait]
+pub trait MentionRepository: Send + Sync {
+    /// ツイートごとの言及されたユーザーID（言及のないツイートは含まれない）
+    async fn for_tweets(&self, tweet_ids: &[Uuid]) -> Result<HashMap<Uuid, Vec<Uuid>>>;
+    /// コメントごとの言及されたユーザーID（言及のないコメントは含まれない）
+    async fn for_comments(&self, comment_ids: &[Uuid]) -> Result<HashMap<Uuid, Vec<Uuid>>>;
+    /// user_id を言及したツイート（新しい順、カーソルはツイートの作成日時 + ID）
+    async fn tweets_page(&self, user_id: Uuid, query: &PageQuery) -> Result<Page<Tweet>>;
+    async fn tweet_count(&self, user_id: Uuid) -> Result<i64>;
+}
+
+/// ハッシュタグの使用状況
+#[derive(Debug, Clone)]
+pub struct HashtagStats {
+    pub tweet_count: i64,
+    pub first_used_at: String,
+    pub last_used_at: String,
+}
+
+#[async_trait]
+pub trait HashtagRepository: Send + Sync {
+    /// ツイートごとのハッシュタグ名
+    async fn for_tweets(&self, tweet_ids: &[Uuid]) -> Result<HashMap<Uuid, Vec<String>>>;
+    /// ハッシュタグの使用状況（付いているツイートがなければ None）
+    async fn stats(&self, name: &str) -> Result<Option<HashtagStats>>;
+    /// ハッシュタグの付いたツイート（新しい順、カーソルはツイートの作成日時 + ID）
+    async fn tweets_page(&self, name: &str, query: &PageQuery) -> Result<Page<Tweet>>;
+    /// since 以上 until 未満に投稿されたツイートのハッシュタグごとの件数
+    async fn counts_between(&self, since: &str, until: &str) -> Result<HashMap<String, i64>>;
+}
+
+#[async_trait]
+pub trait SessionRepository: Send + Sync {
+    async fn insert(&self, session: &Session) -> Result<()>;
+    async fn find(&self, id: Uuid, user_id: Uuid) -> Result<Option<Session>>;
+    async fn touch(&self, id: Uuid, at: &str) -> Result<()>;
+    /// 失効していないセッション（最終アクセスの新しい順）
+    async fn list_active(&self, user_id: Uuid) -> Result<Vec<Session>>;
+    /// セッションを失効させる（user_id が指定されれば本人のものに限る、失効させた場合 true）
+    async fn revoke(&self, id: Uuid, user_id: Option<Uuid>, at: &str) -> Result<bool>;
+    /// except 以外の有効なセッションを失効させ、件数を返す
+    async fn revoke_others(&self, user_id: Uuid, except: Uuid, at: &str) -> Result<u64>;
+}
+
+#[async_trait]
+pub trait RefreshTokenRepository: Send + Sync {
+    async fn insert(&self, token: &RefreshToken) -> Result<()>;
+    async fn find_by_hash(&self, token_hash: &str) -> Result<Option<RefreshToken>>;
+    /// ファミリーの有効なトークンをすべて失効させる
+    async fn revoke_family(&self, family_id: Uuid, at: &str) -> Result<()>;
+    /// ユーザーの except 以外のファミリーをすべて失効させる
+    async fn revoke_other_families(&self, user_id: Uuid, except: Uuid, at: &str) -> Result<()>;
+    /// 有効なトークンを失効させて後継を記録する（既に失効していれば false）
+    async fn mark_replaced(&self, id: Uuid, replaced_by: Uuid, at: &str) -> Result<bool>;
+}
+
+#[async_trait]
+pub trait ReportRepository: Send + Sync {
+    async fn insert(&self, report: &Report) -> Result<()>;
+    /// 未対応の通報（古い順）
+    async fn list_open(&self) -> Result<Vec<Report>>;
+    /// 未対応の通報を対応済みにする（未対応の通報がなければ false）
+    async fn resolve(&self, id: Uuid, resolved_by: Uuid, at: &str) -> Result<bool>;
+}
+
+/// 検索条件（語をすべて含み、日時は since 以上 until 未満）
+#[derive(Debug, Default)]
+pub struct SearchFilter {
+    pub terms: Vec<String>,
+    pub author_id: Option<Uuid>,
+    /// 小文字に正規化したハッシュタグ（すべてを含むもの）
+    pub hashtags: Vec<String>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    /// 検索するユーザー（見えないユーザーの投稿を除く）
+    pub viewer: Option<Uuid>,
+}
+
+#[async_trait]
+pub trait SearchRepository: Send + Sync {
+    /// 条件に合うツイート（新しい順、カーソルはツイートの作成日時 + ID）
+    async fn search_tweets(&self, filter: &SearchFilter, query: &PageQuery) -> Result<Page<Tweet>>;
+    /// 条件に合うコメント（新しい順、ハッシュタグは無視する）
+    async fn search_comments(
+        &self,
+        filter: &SearchFilter,
+        query: &PageQuery,
+    ) -> Result<Page<Comment>>;
+    /// ユーザー名に語をすべて含むユーザー（登録の新しい順）
+    async fn search_users(&self, terms: &[String], query: &PageQuery) -> Result<Page<User>>;
+}
+
+#[async_trait]
+pub trait ModerationLogRepository: Send + Sync {
+    async fn insert(&self, action: &ModerationAction) -> Result<()>;
+    /// 最近の操作（新しい順に limit 件）
+    async fn recent(&self, limit: i64) -> Result<Vec<ModerationAction>>;
+}
+
+/// group_key ごとにまとめた通知
+#[derive(Debug, Clone)]
+pub struct NotificationGroup {
+    /// まとまりの中で最新の通知
+    pub latest: Notification,
+    /// まとめた通知の数（同じユーザーからは1件にまとまるため、操作したユーザーの数）
+    pub actor_count: i64,
+    /// 未読の通知を含むか
+    pub unread: bool,
+}
+
+#[async_trait]
+pub trait NotificationRepository: Send + Sync {
+    /// 通知を保存する（同じユーザーから同じ group_key への通知があれば、対象と日時を更新して未読に戻す）
+    async fn upsert(&self, notification: &Notification) -> Result<()>;
+    /// group_key ごとにまとめた通知（最新の通知の新しい順、カーソルは最新の通知の日時 + ID）
+    async fn groups_page(
+        &self,
+        user_id: Uuid,
+        query: &PageQuery,
+    ) -> Result<Page<NotificationGroup>>;
+    async fn group_count(&self, user_id: Uuid) -> Result<i64>;
+    /// 未読の通知を含むまとまりの数
+    async fn unread_count(&self, user_id: Uuid) -> Result<i64>;
+    /// まとまりごとの最近操作したユーザー（新しい順に limit 人）
+    async fn recent_actors(
+        &self,
+        user_id: Uuid,
+        group_keys: &[String],
+        limit: i64,
+    ) -> Result<HashMap<String, Vec<Uuid>>>;
+    /// 未読の通知をすべて既読にする（既読にした件数）
+    async fn mark_read(&self, user_id: Uuid, at: &str) -> Result<u64>;
+    /// 受け取らない設定にした種類
+    async fn disabled_kinds(&self, user_id: Uuid) -> Result<Vec<NotificationKind>>;
+    async fn set_kind_enabled(
+        &self,
+        user_id: Uuid,
+        kind: NotificationKind,
+        enabled: bool,
+    ) -> Result<()>;
+}
+
+#[async_trait]
+pub trait DirectMessageRepository: Send + Sync {
+    /// 会話と参加者を1つのトランザクションで保存する
+    async fn create_conversation(
+        &self,
+        conversation: &DirectConversation,
+        member_ids: &[Uuid],
+    ) -> Result<()>;
+    /// 参加者がちょうど member_ids（重複なし）の会話
+    async fn find_by_members(&self, member_ids: &[Uuid]) -> Result<Option<DirectConversation>>;
+    /// 会話ごとの参加者
+    async fn members(
+        &self,
+        conversation_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, Vec<DirectConversationMember>>>;
+    /// user_id が参加している会話（最後のメッセージの新しい順、カーソルは最後のメッセージの日時 + ID）
+    async fn conversations_page(
+        &self,
+        user_id: Uuid,
+        query: &PageQuery,
+    ) -> Result<Page<DirectConversation>>;
+    async fn conversation_count(&self, user_id: Uuid) -> Result<i64>;
+    /// メッセージを保存し、会話の最後のメッセージの日時と送信者の既読日時を1つのトランザクションで更新する
+    async fn insert_message(&self, message: &DirectMessage) -> Result<()>;
+    /// 会話のメッセージ（新しい順）
+    async fn messages_page(
+        &self,
+        conversation_id: Uuid,
+        query: &PageQuery,
+    ) -> Result<Page<DirectMessage>>;
+    async fn message_count(&self, conversation_id: Uuid) -> Result<i64>;
+    /// 会話ごとの最後のメッセージ（メッセージのない会話は含まない）
+    async fn last_messages(
+        &self,
+        conversation_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, DirectMessage>>;
+    /// 会話ごとの user_id の未読数（他の参加者が既読日時より後に送ったメッセージ、0件の会話は含まない）
+    async fn unread_counts(
+        &self,
+        user_id: Uuid,
+        conversation_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, i64>>;
+    /// 参加者の既読日時を更新する（参加者でなければ false）
+    async fn mark_read(&self, conversation_id: Uuid, user_id: Uuid, at: &str) -> Result<bool>;
+}
+
+/// アプリケーションが使うリポジトリ一式
+#[derive(Clone)]
+pub struct Repositories {
+    pub users: Arc<dyn UserRepository>,
+    pub tweets: Arc<dyn TweetRepository>,
+    pub likes: Arc<dyn LikeRepository>,
+    pub retweets: Arc<dyn RetweetRepository>,
+    pub follows: Arc<dyn FollowRepository>,
+    pub blocks: Arc<dyn BlockRepository>,
+    pub comments: Arc<dyn CommentRepository>,
+    pub hashtags: Arc<dyn HashtagRepository>,
+    pub mentions: Arc<dyn MentionRepository>,
+    pub sessions: Arc<dyn SessionRepository>,
+    pub refresh_tokens: Arc<dyn RefreshTokenRepository>,
+    pub reports: Arc<dyn ReportRepository>,
+    pub moderation_log: Arc<dyn ModerationLogRepository>,
+    pub notifications: Arc<dyn NotificationRepository>,
+    pub direct_messages: Arc<dyn DirectMessageRepository>,
+    pub search: Arc<dyn SearchRepository>,
+}
+
+impl Repositories {
+    /// 接続中のデータベース（SQLite / PostgreSQL）をバックエンドにする
+    pub fn database(db: Db) -> Self {
+        #[cfg(not(feature = "postgres"))]
+        let backend = SqliteRepository::new(db);
+        #[cfg(feature = "postgres")]
+        let backend = PostgresRepository::new(db);
+
+        Self::from_backend(Arc::new(backend))
+    }
+
+    /// インメモリをバックエンドにする（プロセス終了でデータは消える）
+    pub fn in_memory() -> Self {
+        Self::from_backend(Arc::new(MemoryRepository::default()))
+    }
+
+    fn from_backend<B>(backend: Arc<B>) -> Self
+    where
+        B: UserRepository
+            + TweetRepository
+            + LikeRepository
+            + RetweetRepository
+            + FollowRepository
+            + BlockRepository
+            + CommentRepository
+            + HashtagRepository
+            + MentionRepository
+            + SessionRepository
+            + RefreshTokenRepository
+            + ReportRepository
+            + ModerationLogRepository
+            + NotificationRepository
+            + DirectMessageRepository
+            + SearchRepository
+            + 'static,
+    {
+        Self {
+            users: backend.clone(),
+            tweets: backend.clone(),
+            likes: backend.clone(),
+            retweets: backend.clone(),
+            follows: backend.clone(),
+            blocks: backend.clone(),
+            comments: backend.clone(),
+            hashtags: backend.clone(),
+            mentions: backend.clone(),
+            sessions: backend.clone(),
+            refresh_tokens: backend.clone(),
+            reports: backend.clone(),
+            moderation_log: backend.clone(),
+            notifications: backend.clone(),
+            direct_messages: backend.clone(),
+            search: backend,
+        }
+    }
+}
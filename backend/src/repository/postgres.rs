@@ -0,0 +1,2241 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::FromRow;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use super::{
+    BlockRepository, CommentRepository, DirectMessageRepository, FollowCounts, FollowRepository,
+    HashtagRepository, HashtagStats, LikeRepository, MentionRepository, ModerationLogRepository,
+    NotificationGroup, NotificationRepository, RefreshTokenRepository, ReportRepository, Result,
+    RetweetRepository, SearchFilter, SearchRepository, SessionRepository, TweetRepository,
+    UserRepository,
+};
+use crate::error::AppError;
+use crate::models::{
+    Comment, DirectConversation, DirectConversationMember, DirectMessage, ModerationAction,
+    ModerationActionKind, Notification, NotificationKind, RefreshToken, Report, ReportTarget, Role,
+    Session, TimelineEntry, Tweet, User,
+};
+use crate::pagination::{Cursor, Page, PageQuery};
+use crate::store::Db;
+
+/// PostgreSQLによるリポジトリ実装
+///
+/// 日時は TIMESTAMPTZ で保存し、モデルとの受け渡しでは RFC 3339 文字列に変換する
+pub struct PostgresRepository {
+    db: Db,
+}
+
+impl PostgresRepository {
+    pub fn new(db: Db) -> Self {
+        Self { db }
+    }
+}
+
+/// モデルの RFC 3339 文字列を TIMESTAMPTZ にバインドできる値へ変換する
+fn timestamp(value: &str) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|_| AppError::Internal(format!("Invalid timestamp: {}", value)))
+}
+
+/// キーセットページネーションの条件（AND から始まる）と ORDER BY ... LIMIT を組み立てる
+///
+/// descending は一覧の並び順、next_param は次に使うパラメータ番号。
+/// バインドは page_bindings の順に (key, id) の組、最後に LIMIT
+fn keyset(
+    query: &PageQuery,
+    key: &str,
+    id: &str,
+    descending: bool,
+    mut next_param: usize,
+) -> (String, String) {
+    let (after_op, before_op) = if descending { ("<", ">") } else { (">", "<") };
+
+    let mut conditions = String::new();
+    for (cursor, op) in [(&query.after, after_op), (&query.before, before_op)] {
+        if cursor.is_some() {
+            conditions += &format!(
+                " AND ({}, {}) {} (${}, ${})",
+                key,
+                id,
+                op,
+                next_param,
+                next_param + 1
+            );
+            next_param += 2;
+        }
+    }
+
+    let order = if descending != query.from_end {
+        "DESC"
+    } else {
+        "ASC"
+    };
+    (
+        conditions,
+        format!(
+            "ORDER BY {0} {2}, {1} {2} LIMIT ${3}",
+            key, id, order, next_param
+        ),
+    )
+}
+
+/// カーソルを (TIMESTAMPTZ, UUID) のバインド値に変換する
+fn page_bindings(query: &PageQuery) -> Result<Vec<(DateTime<Utc>, Uuid)>> {
+    query
+        .cursors()
+        .map(|cursor| Ok((timestamp(&cursor.created_at)?, cursor.id)))
+        .collect()
+}
+
+/// 検索条件のバインド値
+enum SearchArg {
+    Text(String),
+    Id(Uuid),
+    Time(DateTime<Utc>),
+}
+
+/// 検索語の条件（AND から始まる）。pg_trgm の索引が効く ILIKE で部分一致を探す
+fn term_conditions(terms: &[String], column: &str, args: &mut Vec<SearchArg>) -> String {
+    let mut conditions = String::new();
+    for term in terms {
+        args.push(SearchArg::Text(like_pattern(term)));
+        conditions += &format!(" AND {} ILIKE ${} ESCAPE '\\'", column, args.len());
+    }
+    conditions
+}
+
+/// 部分一致の LIKE パターン（% と _ はエスケープする）
+fn like_pattern(term: &str) -> String {
+    let escaped = term
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+/// viewer から見えないユーザー（viewer がブロック・ミュートしたか、viewer をブロックした）
+///
+/// param は viewer のIDのパラメータ番号（NULL なら誰も含まない）
+fn hidden_users(param: usize) -> String {
+    format!(
+        r#"
+        SELECT blocked_id FROM blocks WHERE blocker_id = ${0}
+        UNION SELECT blocker_id FROM blocks WHERE blocked_id = ${0}
+        UNION SELECT muted_id FROM mutes WHERE muter_id = ${0}
+        "#,
+        param
+    )
+}
+
+/// 投稿者と日時・見えないユーザーの条件（AND から始まる）
+fn filter_conditions(
+    filter: &SearchFilter,
+    alias: &str,
+    args: &mut Vec<SearchArg>,
+) -> Result<String> {
+    let mut conditions = String::new();
+    if let Some(author_id) = filter.author_id {
+        args.push(SearchArg::Id(author_id));
+        conditions += &format!(" AND {}.user_id = ${}", alias, args.len());
+    }
+    if let Some(since) = &filter.since {
+        args.push(SearchArg::Time(timestamp(since)?));
+        conditions += &format!(" AND {}.created_at >= ${}", alias, args.len());
+    }
+    if let Some(until) = &filter.until {
+        args.push(SearchArg::Time(timestamp(until)?));
+        conditions += &format!(" AND {}.created_at < ${}", alias, args.len());
+    }
+    if let Some(viewer) = filter.viewer {
+        args.push(SearchArg::Id(viewer));
+        conditions += &format!(
+            " AND {}.user_id NOT IN ({})",
+            alias,
+            hidden_users(args.len())
+        );
+    }
+    Ok(conditions)
+}
+
+/// 検索条件とカーソルをバインドして1ページ分の行を取得する
+async fn search_rows<R>(db: &Db, sql: &str, args: &[SearchArg], query: &PageQuery) -> Result<Vec<R>>
+where
+    R: for<'r> FromRow<'r, sqlx::postgres::PgRow> + Send + Unpin,
+{
+    let mut q = sqlx::query_as::<_, R>(sql);
+    for arg in args {
+        q = match arg {
+            SearchArg::Text(text) => q.bind(text.as_str()),
+            SearchArg::Id(id) => q.bind(*id),
+            SearchArg::Time(time) => q.bind(*time),
+        };
+    }
+    for (created_at, id) in page_bindings(query)? {
+        q = q.bind(created_at).bind(id);
+    }
+    Ok(q.bind(query.fetch_limit()).fetch_all(db).await?)
+}
+
+/// タイムラインに載る投稿とリツイート（バインドはユーザーID）
+///
+/// latest はツイートごとに新しい順の番号 n を付けたもので、n = 1 が最後に載った1件
+const TIMELINE_EVENTS: &str = r#"
+    authors (user_id) AS (
+        SELECT $1::UUID UNION SELECT following_id FROM follows WHERE follower_id = $1
+    ),
+    hidden (user_id) AS (
+        SELECT blocked_id FROM blocks WHERE blocker_id = $1
+        UNION SELECT blocker_id FROM blocks WHERE blocked_id = $1
+        UNION SELECT muted_id FROM mutes WHERE muter_id = $1
+    ),
+    events AS (
+        SELECT id AS tweet_id, created_at AS activity_at, NULL::UUID AS retweeted_by
+        FROM tweets
+        WHERE user_id IN (SELECT user_id FROM authors)
+            AND user_id NOT IN (SELECT user_id FROM hidden)
+        UNION ALL
+        SELECT r.tweet_id, r.created_at, r.user_id
+        FROM retweets r JOIN tweets t ON t.id = r.tweet_id
+        WHERE r.user_id IN (SELECT user_id FROM authors)
+            AND r.user_id NOT IN (SELECT user_id FROM hidden)
+            AND t.user_id NOT IN (SELECT user_id FROM hidden)
+    ),
+    latest AS (
+        SELECT *, ROW_NUMBER() OVER (PARTITION BY tweet_id ORDER BY activity_at DESC) AS n
+        FROM events
+    )
+"#;
+
+#[derive(FromRow)]
+struct UserRow {
+    id: Uuid,
+    username: String,
+    email: String,
+    password_hash: String,
+    created_at: DateTime<Utc>,
+    #[sqlx(try_from = "String")]
+    role: Role,
+    suspended_at: Option<DateTime<Utc>>,
+    allow_open_dms: bool,
+}
+
+impl From<UserRow> for User {
+    fn from(row: UserRow) -> Self {
+        Self {
+            id: row.id,
+            username: row.username,
+            email: row.email,
+            password_hash: row.password_hash,
+            created_at: row.created_at.to_rfc3339(),
+            role: row.role,
+            suspended_at: row.suspended_at.map(|t| t.to_rfc3339()),
+            allow_open_dms: row.allow_open_dms,
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct TweetRow {
+    id: Uuid,
+    user_id: Uuid,
+    content: String,
+    created_at: DateTime<Utc>,
+    quoted_tweet_id: Option<Uuid>,
+}
+
+impl From<TweetRow> for Tweet {
+    fn from(row: TweetRow) -> Self {
+        Self {
+            id: row.id,
+            user_id: row.user_id,
+            content: row.content,
+            created_at: row.created_at.to_rfc3339(),
+            quoted_tweet_id: row.quoted_tweet_id,
+        }
+    }
+}
+
+/// 返信先から max_depth 階層までの返信の ID（$1 がツイートID、$2 が返信先ID、$3 が max_depth、$4 が閲覧者ID）
+///
+/// 閲覧者から見えないユーザーのコメントは、その下の返信ごと除く
+const THREAD: &str = r#"
+    WITH RECURSIVE hidden (user_id) AS (
+        SELECT blocked_id FROM blocks WHERE blocker_id = $4::UUID
+        UNION SELECT blocker_id FROM blocks WHERE blocked_id = $4::UUID
+        UNION SELECT muted_id FROM mutes WHERE muter_id = $4::UUID
+    ),
+    thread (id, depth) AS (
+        SELECT id, 1 FROM comments
+        WHERE tweet_id = $1 AND parent_id IS NOT DISTINCT FROM $2::UUID
+            AND user_id NOT IN (SELECT user_id FROM hidden)
+        UNION ALL
+        SELECT c.id, thread.depth + 1 FROM comments c
+        JOIN thread ON c.parent_id = thread.id
+        WHERE thread.depth < $3 AND c.user_id NOT IN (SELECT user_id FROM hidden)
+    )
+"#;
+
+/// タイムラインに載った日時とリツイートしたユーザー付きのツイート
+#[derive(FromRow)]
+struct TimelineRow {
+    #[sqlx(flatten)]
+    tweet: TweetRow,
+    activity_at: DateTime<Utc>,
+    retweeted_by: Option<Uuid>,
+}
+
+#[derive(FromRow)]
+struct CommentRow {
+    id: Uuid,
+    tweet_id: Uuid,
+    user_id: Uuid,
+    content: String,
+    created_at: DateTime<Utc>,
+    parent_id: Option<Uuid>,
+}
+
+/// フォロー日時付きのユーザー
+#[derive(FromRow)]
+struct FollowedUserRow {
+    #[sqlx(flatten)]
+    user: UserRow,
+    followed_at: DateTime<Utc>,
+}
+
+impl From<CommentRow> for Comment {
+    fn from(row: CommentRow) -> Self {
+        Self {
+            id: row.id,
+            tweet_id: row.tweet_id,
+            user_id: row.user_id,
+            content: row.content,
+            created_at: row.created_at.to_rfc3339(),
+            parent_id: row.parent_id,
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct SessionRow {
+    id: Uuid,
+    user_id: Uuid,
+    user_agent: Option<String>,
+    ip_address: Option<String>,
+    created_at: DateTime<Utc>,
+    last_seen_at: DateTime<Utc>,
+    revoked_at: Option<DateTime<Utc>>,
+}
+
+impl From<SessionRow> for Session {
+    fn from(row: SessionRow) -> Self {
+        Self {
+            id: row.id,
+            user_id: row.user_id,
+            user_agent: row.user_agent,
+            ip_address: row.ip_address,
+            created_at: row.created_at.to_rfc3339(),
+            last_seen_at: row.last_seen_at.to_rfc3339(),
+            revoked_at: row.revoked_at.map(|t| t.to_rfc3339()),
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct ReportRow {
+    id: Uuid,
+    reporter_id: Uuid,
+    #[sqlx(try_from = "String")]
+    target_type: ReportTarget,
+    target_id: Uuid,
+    reason: String,
+    created_at: DateTime<Utc>,
+    resolved_at: Option<DateTime<Utc>>,
+    resolved_by: Option<Uuid>,
+}
+
+impl From<ReportRow> for Report {
+    fn from(row: ReportRow) -> Self {
+        Self {
+            id: row.id,
+            reporter_id: row.reporter_id,
+            target_type: row.target_type,
+            target_id: row.target_id,
+            reason: row.reason,
+            created_at: row.created_at.to_rfc3339(),
+            resolved_at: row.resolved_at.map(|t| t.to_rfc3339()),
+            resolved_by: row.resolved_by,
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct ModerationActionRow {
+    id: Uuid,
+    actor_id: Uuid,
+    #[sqlx(try_from = "String")]
+    action: ModerationActionKind,
+    target_id: Uuid,
+    details: Option<String>,
+    created_at: DateTime<Utc>,
+}
+
+impl From<ModerationActionRow> for ModerationAction {
+    fn from(row: ModerationActionRow) -> Self {
+        Self {
+            id: row.id,
+            actor_id: row.actor_id,
+            action: row.action,
+            target_id: row.target_id,
+            details: row.details,
+            created_at: row.created_at.to_rfc3339(),
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct NotificationRow {
+    id: Uuid,
+    user_id: Uuid,
+    #[sqlx(try_from = "String")]
+    kind: NotificationKind,
+    actor_id: Uuid,
+    group_key: String,
+    tweet_id: Option<Uuid>,
+    comment_id: Option<Uuid>,
+    created_at: DateTime<Utc>,
+    read_at: Option<DateTime<Utc>>,
+}
+
+impl From<NotificationRow> for Notification {
+    fn from(row: NotificationRow) -> Self {
+        Self {
+            id: row.id,
+            user_id: row.user_id,
+            kind: row.kind,
+            actor_id: row.actor_id,
+            group_key: row.group_key,
+            tweet_id: row.tweet_id,
+            comment_id: row.comment_id,
+            created_at: row.created_at.to_rfc3339(),
+            read_at: row.read_at.map(|t| t.to_rfc3339()),
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct DirectConversationRow {
+    id: Uuid,
+    created_at: DateTime<Utc>,
+    last_message_at: DateTime<Utc>,
+}
+
+impl From<DirectConversationRow> for DirectConversation {
+    fn from(row: DirectConversationRow) -> Self {
+        Self {
+            id: row.id,
+            created_at: row.created_at.to_rfc3339(),
+            last_message_at: row.last_message_at.to_rfc3339(),
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct DirectConversationMemberRow {
+    conversation_id: Uuid,
+    user_id: Uuid,
+    last_read_at: Option<DateTime<Utc>>,
+}
+
+impl From<DirectConversationMemberRow> for DirectConversationMember {
+    fn from(row: DirectConversationMemberRow) -> Self {
+        Self {
+            conversation_id: row.conversation_id,
+            user_id: row.user_id,
+            last_read_at: row.last_read_at.map(|t| t.to_rfc3339()),
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct DirectMessageRow {
+    id: Uuid,
+    conversation_id: Uuid,
+    sender_id: Uuid,
+    content: String,
+    created_at: DateTime<Utc>,
+}
+
+impl From<DirectMessageRow> for DirectMessage {
+    fn from(row: DirectMessageRow) -> Self {
+        Self {
+            id: row.id,
+            conversation_id: row.conversation_id,
+            sender_id: row.sender_id,
+            content: row.content,
+            created_at: row.created_at.to_rfc3339(),
+        }
+    }
+}
+
+/// まとまりの集計付きの最新の通知
+#[derive(FromRow)]
+struct NotificationGroupRow {
+    #[sqlx(flatten)]
+    notification: NotificationRow,
+    actor_count: i64,
+    unread: bool,
+}
+
+impl From<NotificationGroupRow> for NotificationGroup {
+    fn from(row: NotificationGroupRow) -> Self {
+        Self {
+            latest: Notification::from(row.notification),
+            actor_count: row.actor_count,
+            unread: row.unread,
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct RefreshTokenRow {
+    id: Uuid,
+    user_id: Uuid,
+    family_id: Uuid,
+    token_hash: String,
+    created_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+    revoked_at: Option<DateTime<Utc>>,
+    replaced_by: Option<Uuid>,
+}
+
+impl From<RefreshTokenRow> for RefreshToken {
+    fn from(row: RefreshTokenRow) -> Self {
+        Self {
+            id: row.id,
+            user_id: row.user_id,
+            family_id: row.family_id,
+            token_hash: row.token_hash,
+            created_at: row.created_at.to_rfc3339(),
+            expires_at: row.expires_at.to_rfc3339(),
+            revoked_at: row.revoked_at.map(|t| t.to_rfc3339()),
+            replaced_by: row.replaced_by,
+        }
+    }
+}
+
+#[async_trait]
+impl UserRepository for PostgresRepository {
+    async fn insert(&self, user: &User) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO users
+                (id, username, email, password_hash, created_at, role, suspended_at, allow_open_dms)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(user.id)
+        .bind(&user.username)
+        .bind(&user.email)
+        .bind(&user.password_hash)
+        .bind(timestamp(&user.created_at)?)
+        .bind(user.role.as_str())
+        .bind(user.suspended_at.as_deref().map(timestamp).transpose()?)
+        .bind(user.allow_open_dms)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<User>> {
+        let row: Option<UserRow> = sqlx::query_as("SELECT * FROM users WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.db)
+            .await?;
+        Ok(row.map(User::from))
+    }
+
+    async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<User>> {
+        let rows: Vec<UserRow> = sqlx::query_as("SELECT * FROM users WHERE id = ANY($1)")
+            .bind(ids)
+            .fetch_all(&self.db)
+            .await?;
+        Ok(rows.into_iter().map(User::from).collect())
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<User>> {
+        let row: Option<UserRow> = sqlx::query_as("SELECT * FROM users WHERE email = $1")
+            .bind(email)
+            .fetch_optional(&self.db)
+            .await?;
+        Ok(row.map(User::from))
+    }
+
+    async fn find_by_username(&self, username: &str) -> Result<Option<User>> {
+        let row: Option<UserRow> = sqlx::query_as("SELECT * FROM users WHERE username = $1")
+            .bind(username)
+            .fetch_optional(&self.db)
+            .await?;
+        Ok(row.map(User::from))
+    }
+
+    async fn find_by_usernames(&self, usernames: &[String]) -> Result<Vec<User>> {
+        let rows: Vec<UserRow> = sqlx::query_as("SELECT * FROM users WHERE username = ANY($1)")
+            .bind(usernames)
+            .fetch_all(&self.db)
+            .await?;
+        Ok(rows.into_iter().map(User::from).collect())
+    }
+
+    async fn set_role(&self, id: Uuid, role: Role) -> Result<bool> {
+        let result = sqlx::query("UPDATE users SET role = $1 WHERE id = $2")
+            .bind(role.as_str())
+            .bind(id)
+            .execute(&self.db)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn set_suspended_at(&self, id: Uuid, at: Option<&str>) -> Result<bool> {
+        let result = sqlx::query("UPDATE users SET suspended_at = $1 WHERE id = $2")
+            .bind(at.map(timestamp).transpose()?)
+            .bind(id)
+            .execute(&self.db)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn set_allow_open_dms(&self, id: Uuid, allow: bool) -> Result<bool> {
+        let result = sqlx::query("UPDATE users SET allow_open_dms = $1 WHERE id = $2")
+            .bind(allow)
+            .bind(id)
+            .execute(&self.db)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[async_trait]
+impl TweetRepository for PostgresRepository {
+    async fn insert(&self, tweet: &Tweet, hashtags: &[String], mentions: &[Uuid]) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO tweets (id, user_id, content, created_at, quoted_tweet_id)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(tweet.id)
+        .bind(tweet.user_id)
+        .bind(&tweet.content)
+        .bind(timestamp(&tweet.created_at)?)
+        .bind(tweet.quoted_tweet_id)
+        .execute(&mut *tx)
+        .await?;
+
+        for tag_name in hashtags {
+            // 既存のハッシュタグでもIDを返すよう、衝突時は同じ値で更新する
+            let (hashtag_id,): (Uuid,) = sqlx::query_as(
+                r#"
+                INSERT INTO hashtags (id, name) VALUES ($1, $2)
+                ON CONFLICT (name) DO UPDATE SET name = EXCLUDED.name
+                RETURNING id
+                "#,
+            )
+            .bind(Uuid::new_v4())
+            .bind(tag_name)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            sqlx::query("INSERT INTO tweet_hashtags (tweet_id, hashtag_id) VALUES ($1, $2)")
+                .bind(tweet.id)
+                .bind(hashtag_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        sqlx::query("INSERT INTO tweet_mentions (tweet_id, user_id) SELECT $1, UNNEST($2::UUID[])")
+            .bind(tweet.id)
+            .bind(mentions)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<Tweet>> {
+        let row: Option<TweetRow> = sqlx::query_as("SELECT * FROM tweets WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.db)
+            .await?;
+        Ok(row.map(Tweet::from))
+    }
+
+    async fn find_many(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, Tweet>> {
+        let rows: Vec<TweetRow> = sqlx::query_as("SELECT * FROM tweets WHERE id = ANY($1)")
+            .bind(ids)
+            .fetch_all(&self.db)
+            .await?;
+        Ok(rows.into_iter().map(|r| (r.id, Tweet::from(r))).collect())
+    }
+
+    async fn delete(&self, id: Uuid, user_id: Option<Uuid>) -> Result<bool> {
+        let mut tx = self.db.begin().await?;
+
+        // likes は ON DELETE CASCADE ではないため先に削除する
+        sqlx::query(
+            r#"
+            DELETE FROM likes WHERE tweet_id IN (
+                SELECT id FROM tweets WHERE id = $1 AND ($2::UUID IS NULL OR user_id = $2)
+            )
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        let result =
+            sqlx::query("DELETE FROM tweets WHERE id = $1 AND ($2::UUID IS NULL OR user_id = $2)")
+                .bind(id)
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+
+        tx.commit().await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn timeline(&self, user_id: Uuid) -> Result<Vec<Tweet>> {
+        let query = format!(
+            r#"
+            SELECT t.* FROM tweets t
+            WHERE (t.user_id = $1
+               OR t.user_id IN (SELECT following_id FROM follows WHERE follower_id = $1))
+              AND t.user_id NOT IN ({})
+            ORDER BY t.created_at DESC
+            "#,
+            hidden_users(1)
+        );
+        let rows: Vec<TweetRow> = sqlx::query_as(&query)
+            .bind(user_id)
+            .fetch_all(&self.db)
+            .await?;
+        Ok(rows.into_iter().map(Tweet::from).collect())
+    }
+
+    async fn timeline_page(&self, user_id: Uuid, query: &PageQuery) -> Result<Page<TimelineEntry>> {
+        let (conditions, order) = keyset(query, "e.activity_at", "t.id", true, 2);
+        let sql = format!(
+            r#"
+            WITH {}
+            SELECT t.*, e.activity_at, e.retweeted_by FROM latest e
+            JOIN tweets t ON t.id = e.tweet_id
+            WHERE e.n = 1{}
+            {}
+            "#,
+            TIMELINE_EVENTS, conditions, order
+        );
+
+        let mut q = sqlx::query_as::<_, TimelineRow>(&sql).bind(user_id);
+        for (created_at, id) in page_bindings(query)? {
+            q = q.bind(created_at).bind(id);
+        }
+        let rows = q.bind(query.fetch_limit()).fetch_all(&self.db).await?;
+
+        Ok(query.page(
+            rows.into_iter()
+                .map(|r| {
+                    let activity_at = r.activity_at.to_rfc3339();
+                    let tweet = Tweet::from(r.tweet);
+                    let cursor = Cursor::new(&activity_at, tweet.id);
+                    let entry = TimelineEntry {
+                        tweet,
+                        retweeted_by: r.retweeted_by,
+                        activity_at,
+                    };
+                    (cursor, entry)
+                })
+                .collect(),
+        ))
+    }
+
+    async fn timeline_count(&self, user_id: Uuid) -> Result<i64> {
+        let sql = format!(
+            "WITH {} SELECT COUNT(*) FROM latest WHERE n = 1",
+            TIMELINE_EVENTS
+        );
+        let (count,) = sqlx::query_as(&sql)
+            .bind(user_id)
+            .fetch_one(&self.db)
+            .await?;
+        Ok(count)
+    }
+
+    async fn quote_counts(&self, tweet_ids: &[Uuid]) -> Result<HashMap<Uuid, i64>> {
+        let rows: Vec<(Uuid, i64)> = sqlx::query_as(
+            r#"
+            SELECT quoted_tweet_id, COUNT(*) FROM tweets
+            WHERE quoted_tweet_id = ANY($1)
+            GROUP BY quoted_tweet_id
+            "#,
+        )
+        .bind(tweet_ids)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(rows.into_iter().collect())
+    }
+}
+
+#[async_trait]
+impl RetweetRepository for PostgresRepository {
+    async fn insert(&self, user_id: Uuid, tweet_id: Uuid, created_at: &str) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO retweets (user_id, tweet_id, created_at) VALUES ($1, $2, $3)
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(user_id)
+        .bind(tweet_id)
+        .bind(timestamp(created_at)?)
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn delete(&self, user_id: Uuid, tweet_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM retweets WHERE tweet_id = $1 AND user_id = $2")
+            .bind(tweet_id)
+            .bind(user_id)
+            .execute(&self.db)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn counts(&self, tweet_ids: &[Uuid]) -> Result<HashMap<Uuid, i64>> {
+        let rows: Vec<(Uuid, i64)> = sqlx::query_as(
+            "SELECT tweet_id, COUNT(*) FROM retweets WHERE tweet_id = ANY($1) GROUP BY tweet_id",
+        )
+        .bind(tweet_ids)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(rows.into_iter().collect())
+    }
+
+    async fn retweeted_by(&self, user_id: Uuid, tweet_ids: &[Uuid]) -> Result<HashSet<Uuid>> {
+        let rows: Vec<(Uuid,)> = sqlx::query_as(
+            "SELECT tweet_id FROM retweets WHERE tweet_id = ANY($1) AND user_id = $2",
+        )
+        .bind(tweet_ids)
+        .bind(user_id)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(rows.into_iter().map(|(id,)| id).collect())
+    }
+}
+
+#[async_trait]
+impl LikeRepository for PostgresRepository {
+    async fn insert(&self, user_id: Uuid, tweet_id: Uuid, created_at: &str) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO likes (user_id, tweet_id, created_at) VALUES ($1, $2, $3)
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(user_id)
+        .bind(tweet_id)
+        .bind(timestamp(created_at)?)
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn delete(&self, user_id: Uuid, tweet_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM likes WHERE tweet_id = $1 AND user_id = $2")
+            .bind(tweet_id)
+            .bind(user_id)
+            .execute(&self.db)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn counts(&self, tweet_ids: &[Uuid]) -> Result<HashMap<Uuid, i64>> {
+        let rows: Vec<(Uuid, i64)> = sqlx::query_as(
+            "SELECT tweet_id, COUNT(*) FROM likes WHERE tweet_id = ANY($1) GROUP BY tweet_id",
+        )
+        .bind(tweet_ids)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(rows.into_iter().collect())
+    }
+
+    async fn liked_by(&self, user_id: Uuid, tweet_ids: &[Uuid]) -> Result<HashSet<Uuid>> {
+        let rows: Vec<(Uuid,)> =
+            sqlx::query_as("SELECT tweet_id FROM likes WHERE tweet_id = ANY($1) AND user_id = $2")
+                .bind(tweet_ids)
+                .bind(user_id)
+                .fetch_all(&self.db)
+                .await?;
+        Ok(rows.into_iter().map(|(id,)| id).collect())
+    }
+}
+
+#[async_trait]
+impl FollowRepository for PostgresRepository {
+    async fn insert(
+        &self,
+        follower_id: Uuid,
+        following_id: Uuid,
+        created_at: &str,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO follows (follower_id, following_id, created_at) VALUES ($1, $2, $3)
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(follower_id)
+        .bind(following_id)
+        .bind(timestamp(created_at)?)
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn delete(&self, follower_id: Uuid, following_id: Uuid) -> Result<bool> {
+        let result =
+            sqlx::query("DELETE FROM follows WHERE follower_id = $1 AND following_id = $2")
+                .bind(follower_id)
+                .bind(following_id)
+                .execute(&self.db)
+                .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn counts(&self, user_ids: &[Uuid]) -> Result<HashMap<Uuid, FollowCounts>> {
+        let rows: Vec<(Uuid, i64, i64)> = sqlx::query_as(
+            r#"
+            SELECT
+                u.id,
+                (SELECT COUNT(*) FROM follows WHERE following_id = u.id) AS followers_count,
+                (SELECT COUNT(*) FROM follows WHERE follower_id = u.id) AS following_count
+            FROM users u
+            WHERE u.id = ANY($1)
+            "#,
+        )
+        .bind(user_ids)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(id, followers, following)| {
+                (
+                    id,
+                    FollowCounts {
+                        followers,
+                        following,
+                    },
+                )
+            })
+            .collect())
+    }
+
+    async fn following_set(&self, follower_id: Uuid, target_ids: &[Uuid]) -> Result<HashSet<Uuid>> {
+        let rows: Vec<(Uuid,)> = sqlx::query_as(
+            "SELECT following_id FROM follows WHERE follower_id = $1 AND following_id = ANY($2)",
+        )
+        .bind(follower_id)
+        .bind(target_ids)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(rows.into_iter().map(|(id,)| id).collect())
+    }
+
+    async fn followers(&self, user_id: Uuid, query: &PageQuery) -> Result<Page<User>> {
+        self.follow_page("f.follower_id", "f.following_id", user_id, query)
+            .await
+    }
+
+    async fn following(&self, user_id: Uuid, query: &PageQuery) -> Result<Page<User>> {
+        self.follow_page("f.following_id", "f.follower_id", user_id, query)
+            .await
+    }
+}
+
+impl PostgresRepository {
+    /// follows を user_column で users に結合し、filter_column = user_id の行をページングする
+    async fn follow_page(
+        &self,
+        user_column: &str,
+        filter_column: &str,
+        user_id: Uuid,
+        query: &PageQuery,
+    ) -> Result<Page<User>> {
+        let (conditions, order) = keyset(query, "f.created_at", "u.id", true, 2);
+        let sql = format!(
+            r#"
+            SELECT u.*, f.created_at AS followed_at FROM users u
+            JOIN follows f ON u.id = {}
+            WHERE {} = $1{}
+            {}
+            "#,
+            user_column, filter_column, conditions, order
+        );
+
+        let mut q = sqlx::query_as::<_, FollowedUserRow>(&sql).bind(user_id);
+        for (created_at, id) in page_bindings(query)? {
+            q = q.bind(created_at).bind(id);
+        }
+        let rows = q.bind(query.fetch_limit()).fetch_all(&self.db).await?;
+
+        Ok(query.page(
+            rows.into_iter()
+                .map(|r| {
+                    let cursor = Cursor::new(&r.followed_at.to_rfc3339(), r.user.id);
+                    (cursor, User::from(r.user))
+                })
+                .collect(),
+        ))
+    }
+}
+
+#[async_trait]
+impl BlockRepository for PostgresRepository {
+    async fn block(&self, blocker_id: Uuid, blocked_id: Uuid, created_at: &str) -> Result<bool> {
+        let mut tx = self.db.begin().await?;
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO blocks (blocker_id, blocked_id, created_at) VALUES ($1, $2, $3)
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(blocker_id)
+        .bind(blocked_id)
+        .bind(timestamp(created_at)?)
+        .execute(&mut *tx)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        sqlx::query(
+            r#"
+            DELETE FROM follows
+            WHERE (follower_id = $1 AND following_id = $2) OR (follower_id = $2 AND following_id = $1)
+            "#,
+        )
+        .bind(blocker_id)
+        .bind(blocked_id)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            r#"
+            DELETE FROM notifications
+            WHERE (user_id = $1 AND actor_id = $2) OR (user_id = $2 AND actor_id = $1)
+            "#,
+        )
+        .bind(blocker_id)
+        .bind(blocked_id)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            r#"
+            DELETE FROM retweets r USING tweets t
+            WHERE t.id = r.tweet_id
+                AND ((r.user_id = $1 AND t.user_id = $2) OR (r.user_id = $2 AND t.user_id = $1))
+            "#,
+        )
+        .bind(blocker_id)
+        .bind(blocked_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn unblock(&self, blocker_id: Uuid, blocked_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM blocks WHERE blocker_id = $1 AND blocked_id = $2")
+            .bind(blocker_id)
+            .bind(blocked_id)
+            .execute(&self.db)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn mute(&self, muter_id: Uuid, muted_id: Uuid, created_at: &str) -> Result<bool> {
+        let mut tx = self.db.begin().await?;
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO mutes (muter_id, muted_id, created_at) VALUES ($1, $2, $3)
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(muter_id)
+        .bind(muted_id)
+        .bind(timestamp(created_at)?)
+        .execute(&mut *tx)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        sqlx::query("DELETE FROM notifications WHERE user_id = $1 AND actor_id = $2")
+            .bind(muter_id)
+            .bind(muted_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn unmute(&self, muter_id: Uuid, muted_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM mutes WHERE muter_id = $1 AND muted_id = $2")
+            .bind(muter_id)
+            .bind(muted_id)
+            .execute(&self.db)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn is_blocked(&self, a: Uuid, b: Uuid) -> Result<bool> {
+        let (blocked,) = sqlx::query_as(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM blocks
+                WHERE (blocker_id = $1 AND blocked_id = $2) OR (blocker_id = $2 AND blocked_id = $1)
+            )
+            "#,
+        )
+        .bind(a)
+        .bind(b)
+        .fetch_one(&self.db)
+        .await?;
+        Ok(blocked)
+    }
+
+    async fn hidden_set(&self, viewer: Uuid, user_ids: &[Uuid]) -> Result<HashSet<Uuid>> {
+        let query = format!(
+            "SELECT id FROM users WHERE id = ANY($2) AND id IN ({})",
+            hidden_users(1)
+        );
+        let rows: Vec<(Uuid,)> = sqlx::query_as(&query)
+            .bind(viewer)
+            .bind(user_ids)
+            .fetch_all(&self.db)
+            .await?;
+        Ok(rows.into_iter().map(|(id,)| id).collect())
+    }
+}
+
+#[async_trait]
+impl CommentRepository for PostgresRepository {
+    async fn insert(&self, comment: &Comment, mentions: &[Uuid]) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO comments (id, tweet_id, user_id, content, created_at, parent_id)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(comment.id)
+        .bind(comment.tweet_id)
+        .bind(comment.user_id)
+        .bind(&comment.content)
+        .bind(timestamp(&comment.created_at)?)
+        .bind(comment.parent_id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            "INSERT INTO comment_mentions (comment_id, user_id) SELECT $1, UNNEST($2::UUID[])",
+        )
+        .bind(comment.id)
+        .bind(mentions)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn list_for_tweet(
+        &self,
+        tweet_id: Uuid,
+        viewer: Option<Uuid>,
+        query: &PageQuery,
+    ) -> Result<Page<Comment>> {
+        let (conditions, order) = keyset(query, "created_at", "id", false, 3);
+        let sql = format!(
+            "SELECT * FROM comments WHERE tweet_id = $1 AND user_id NOT IN ({}){} {}",
+            hidden_users(2),
+            conditions,
+            order
+        );
+
+        let mut q = sqlx::query_as::<_, CommentRow>(&sql)
+            .bind(tweet_id)
+            .bind(viewer);
+        for (created_at, id) in page_bindings(query)? {
+            q = q.bind(created_at).bind(id);
+        }
+        let rows = q.bind(query.fetch_limit()).fetch_all(&self.db).await?;
+
+        Ok(query.page(
+            rows.into_iter()
+                .map(Comment::from)
+                .map(|c| (Cursor::new(&c.created_at, c.id), c))
+                .collect(),
+        ))
+    }
+
+    async fn count_for_tweet(&self, tweet_id: Uuid, viewer: Option<Uuid>) -> Result<i64> {
+        let query = format!(
+            "SELECT COUNT(*) FROM comments WHERE tweet_id = $1 AND user_id NOT IN ({})",
+            hidden_users(2)
+        );
+        let (count,) = sqlx::query_as(&query)
+            .bind(tweet_id)
+            .bind(viewer)
+            .fetch_one(&self.db)
+            .await?;
+        Ok(count)
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<Comment>> {
+        let row: Option<CommentRow> = sqlx::query_as("SELECT * FROM comments WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.db)
+            .await?;
+        Ok(row.map(Comment::from))
+    }
+
+    async fn find_many(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, Comment>> {
+        let rows: Vec<CommentRow> = sqlx::query_as("SELECT * FROM comments WHERE id = ANY($1)")
+            .bind(ids)
+            .fetch_all(&self.db)
+            .await?;
+        Ok(rows.into_iter().map(|r| (r.id, Comment::from(r))).collect())
+    }
+
+    async fn delete(&self, id: Uuid, user_id: Option<Uuid>) -> Result<bool> {
+        // 返信は parent_id の外部キーでまとめて削除される
+        let result = sqlx::query(
+            "DELETE FROM comments WHERE id = $1 AND ($2::UUID IS NULL OR user_id = $2)",
+        )
+        .bind(id)
+        .bind(user_id)
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn thread_page(
+        &self,
+        tweet_id: Uuid,
+        parent_id: Option<Uuid>,
+        max_depth: i64,
+        viewer: Option<Uuid>,
+        query: &PageQuery,
+    ) -> Result<Page<Comment>> {
+        let (conditions, order) = keyset(query, "c.created_at", "c.id", false, 5);
+        let sql = format!(
+            "{} SELECT c.* FROM comments c JOIN thread ON thread.id = c.id WHERE TRUE{} {}",
+            THREAD, conditions, order
+        );
+
+        let mut q = sqlx::query_as::<_, CommentRow>(&sql)
+            .bind(tweet_id)
+            .bind(parent_id)
+            .bind(max_depth)
+            .bind(viewer);
+        for (created_at, id) in page_bindings(query)? {
+            q = q.bind(created_at).bind(id);
+        }
+        let rows = q.bind(query.fetch_limit()).fetch_all(&self.db).await?;
+
+        Ok(query.page(
+            rows.into_iter()
+                .map(Comment::from)
+                .map(|c| (Cursor::new(&c.created_at, c.id), c))
+                .collect(),
+        ))
+    }
+
+    async fn thread_count(
+        &self,
+        tweet_id: Uuid,
+        parent_id: Option<Uuid>,
+        max_depth: i64,
+        viewer: Option<Uuid>,
+    ) -> Result<i64> {
+        let sql = format!("{} SELECT COUNT(*) FROM thread", THREAD);
+        let (count,) = sqlx::query_as(&sql)
+            .bind(tweet_id)
+            .bind(parent_id)
+            .bind(max_depth)
+            .bind(viewer)
+            .fetch_one(&self.db)
+            .await?;
+        Ok(count)
+    }
+
+    async fn ancestors(&self, id: Uuid) -> Result<Vec<Comment>> {
+        let rows: Vec<CommentRow> = sqlx::query_as(
+            r#"
+            WITH RECURSIVE ancestors (id, parent_id, distance) AS (
+                SELECT id, parent_id, 0 FROM comments WHERE id = $1
+                UNION ALL
+                SELECT c.id, c.parent_id, a.distance + 1 FROM comments c
+                JOIN ancestors a ON c.id = a.parent_id
+            )
+            SELECT c.* FROM comments c JOIN ancestors a ON a.id = c.id
+            WHERE a.distance > 0
+            ORDER BY a.distance DESC
+            "#,
+        )
+        .bind(id)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(rows.into_iter().map(Comment::from).collect())
+    }
+
+    async fn reply_counts(
+        &self,
+        comment_ids: &[Uuid],
+        viewer: Option<Uuid>,
+    ) -> Result<HashMap<Uuid, i64>> {
+        let query = format!(
+            r#"
+            SELECT parent_id, COUNT(*) FROM comments
+            WHERE parent_id = ANY($1) AND user_id NOT IN ({})
+            GROUP BY parent_id
+            "#,
+            hidden_users(2)
+        );
+        let rows: Vec<(Uuid, i64)> = sqlx::query_as(&query)
+            .bind(comment_ids)
+            .bind(viewer)
+            .fetch_all(&self.db)
+            .await?;
+        Ok(rows.into_iter().collect())
+    }
+
+    async fn top_level_counts(
+        &self,
+        tweet_ids: &[Uuid],
+        viewer: Option<Uuid>,
+    ) -> Result<HashMap<Uuid, i64>> {
+        let query = format!(
+            r#"
+            SELECT tweet_id, COUNT(*) FROM comments
+            WHERE tweet_id = ANY($1) AND parent_id IS NULL AND user_id NOT IN ({})
+            GROUP BY tweet_id
+            "#,
+            hidden_users(2)
+        );
+        let rows: Vec<(Uuid, i64)> = sqlx::query_as(&query)
+            .bind(tweet_ids)
+            .bind(viewer)
+            .fetch_all(&self.db)
+            .await?;
+        Ok(rows.into_iter().collect())
+    }
+}
+
+#[async_trait]
+impl HashtagRepository for PostgresRepository {
+    async fn for_tweets(&self, tweet_ids: &[Uuid]) -> Result<HashMap<Uuid, Vec<String>>> {
+        let rows: Vec<(Uuid, String)> = sqlx::query_as(
+            r#"
+            SELECT th.tweet_id, h.name
+            FROM tweet_hashtags th
+            JOIN hashtags h ON th.hashtag_id = h.id
+            WHERE th.tweet_id = ANY($1)
+            "#,
+        )
+        .bind(tweet_ids)
+        .fetch_all(&self.db)
+        .await?;
+
+        // ツイートIDごとにハッシュタグをグループ化
+        let mut hashtag_map: HashMap<Uuid, Vec<String>> = HashMap::new();
+        for (tweet_id, tag_name) in rows {
+            hashtag_map.entry(tweet_id).or_default().push(tag_name);
+        }
+        Ok(hashtag_map)
+    }
+
+    async fn stats(&self, name: &str) -> Result<Option<HashtagStats>> {
+        let (tweet_count, first_used_at, last_used_at): (
+            i64,
+            Option<DateTime<Utc>>,
+            Option<DateTime<Utc>>,
+        ) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*), MIN(t.created_at), MAX(t.created_at)
+            FROM tweets t
+            JOIN tweet_hashtags th ON th.tweet_id = t.id
+            JOIN hashtags h ON th.hashtag_id = h.id
+            WHERE h.name = $1
+            "#,
+        )
+        .bind(name)
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(first_used_at
+            .zip(last_used_at)
+            .map(|(first_used_at, last_used_at)| HashtagStats {
+                tweet_count,
+                first_used_at: first_used_at.to_rfc3339(),
+                last_used_at: last_used_at.to_rfc3339(),
+            }))
+    }
+
+    async fn tweets_page(&self, name: &str, query: &PageQuery) -> Result<Page<Tweet>> {
+        let (conditions, order) = keyset(query, "t.created_at", "t.id", true, 2);
+        let sql = format!(
+            r#"
+            SELECT t.* FROM tweets t
+            WHERE t.id IN (
+                SELECT th.tweet_id FROM tweet_hashtags th
+                JOIN hashtags h ON th.hashtag_id = h.id
+                WHERE h.name = $1
+            ){}
+            {}
+            "#,
+            conditions, order
+        );
+
+        let mut q = sqlx::query_as::<_, TweetRow>(&sql).bind(name);
+        for (created_at, id) in page_bindings(query)? {
+            q = q.bind(created_at).bind(id);
+        }
+        let rows = q.bind(query.fetch_limit()).fetch_all(&self.db).await?;
+
+        Ok(query.page(
+            rows.into_iter()
+                .map(Tweet::from)
+                .map(|t| (Cursor::new(&t.created_at, t.id), t))
+                .collect(),
+        ))
+    }
+
+    async fn counts_between(&self, since: &str, until: &str) -> Result<HashMap<String, i64>> {
+        let rows: Vec<(String, i64)> = sqlx::query_as(
+            r#"
+            SELECT h.name, COUNT(*)
+            FROM tweets t
+            JOIN tweet_hashtags th ON th.tweet_id = t.id
+            JOIN hashtags h ON th.hashtag_id = h.id
+            WHERE t.created_at >= $1 AND t.created_at < $2
+            GROUP BY h.name
+            "#,
+        )
+        .bind(timestamp(since)?)
+        .bind(timestamp(until)?)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(rows.into_iter().collect())
+    }
+}
+
+#[async_trait]
+impl MentionRepository for PostgresRepository {
+    async fn for_tweets(&self, tweet_ids: &[Uuid]) -> Result<HashMap<Uuid, Vec<Uuid>>> {
+        self.mentions("tweet_mentions", "tweet_id", tweet_ids).await
+    }
+
+    async fn for_comments(&self, comment_ids: &[Uuid]) -> Result<HashMap<Uuid, Vec<Uuid>>> {
+        self.mentions("comment_mentions", "comment_id", comment_ids)
+            .await
+    }
+
+    async fn tweets_page(&self, user_id: Uuid, query: &PageQuery) -> Result<Page<Tweet>> {
+        let (conditions, order) = keyset(query, "t.created_at", "t.id", true, 2);
+        let sql = format!(
+            r#"
+            SELECT t.* FROM tweets t
+            JOIN tweet_mentions m ON m.tweet_id = t.id
+            WHERE m.user_id = $1{}
+            {}
+            "#,
+            conditions, order
+        );
+
+        let mut q = sqlx::query_as::<_, TweetRow>(&sql).bind(user_id);
+        for (created_at, id) in page_bindings(query)? {
+            q = q.bind(created_at).bind(id);
+        }
+        let rows = q.bind(query.fetch_limit()).fetch_all(&self.db).await?;
+
+        Ok(query.page(
+            rows.into_iter()
+                .map(Tweet::from)
+                .map(|t| (Cursor::new(&t.created_at, t.id), t))
+                .collect(),
+        ))
+    }
+
+    async fn tweet_count(&self, user_id: Uuid) -> Result<i64> {
+        let (count,) = sqlx::query_as("SELECT COUNT(*) FROM tweet_mentions WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_one(&self.db)
+            .await?;
+        Ok(count)
+    }
+}
+
+impl PostgresRepository {
+    /// table の key 列ごとの言及されたユーザーID
+    async fn mentions(
+        &self,
+        table: &str,
+        key: &str,
+        ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, Vec<Uuid>>> {
+        let query = format!(
+            "SELECT {0}, user_id FROM {1} WHERE {0} = ANY($1)",
+            key, table
+        );
+        let rows: Vec<(Uuid, Uuid)> = sqlx::query_as(&query).bind(ids).fetch_all(&self.db).await?;
+
+        let mut mention_map: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+        for (id, user_id) in rows {
+            mention_map.entry(id).or_default().push(user_id);
+        }
+        Ok(mention_map)
+    }
+}
+
+#[async_trait]
+impl SessionRepository for PostgresRepository {
+    async fn insert(&self, session: &Session) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO sessions (id, user_id, user_agent, ip_address, created_at, last_seen_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(session.id)
+        .bind(session.user_id)
+        .bind(&session.user_agent)
+        .bind(&session.ip_address)
+        .bind(timestamp(&session.created_at)?)
+        .bind(timestamp(&session.last_seen_at)?)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    async fn find(&self, id: Uuid, user_id: Uuid) -> Result<Option<Session>> {
+        let row: Option<SessionRow> =
+            sqlx::query_as("SELECT * FROM sessions WHERE id = $1 AND user_id = $2")
+                .bind(id)
+                .bind(user_id)
+                .fetch_optional(&self.db)
+                .await?;
+        Ok(row.map(Session::from))
+    }
+
+    async fn touch(&self, id: Uuid, at: &str) -> Result<()> {
+        sqlx::query("UPDATE sessions SET last_seen_at = $1 WHERE id = $2")
+            .bind(timestamp(at)?)
+            .bind(id)
+            .execute(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    async fn list_active(&self, user_id: Uuid) -> Result<Vec<Session>> {
+        let rows: Vec<SessionRow> = sqlx::query_as(
+            r#"
+            SELECT * FROM sessions
+            WHERE user_id = $1 AND revoked_at IS NULL
+            ORDER BY last_seen_at DESC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(rows.into_iter().map(Session::from).collect())
+    }
+
+    async fn revoke(&self, id: Uuid, user_id: Option<Uuid>, at: &str) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE sessions SET revoked_at = $1
+            WHERE id = $2 AND ($3::UUID IS NULL OR user_id = $3) AND revoked_at IS NULL
+            "#,
+        )
+        .bind(timestamp(at)?)
+        .bind(id)
+        .bind(user_id)
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn revoke_others(&self, user_id: Uuid, except: Uuid, at: &str) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            UPDATE sessions SET revoked_at = $1
+            WHERE user_id = $2 AND id <> $3 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(timestamp(at)?)
+        .bind(user_id)
+        .bind(except)
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+#[async_trait]
+impl RefreshTokenRepository for PostgresRepository {
+    async fn insert(&self, token: &RefreshToken) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO refresh_tokens (id, user_id, family_id, token_hash, created_at, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(token.id)
+        .bind(token.user_id)
+        .bind(token.family_id)
+        .bind(&token.token_hash)
+        .bind(timestamp(&token.created_at)?)
+        .bind(timestamp(&token.expires_at)?)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    async fn find_by_hash(&self, token_hash: &str) -> Result<Option<RefreshToken>> {
+        let row: Option<RefreshTokenRow> =
+            sqlx::query_as("SELECT * FROM refresh_tokens WHERE token_hash = $1")
+                .bind(token_hash)
+                .fetch_optional(&self.db)
+                .await?;
+        Ok(row.map(RefreshToken::from))
+    }
+
+    async fn revoke_family(&self, family_id: Uuid, at: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE refresh_tokens SET revoked_at = $1 WHERE family_id = $2 AND revoked_at IS NULL",
+        )
+        .bind(timestamp(at)?)
+        .bind(family_id)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    async fn revoke_other_families(&self, user_id: Uuid, except: Uuid, at: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE refresh_tokens SET revoked_at = $1
+            WHERE user_id = $2 AND family_id <> $3 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(timestamp(at)?)
+        .bind(user_id)
+        .bind(except)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    async fn mark_replaced(&self, id: Uuid, replaced_by: Uuid, at: &str) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE refresh_tokens SET revoked_at = $1, replaced_by = $2
+            WHERE id = $3 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(timestamp(at)?)
+        .bind(replaced_by)
+        .bind(id)
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[async_trait]
+impl ReportRepository for PostgresRepository {
+    async fn insert(&self, report: &Report) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO reports (id, reporter_id, target_type, target_id, reason, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(report.id)
+        .bind(report.reporter_id)
+        .bind(report.target_type.as_str())
+        .bind(report.target_id)
+        .bind(&report.reason)
+        .bind(timestamp(&report.created_at)?)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    async fn list_open(&self) -> Result<Vec<Report>> {
+        let rows: Vec<ReportRow> = sqlx::query_as(
+            "SELECT * FROM reports WHERE resolved_at IS NULL ORDER BY created_at ASC, id ASC",
+        )
+        .fetch_all(&self.db)
+        .await?;
+        Ok(rows.into_iter().map(Report::from).collect())
+    }
+
+    async fn resolve(&self, id: Uuid, resolved_by: Uuid, at: &str) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE reports SET resolved_at = $1, resolved_by = $2
+            WHERE id = $3 AND resolved_at IS NULL
+            "#,
+        )
+        .bind(timestamp(at)?)
+        .bind(resolved_by)
+        .bind(id)
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[async_trait]
+impl ModerationLogRepository for PostgresRepository {
+    async fn insert(&self, action: &ModerationAction) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO moderation_actions (id, actor_id, action, target_id, details, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(action.id)
+        .bind(action.actor_id)
+        .bind(action.action.as_str())
+        .bind(action.target_id)
+        .bind(&action.details)
+        .bind(timestamp(&action.created_at)?)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    async fn recent(&self, limit: i64) -> Result<Vec<ModerationAction>> {
+        let rows: Vec<ModerationActionRow> = sqlx::query_as(
+            "SELECT * FROM moderation_actions ORDER BY created_at DESC, id DESC LIMIT $1",
+        )
+        .bind(limit)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(rows.into_iter().map(ModerationAction::from).collect())
+    }
+}
+
+#[async_trait]
+impl NotificationRepository for PostgresRepository {
+    async fn upsert(&self, notification: &Notification) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO notifications
+                (id, user_id, kind, actor_id, group_key, tweet_id, comment_id, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (user_id, group_key, actor_id) DO UPDATE SET
+                tweet_id = excluded.tweet_id,
+                comment_id = excluded.comment_id,
+                created_at = excluded.created_at,
+                read_at = NULL
+            "#,
+        )
+        .bind(notification.id)
+        .bind(notification.user_id)
+        .bind(notification.kind.as_str())
+        .bind(notification.actor_id)
+        .bind(&notification.group_key)
+        .bind(notification.tweet_id)
+        .bind(notification.comment_id)
+        .bind(timestamp(&notification.created_at)?)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    async fn groups_page(
+        &self,
+        user_id: Uuid,
+        query: &PageQuery,
+    ) -> Result<Page<NotificationGroup>> {
+        let (conditions, order) = keyset(query, "created_at", "id", true, 2);
+        let sql = format!(
+            r#"
+            WITH ranked AS (
+                SELECT *,
+                    ROW_NUMBER() OVER (
+                        PARTITION BY group_key ORDER BY created_at DESC, id DESC
+                    ) AS n_rank,
+                    COUNT(*) OVER (PARTITION BY group_key) AS actor_count,
+                    BOOL_OR(read_at IS NULL) OVER (PARTITION BY group_key) AS unread
+                FROM notifications WHERE user_id = $1
+            )
+            SELECT * FROM ranked WHERE n_rank = 1{}
+            {}
+            "#,
+            conditions, order
+        );
+
+        let mut q = sqlx::query_as::<_, NotificationGroupRow>(&sql).bind(user_id);
+        for (created_at, id) in page_bindings(query)? {
+            q = q.bind(created_at).bind(id);
+        }
+        let rows = q.bind(query.fetch_limit()).fetch_all(&self.db).await?;
+
+        Ok(query.page(
+            rows.into_iter()
+                .map(NotificationGroup::from)
+                .map(|g| (Cursor::new(&g.latest.created_at, g.latest.id), g))
+                .collect(),
+        ))
+    }
+
+    async fn group_count(&self, user_id: Uuid) -> Result<i64> {
+        let (count,) = sqlx::query_as(
+            "SELECT COUNT(DISTINCT group_key) FROM notifications WHERE user_id = $1",
+        )
+        .bind(user_id)
+        .fetch_one(&self.db)
+        .await?;
+        Ok(count)
+    }
+
+    async fn unread_count(&self, user_id: Uuid) -> Result<i64> {
+        let (count,) = sqlx::query_as(
+            r#"
+            SELECT COUNT(DISTINCT group_key) FROM notifications
+            WHERE user_id = $1 AND read_at IS NULL
+            "#,
+        )
+        .bind(user_id)
+        .fetch_one(&self.db)
+        .await?;
+        Ok(count)
+    }
+
+    async fn recent_actors(
+        &self,
+        user_id: Uuid,
+        group_keys: &[String],
+        limit: i64,
+    ) -> Result<HashMap<String, Vec<Uuid>>> {
+        let rows: Vec<(String, Uuid)> = sqlx::query_as(
+            r#"
+            SELECT group_key, actor_id FROM (
+                SELECT group_key, actor_id,
+                    ROW_NUMBER() OVER (
+                        PARTITION BY group_key ORDER BY created_at DESC, id DESC
+                    ) AS n_rank
+                FROM notifications WHERE user_id = $1 AND group_key = ANY($2)
+            ) ranked
+            WHERE n_rank <= $3
+            ORDER BY group_key, n_rank
+            "#,
+        )
+        .bind(user_id)
+        .bind(group_keys)
+        .bind(limit)
+        .fetch_all(&self.db)
+        .await?;
+
+        let mut actors: HashMap<String, Vec<Uuid>> = HashMap::new();
+        for (key, actor_id) in rows {
+            actors.entry(key).or_default().push(actor_id);
+        }
+        Ok(actors)
+    }
+
+    async fn mark_read(&self, user_id: Uuid, at: &str) -> Result<u64> {
+        let result = sqlx::query(
+            "UPDATE notifications SET read_at = $1 WHERE user_id = $2 AND read_at IS NULL",
+        )
+        .bind(timestamp(at)?)
+        .bind(user_id)
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn disabled_kinds(&self, user_id: Uuid) -> Result<Vec<NotificationKind>> {
+        let kinds: Vec<(String,)> =
+            sqlx::query_as("SELECT kind FROM disabled_notification_kinds WHERE user_id = $1")
+                .bind(user_id)
+                .fetch_all(&self.db)
+                .await?;
+        // 不明な種類は無視する
+        Ok(kinds
+            .into_iter()
+            .filter_map(|(kind,)| NotificationKind::try_from(kind).ok())
+            .collect())
+    }
+
+    async fn set_kind_enabled(
+        &self,
+        user_id: Uuid,
+        kind: NotificationKind,
+        enabled: bool,
+    ) -> Result<()> {
+        let sql = if enabled {
+            "DELETE FROM disabled_notification_kinds WHERE user_id = $1 AND kind = $2"
+        } else {
+            r#"
+            INSERT INTO disabled_notification_kinds (user_id, kind) VALUES ($1, $2)
+            ON CONFLICT (user_id, kind) DO NOTHING
+            "#
+        };
+        sqlx::query(sql)
+            .bind(user_id)
+            .bind(kind.as_str())
+            .execute(&self.db)
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl DirectMessageRepository for PostgresRepository {
+    async fn create_conversation(
+        &self,
+        conversation: &DirectConversation,
+        member_ids: &[Uuid],
+    ) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+
+        sqlx::query(
+            "INSERT INTO direct_conversations (id, created_at, last_message_at) VALUES ($1, $2, $3)",
+        )
+        .bind(conversation.id)
+        .bind(timestamp(&conversation.created_at)?)
+        .bind(timestamp(&conversation.last_message_at)?)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO direct_conversation_members (conversation_id, user_id)
+            SELECT $1, UNNEST($2::UUID[])
+            "#,
+        )
+        .bind(conversation.id)
+        .bind(member_ids)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn find_by_members(&self, member_ids: &[Uuid]) -> Result<Option<DirectConversation>> {
+        let row: Option<DirectConversationRow> = sqlx::query_as(
+            r#"
+            SELECT * FROM direct_conversations WHERE id IN (
+                SELECT conversation_id FROM direct_conversation_members
+                GROUP BY conversation_id
+                HAVING COUNT(*) = $2 AND COUNT(*) FILTER (WHERE user_id = ANY($1)) = $2
+            )
+            LIMIT 1
+            "#,
+        )
+        .bind(member_ids)
+        .bind(member_ids.len() as i64)
+        .fetch_optional(&self.db)
+        .await?;
+        Ok(row.map(DirectConversation::from))
+    }
+
+    async fn members(
+        &self,
+        conversation_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, Vec<DirectConversationMember>>> {
+        let rows: Vec<DirectConversationMemberRow> = sqlx::query_as(
+            "SELECT * FROM direct_conversation_members WHERE conversation_id = ANY($1)",
+        )
+        .bind(conversation_ids)
+        .fetch_all(&self.db)
+        .await?;
+
+        let mut members: HashMap<Uuid, Vec<DirectConversationMember>> = HashMap::new();
+        for row in rows {
+            members
+                .entry(row.conversation_id)
+                .or_default()
+                .push(DirectConversationMember::from(row));
+        }
+        Ok(members)
+    }
+
+    async fn conversations_page(
+        &self,
+        user_id: Uuid,
+        query: &PageQuery,
+    ) -> Result<Page<DirectConversation>> {
+        let (conditions, order) = keyset(query, "c.last_message_at", "c.id", true, 2);
+        let sql = format!(
+            r#"
+            SELECT c.* FROM direct_conversations c
+            JOIN direct_conversation_members m ON m.conversation_id = c.id
+            WHERE m.user_id = $1{}
+            {}
+            "#,
+            conditions, order
+        );
+
+        let mut q = sqlx::query_as::<_, DirectConversationRow>(&sql).bind(user_id);
+        for (created_at, id) in page_bindings(query)? {
+            q = q.bind(created_at).bind(id);
+        }
+        let rows = q.bind(query.fetch_limit()).fetch_all(&self.db).await?;
+
+        Ok(query.page(
+            rows.into_iter()
+                .map(DirectConversation::from)
+                .map(|c| (Cursor::new(&c.last_message_at, c.id), c))
+                .collect(),
+        ))
+    }
+
+    async fn conversation_count(&self, user_id: Uuid) -> Result<i64> {
+        let (count,) =
+            sqlx::query_as("SELECT COUNT(*) FROM direct_conversation_members WHERE user_id = $1")
+                .bind(user_id)
+                .fetch_one(&self.db)
+                .await?;
+        Ok(count)
+    }
+
+    async fn insert_message(&self, message: &DirectMessage) -> Result<()> {
+        let created_at = timestamp(&message.created_at)?;
+        let mut tx = self.db.begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO direct_messages (id, conversation_id, sender_id, content, created_at)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(message.id)
+        .bind(message.conversation_id)
+        .bind(message.sender_id)
+        .bind(&message.content)
+        .bind(created_at)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("UPDATE direct_conversations SET last_message_at = $1 WHERE id = $2")
+            .bind(created_at)
+            .bind(message.conversation_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(
+            r#"
+            UPDATE direct_conversation_members SET last_read_at = $1
+            WHERE conversation_id = $2 AND user_id = $3
+            "#,
+        )
+        .bind(created_at)
+        .bind(message.conversation_id)
+        .bind(message.sender_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn messages_page(
+        &self,
+        conversation_id: Uuid,
+        query: &PageQuery,
+    ) -> Result<Page<DirectMessage>> {
+        let (conditions, order) = keyset(query, "created_at", "id", true, 2);
+        let sql = format!(
+            "SELECT * FROM direct_messages WHERE conversation_id = $1{} {}",
+            conditions, order
+        );
+
+        let mut q = sqlx::query_as::<_, DirectMessageRow>(&sql).bind(conversation_id);
+        for (created_at, id) in page_bindings(query)? {
+            q = q.bind(created_at).bind(id);
+        }
+        let rows = q.bind(query.fetch_limit()).fetch_all(&self.db).await?;
+
+        Ok(query.page(
+            rows.into_iter()
+                .map(DirectMessage::from)
+                .map(|m| (Cursor::new(&m.created_at, m.id), m))
+                .collect(),
+        ))
+    }
+
+    async fn message_count(&self, conversation_id: Uuid) -> Result<i64> {
+        let (count,) =
+            sqlx::query_as("SELECT COUNT(*) FROM direct_messages WHERE conversation_id = $1")
+                .bind(conversation_id)
+                .fetch_one(&self.db)
+                .await?;
+        Ok(count)
+    }
+
+    async fn last_messages(
+        &self,
+        conversation_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, DirectMessage>> {
+        let rows: Vec<DirectMessageRow> = sqlx::query_as(
+            r#"
+            SELECT DISTINCT ON (conversation_id) * FROM direct_messages
+            WHERE conversation_id = ANY($1)
+            ORDER BY conversation_id, created_at DESC, id DESC
+            "#,
+        )
+        .bind(conversation_ids)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|r| (r.conversation_id, DirectMessage::from(r)))
+            .collect())
+    }
+
+    async fn unread_counts(
+        &self,
+        user_id: Uuid,
+        conversation_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, i64>> {
+        let rows: Vec<(Uuid, i64)> = sqlx::query_as(
+            r#"
+            SELECT d.conversation_id, COUNT(*) FROM direct_messages d
+            JOIN direct_conversation_members m
+                ON m.conversation_id = d.conversation_id AND m.user_id = $1
+            WHERE d.conversation_id = ANY($2)
+                AND d.sender_id <> m.user_id
+                AND (m.last_read_at IS NULL OR d.created_at > m.last_read_at)
+            GROUP BY d.conversation_id
+            "#,
+        )
+        .bind(user_id)
+        .bind(conversation_ids)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(rows.into_iter().collect())
+    }
+
+    async fn mark_read(&self, conversation_id: Uuid, user_id: Uuid, at: &str) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE direct_conversation_members SET last_read_at = $1
+            WHERE conversation_id = $2 AND user_id = $3
+            "#,
+        )
+        .bind(timestamp(at)?)
+        .bind(conversation_id)
+        .bind(user_id)
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[async_trait]
+impl SearchRepository for PostgresRepository {
+    async fn search_tweets(&self, filter: &SearchFilter, query: &PageQuery) -> Result<Page<Tweet>> {
+        let mut args = Vec::new();
+        let mut conditions = term_conditions(&filter.terms, "t.content", &mut args);
+        conditions += &filter_conditions(filter, "t", &mut args)?;
+        for tag in &filter.hashtags {
+            args.push(SearchArg::Text(tag.clone()));
+            conditions += &format!(
+                r#"
+                AND t.id IN (
+                    SELECT th.tweet_id FROM tweet_hashtags th
+                    JOIN hashtags h ON h.id = th.hashtag_id
+                    WHERE h.name = ${}
+                )"#,
+                args.len()
+            );
+        }
+
+        let (page_conditions, order) = keyset(query, "t.created_at", "t.id", true, args.len() + 1);
+        let sql = format!(
+            "SELECT t.* FROM tweets t WHERE TRUE{}{} {}",
+            conditions, page_conditions, order
+        );
+        let rows: Vec<TweetRow> = search_rows(&self.db, &sql, &args, query).await?;
+
+        Ok(query.page(
+            rows.into_iter()
+                .map(Tweet::from)
+                .map(|t| (Cursor::new(&t.created_at, t.id), t))
+                .collect(),
+        ))
+    }
+
+    async fn search_comments(
+        &self,
+        filter: &SearchFilter,
+        query: &PageQuery,
+    ) -> Result<Page<Comment>> {
+        let mut args = Vec::new();
+        let mut conditions = term_conditions(&filter.terms, "c.content", &mut args);
+        conditions += &filter_conditions(filter, "c", &mut args)?;
+
+        let (page_conditions, order) = keyset(query, "c.created_at", "c.id", true, args.len() + 1);
+        let sql = format!(
+            "SELECT c.* FROM comments c WHERE TRUE{}{} {}",
+            conditions, page_conditions, order
+        );
+        let rows: Vec<CommentRow> = search_rows(&self.db, &sql, &args, query).await?;
+
+        Ok(query.page(
+            rows.into_iter()
+                .map(Comment::from)
+                .map(|c| (Cursor::new(&c.created_at, c.id), c))
+                .collect(),
+        ))
+    }
+
+    async fn search_users(&self, terms: &[String], query: &PageQuery) -> Result<Page<User>> {
+        let mut args = Vec::new();
+        let conditions = term_conditions(terms, "u.username", &mut args);
+
+        let (page_conditions, order) = keyset(query, "u.created_at", "u.id", true, args.len() + 1);
+        let sql = format!(
+            "SELECT u.* FROM users u WHERE TRUE{}{} {}",
+            conditions, page_conditions, order
+        );
+        let rows: Vec<UserRow> = search_rows(&self.db, &sql, &args, query).await?;
+
+        Ok(query.page(
+            rows.into_iter()
+                .map(User::from)
+                .map(|u| (Cursor::new(&u.created_at, u.id), u))
+                .collect(),
+        ))
+    }
+}
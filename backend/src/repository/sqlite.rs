@@ -0,0 +1,2109 @@
+use async_trait::async_trait;
+use sqlx::FromRow;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use super::{
+    BlockRepository, CommentRepository, DirectMessageRepository, FollowCounts, FollowRepository,
+    HashtagRepository, HashtagStats, LikeRepository, MentionRepository, ModerationLogRepository,
+    NotificationGroup, NotificationRepository, RefreshTokenRepository, ReportRepository, Result,
+    RetweetRepository, SearchFilter, SearchRepository, SessionRepository, TweetRepository,
+    UserRepository,
+};
+use crate::models::{
+    Comment, DirectConversation, DirectConversationMember, DirectMessage, ModerationAction,
+    Notification, NotificationKind, RefreshToken, Report, Role, Session, TimelineEntry, Tweet,
+    User,
+};
+use crate::pagination::{Cursor, Page, PageQuery};
+use crate::store::Db;
+
+/// SQLiteによるリポジトリ実装
+pub struct SqliteRepository {
+    db: Db,
+}
+
+impl SqliteRepository {
+    pub fn new(db: Db) -> Self {
+        Self { db }
+    }
+}
+
+/// SQLiteでIN句を使うため、プレースホルダを動的に生成
+fn placeholders(ids: &[Uuid]) -> String {
+    ids.iter().map(|_| "?").collect::<Vec<_>>().join(",")
+}
+
+/// キーセットページネーションの条件（AND から始まる）と ORDER BY ... LIMIT を組み立てる
+///
+/// descending は一覧の並び順。バインドは query.cursors() の順に (key, id) の組、最後に LIMIT
+fn keyset(query: &PageQuery, key: &str, id: &str, descending: bool) -> (String, String) {
+    let (after_op, before_op) = if descending { ("<", ">") } else { (">", "<") };
+
+    let mut conditions = String::new();
+    if query.after.is_some() {
+        conditions += &format!(" AND ({}, {}) {} (?, ?)", key, id, after_op);
+    }
+    if query.before.is_some() {
+        conditions += &format!(" AND ({}, {}) {} (?, ?)", key, id, before_op);
+    }
+
+    let order = if descending != query.from_end {
+        "DESC"
+    } else {
+        "ASC"
+    };
+    (
+        conditions,
+        format!("ORDER BY {0} {2}, {1} {2} LIMIT ?", key, id, order),
+    )
+}
+
+/// 検索条件のバインド値
+enum SearchArg {
+    Text(String),
+    Id(Uuid),
+}
+
+/// trigram トークナイザが一致できる最短の語（これより短い語は LIKE で探す）
+const TRIGRAM_LENGTH: usize = 3;
+
+/// 検索語の条件（AND から始まる）。語は FTS5 のフレーズとして ID で絞り込む
+fn term_conditions(
+    terms: &[String],
+    id: &str,
+    fts: &str,
+    column: &str,
+    args: &mut Vec<SearchArg>,
+) -> String {
+    let mut conditions = String::new();
+
+    let phrases: Vec<String> = terms
+        .iter()
+        .filter(|t| t.chars().count() >= TRIGRAM_LENGTH)
+        .map(|t| format!("\"{}\"", t.replace('"', "\"\"")))
+        .collect();
+    if !phrases.is_empty() {
+        conditions += &format!(
+            " AND {} IN (SELECT s.id FROM {1} JOIN search_ids s ON s.rowid = {1}.rowid \
+             WHERE {1} MATCH ?)",
+            id, fts
+        );
+        args.push(SearchArg::Text(phrases.join(" ")));
+    }
+
+    for term in terms.iter().filter(|t| t.chars().count() < TRIGRAM_LENGTH) {
+        conditions += &format!(" AND {} LIKE ? ESCAPE '\\'", column);
+        args.push(SearchArg::Text(like_pattern(term)));
+    }
+    conditions
+}
+
+/// 部分一致の LIKE パターン（% と _ はエスケープする）
+fn like_pattern(term: &str) -> String {
+    let escaped = term
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+/// 投稿者と日時の条件（AND から始まる）
+fn filter_conditions(filter: &SearchFilter, alias: &str, args: &mut Vec<SearchArg>) -> String {
+    let mut conditions = String::new();
+    if let Some(author_id) = filter.author_id {
+        conditions += &format!(" AND {}.user_id = ?", alias);
+        args.push(SearchArg::Id(author_id));
+    }
+    if let Some(since) = &filter.since {
+        conditions += &format!(" AND {}.created_at >= ?", alias);
+        args.push(SearchArg::Text(since.clone()));
+    }
+    if let Some(until) = &filter.until {
+        conditions += &format!(" AND {}.created_at < ?", alias);
+        args.push(SearchArg::Text(until.clone()));
+    }
+    if let Some(viewer) = filter.viewer {
+        conditions += &format!(" AND {}.user_id NOT IN ({})", alias, HIDDEN_USERS);
+        args.extend([
+            SearchArg::Id(viewer),
+            SearchArg::Id(viewer),
+            SearchArg::Id(viewer),
+        ]);
+    }
+    conditions
+}
+
+/// 検索条件とカーソルをバインドして1ページ分を取得する
+async fn search_page<T>(db: &Db, sql: &str, args: &[SearchArg], query: &PageQuery) -> Result<Vec<T>>
+where
+    T: for<'r> FromRow<'r, sqlx::sqlite::SqliteRow> + Send + Unpin,
+{
+    let mut q = sqlx::query_as::<_, T>(sql);
+    for arg in args {
+        q = match arg {
+            SearchArg::Text(text) => q.bind(text.as_str()),
+            SearchArg::Id(id) => q.bind(*id),
+        };
+    }
+    for cursor in query.cursors() {
+        q = q.bind(&cursor.created_at).bind(cursor.id);
+    }
+    Ok(q.bind(query.fetch_limit()).fetch_all(db).await?)
+}
+
+/// viewer から見えないユーザー（viewer がブロック・ミュートしたか、viewer をブロックした）
+///
+/// バインドは viewer のIDを3回（NULL なら誰も含まない）
+const HIDDEN_USERS: &str = r#"
+    SELECT blocked_id FROM blocks WHERE blocker_id = ?
+    UNION SELECT blocker_id FROM blocks WHERE blocked_id = ?
+    UNION SELECT muted_id FROM mutes WHERE muter_id = ?
+"#;
+
+/// タイムラインに載る投稿とリツイート（バインドはユーザーID）
+///
+/// latest はツイートごとに新しい順の番号 n を付けたもので、n = 1 が最後に載った1件
+const TIMELINE_EVENTS: &str = r#"
+    viewer (id) AS (SELECT ?),
+    authors (user_id) AS (
+        SELECT id FROM viewer
+        UNION SELECT following_id FROM follows WHERE follower_id IN (SELECT id FROM viewer)
+    ),
+    hidden (user_id) AS (
+        SELECT blocked_id FROM blocks WHERE blocker_id IN (SELECT id FROM viewer)
+        UNION SELECT blocker_id FROM blocks WHERE blocked_id IN (SELECT id FROM viewer)
+        UNION SELECT muted_id FROM mutes WHERE muter_id IN (SELECT id FROM viewer)
+    ),
+    events AS (
+        SELECT id AS tweet_id, created_at AS activity_at, NULL AS retweeted_by
+        FROM tweets
+        WHERE user_id IN (SELECT user_id FROM authors)
+            AND user_id NOT IN (SELECT user_id FROM hidden)
+        UNION ALL
+        SELECT r.tweet_id, r.created_at, r.user_id
+        FROM retweets r JOIN tweets t ON t.id = r.tweet_id
+        WHERE r.user_id IN (SELECT user_id FROM authors)
+            AND r.user_id NOT IN (SELECT user_id FROM hidden)
+            AND t.user_id NOT IN (SELECT user_id FROM hidden)
+    ),
+    latest AS (
+        SELECT *, ROW_NUMBER() OVER (PARTITION BY tweet_id ORDER BY activity_at DESC) AS n
+        FROM events
+    )
+"#;
+
+/// 返信先から max_depth 階層までの返信の ID（バインドは閲覧者ID・ツイートID・返信先ID・max_depth）
+///
+/// 閲覧者から見えないユーザーのコメントは、その下の返信ごと除く
+const THREAD: &str = r#"
+    WITH RECURSIVE viewer (id) AS (SELECT ?),
+    hidden (user_id) AS (
+        SELECT blocked_id FROM blocks WHERE blocker_id IN (SELECT id FROM viewer)
+        UNION SELECT blocker_id FROM blocks WHERE blocked_id IN (SELECT id FROM viewer)
+        UNION SELECT muted_id FROM mutes WHERE muter_id IN (SELECT id FROM viewer)
+    ),
+    thread (id, depth) AS (
+        SELECT id, 1 FROM comments
+        WHERE tweet_id = ? AND parent_id IS ?
+            AND user_id NOT IN (SELECT user_id FROM hidden)
+        UNION ALL
+        SELECT c.id, thread.depth + 1 FROM comments c
+        JOIN thread ON c.parent_id = thread.id
+        WHERE thread.depth < ? AND c.user_id NOT IN (SELECT user_id FROM hidden)
+    )
+"#;
+
+/// タイムラインに載った日時とリツイートしたユーザー付きのツイート
+#[derive(FromRow)]
+struct TimelineRow {
+    #[sqlx(flatten)]
+    tweet: Tweet,
+    activity_at: String,
+    retweeted_by: Option<Uuid>,
+}
+
+/// フォロー日時付きのユーザー
+#[derive(FromRow)]
+struct FollowedUser {
+    #[sqlx(flatten)]
+    user: User,
+    followed_at: String,
+}
+
+/// まとまりの集計付きの最新の通知
+#[derive(FromRow)]
+struct NotificationGroupRow {
+    #[sqlx(flatten)]
+    notification: Notification,
+    actor_count: i64,
+    unread: bool,
+}
+
+impl From<NotificationGroupRow> for NotificationGroup {
+    fn from(row: NotificationGroupRow) -> Self {
+        Self {
+            latest: row.notification,
+            actor_count: row.actor_count,
+            unread: row.unread,
+        }
+    }
+}
+
+#[async_trait]
+impl UserRepository for SqliteRepository {
+    async fn insert(&self, user: &User) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO users
+                (id, username, email, password_hash, created_at, role, suspended_at, allow_open_dms)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(user.id)
+        .bind(&user.username)
+        .bind(&user.email)
+        .bind(&user.password_hash)
+        .bind(&user.created_at)
+        .bind(user.role.as_str())
+        .bind(&user.suspended_at)
+        .bind(user.allow_open_dms)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<User>> {
+        let user = sqlx::query_as("SELECT * FROM users WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.db)
+            .await?;
+        Ok(user)
+    }
+
+    async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<User>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let query = format!("SELECT * FROM users WHERE id IN ({})", placeholders(ids));
+        let mut q = sqlx::query_as(&query);
+        for id in ids {
+            q = q.bind(id);
+        }
+        Ok(q.fetch_all(&self.db).await?)
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<User>> {
+        let user = sqlx::query_as("SELECT * FROM users WHERE email = ?")
+            .bind(email)
+            .fetch_optional(&self.db)
+            .await?;
+        Ok(user)
+    }
+
+    async fn find_by_username(&self, username: &str) -> Result<Option<User>> {
+        let user = sqlx::query_as("SELECT * FROM users WHERE username = ?")
+            .bind(username)
+            .fetch_optional(&self.db)
+            .await?;
+        Ok(user)
+    }
+
+    async fn find_by_usernames(&self, usernames: &[String]) -> Result<Vec<User>> {
+        if usernames.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let query = format!(
+            "SELECT * FROM users WHERE username IN ({})",
+            vec!["?"; usernames.len()].join(",")
+        );
+        let mut q = sqlx::query_as(&query);
+        for username in usernames {
+            q = q.bind(username);
+        }
+        Ok(q.fetch_all(&self.db).await?)
+    }
+
+    async fn set_role(&self, id: Uuid, role: Role) -> Result<bool> {
+        let result = sqlx::query("UPDATE users SET role = ? WHERE id = ?")
+            .bind(role.as_str())
+            .bind(id)
+            .execute(&self.db)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn set_suspended_at(&self, id: Uuid, at: Option<&str>) -> Result<bool> {
+        let result = sqlx::query("UPDATE users SET suspended_at = ? WHERE id = ?")
+            .bind(at)
+            .bind(id)
+            .execute(&self.db)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn set_allow_open_dms(&self, id: Uuid, allow: bool) -> Result<bool> {
+        let result = sqlx::query("UPDATE users SET allow_open_dms = ? WHERE id = ?")
+            .bind(allow)
+            .bind(id)
+            .execute(&self.db)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[async_trait]
+impl TweetRepository for SqliteRepository {
+    async fn insert(&self, tweet: &Tweet, hashtags: &[String], mentions: &[Uuid]) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO tweets (id, user_id, content, created_at, quoted_tweet_id)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(tweet.id)
+        .bind(tweet.user_id)
+        .bind(&tweet.content)
+        .bind(&tweet.created_at)
+        .bind(tweet.quoted_tweet_id)
+        .execute(&mut *tx)
+        .await?;
+
+        for tag_name in hashtags {
+            sqlx::query("INSERT OR IGNORE INTO hashtags (id, name) VALUES (?, ?)")
+                .bind(Uuid::new_v4())
+                .bind(tag_name)
+                .execute(&mut *tx)
+                .await?;
+
+            let (hashtag_id,): (Uuid,) = sqlx::query_as("SELECT id FROM hashtags WHERE name = ?")
+                .bind(tag_name)
+                .fetch_one(&mut *tx)
+                .await?;
+
+            sqlx::query("INSERT INTO tweet_hashtags (tweet_id, hashtag_id) VALUES (?, ?)")
+                .bind(tweet.id)
+                .bind(hashtag_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        for user_id in mentions {
+            sqlx::query("INSERT INTO tweet_mentions (tweet_id, user_id) VALUES (?, ?)")
+                .bind(tweet.id)
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<Tweet>> {
+        let tweet = sqlx::query_as("SELECT * FROM tweets WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.db)
+            .await?;
+        Ok(tweet)
+    }
+
+    async fn find_many(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, Tweet>> {
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let query = format!("SELECT * FROM tweets WHERE id IN ({})", placeholders(ids));
+        let mut q = sqlx::query_as::<_, Tweet>(&query);
+        for id in ids {
+            q = q.bind(id);
+        }
+        let tweets = q.fetch_all(&self.db).await?;
+        Ok(tweets.into_iter().map(|t| (t.id, t)).collect())
+    }
+
+    async fn delete(&self, id: Uuid, user_id: Option<Uuid>) -> Result<bool> {
+        let mut tx = self.db.begin().await?;
+
+        // likes は ON DELETE CASCADE ではないため先に削除する
+        sqlx::query(
+            r#"
+            DELETE FROM likes WHERE tweet_id IN (
+                SELECT id FROM tweets WHERE id = ? AND (? IS NULL OR user_id = ?)
+            )
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        let result = sqlx::query("DELETE FROM tweets WHERE id = ? AND (? IS NULL OR user_id = ?)")
+            .bind(id)
+            .bind(user_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn timeline(&self, user_id: Uuid) -> Result<Vec<Tweet>> {
+        let query = format!(
+            r#"
+            SELECT t.* FROM tweets t
+            WHERE (t.user_id = ?
+               OR t.user_id IN (SELECT following_id FROM follows WHERE follower_id = ?))
+              AND t.user_id NOT IN ({})
+            ORDER BY t.created_at DESC
+            "#,
+            HIDDEN_USERS
+        );
+        let tweets = sqlx::query_as(&query)
+            .bind(user_id)
+            .bind(user_id)
+            .bind(user_id)
+            .bind(user_id)
+            .bind(user_id)
+            .fetch_all(&self.db)
+            .await?;
+        Ok(tweets)
+    }
+
+    async fn timeline_page(&self, user_id: Uuid, query: &PageQuery) -> Result<Page<TimelineEntry>> {
+        let (conditions, order) = keyset(query, "e.activity_at", "t.id", true);
+        let sql = format!(
+            r#"
+            WITH {}
+            SELECT t.*, e.activity_at, e.retweeted_by FROM latest e
+            JOIN tweets t ON t.id = e.tweet_id
+            WHERE e.n = 1{}
+            {}
+            "#,
+            TIMELINE_EVENTS, conditions, order
+        );
+
+        let mut q = sqlx::query_as::<_, TimelineRow>(&sql).bind(user_id);
+        for cursor in query.cursors() {
+            q = q.bind(&cursor.created_at).bind(cursor.id);
+        }
+        let rows = q.bind(query.fetch_limit()).fetch_all(&self.db).await?;
+
+        Ok(query.page(
+            rows.into_iter()
+                .map(|r| {
+                    let cursor = Cursor::new(&r.activity_at, r.tweet.id);
+                    let entry = TimelineEntry {
+                        tweet: r.tweet,
+                        retweeted_by: r.retweeted_by,
+                        activity_at: r.activity_at,
+                    };
+                    (cursor, entry)
+                })
+                .collect(),
+        ))
+    }
+
+    async fn timeline_count(&self, user_id: Uuid) -> Result<i64> {
+        let sql = format!(
+            "WITH {} SELECT COUNT(*) FROM latest WHERE n = 1",
+            TIMELINE_EVENTS
+        );
+        let (count,) = sqlx::query_as(&sql)
+            .bind(user_id)
+            .fetch_one(&self.db)
+            .await?;
+        Ok(count)
+    }
+
+    async fn quote_counts(&self, tweet_ids: &[Uuid]) -> Result<HashMap<Uuid, i64>> {
+        if tweet_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let query = format!(
+            r#"
+            SELECT quoted_tweet_id, COUNT(*) FROM tweets
+            WHERE quoted_tweet_id IN ({})
+            GROUP BY quoted_tweet_id
+            "#,
+            placeholders(tweet_ids)
+        );
+        let mut q = sqlx::query_as::<_, (Uuid, i64)>(&query);
+        for id in tweet_ids {
+            q = q.bind(id);
+        }
+        Ok(q.fetch_all(&self.db).await?.into_iter().collect())
+    }
+}
+
+#[async_trait]
+impl RetweetRepository for SqliteRepository {
+    async fn insert(&self, user_id: Uuid, tweet_id: Uuid, created_at: &str) -> Result<bool> {
+        let result = sqlx::query(
+            "INSERT OR IGNORE INTO retweets (user_id, tweet_id, created_at) VALUES (?, ?, ?)",
+        )
+        .bind(user_id)
+        .bind(tweet_id)
+        .bind(created_at)
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn delete(&self, user_id: Uuid, tweet_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM retweets WHERE tweet_id = ? AND user_id = ?")
+            .bind(tweet_id)
+            .bind(user_id)
+            .execute(&self.db)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn counts(&self, tweet_ids: &[Uuid]) -> Result<HashMap<Uuid, i64>> {
+        if tweet_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let query = format!(
+            "SELECT tweet_id, COUNT(*) FROM retweets WHERE tweet_id IN ({}) GROUP BY tweet_id",
+            placeholders(tweet_ids)
+        );
+        let mut q = sqlx::query_as::<_, (Uuid, i64)>(&query);
+        for id in tweet_ids {
+            q = q.bind(id);
+        }
+        Ok(q.fetch_all(&self.db).await?.into_iter().collect())
+    }
+
+    async fn retweeted_by(&self, user_id: Uuid, tweet_ids: &[Uuid]) -> Result<HashSet<Uuid>> {
+        if tweet_ids.is_empty() {
+            return Ok(HashSet::new());
+        }
+
+        let query = format!(
+            "SELECT tweet_id FROM retweets WHERE tweet_id IN ({}) AND user_id = ?",
+            placeholders(tweet_ids)
+        );
+        let mut q = sqlx::query_as::<_, (Uuid,)>(&query);
+        for id in tweet_ids {
+            q = q.bind(id);
+        }
+        q = q.bind(user_id);
+
+        let rows = q.fetch_all(&self.db).await?;
+        Ok(rows.into_iter().map(|(id,)| id).collect())
+    }
+}
+
+#[async_trait]
+impl LikeRepository for SqliteRepository {
+    async fn insert(&self, user_id: Uuid, tweet_id: Uuid, created_at: &str) -> Result<bool> {
+        let result = sqlx::query(
+            "INSERT OR IGNORE INTO likes (user_id, tweet_id, created_at) VALUES (?, ?, ?)",
+        )
+        .bind(user_id)
+        .bind(tweet_id)
+        .bind(created_at)
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn delete(&self, user_id: Uuid, tweet_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM likes WHERE tweet_id = ? AND user_id = ?")
+            .bind(tweet_id)
+            .bind(user_id)
+            .execute(&self.db)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn counts(&self, tweet_ids: &[Uuid]) -> Result<HashMap<Uuid, i64>> {
+        if tweet_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let query = format!(
+            "SELECT tweet_id, COUNT(*) as count FROM likes WHERE tweet_id IN ({}) GROUP BY tweet_id",
+            placeholders(tweet_ids)
+        );
+        let mut q = sqlx::query_as::<_, (Uuid, i64)>(&query);
+        for id in tweet_ids {
+            q = q.bind(id);
+        }
+        Ok(q.fetch_all(&self.db).await?.into_iter().collect())
+    }
+
+    async fn liked_by(&self, user_id: Uuid, tweet_ids: &[Uuid]) -> Result<HashSet<Uuid>> {
+        if tweet_ids.is_empty() {
+            return Ok(HashSet::new());
+        }
+
+        let query = format!(
+            "SELECT tweet_id FROM likes WHERE tweet_id IN ({}) AND user_id = ?",
+            placeholders(tweet_ids)
+        );
+        let mut q = sqlx::query_as::<_, (Uuid,)>(&query);
+        for id in tweet_ids {
+            q = q.bind(id);
+        }
+        q = q.bind(user_id);
+
+        let rows = q.fetch_all(&self.db).await?;
+        Ok(rows.into_iter().map(|(id,)| id).collect())
+    }
+}
+
+#[async_trait]
+impl FollowRepository for SqliteRepository {
+    async fn insert(
+        &self,
+        follower_id: Uuid,
+        following_id: Uuid,
+        created_at: &str,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            "INSERT OR IGNORE INTO follows (follower_id, following_id, created_at) VALUES (?, ?, ?)",
+        )
+        .bind(follower_id)
+        .bind(following_id)
+        .bind(created_at)
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn delete(&self, follower_id: Uuid, following_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM follows WHERE follower_id = ? AND following_id = ?")
+            .bind(follower_id)
+            .bind(following_id)
+            .execute(&self.db)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn counts(&self, user_ids: &[Uuid]) -> Result<HashMap<Uuid, FollowCounts>> {
+        if user_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let query = format!(
+            r#"
+            SELECT
+                u.id,
+                (SELECT COUNT(*) FROM follows WHERE following_id = u.id) as followers_count,
+                (SELECT COUNT(*) FROM follows WHERE follower_id = u.id) as following_count
+            FROM users u
+            WHERE u.id IN ({})
+            "#,
+            placeholders(user_ids)
+        );
+        let mut q = sqlx::query_as::<_, (Uuid, i64, i64)>(&query);
+        for id in user_ids {
+            q = q.bind(id);
+        }
+
+        let rows = q.fetch_all(&self.db).await?;
+        Ok(rows
+            .into_iter()
+            .map(|(id, followers, following)| {
+                (
+                    id,
+                    FollowCounts {
+                        followers,
+                        following,
+                    },
+                )
+            })
+            .collect())
+    }
+
+    async fn following_set(&self, follower_id: Uuid, target_ids: &[Uuid]) -> Result<HashSet<Uuid>> {
+        if target_ids.is_empty() {
+            return Ok(HashSet::new());
+        }
+
+        let query = format!(
+            "SELECT following_id FROM follows WHERE follower_id = ? AND following_id IN ({})",
+            placeholders(target_ids)
+        );
+        let mut q = sqlx::query_as::<_, (Uuid,)>(&query).bind(follower_id);
+        for id in target_ids {
+            q = q.bind(id);
+        }
+
+        let rows = q.fetch_all(&self.db).await?;
+        Ok(rows.into_iter().map(|(id,)| id).collect())
+    }
+
+    async fn followers(&self, user_id: Uuid, query: &PageQuery) -> Result<Page<User>> {
+        self.follow_page("f.follower_id", "f.following_id", user_id, query)
+            .await
+    }
+
+    async fn following(&self, user_id: Uuid, query: &PageQuery) -> Result<Page<User>> {
+        self.follow_page("f.following_id", "f.follower_id", user_id, query)
+            .await
+    }
+}
+
+impl SqliteRepository {
+    /// follows を user_column で users に結合し、filter_column = user_id の行をページングする
+    async fn follow_page(
+        &self,
+        user_column: &str,
+        filter_column: &str,
+        user_id: Uuid,
+        query: &PageQuery,
+    ) -> Result<Page<User>> {
+        let (conditions, order) = keyset(query, "f.created_at", "u.id", true);
+        let sql = format!(
+            r#"
+            SELECT u.*, f.created_at AS followed_at FROM users u
+            JOIN follows f ON u.id = {}
+            WHERE {} = ?{}
+            {}
+            "#,
+            user_column, filter_column, conditions, order
+        );
+
+        let mut q = sqlx::query_as::<_, FollowedUser>(&sql).bind(user_id);
+        for cursor in query.cursors() {
+            q = q.bind(&cursor.created_at).bind(cursor.id);
+        }
+        let rows = q.bind(query.fetch_limit()).fetch_all(&self.db).await?;
+
+        Ok(query.page(
+            rows.into_iter()
+                .map(|r| (Cursor::new(&r.followed_at, r.user.id), r.user))
+                .collect(),
+        ))
+    }
+}
+
+#[async_trait]
+impl BlockRepository for SqliteRepository {
+    async fn block(&self, blocker_id: Uuid, blocked_id: Uuid, created_at: &str) -> Result<bool> {
+        let mut tx = self.db.begin().await?;
+
+        let result = sqlx::query(
+            "INSERT OR IGNORE INTO blocks (blocker_id, blocked_id, created_at) VALUES (?, ?, ?)",
+        )
+        .bind(blocker_id)
+        .bind(blocked_id)
+        .bind(created_at)
+        .execute(&mut *tx)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        sqlx::query(
+            r#"
+            DELETE FROM follows
+            WHERE (follower_id = ? AND following_id = ?) OR (follower_id = ? AND following_id = ?)
+            "#,
+        )
+        .bind(blocker_id)
+        .bind(blocked_id)
+        .bind(blocked_id)
+        .bind(blocker_id)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            r#"
+            DELETE FROM notifications
+            WHERE (user_id = ? AND actor_id = ?) OR (user_id = ? AND actor_id = ?)
+            "#,
+        )
+        .bind(blocker_id)
+        .bind(blocked_id)
+        .bind(blocked_id)
+        .bind(blocker_id)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            r#"
+            DELETE FROM retweets
+            WHERE (user_id = ? AND tweet_id IN (SELECT id FROM tweets WHERE user_id = ?))
+                OR (user_id = ? AND tweet_id IN (SELECT id FROM tweets WHERE user_id = ?))
+            "#,
+        )
+        .bind(blocker_id)
+        .bind(blocked_id)
+        .bind(blocked_id)
+        .bind(blocker_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn unblock(&self, blocker_id: Uuid, blocked_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM blocks WHERE blocker_id = ? AND blocked_id = ?")
+            .bind(blocker_id)
+            .bind(blocked_id)
+            .execute(&self.db)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn mute(&self, muter_id: Uuid, muted_id: Uuid, created_at: &str) -> Result<bool> {
+        let mut tx = self.db.begin().await?;
+
+        let result = sqlx::query(
+            "INSERT OR IGNORE INTO mutes (muter_id, muted_id, created_at) VALUES (?, ?, ?)",
+        )
+        .bind(muter_id)
+        .bind(muted_id)
+        .bind(created_at)
+        .execute(&mut *tx)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        sqlx::query("DELETE FROM notifications WHERE user_id = ? AND actor_id = ?")
+            .bind(muter_id)
+            .bind(muted_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn unmute(&self, muter_id: Uuid, muted_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM mutes WHERE muter_id = ? AND muted_id = ?")
+            .bind(muter_id)
+            .bind(muted_id)
+            .execute(&self.db)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn is_blocked(&self, a: Uuid, b: Uuid) -> Result<bool> {
+        let (blocked,) = sqlx::query_as(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM blocks
+                WHERE (blocker_id = ? AND blocked_id = ?) OR (blocker_id = ? AND blocked_id = ?)
+            )
+            "#,
+        )
+        .bind(a)
+        .bind(b)
+        .bind(b)
+        .bind(a)
+        .fetch_one(&self.db)
+        .await?;
+        Ok(blocked)
+    }
+
+    async fn hidden_set(&self, viewer: Uuid, user_ids: &[Uuid]) -> Result<HashSet<Uuid>> {
+        if user_ids.is_empty() {
+            return Ok(HashSet::new());
+        }
+
+        let query = format!(
+            "SELECT id FROM users WHERE id IN ({}) AND id IN ({})",
+            placeholders(user_ids),
+            HIDDEN_USERS
+        );
+        let mut q = sqlx::query_as::<_, (Uuid,)>(&query);
+        for id in user_ids {
+            q = q.bind(id);
+        }
+
+        let rows = q
+            .bind(viewer)
+            .bind(viewer)
+            .bind(viewer)
+            .fetch_all(&self.db)
+            .await?;
+        Ok(rows.into_iter().map(|(id,)| id).collect())
+    }
+}
+
+#[async_trait]
+impl CommentRepository for SqliteRepository {
+    async fn insert(&self, comment: &Comment, mentions: &[Uuid]) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO comments (id, tweet_id, user_id, content, created_at, parent_id)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(comment.id)
+        .bind(comment.tweet_id)
+        .bind(comment.user_id)
+        .bind(&comment.content)
+        .bind(&comment.created_at)
+        .bind(comment.parent_id)
+        .execute(&mut *tx)
+        .await?;
+
+        for user_id in mentions {
+            sqlx::query("INSERT INTO comment_mentions (comment_id, user_id) VALUES (?, ?)")
+                .bind(comment.id)
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn list_for_tweet(
+        &self,
+        tweet_id: Uuid,
+        viewer: Option<Uuid>,
+        query: &PageQuery,
+    ) -> Result<Page<Comment>> {
+        let (conditions, order) = keyset(query, "created_at", "id", false);
+        let sql = format!(
+            "SELECT * FROM comments WHERE tweet_id = ? AND user_id NOT IN ({}){} {}",
+            HIDDEN_USERS, conditions, order
+        );
+
+        let mut q = sqlx::query_as::<_, Comment>(&sql)
+            .bind(tweet_id)
+            .bind(viewer)
+            .bind(viewer)
+            .bind(viewer);
+        for cursor in query.cursors() {
+            q = q.bind(&cursor.created_at).bind(cursor.id);
+        }
+        let comments = q.bind(query.fetch_limit()).fetch_all(&self.db).await?;
+
+        Ok(query.page(
+            comments
+                .into_iter()
+                .map(|c| (Cursor::new(&c.created_at, c.id), c))
+                .collect(),
+        ))
+    }
+
+    async fn count_for_tweet(&self, tweet_id: Uuid, viewer: Option<Uuid>) -> Result<i64> {
+        let query = format!(
+            "SELECT COUNT(*) FROM comments WHERE tweet_id = ? AND user_id NOT IN ({})",
+            HIDDEN_USERS
+        );
+        let (count,) = sqlx::query_as(&query)
+            .bind(tweet_id)
+            .bind(viewer)
+            .bind(viewer)
+            .bind(viewer)
+            .fetch_one(&self.db)
+            .await?;
+        Ok(count)
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<Comment>> {
+        let comment = sqlx::query_as("SELECT * FROM comments WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.db)
+            .await?;
+        Ok(comment)
+    }
+
+    async fn find_many(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, Comment>> {
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let query = format!("SELECT * FROM comments WHERE id IN ({})", placeholders(ids));
+        let mut q = sqlx::query_as::<_, Comment>(&query);
+        for id in ids {
+            q = q.bind(id);
+        }
+        let comments = q.fetch_all(&self.db).await?;
+        Ok(comments.into_iter().map(|c| (c.id, c)).collect())
+    }
+
+    async fn delete(&self, id: Uuid, user_id: Option<Uuid>) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            WITH RECURSIVE subtree (id) AS (
+                SELECT id FROM comments WHERE id = ? AND (? IS NULL OR user_id = ?)
+                UNION ALL
+                SELECT c.id FROM comments c JOIN subtree ON c.parent_id = subtree.id
+            )
+            DELETE FROM comments WHERE id IN (SELECT id FROM subtree)
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(user_id)
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn thread_page(
+        &self,
+        tweet_id: Uuid,
+        parent_id: Option<Uuid>,
+        max_depth: i64,
+        viewer: Option<Uuid>,
+        query: &PageQuery,
+    ) -> Result<Page<Comment>> {
+        let (conditions, order) = keyset(query, "c.created_at", "c.id", false);
+        let sql = format!(
+            "{} SELECT c.* FROM comments c JOIN thread ON thread.id = c.id WHERE 1 = 1{} {}",
+            THREAD, conditions, order
+        );
+
+        let mut q = sqlx::query_as::<_, Comment>(&sql)
+            .bind(viewer)
+            .bind(tweet_id)
+            .bind(parent_id)
+            .bind(max_depth);
+        for cursor in query.cursors() {
+            q = q.bind(&cursor.created_at).bind(cursor.id);
+        }
+        let comments = q.bind(query.fetch_limit()).fetch_all(&self.db).await?;
+
+        Ok(query.page(
+            comments
+                .into_iter()
+                .map(|c| (Cursor::new(&c.created_at, c.id), c))
+                .collect(),
+        ))
+    }
+
+    async fn thread_count(
+        &self,
+        tweet_id: Uuid,
+        parent_id: Option<Uuid>,
+        max_depth: i64,
+        viewer: Option<Uuid>,
+    ) -> Result<i64> {
+        let sql = format!("{} SELECT COUNT(*) FROM thread", THREAD);
+        let (count,) = sqlx::query_as(&sql)
+            .bind(viewer)
+            .bind(tweet_id)
+            .bind(parent_id)
+            .bind(max_depth)
+            .fetch_one(&self.db)
+            .await?;
+        Ok(count)
+    }
+
+    async fn ancestors(&self, id: Uuid) -> Result<Vec<Comment>> {
+        let comments = sqlx::query_as(
+            r#"
+            WITH RECURSIVE ancestors (id, parent_id, distance) AS (
+                SELECT id, parent_id, 0 FROM comments WHERE id = ?
+                UNION ALL
+                SELECT c.id, c.parent_id, a.distance + 1 FROM comments c
+                JOIN ancestors a ON c.id = a.parent_id
+            )
+            SELECT c.* FROM comments c JOIN ancestors a ON a.id = c.id
+            WHERE a.distance > 0
+            ORDER BY a.distance DESC
+            "#,
+        )
+        .bind(id)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(comments)
+    }
+
+    async fn reply_counts(
+        &self,
+        comment_ids: &[Uuid],
+        viewer: Option<Uuid>,
+    ) -> Result<HashMap<Uuid, i64>> {
+        if comment_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let query = format!(
+            r#"
+            SELECT parent_id, COUNT(*) FROM comments
+            WHERE parent_id IN ({}) AND user_id NOT IN ({})
+            GROUP BY parent_id
+            "#,
+            placeholders(comment_ids),
+            HIDDEN_USERS
+        );
+        let mut q = sqlx::query_as::<_, (Uuid, i64)>(&query);
+        for id in comment_ids {
+            q = q.bind(id);
+        }
+        q = q.bind(viewer).bind(viewer).bind(viewer);
+        Ok(q.fetch_all(&self.db).await?.into_iter().collect())
+    }
+
+    async fn top_level_counts(
+        &self,
+        tweet_ids: &[Uuid],
+        viewer: Option<Uuid>,
+    ) -> Result<HashMap<Uuid, i64>> {
+        if tweet_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let query = format!(
+            r#"
+            SELECT tweet_id, COUNT(*) FROM comments
+            WHERE tweet_id IN ({}) AND parent_id IS NULL AND user_id NOT IN ({})
+            GROUP BY tweet_id
+            "#,
+            placeholders(tweet_ids),
+            HIDDEN_USERS
+        );
+        let mut q = sqlx::query_as::<_, (Uuid, i64)>(&query);
+        for id in tweet_ids {
+            q = q.bind(id);
+        }
+        q = q.bind(viewer).bind(viewer).bind(viewer);
+        Ok(q.fetch_all(&self.db).await?.into_iter().collect())
+    }
+}
+
+#[async_trait]
+impl HashtagRepository for SqliteRepository {
+    async fn for_tweets(&self, tweet_ids: &[Uuid]) -> Result<HashMap<Uuid, Vec<String>>> {
+        if tweet_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let query = format!(
+            r#"
+            SELECT th.tweet_id, h.name
+            FROM tweet_hashtags th
+            JOIN hashtags h ON th.hashtag_id = h.id
+            WHERE th.tweet_id IN ({})
+            "#,
+            placeholders(tweet_ids)
+        );
+        let mut q = sqlx::query_as::<_, (Uuid, String)>(&query);
+        for id in tweet_ids {
+            q = q.bind(id);
+        }
+
+        // ツイートIDごとにハッシュタグをグループ化
+        let mut hashtag_map: HashMap<Uuid, Vec<String>> = HashMap::new();
+        for (tweet_id, tag_name) in q.fetch_all(&self.db).await? {
+            hashtag_map.entry(tweet_id).or_default().push(tag_name);
+        }
+        Ok(hashtag_map)
+    }
+
+    async fn stats(&self, name: &str) -> Result<Option<HashtagStats>> {
+        let (tweet_count, first_used_at, last_used_at): (i64, Option<String>, Option<String>) =
+            sqlx::query_as(
+                r#"
+                SELECT COUNT(*), MIN(t.created_at), MAX(t.created_at)
+                FROM tweets t
+                JOIN tweet_hashtags th ON th.tweet_id = t.id
+                JOIN hashtags h ON th.hashtag_id = h.id
+                WHERE h.name = ?
+                "#,
+            )
+            .bind(name)
+            .fetch_one(&self.db)
+            .await?;
+
+        Ok(first_used_at
+            .zip(last_used_at)
+            .map(|(first_used_at, last_used_at)| HashtagStats {
+                tweet_count,
+                first_used_at,
+                last_used_at,
+            }))
+    }
+
+    async fn tweets_page(&self, name: &str, query: &PageQuery) -> Result<Page<Tweet>> {
+        let (conditions, order) = keyset(query, "t.created_at", "t.id", true);
+        let sql = format!(
+            r#"
+            SELECT t.* FROM tweets t
+            WHERE t.id IN (
+                SELECT th.tweet_id FROM tweet_hashtags th
+                JOIN hashtags h ON th.hashtag_id = h.id
+                WHERE h.name = ?
+            ){}
+            {}
+            "#,
+            conditions, order
+        );
+
+        let mut q = sqlx::query_as::<_, Tweet>(&sql).bind(name);
+        for cursor in query.cursors() {
+            q = q.bind(&cursor.created_at).bind(cursor.id);
+        }
+        let tweets = q.bind(query.fetch_limit()).fetch_all(&self.db).await?;
+
+        Ok(query.page(
+            tweets
+                .into_iter()
+                .map(|t| (Cursor::new(&t.created_at, t.id), t))
+                .collect(),
+        ))
+    }
+
+    async fn counts_between(&self, since: &str, until: &str) -> Result<HashMap<String, i64>> {
+        let rows: Vec<(String, i64)> = sqlx::query_as(
+            r#"
+            SELECT h.name, COUNT(*)
+            FROM tweets t
+            JOIN tweet_hashtags th ON th.tweet_id = t.id
+            JOIN hashtags h ON th.hashtag_id = h.id
+            WHERE t.created_at >= ? AND t.created_at < ?
+            GROUP BY h.name
+            "#,
+        )
+        .bind(since)
+        .bind(until)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(rows.into_iter().collect())
+    }
+}
+
+#[async_trait]
+impl MentionRepository for SqliteRepository {
+    async fn for_tweets(&self, tweet_ids: &[Uuid]) -> Result<HashMap<Uuid, Vec<Uuid>>> {
+        self.mentions("tweet_mentions", "tweet_id", tweet_ids).await
+    }
+
+    async fn for_comments(&self, comment_ids: &[Uuid]) -> Result<HashMap<Uuid, Vec<Uuid>>> {
+        self.mentions("comment_mentions", "comment_id", comment_ids)
+            .await
+    }
+
+    async fn tweets_page(&self, user_id: Uuid, query: &PageQuery) -> Result<Page<Tweet>> {
+        let (conditions, order) = keyset(query, "t.created_at", "t.id", true);
+        let sql = format!(
+            r#"
+            SELECT t.* FROM tweets t
+            JOIN tweet_mentions m ON m.tweet_id = t.id
+            WHERE m.user_id = ?{}
+            {}
+            "#,
+            conditions, order
+        );
+
+        let mut q = sqlx::query_as::<_, Tweet>(&sql).bind(user_id);
+        for cursor in query.cursors() {
+            q = q.bind(&cursor.created_at).bind(cursor.id);
+        }
+        let tweets = q.bind(query.fetch_limit()).fetch_all(&self.db).await?;
+
+        Ok(query.page(
+            tweets
+                .into_iter()
+                .map(|t| (Cursor::new(&t.created_at, t.id), t))
+                .collect(),
+        ))
+    }
+
+    async fn tweet_count(&self, user_id: Uuid) -> Result<i64> {
+        let (count,) = sqlx::query_as("SELECT COUNT(*) FROM tweet_mentions WHERE user_id = ?")
+            .bind(user_id)
+            .fetch_one(&self.db)
+            .await?;
+        Ok(count)
+    }
+}
+
+impl SqliteRepository {
+    /// table の key 列ごとの言及されたユーザーID
+    async fn mentions(
+        &self,
+        table: &str,
+        key: &str,
+        ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, Vec<Uuid>>> {
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let query = format!(
+            "SELECT {0}, user_id FROM {1} WHERE {0} IN ({2})",
+            key,
+            table,
+            placeholders(ids)
+        );
+        let mut q = sqlx::query_as::<_, (Uuid, Uuid)>(&query);
+        for id in ids {
+            q = q.bind(id);
+        }
+
+        let mut mention_map: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+        for (id, user_id) in q.fetch_all(&self.db).await? {
+            mention_map.entry(id).or_default().push(user_id);
+        }
+        Ok(mention_map)
+    }
+}
+
+#[async_trait]
+impl SessionRepository for SqliteRepository {
+    async fn insert(&self, session: &Session) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO sessions (id, user_id, user_agent, ip_address, created_at, last_seen_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(session.id)
+        .bind(session.user_id)
+        .bind(&session.user_agent)
+        .bind(&session.ip_address)
+        .bind(&session.created_at)
+        .bind(&session.last_seen_at)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    async fn find(&self, id: Uuid, user_id: Uuid) -> Result<Option<Session>> {
+        let session = sqlx::query_as("SELECT * FROM sessions WHERE id = ? AND user_id = ?")
+            .bind(id)
+            .bind(user_id)
+            .fetch_optional(&self.db)
+            .await?;
+        Ok(session)
+    }
+
+    async fn touch(&self, id: Uuid, at: &str) -> Result<()> {
+        sqlx::query("UPDATE sessions SET last_seen_at = ? WHERE id = ?")
+            .bind(at)
+            .bind(id)
+            .execute(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    async fn list_active(&self, user_id: Uuid) -> Result<Vec<Session>> {
+        let sessions = sqlx::query_as(
+            r#"
+            SELECT * FROM sessions
+            WHERE user_id = ? AND revoked_at IS NULL
+            ORDER BY last_seen_at DESC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(sessions)
+    }
+
+    async fn revoke(&self, id: Uuid, user_id: Option<Uuid>, at: &str) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE sessions SET revoked_at = ?
+            WHERE id = ? AND (? IS NULL OR user_id = ?) AND revoked_at IS NULL
+            "#,
+        )
+        .bind(at)
+        .bind(id)
+        .bind(user_id)
+        .bind(user_id)
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn revoke_others(&self, user_id: Uuid, except: Uuid, at: &str) -> Result<u64> {
+        let result = sqlx::query(
+            "UPDATE sessions SET revoked_at = ? WHERE user_id = ? AND id != ? AND revoked_at IS NULL",
+        )
+        .bind(at)
+        .bind(user_id)
+        .bind(except)
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+#[async_trait]
+impl RefreshTokenRepository for SqliteRepository {
+    async fn insert(&self, token: &RefreshToken) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO refresh_tokens (id, user_id, family_id, token_hash, created_at, expires_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(token.id)
+        .bind(token.user_id)
+        .bind(token.family_id)
+        .bind(&token.token_hash)
+        .bind(&token.created_at)
+        .bind(&token.expires_at)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    async fn find_by_hash(&self, token_hash: &str) -> Result<Option<RefreshToken>> {
+        let token = sqlx::query_as("SELECT * FROM refresh_tokens WHERE token_hash = ?")
+            .bind(token_hash)
+            .fetch_optional(&self.db)
+            .await?;
+        Ok(token)
+    }
+
+    async fn revoke_family(&self, family_id: Uuid, at: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE refresh_tokens SET revoked_at = ? WHERE family_id = ? AND revoked_at IS NULL",
+        )
+        .bind(at)
+        .bind(family_id)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    async fn revoke_other_families(&self, user_id: Uuid, except: Uuid, at: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE refresh_tokens SET revoked_at = ?
+            WHERE user_id = ? AND family_id != ? AND revoked_at IS NULL
+            "#,
+        )
+        .bind(at)
+        .bind(user_id)
+        .bind(except)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    async fn mark_replaced(&self, id: Uuid, replaced_by: Uuid, at: &str) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE refresh_tokens SET revoked_at = ?, replaced_by = ?
+            WHERE id = ? AND revoked_at IS NULL
+            "#,
+        )
+        .bind(at)
+        .bind(replaced_by)
+        .bind(id)
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[async_trait]
+impl ReportRepository for SqliteRepository {
+    async fn insert(&self, report: &Report) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO reports (id, reporter_id, target_type, target_id, reason, created_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(report.id)
+        .bind(report.reporter_id)
+        .bind(report.target_type.as_str())
+        .bind(report.target_id)
+        .bind(&report.reason)
+        .bind(&report.created_at)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    async fn list_open(&self) -> Result<Vec<Report>> {
+        let reports = sqlx::query_as(
+            "SELECT * FROM reports WHERE resolved_at IS NULL ORDER BY created_at ASC, id ASC",
+        )
+        .fetch_all(&self.db)
+        .await?;
+        Ok(reports)
+    }
+
+    async fn resolve(&self, id: Uuid, resolved_by: Uuid, at: &str) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE reports SET resolved_at = ?, resolved_by = ? WHERE id = ? AND resolved_at IS NULL",
+        )
+        .bind(at)
+        .bind(resolved_by)
+        .bind(id)
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[async_trait]
+impl ModerationLogRepository for SqliteRepository {
+    async fn insert(&self, action: &ModerationAction) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO moderation_actions (id, actor_id, action, target_id, details, created_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(action.id)
+        .bind(action.actor_id)
+        .bind(action.action.as_str())
+        .bind(action.target_id)
+        .bind(&action.details)
+        .bind(&action.created_at)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    async fn recent(&self, limit: i64) -> Result<Vec<ModerationAction>> {
+        let actions = sqlx::query_as(
+            "SELECT * FROM moderation_actions ORDER BY created_at DESC, id DESC LIMIT ?",
+        )
+        .bind(limit)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(actions)
+    }
+}
+
+#[async_trait]
+impl NotificationRepository for SqliteRepository {
+    async fn upsert(&self, notification: &Notification) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO notifications
+                (id, user_id, kind, actor_id, group_key, tweet_id, comment_id, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT (user_id, group_key, actor_id) DO UPDATE SET
+                tweet_id = excluded.tweet_id,
+                comment_id = excluded.comment_id,
+                created_at = excluded.created_at,
+                read_at = NULL
+            "#,
+        )
+        .bind(notification.id)
+        .bind(notification.user_id)
+        .bind(notification.kind.as_str())
+        .bind(notification.actor_id)
+        .bind(&notification.group_key)
+        .bind(notification.tweet_id)
+        .bind(notification.comment_id)
+        .bind(&notification.created_at)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    async fn groups_page(
+        &self,
+        user_id: Uuid,
+        query: &PageQuery,
+    ) -> Result<Page<NotificationGroup>> {
+        let (conditions, order) = keyset(query, "created_at", "id", true);
+        let sql = format!(
+            r#"
+            WITH ranked AS (
+                SELECT *,
+                    ROW_NUMBER() OVER (
+                        PARTITION BY group_key ORDER BY created_at DESC, id DESC
+                    ) AS n_rank,
+                    COUNT(*) OVER (PARTITION BY group_key) AS actor_count,
+                    MAX(read_at IS NULL) OVER (PARTITION BY group_key) AS unread
+                FROM notifications WHERE user_id = ?
+            )
+            SELECT * FROM ranked WHERE n_rank = 1{}
+            {}
+            "#,
+            conditions, order
+        );
+
+        let mut q = sqlx::query_as::<_, NotificationGroupRow>(&sql).bind(user_id);
+        for cursor in query.cursors() {
+            q = q.bind(&cursor.created_at).bind(cursor.id);
+        }
+        let rows = q.bind(query.fetch_limit()).fetch_all(&self.db).await?;
+
+        Ok(query.page(
+            rows.into_iter()
+                .map(|r| {
+                    let group = NotificationGroup::from(r);
+                    (
+                        Cursor::new(&group.latest.created_at, group.latest.id),
+                        group,
+                    )
+                })
+                .collect(),
+        ))
+    }
+
+    async fn group_count(&self, user_id: Uuid) -> Result<i64> {
+        let (count,) =
+            sqlx::query_as("SELECT COUNT(DISTINCT group_key) FROM notifications WHERE user_id = ?")
+                .bind(user_id)
+                .fetch_one(&self.db)
+                .await?;
+        Ok(count)
+    }
+
+    async fn unread_count(&self, user_id: Uuid) -> Result<i64> {
+        let (count,) = sqlx::query_as(
+            r#"
+            SELECT COUNT(DISTINCT group_key) FROM notifications
+            WHERE user_id = ? AND read_at IS NULL
+            "#,
+        )
+        .bind(user_id)
+        .fetch_one(&self.db)
+        .await?;
+        Ok(count)
+    }
+
+    async fn recent_actors(
+        &self,
+        user_id: Uuid,
+        group_keys: &[String],
+        limit: i64,
+    ) -> Result<HashMap<String, Vec<Uuid>>> {
+        if group_keys.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let query = format!(
+            r#"
+            SELECT group_key, actor_id FROM (
+                SELECT group_key, actor_id,
+                    ROW_NUMBER() OVER (
+                        PARTITION BY group_key ORDER BY created_at DESC, id DESC
+                    ) AS n_rank
+                FROM notifications WHERE user_id = ? AND group_key IN ({})
+            )
+            WHERE n_rank <= ?
+            ORDER BY group_key, n_rank
+            "#,
+            group_keys.iter().map(|_| "?").collect::<Vec<_>>().join(",")
+        );
+        let mut q = sqlx::query_as::<_, (String, Uuid)>(&query).bind(user_id);
+        for key in group_keys {
+            q = q.bind(key);
+        }
+
+        let mut actors: HashMap<String, Vec<Uuid>> = HashMap::new();
+        for (key, actor_id) in q.bind(limit).fetch_all(&self.db).await? {
+            actors.entry(key).or_default().push(actor_id);
+        }
+        Ok(actors)
+    }
+
+    async fn mark_read(&self, user_id: Uuid, at: &str) -> Result<u64> {
+        let result = sqlx::query(
+            "UPDATE notifications SET read_at = ? WHERE user_id = ? AND read_at IS NULL",
+        )
+        .bind(at)
+        .bind(user_id)
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn disabled_kinds(&self, user_id: Uuid) -> Result<Vec<NotificationKind>> {
+        let kinds: Vec<(String,)> =
+            sqlx::query_as("SELECT kind FROM disabled_notification_kinds WHERE user_id = ?")
+                .bind(user_id)
+                .fetch_all(&self.db)
+                .await?;
+        // 不明な種類は無視する
+        Ok(kinds
+            .into_iter()
+            .filter_map(|(kind,)| NotificationKind::try_from(kind).ok())
+            .collect())
+    }
+
+    async fn set_kind_enabled(
+        &self,
+        user_id: Uuid,
+        kind: NotificationKind,
+        enabled: bool,
+    ) -> Result<()> {
+        let sql = if enabled {
+            "DELETE FROM disabled_notification_kinds WHERE user_id = ? AND kind = ?"
+        } else {
+            r#"
+            INSERT INTO disabled_notification_kinds (user_id, kind) VALUES (?, ?)
+            ON CONFLICT (user_id, kind) DO NOTHING
+            "#
+        };
+        sqlx::query(sql)
+            .bind(user_id)
+            .bind(kind.as_str())
+            .execute(&self.db)
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl DirectMessageRepository for SqliteRepository {
+    async fn create_conversation(
+        &self,
+        conversation: &DirectConversation,
+        member_ids: &[Uuid],
+    ) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+
+        sqlx::query(
+            "INSERT INTO direct_conversations (id, created_at, last_message_at) VALUES (?, ?, ?)",
+        )
+        .bind(conversation.id)
+        .bind(&conversation.created_at)
+        .bind(&conversation.last_message_at)
+        .execute(&mut *tx)
+        .await?;
+
+        for user_id in member_ids {
+            sqlx::query(
+                "INSERT INTO direct_conversation_members (conversation_id, user_id) VALUES (?, ?)",
+            )
+            .bind(conversation.id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn find_by_members(&self, member_ids: &[Uuid]) -> Result<Option<DirectConversation>> {
+        if member_ids.is_empty() {
+            return Ok(None);
+        }
+
+        let query = format!(
+            r#"
+            SELECT * FROM direct_conversations WHERE id IN (
+                SELECT conversation_id FROM direct_conversation_members
+                GROUP BY conversation_id
+                HAVING COUNT(*) = ? AND SUM(CASE WHEN user_id IN ({}) THEN 1 ELSE 0 END) = ?
+            )
+            LIMIT 1
+            "#,
+            placeholders(member_ids)
+        );
+        let mut q = sqlx::query_as::<_, DirectConversation>(&query).bind(member_ids.len() as i64);
+        for id in member_ids {
+            q = q.bind(id);
+        }
+        let conversation = q
+            .bind(member_ids.len() as i64)
+            .fetch_optional(&self.db)
+            .await?;
+        Ok(conversation)
+    }
+
+    async fn members(
+        &self,
+        conversation_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, Vec<DirectConversationMember>>> {
+        if conversation_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let query = format!(
+            "SELECT * FROM direct_conversation_members WHERE conversation_id IN ({})",
+            placeholders(conversation_ids)
+        );
+        let mut q = sqlx::query_as::<_, DirectConversationMember>(&query);
+        for id in conversation_ids {
+            q = q.bind(id);
+        }
+
+        let mut members: HashMap<Uuid, Vec<DirectConversationMember>> = HashMap::new();
+        for member in q.fetch_all(&self.db).await? {
+            members
+                .entry(member.conversation_id)
+                .or_default()
+                .push(member);
+        }
+        Ok(members)
+    }
+
+    async fn conversations_page(
+        &self,
+        user_id: Uuid,
+        query: &PageQuery,
+    ) -> Result<Page<DirectConversation>> {
+        let (conditions, order) = keyset(query, "c.last_message_at", "c.id", true);
+        let sql = format!(
+            r#"
+            SELECT c.* FROM direct_conversations c
+            JOIN direct_conversation_members m ON m.conversation_id = c.id
+            WHERE m.user_id = ?{}
+            {}
+            "#,
+            conditions, order
+        );
+
+        let mut q = sqlx::query_as::<_, DirectConversation>(&sql).bind(user_id);
+        for cursor in query.cursors() {
+            q = q.bind(&cursor.created_at).bind(cursor.id);
+        }
+        let conversations = q.bind(query.fetch_limit()).fetch_all(&self.db).await?;
+
+        Ok(query.page(
+            conversations
+                .into_iter()
+                .map(|c| (Cursor::new(&c.last_message_at, c.id), c))
+                .collect(),
+        ))
+    }
+
+    async fn conversation_count(&self, user_id: Uuid) -> Result<i64> {
+        let (count,) =
+            sqlx::query_as("SELECT COUNT(*) FROM direct_conversation_members WHERE user_id = ?")
+                .bind(user_id)
+                .fetch_one(&self.db)
+                .await?;
+        Ok(count)
+    }
+
+    async fn insert_message(&self, message: &DirectMessage) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO direct_messages (id, conversation_id, sender_id, content, created_at)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(message.id)
+        .bind(message.conversation_id)
+        .bind(message.sender_id)
+        .bind(&message.content)
+        .bind(&message.created_at)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("UPDATE direct_conversations SET last_message_at = ? WHERE id = ?")
+            .bind(&message.created_at)
+            .bind(message.conversation_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(
+            r#"
+            UPDATE direct_conversation_members SET last_read_at = ?
+            WHERE conversation_id = ? AND user_id = ?
+            "#,
+        )
+        .bind(&message.created_at)
+        .bind(message.conversation_id)
+        .bind(message.sender_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn messages_page(
+        &self,
+        conversation_id: Uuid,
+        query: &PageQuery,
+    ) -> Result<Page<DirectMessage>> {
+        let (conditions, order) = keyset(query, "created_at", "id", true);
+        let sql = format!(
+            "SELECT * FROM direct_messages WHERE conversation_id = ?{} {}",
+            conditions, order
+        );
+
+        let mut q = sqlx::query_as::<_, DirectMessage>(&sql).bind(conversation_id);
+        for cursor in query.cursors() {
+            q = q.bind(&cursor.created_at).bind(cursor.id);
+        }
+        let messages = q.bind(query.fetch_limit()).fetch_all(&self.db).await?;
+
+        Ok(query.page(
+            messages
+                .into_iter()
+                .map(|m| (Cursor::new(&m.created_at, m.id), m))
+                .collect(),
+        ))
+    }
+
+    async fn message_count(&self, conversation_id: Uuid) -> Result<i64> {
+        let (count,) =
+            sqlx::query_as("SELECT COUNT(*) FROM direct_messages WHERE conversation_id = ?")
+                .bind(conversation_id)
+                .fetch_one(&self.db)
+                .await?;
+        Ok(count)
+    }
+
+    async fn last_messages(
+        &self,
+        conversation_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, DirectMessage>> {
+        if conversation_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let query = format!(
+            r#"
+            SELECT * FROM (
+                SELECT *,
+                    ROW_NUMBER() OVER (
+                        PARTITION BY conversation_id ORDER BY created_at DESC, id DESC
+                    ) AS n_rank
+                FROM direct_messages WHERE conversation_id IN ({})
+            )
+            WHERE n_rank = 1
+            "#,
+            placeholders(conversation_ids)
+        );
+        let mut q = sqlx::query_as::<_, DirectMessage>(&query);
+        for id in conversation_ids {
+            q = q.bind(id);
+        }
+        let messages = q.fetch_all(&self.db).await?;
+        Ok(messages
+            .into_iter()
+            .map(|m| (m.conversation_id, m))
+            .collect())
+    }
+
+    async fn unread_counts(
+        &self,
+        user_id: Uuid,
+        conversation_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, i64>> {
+        if conversation_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let query = format!(
+            r#"
+            SELECT d.conversation_id, COUNT(*) FROM direct_messages d
+            JOIN direct_conversation_members m
+                ON m.conversation_id = d.conversation_id AND m.user_id = ?
+            WHERE d.conversation_id IN ({})
+                AND d.sender_id <> m.user_id
+                AND (m.last_read_at IS NULL OR d.created_at > m.last_read_at)
+            GROUP BY d.conversation_id
+            "#,
+            placeholders(conversation_ids)
+        );
+        let mut q = sqlx::query_as::<_, (Uuid, i64)>(&query).bind(user_id);
+        for id in conversation_ids {
+            q = q.bind(id);
+        }
+        let counts = q.fetch_all(&self.db).await?;
+        Ok(counts.into_iter().collect())
+    }
+
+    async fn mark_read(&self, conversation_id: Uuid, user_id: Uuid, at: &str) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE direct_conversation_members SET last_read_at = ?
+            WHERE conversation_id = ? AND user_id = ?
+            "#,
+        )
+        .bind(at)
+        .bind(conversation_id)
+        .bind(user_id)
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[async_trait]
+impl SearchRepository for SqliteRepository {
+    async fn search_tweets(&self, filter: &SearchFilter, query: &PageQuery) -> Result<Page<Tweet>> {
+        let mut args = Vec::new();
+        let mut conditions =
+            term_conditions(&filter.terms, "t.id", "tweets_fts", "t.content", &mut args);
+        conditions += &filter_conditions(filter, "t", &mut args);
+        for tag in &filter.hashtags {
+            conditions += r#"
+                AND t.id IN (
+                    SELECT th.tweet_id FROM tweet_hashtags th
+                    JOIN hashtags h ON h.id = th.hashtag_id
+                    WHERE h.name = ?
+                )"#;
+            args.push(SearchArg::Text(tag.clone()));
+        }
+
+        let (page_conditions, order) = keyset(query, "t.created_at", "t.id", true);
+        let sql = format!(
+            "SELECT t.* FROM tweets t WHERE 1 = 1{}{} {}",
+            conditions, page_conditions, order
+        );
+        let tweets: Vec<Tweet> = search_page(&self.db, &sql, &args, query).await?;
+
+        Ok(query.page(
+            tweets
+                .into_iter()
+                .map(|t| (Cursor::new(&t.created_at, t.id), t))
+                .collect(),
+        ))
+    }
+
+    async fn search_comments(
+        &self,
+        filter: &SearchFilter,
+        query: &PageQuery,
+    ) -> Result<Page<Comment>> {
+        let mut args = Vec::new();
+        let mut conditions = term_conditions(
+            &filter.terms,
+            "c.id",
+            "comments_fts",
+            "c.content",
+            &mut args,
+        );
+        conditions += &filter_conditions(filter, "c", &mut args);
+
+        let (page_conditions, order) = keyset(query, "c.created_at", "c.id", true);
+        let sql = format!(
+            "SELECT c.* FROM comments c WHERE 1 = 1{}{} {}",
+            conditions, page_conditions, order
+        );
+        let comments: Vec<Comment> = search_page(&self.db, &sql, &args, query).await?;
+
+        Ok(query.page(
+            comments
+                .into_iter()
+                .map(|c| (Cursor::new(&c.created_at, c.id), c))
+                .collect(),
+        ))
+    }
+
+    async fn search_users(&self, terms: &[String], query: &PageQuery) -> Result<Page<User>> {
+        let mut args = Vec::new();
+        let conditions = term_conditions(terms, "u.id", "users_fts", "u.username", &mut args);
+
+        let (page_conditions, order) = keyset(query, "u.created_at", "u.id", true);
+        let sql = format!(
+            "SELECT u.* FROM users u WHERE 1 = 1{}{} {}",
+            conditions, page_conditions, order
+        );
+        let users: Vec<User> = search_page(&self.db, &sql, &args, query).await?;
+
+        Ok(query.page(
+            users
+                .into_iter()
+                .map(|u| (Cursor::new(&u.created_at, u.id), u))
+                .collect(),
+        ))
+    }
+}
@@ -0,0 +1,72 @@
+//! ロールと権限
+//!
+//! ロールはアクセストークンのクレームに埋め込み、`RoleGuard` で REST と GraphQL の両方から確認する
+
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::models::Role;
+
+/// 一般ユーザーにはない権限
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    /// 他のユーザーのツイート・コメントを削除する
+    DeleteAnyContent,
+    /// ユーザーを利用停止にする・解除する
+    SuspendUsers,
+    /// 通報を閲覧・対応する
+    ViewReports,
+    /// ユーザーのロールを変更する
+    ManageRoles,
+    /// モデレーションの監査ログを閲覧する
+    ViewAuditLog,
+    /// 他のユーザーの email などの非公開フィールドを閲覧する
+    ViewPrivateFields,
+}
+
+impl Role {
+    pub fn permissions(self) -> &'static [Permission] {
+        use Permission::*;
+        match self {
+            Role::User => &[],
+            Role::Moderator => &[DeleteAnyContent, SuspendUsers, ViewReports],
+            Role::Admin => &[
+                DeleteAnyContent,
+                SuspendUsers,
+                ViewReports,
+                ManageRoles,
+                ViewAuditLog,
+                ViewPrivateFields,
+            ],
+        }
+    }
+
+    pub fn can(self, permission: Permission) -> bool {
+        self.permissions().contains(&permission)
+    }
+}
+
+/// 操作するユーザー（アクセストークンのユーザーIDとロール）
+#[derive(Debug, Clone, Copy)]
+pub struct Actor {
+    pub user_id: Uuid,
+    pub role: Role,
+}
+
+/// 権限を持つロールのみ（持たなければ FORBIDDEN）
+pub struct RoleGuard {
+    permission: Permission,
+}
+
+impl RoleGuard {
+    pub fn new(permission: Permission) -> Self {
+        Self { permission }
+    }
+
+    pub fn check(&self, role: Role) -> Result<(), AppError> {
+        if role.can(self.permission) {
+            return Ok(());
+        }
+        Err(AppError::Forbidden("Insufficient permissions".to_string()))
+    }
+}
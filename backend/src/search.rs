@@ -0,0 +1,237 @@
+//! 検索クエリの解析とハイライト
+//!
+//! クエリは空白区切りの語（すべてを含むもの）で、`"..."` で空白を含む語を指定する。
+//! `from:username`・`#hashtag`・`since:YYYY-MM-DD`・`until:YYYY-MM-DD` で絞り込める
+
+use chrono::{Days, NaiveDate};
+
+use crate::error::AppError;
+use crate::models::{Comment, Tweet, User};
+
+/// クエリの最大文字数
+const MAX_QUERY_LENGTH: usize = 200;
+/// スニペットの最大文字数（本文がこれより長ければ最初の一致の前後を切り出す）
+const SNIPPET_LENGTH: usize = 120;
+/// スニペットで最初の一致より前に残す文字数
+const SNIPPET_LEADING: usize = 30;
+
+/// 検索対象
+#[derive(Debug, Clone, Copy, PartialEq, Eq, async_graphql::Enum)]
+pub enum SearchType {
+    Tweets,
+    Comments,
+    Users,
+}
+
+/// 解析済みの検索クエリ
+#[derive(Debug, Default, PartialEq)]
+pub struct SearchQuery {
+    pub terms: Vec<String>,
+    /// 投稿者のユーザー名
+    pub from: Option<String>,
+    /// 小文字に正規化したハッシュタグ（# なし）
+    pub hashtags: Vec<String>,
+    /// この日時以降（RFC 3339）
+    pub since: Option<String>,
+    /// この日時より前（RFC 3339、until の翌日の0時）
+    pub until: Option<String>,
+}
+
+impl SearchQuery {
+    pub fn parse(input: &str) -> Result<Self, AppError> {
+        if input.chars().count() > MAX_QUERY_LENGTH {
+            return Err(AppError::validation(
+                "query",
+                format!(
+                    "Search query must be at most {} characters",
+                    MAX_QUERY_LENGTH
+                ),
+            ));
+        }
+
+        let mut query = SearchQuery::default();
+        for (token, quoted) in tokenize(input) {
+            if quoted {
+                query.terms.push(token);
+                continue;
+            }
+
+            if let Some(username) = token.strip_prefix("from:").filter(|s| !s.is_empty()) {
+                query.from = Some(username.to_string());
+            } else if let Some(tag) = token.strip_prefix('#').filter(|s| !s.is_empty()) {
+                query.hashtags.push(tag.to_lowercase());
+            } else if let Some(date) = token.strip_prefix("since:") {
+                query.since = Some(start_of_day(parse_date(date)?));
+            } else if let Some(date) = token.strip_prefix("until:") {
+                let next_day = parse_date(date)?
+                    .checked_add_days(Days::new(1))
+                    .ok_or_else(|| invalid_date(date))?;
+                query.until = Some(start_of_day(next_day));
+            } else {
+                query.terms.push(token);
+            }
+        }
+
+        if query.terms.is_empty() && !query.has_filters() {
+            return Err(AppError::validation(
+                "query",
+                "Search query must not be empty",
+            ));
+        }
+        Ok(query)
+    }
+
+    /// 語以外の絞り込みがあるか
+    pub fn has_filters(&self) -> bool {
+        self.from.is_some()
+            || !self.hashtags.is_empty()
+            || self.since.is_some()
+            || self.until.is_some()
+    }
+}
+
+/// 空白で区切り、`"..."` は1つの語にする（(語, 引用符付きか) の組）
+fn tokenize(input: &str) -> Vec<(String, bool)> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut was_quoted = false;
+
+    for c in input.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                was_quoted = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push((std::mem::take(&mut current), was_quoted));
+                }
+                was_quoted = false;
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push((current, was_quoted));
+    }
+    tokens
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate, AppError> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| invalid_date(value))
+}
+
+fn invalid_date(value: &str) -> AppError {
+    AppError::validation(
+        "query",
+        format!("Invalid date (expected YYYY-MM-DD): {}", value),
+    )
+}
+
+/// 日付の0時（UTC）を作成日時と比較できる RFC 3339 にする
+fn start_of_day(date: NaiveDate) -> String {
+    date.and_hms_opt(0, 0, 0)
+        .expect("midnight is a valid time")
+        .and_utc()
+        .to_rfc3339()
+}
+
+/// 検索結果の要素
+pub enum SearchItem {
+    Tweet(Tweet),
+    Comment(Comment),
+    User(User),
+}
+
+/// 検索結果とハイライト付きのスニペット
+pub struct SearchHit {
+    pub item: SearchItem,
+    pub snippet: Vec<Fragment>,
+}
+
+/// スニペットの断片（highlighted は検索語に一致した部分）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fragment {
+    pub text: String,
+    pub highlighted: bool,
+}
+
+/// 本文から検索語の周辺を切り出し、一致した部分を分けた断片にする（大文字小文字は区別しない）
+pub fn snippet(text: &str, terms: &[String]) -> Vec<Fragment> {
+    let chars: Vec<char> = text.chars().collect();
+    let matches = find_matches(&chars, terms);
+
+    // 長い本文は最初の一致の少し前から切り出す
+    let start = match matches.first() {
+        Some(&(first, _)) if chars.len() > SNIPPET_LENGTH => first
+            .saturating_sub(SNIPPET_LEADING)
+            .min(chars.len() - SNIPPET_LENGTH),
+        _ => 0,
+    };
+    let end = chars.len().min(start + SNIPPET_LENGTH);
+
+    let mut fragments = Vec::new();
+    let mut push = |range: &[char], highlighted: bool| {
+        if !range.is_empty() {
+            fragments.push(Fragment {
+                text: range.iter().collect(),
+                highlighted,
+            });
+        }
+    };
+
+    if start > 0 {
+        push(&['…'], false);
+    }
+    let mut position = start;
+    for &(from, to) in &matches {
+        let (from, to) = (from.max(position), to.min(end));
+        if from >= to {
+            continue;
+        }
+        push(&chars[position..from], false);
+        push(&chars[from..to], true);
+        position = to;
+    }
+    push(&chars[position..end], false);
+    if end < chars.len() {
+        push(&['…'], false);
+    }
+    fragments
+}
+
+/// 検索語に一致する範囲（文字単位、重なりはまとめて開始位置の順）
+fn find_matches(chars: &[char], terms: &[String]) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    for term in terms {
+        let term: Vec<char> = term.chars().collect();
+        if term.is_empty() || term.len() > chars.len() {
+            continue;
+        }
+        for start in 0..=chars.len() - term.len() {
+            let window = &chars[start..start + term.len()];
+            if window
+                .iter()
+                .zip(&term)
+                .all(|(a, b)| eq_ignore_case(*a, *b))
+            {
+                ranges.push((start, start + term.len()));
+            }
+        }
+    }
+
+    ranges.sort();
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (from, to) in ranges {
+        match merged.last_mut() {
+            Some(last) if from <= last.1 => last.1 = last.1.max(to),
+            _ => merged.push((from, to)),
+        }
+    }
+    merged
+}
+
+fn eq_ignore_case(a: char, b: char) -> bool {
+    a == b || a.to_lowercase().eq(b.to_lowercase())
+}
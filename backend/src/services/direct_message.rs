@@ -0,0 +1,284 @@
+use chrono::Utc;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::models::{DirectConversation, DirectConversationMember, DirectMessage, User};
+use crate::pagination::{Page, PageQuery};
+use crate::repository::Repositories;
+use crate::services::social::ensure_not_blocked;
+
+type Result<T> = std::result::Result<T, AppError>;
+
+/// メッセージ本文の最大文字数
+const MAX_MESSAGE_LENGTH: usize = 1000;
+/// 会話の最大人数（自分を含む）
+const MAX_CONVERSATION_MEMBERS: usize = 10;
+
+/// ダイレクトメッセージに関するビジネスルール
+///
+/// 会話を始められるのは相互フォローの相手か、誰からでも受け取る設定にしたユーザーだけ。
+/// 会話の参加者以外には、会話があることも見えない
+#[derive(Clone)]
+pub struct DirectMessageService {
+    repos: Repositories,
+}
+
+impl DirectMessageService {
+    pub fn new(repos: Repositories) -> Self {
+        Self { repos }
+    }
+
+    /// メッセージを送る（conversation_id か、新しい会話の相手の recipient_ids のどちらかを指定する）
+    ///
+    /// 相手がまったく同じ会話がすでにあれば、その会話に送る
+    pub async fn send(
+        &self,
+        sender_id: Uuid,
+        conversation_id: Option<Uuid>,
+        recipient_ids: Option<&[Uuid]>,
+        content: &str,
+    ) -> Result<DirectMessage> {
+        if content.is_empty() || content.chars().count() > MAX_MESSAGE_LENGTH {
+            return Err(AppError::validation(
+                "content",
+                format!(
+                    "Message content must be between 1 and {} characters",
+                    MAX_MESSAGE_LENGTH
+                ),
+            ));
+        }
+
+        let conversation_id = match (conversation_id, recipient_ids) {
+            (Some(id), None) => {
+                // 会話を始めた後にブロックされた場合も、送るたびに確かめる
+                let members = self.ensure_member(id, sender_id).await?;
+                for member in members.iter().filter(|m| m.user_id != sender_id) {
+                    ensure_not_blocked(
+                        &self.repos,
+                        sender_id,
+                        member.user_id,
+                        "Cannot message this conversation",
+                    )
+                    .await?;
+                }
+                id
+            }
+            (None, Some(recipient_ids)) => self.start(sender_id, recipient_ids).await?.id,
+            _ => {
+                return Err(AppError::BadRequest(
+                    "Specify either conversationId or recipientIds".to_string(),
+                ));
+            }
+        };
+
+        let message = DirectMessage {
+            id: Uuid::new_v4(),
+            conversation_id,
+            sender_id,
+            content: content.to_string(),
+            created_at: Utc::now().to_rfc3339(),
+        };
+        self.repos.direct_messages.insert_message(&message).await?;
+
+        Ok(message)
+    }
+
+    /// user_id が参加している会話の1ページ（最後のメッセージの新しい順）
+    pub async fn conversations(
+        &self,
+        user_id: Uuid,
+        query: &PageQuery,
+    ) -> Result<Page<DirectConversation>> {
+        self.repos
+            .direct_messages
+            .conversations_page(user_id, query)
+            .await
+    }
+
+    pub async fn conversation_count(&self, user_id: Uuid) -> Result<i64> {
+        self.repos.direct_messages.conversation_count(user_id).await
+    }
+
+    /// 会話のメッセージの1ページ（新しい順、参加者以外には NotFound）
+    pub async fn messages(
+        &self,
+        user_id: Uuid,
+        conversation_id: Uuid,
+        query: &PageQuery,
+    ) -> Result<Page<DirectMessage>> {
+        self.ensure_member(conversation_id, user_id).await?;
+        self.repos
+            .direct_messages
+            .messages_page(conversation_id, query)
+            .await
+    }
+
+    pub async fn message_count(&self, conversation_id: Uuid) -> Result<i64> {
+        self.repos
+            .direct_messages
+            .message_count(conversation_id)
+            .await
+    }
+
+    /// 会話ごとの参加者と既読日時
+    pub async fn members(
+        &self,
+        conversation_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, Vec<DirectConversationMember>>> {
+        self.repos.direct_messages.members(conversation_ids).await
+    }
+
+    /// 会話ごとの最後のメッセージ
+    pub async fn last_messages(
+        &self,
+        conversation_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, DirectMessage>> {
+        self.repos
+            .direct_messages
+            .last_messages(conversation_ids)
+            .await
+    }
+
+    /// 会話ごとの user_id の未読メッセージ数
+    pub async fn unread_counts(
+        &self,
+        user_id: Uuid,
+        conversation_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, i64>> {
+        self.repos
+            .direct_messages
+            .unread_counts(user_id, conversation_ids)
+            .await
+    }
+
+    /// 会話のこれまでのメッセージを既読にする
+    pub async fn mark_read(&self, user_id: Uuid, conversation_id: Uuid) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        if !self
+            .repos
+            .direct_messages
+            .mark_read(conversation_id, user_id, &now)
+            .await?
+        {
+            return Err(conversation_not_found());
+        }
+        Ok(())
+    }
+
+    /// 相互フォロー以外のユーザーからも会話を始められるようにするかを設定する
+    pub async fn set_allow_open_dms(&self, user_id: Uuid, allow: bool) -> Result<()> {
+        if !self.repos.users.set_allow_open_dms(user_id, allow).await? {
+            return Err(AppError::NotFound("User not found".to_string()));
+        }
+        Ok(())
+    }
+
+    /// 相手がまったく同じ会話を探し、なければ作る
+    async fn start(&self, sender_id: Uuid, recipient_ids: &[Uuid]) -> Result<DirectConversation> {
+        let mut member_ids = vec![sender_id];
+        for id in recipient_ids {
+            if !member_ids.contains(id) {
+                member_ids.push(*id);
+            }
+        }
+        if member_ids.len() < 2 {
+            return Err(AppError::validation(
+                "recipientIds",
+                "At least one recipient other than yourself is required",
+            ));
+        }
+        if member_ids.len() > MAX_CONVERSATION_MEMBERS {
+            return Err(AppError::validation(
+                "recipientIds",
+                format!(
+                    "A conversation can have at most {} members",
+                    MAX_CONVERSATION_MEMBERS
+                ),
+            ));
+        }
+
+        let recipients = self.repos.users.find_by_ids(&member_ids[1..]).await?;
+        if recipients.len() != member_ids.len() - 1 {
+            return Err(AppError::NotFound("User not found".to_string()));
+        }
+        for recipient in &recipients {
+            if !self.can_start(sender_id, recipient).await? {
+                return Err(AppError::Forbidden(format!(
+                    "Cannot message {}: only mutual followers or users who allow open messages can be messaged",
+                    recipient.username
+                )));
+            }
+        }
+
+        member_ids.sort();
+        if let Some(existing) = self
+            .repos
+            .direct_messages
+            .find_by_members(&member_ids)
+            .await?
+        {
+            return Ok(existing);
+        }
+
+        let now = Utc::now().to_rfc3339();
+        let conversation = DirectConversation {
+            id: Uuid::new_v4(),
+            created_at: now.clone(),
+            last_message_at: now,
+        };
+        self.repos
+            .direct_messages
+            .create_conversation(&conversation, &member_ids)
+            .await?;
+
+        Ok(conversation)
+    }
+
+    /// sender が recipient との会話を始められるか（ブロックがなく、相互フォローか recipient が誰からでも受け取る設定）
+    async fn can_start(&self, sender_id: Uuid, recipient: &User) -> Result<bool> {
+        if self
+            .repos
+            .blocks
+            .is_blocked(sender_id, recipient.id)
+            .await?
+        {
+            return Ok(false);
+        }
+        if recipient.allow_open_dms {
+            return Ok(true);
+        }
+        let follows = &self.repos.follows;
+        Ok(follows
+            .following_set(sender_id, &[recipient.id])
+            .await?
+            .contains(&recipient.id)
+            && follows
+                .following_set(recipient.id, &[sender_id])
+                .await?
+                .contains(&sender_id))
+    }
+
+    /// 参加者でなければ会話がないものとして扱う（参加者なら会話の参加者一覧を返す）
+    async fn ensure_member(
+        &self,
+        conversation_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Vec<DirectConversationMember>> {
+        let members = self
+            .repos
+            .direct_messages
+            .members(&[conversation_id])
+            .await?
+            .remove(&conversation_id)
+            .unwrap_or_default();
+        if !members.iter().any(|m| m.user_id == user_id) {
+            return Err(conversation_not_found());
+        }
+        Ok(members)
+    }
+}
+
+fn conversation_not_found() -> AppError {
+    AppError::NotFound("Conversation not found".to_string())
+}
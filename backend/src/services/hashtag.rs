@@ -0,0 +1,132 @@
+use chrono::{Duration, Utc};
+use std::collections::HashMap;
+
+use crate::error::AppError;
+use crate::models::Tweet;
+use crate::pagination::{MAX_PAGE_SIZE, Page, PageQuery};
+use crate::repository::{HashtagStats, Repositories};
+
+type Result<T> = std::result::Result<T, AppError>;
+
+/// trending の件数を省略した場合の件数
+pub const DEFAULT_TRENDING_LIMIT: usize = 10;
+/// 直近の期間と比べる基準（その直前の BASELINE_WINDOWS 期間の平均）
+const BASELINE_WINDOWS: i32 = 7;
+
+/// トレンドを集計する期間（現在時刻から遡る）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, async_graphql::Enum)]
+pub enum TrendWindow {
+    Hour,
+    Day,
+    Week,
+}
+
+impl TrendWindow {
+    fn duration(self) -> Duration {
+        match self {
+            TrendWindow::Hour => Duration::hours(1),
+            TrendWindow::Day => Duration::days(1),
+            TrendWindow::Week => Duration::weeks(1),
+        }
+    }
+}
+
+/// 使用状況付きのハッシュタグ
+pub struct Hashtag {
+    pub name: String,
+    pub stats: HashtagStats,
+}
+
+/// 直近の期間に勢いのあるハッシュタグ
+#[derive(Debug, Clone)]
+pub struct TrendingHashtag {
+    pub name: String,
+    /// 直近の期間に付けられたツイート数
+    pub recent_count: i64,
+    /// 基準の期間での1期間あたりの平均ツイート数
+    pub baseline: f64,
+    /// 基準からの伸び（件数の増分を基準の大きさで割ったもの）
+    pub score: f64,
+}
+
+/// ハッシュタグの閲覧とトレンド
+#[derive(Clone)]
+pub struct HashtagService {
+    repos: Repositories,
+}
+
+impl HashtagService {
+    pub fn new(repos: Repositories) -> Self {
+        Self { repos }
+    }
+
+    /// ハッシュタグの使用状況（どのツイートにも付いていなければ None）
+    pub async fn find(&self, name: &str) -> Result<Option<Hashtag>> {
+        let name = normalize(name);
+        let stats = self.repos.hashtags.stats(&name).await?;
+        Ok(stats.map(|stats| Hashtag { name, stats }))
+    }
+
+    /// ハッシュタグの付いたツイート一覧の1ページ（新しい順）
+    pub async fn tweets(&self, name: &str, query: &PageQuery) -> Result<Page<Tweet>> {
+        self.repos
+            .hashtags
+            .tweets_page(&normalize(name), query)
+            .await
+    }
+
+    /// 直近の期間の件数が基準を上回るハッシュタグ（伸びの大きい順に最大 limit 件）
+    ///
+    /// 基準の平均を expected として score = (recent - expected) / sqrt(expected + 1) で比べ、
+    /// 件数が多いだけで伸びていないハッシュタグより、急に使われ始めたものを上位にする
+    pub async fn trending(
+        &self,
+        window: TrendWindow,
+        limit: usize,
+    ) -> Result<Vec<TrendingHashtag>> {
+        let limit = limit.min(MAX_PAGE_SIZE);
+        let now = Utc::now();
+        let recent_start = now - window.duration();
+        let baseline_start = recent_start - window.duration() * BASELINE_WINDOWS;
+
+        let recent = self
+            .repos
+            .hashtags
+            .counts_between(&recent_start.to_rfc3339(), &now.to_rfc3339())
+            .await?;
+        let baseline: HashMap<String, i64> = self
+            .repos
+            .hashtags
+            .counts_between(&baseline_start.to_rfc3339(), &recent_start.to_rfc3339())
+            .await?;
+
+        let mut trending: Vec<TrendingHashtag> = recent
+            .into_iter()
+            .map(|(name, recent_count)| {
+                let expected =
+                    baseline.get(&name).copied().unwrap_or(0) as f64 / f64::from(BASELINE_WINDOWS);
+                TrendingHashtag {
+                    score: (recent_count as f64 - expected) / (expected + 1.0).sqrt(),
+                    name,
+                    recent_count,
+                    baseline: expected,
+                }
+            })
+            .filter(|t| t.score > 0.0)
+            .collect();
+
+        trending.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then(b.recent_count.cmp(&a.recent_count))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        trending.truncate(limit);
+        Ok(trending)
+    }
+}
+
+/// `#Rust` のような入力を保存時と同じ小文字・# なしの名前にする
+fn normalize(name: &str) -> String {
+    name.trim().trim_start_matches('#').to_lowercase()
+}
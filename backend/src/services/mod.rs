@@ -0,0 +1,57 @@
+//! RESTとGraphQLで共有するドメインサービス
+//!
+//! ビジネスルール（入力検証、権限チェック、関連データの更新）はここに集約し、
+//! `handlers` と `graphql` はリクエストの変換とレスポンスの組み立てだけを行う
+
+mod direct_message;
+mod hashtag;
+mod moderation;
+mod notification;
+mod search;
+mod social;
+mod tweet;
+mod user;
+
+pub use direct_message::DirectMessageService;
+pub use hashtag::{DEFAULT_TRENDING_LIMIT, Hashtag, HashtagService, TrendWindow, TrendingHashtag};
+pub use moderation::ModerationService;
+pub use notification::{MAX_NOTIFICATION_ACTORS, NotificationPreference, NotificationService};
+pub use search::SearchService;
+pub use social::SocialGraphService;
+pub use tweet::{Conversation, DEFAULT_THREAD_DEPTH, TweetDetails, TweetService};
+pub use user::{AuthSession, UserService};
+
+use crate::events::EventBus;
+use crate::repository::Repositories;
+
+/// アプリケーション全体で共有するサービス一式
+#[derive(Clone)]
+pub struct Services {
+    pub users: UserService,
+    pub tweets: TweetService,
+    pub social: SocialGraphService,
+    pub hashtags: HashtagService,
+    pub moderation: ModerationService,
+    pub notifications: NotificationService,
+    pub direct_messages: DirectMessageService,
+    pub search: SearchService,
+    /// サブスクリプションに配信するイベント
+    pub events: EventBus,
+}
+
+impl Services {
+    pub fn new(repos: Repositories) -> Self {
+        let events = EventBus::new();
+        Self {
+            users: UserService::new(repos.clone()),
+            tweets: TweetService::new(repos.clone(), events.clone()),
+            social: SocialGraphService::new(repos.clone()),
+            hashtags: HashtagService::new(repos.clone()),
+            moderation: ModerationService::new(repos.clone()),
+            notifications: NotificationService::new(repos.clone()),
+            direct_messages: DirectMessageService::new(repos.clone()),
+            search: SearchService::new(repos),
+            events,
+        }
+    }
+}
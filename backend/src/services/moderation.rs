@@ -0,0 +1,227 @@
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::models::{ModerationAction, ModerationActionKind, Report, ReportTarget, Role, User};
+use crate::pagination::MAX_PAGE_SIZE;
+use crate::repository::Repositories;
+use crate::roles::Actor;
+
+type Result<T> = std::result::Result<T, AppError>;
+
+/// 通報理由の最大文字数
+const MAX_REASON_LENGTH: usize = 500;
+
+/// 通報・利用停止・ロール変更に関するビジネスルール
+///
+/// 権限の確認は呼び出し側の `RoleGuard` で行い、ここではロールの上下関係などを確認する
+#[derive(Clone)]
+pub struct ModerationService {
+    repos: Repositories,
+}
+
+impl ModerationService {
+    pub fn new(repos: Repositories) -> Self {
+        Self { repos }
+    }
+
+    /// ツイート・コメント・ユーザーを通報する
+    pub async fn report(
+        &self,
+        reporter_id: Uuid,
+        target_type: ReportTarget,
+        target_id: Uuid,
+        reason: &str,
+    ) -> Result<Report> {
+        if reason.is_empty() || reason.chars().count() > MAX_REASON_LENGTH {
+            return Err(AppError::validation(
+                "reason",
+                format!(
+                    "Reason must be between 1 and {} characters",
+                    MAX_REASON_LENGTH
+                ),
+            ));
+        }
+
+        let exists = match target_type {
+            ReportTarget::Tweet => self.repos.tweets.find_by_id(target_id).await?.is_some(),
+            ReportTarget::Comment => self.repos.comments.find_by_id(target_id).await?.is_some(),
+            ReportTarget::User => self.repos.users.find_by_id(target_id).await?.is_some(),
+        };
+        if !exists {
+            return Err(AppError::NotFound("Report target not found".to_string()));
+        }
+
+        let report = Report {
+            id: Uuid::new_v4(),
+            reporter_id,
+            target_type,
+            target_id,
+            reason: reason.to_string(),
+            created_at: Utc::now().to_rfc3339(),
+            resolved_at: None,
+            resolved_by: None,
+        };
+        self.repos.reports.insert(&report).await?;
+
+        Ok(report)
+    }
+
+    /// 未対応の通報（古い順）
+    pub async fn open_reports(&self) -> Result<Vec<Report>> {
+        self.repos.reports.list_open().await
+    }
+
+    /// 通報を対応済みにする
+    pub async fn resolve_report(&self, actor: Actor, id: Uuid) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        if !self.repos.reports.resolve(id, actor.user_id, &now).await? {
+            return Err(AppError::NotFound(
+                "Report not found or already resolved".to_string(),
+            ));
+        }
+        record(
+            &self.repos,
+            actor,
+            ModerationActionKind::ResolveReport,
+            id,
+            None,
+        )
+        .await
+    }
+
+    /// ユーザーを利用停止にし、すべてのセッションを失効させる
+    pub async fn suspend(&self, actor: Actor, user_id: Uuid, reason: Option<&str>) -> Result<()> {
+        let user = self.find_subordinate(actor, user_id).await?;
+        if user.suspended_at.is_some() {
+            return Err(AppError::Conflict("User is already suspended".to_string()));
+        }
+
+        let now = Utc::now().to_rfc3339();
+        self.repos
+            .users
+            .set_suspended_at(user_id, Some(&now))
+            .await?;
+        self.revoke_all_sessions(user_id, &now).await?;
+
+        record(
+            &self.repos,
+            actor,
+            ModerationActionKind::SuspendUser,
+            user_id,
+            reason.map(str::to_string),
+        )
+        .await
+    }
+
+    /// 利用停止を解除する
+    pub async fn unsuspend(&self, actor: Actor, user_id: Uuid) -> Result<()> {
+        let user = self.find_subordinate(actor, user_id).await?;
+        if user.suspended_at.is_none() {
+            return Err(AppError::Conflict("User is not suspended".to_string()));
+        }
+
+        self.repos.users.set_suspended_at(user_id, None).await?;
+
+        record(
+            &self.repos,
+            actor,
+            ModerationActionKind::UnsuspendUser,
+            user_id,
+            None,
+        )
+        .await
+    }
+
+    /// ユーザーのロールを変更する（古いロールのトークンが残らないよう、すべてのセッションを失効させる）
+    ///
+    /// 自分と同格以上のユーザーは変更できず、自分より強いロールは与えられない
+    pub async fn set_role(&self, actor: Actor, user_id: Uuid, role: Role) -> Result<User> {
+        if actor.user_id == user_id {
+            return Err(AppError::Forbidden(
+                "Cannot change your own role".to_string(),
+            ));
+        }
+        self.find_subordinate(actor, user_id).await?;
+        if role > actor.role {
+            return Err(AppError::Forbidden(
+                "Cannot grant a role higher than your own".to_string(),
+            ));
+        }
+        if !self.repos.users.set_role(user_id, role).await? {
+            return Err(AppError::NotFound("User not found".to_string()));
+        }
+        self.revoke_all_sessions(user_id, &Utc::now().to_rfc3339())
+            .await?;
+
+        record(
+            &self.repos,
+            actor,
+            ModerationActionKind::ChangeRole,
+            user_id,
+            Some(role.as_str().to_string()),
+        )
+        .await?;
+
+        self.repos
+            .users
+            .find_by_id(user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))
+    }
+
+    /// 最近のモデレーション操作（新しい順、最大 MAX_PAGE_SIZE 件）
+    pub async fn log(&self, limit: usize) -> Result<Vec<ModerationAction>> {
+        let limit = limit.min(MAX_PAGE_SIZE) as i64;
+        self.repos.moderation_log.recent(limit).await
+    }
+
+    /// すべてのセッションとリフレッシュトークンを失効させる
+    async fn revoke_all_sessions(&self, user_id: Uuid, now: &str) -> Result<()> {
+        // 除外するセッションがないよう nil を渡す
+        self.repos
+            .sessions
+            .revoke_others(user_id, Uuid::nil(), now)
+            .await?;
+        self.repos
+            .refresh_tokens
+            .revoke_other_families(user_id, Uuid::nil(), now)
+            .await
+    }
+
+    /// 操作するユーザーより弱いロールのユーザーを取得する（自分や同格以上は操作できない）
+    async fn find_subordinate(&self, actor: Actor, user_id: Uuid) -> Result<User> {
+        let user = self
+            .repos
+            .users
+            .find_by_id(user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+        if user.role >= actor.role {
+            return Err(AppError::Forbidden(
+                "Cannot moderate a user with an equal or higher role".to_string(),
+            ));
+        }
+        Ok(user)
+    }
+}
+
+/// モデレーション操作を監査ログに記録する
+pub(super) async fn record(
+    repos: &Repositories,
+    actor: Actor,
+    action: ModerationActionKind,
+    target_id: Uuid,
+    details: Option<String>,
+) -> Result<()> {
+    let entry = ModerationAction {
+        id: Uuid::new_v4(),
+        actor_id: actor.user_id,
+        action,
+        target_id,
+        details,
+        created_at: Utc::now().to_rfc3339(),
+    };
+    repos.moderation_log.insert(&entry).await
+}
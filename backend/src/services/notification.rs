@@ -0,0 +1,155 @@
+use chrono::Utc;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::models::{Notification, NotificationKind};
+use crate::pagination::{Page, PageQuery};
+use crate::repository::{NotificationGroup, Repositories};
+
+type Result<T> = std::result::Result<T, AppError>;
+
+/// まとめた通知ごとに返す最近操作したユーザーの最大数
+pub const MAX_NOTIFICATION_ACTORS: i64 = 3;
+
+/// 設定で切り替えられる通知の種類（設定一覧の並び順）
+const KINDS: [NotificationKind; 4] = [
+    NotificationKind::Like,
+    NotificationKind::Follow,
+    NotificationKind::Comment,
+    NotificationKind::Mention,
+];
+
+/// 通知の種類ごとの受け取り設定
+pub struct NotificationPreference {
+    pub kind: NotificationKind,
+    pub enabled: bool,
+}
+
+/// 通知の一覧・既読・受け取り設定に関するビジネスルール
+///
+/// 通知の作成は各サービスの操作から `notify` で行う
+#[derive(Clone)]
+pub struct NotificationService {
+    repos: Repositories,
+}
+
+impl NotificationService {
+    pub fn new(repos: Repositories) -> Self {
+        Self { repos }
+    }
+
+    /// まとめた通知の1ページ（最新の通知の新しい順）
+    pub async fn page(&self, user_id: Uuid, query: &PageQuery) -> Result<Page<NotificationGroup>> {
+        self.repos.notifications.groups_page(user_id, query).await
+    }
+
+    pub async fn group_count(&self, user_id: Uuid) -> Result<i64> {
+        self.repos.notifications.group_count(user_id).await
+    }
+
+    /// 未読の通知を含むまとまりの数
+    pub async fn unread_count(&self, user_id: Uuid) -> Result<i64> {
+        self.repos.notifications.unread_count(user_id).await
+    }
+
+    /// まとまりごとの最近操作したユーザー（新しい順に limit 人）
+    pub async fn recent_actors(
+        &self,
+        user_id: Uuid,
+        group_keys: &[String],
+        limit: i64,
+    ) -> Result<HashMap<String, Vec<Uuid>>> {
+        self.repos
+            .notifications
+            .recent_actors(user_id, group_keys, limit)
+            .await
+    }
+
+    /// 未読の通知をすべて既読にする（既読にした件数）
+    pub async fn mark_read(&self, user_id: Uuid) -> Result<u64> {
+        let now = Utc::now().to_rfc3339();
+        self.repos.notifications.mark_read(user_id, &now).await
+    }
+
+    /// すべての種類の受け取り設定
+    pub async fn preferences(&self, user_id: Uuid) -> Result<Vec<NotificationPreference>> {
+        let disabled = self.repos.notifications.disabled_kinds(user_id).await?;
+        Ok(KINDS
+            .into_iter()
+            .map(|kind| NotificationPreference {
+                kind,
+                enabled: !disabled.contains(&kind),
+            })
+            .collect())
+    }
+
+    /// 種類ごとに通知を受け取るかを設定する（それまでに届いた通知は残す）
+    pub async fn set_preference(
+        &self,
+        user_id: Uuid,
+        kind: NotificationKind,
+        enabled: bool,
+    ) -> Result<NotificationPreference> {
+        self.repos
+            .notifications
+            .set_kind_enabled(user_id, kind, enabled)
+            .await?;
+        Ok(NotificationPreference { kind, enabled })
+    }
+}
+
+/// recipient に通知する（自分の操作、受け取らない設定にした種類、ブロック・ミュートしたユーザーの操作は通知しない）
+///
+/// 同じ対象への同じ種類の通知は group_key でまとめて表示する
+pub(super) async fn notify(
+    repos: &Repositories,
+    kind: NotificationKind,
+    recipient: Uuid,
+    actor: Uuid,
+    tweet_id: Option<Uuid>,
+    comment_id: Option<Uuid>,
+) -> Result<()> {
+    if recipient == actor {
+        return Ok(());
+    }
+    if repos
+        .notifications
+        .disabled_kinds(recipient)
+        .await?
+        .contains(&kind)
+    {
+        return Ok(());
+    }
+    if !repos
+        .blocks
+        .hidden_set(recipient, &[actor])
+        .await?
+        .is_empty()
+    {
+        return Ok(());
+    }
+
+    // いいね・コメントはツイートごと、言及は投稿ごと、フォローは1つにまとめる
+    let target = match kind {
+        NotificationKind::Like | NotificationKind::Comment => tweet_id,
+        NotificationKind::Mention => comment_id.or(tweet_id),
+        NotificationKind::Follow => None,
+    };
+    let group_key = match target {
+        Some(id) => format!("{}:{}", kind.as_str(), id),
+        None => kind.as_str().to_string(),
+    };
+    let notification = Notification {
+        id: Uuid::new_v4(),
+        user_id: recipient,
+        kind,
+        actor_id: actor,
+        group_key,
+        tweet_id,
+        comment_id,
+        created_at: Utc::now().to_rfc3339(),
+        read_at: None,
+    };
+    repos.notifications.upsert(&notification).await
+}
@@ -0,0 +1,123 @@
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::pagination::{Page, PageQuery};
+use crate::repository::{Repositories, SearchFilter};
+use crate::search::{SearchHit, SearchItem, SearchQuery, SearchType, snippet};
+
+type Result<T> = std::result::Result<T, AppError>;
+
+/// ツイート・コメント・ユーザーの検索
+#[derive(Clone)]
+pub struct SearchService {
+    repos: Repositories,
+}
+
+impl SearchService {
+    pub fn new(repos: Repositories) -> Self {
+        Self { repos }
+    }
+
+    /// クエリを解析して検索し、一致した部分をハイライトしたスニペットを付ける（新しい順）
+    ///
+    /// viewer がブロック・ミュートしたユーザーのツイート・コメントは除く
+    pub async fn search(
+        &self,
+        input: &str,
+        search_type: SearchType,
+        viewer: Option<Uuid>,
+        query: &PageQuery,
+    ) -> Result<Page<SearchHit>> {
+        let parsed = SearchQuery::parse(input)?;
+
+        match search_type {
+            SearchType::Tweets => {
+                let Some(filter) = self.filter(parsed, viewer).await? else {
+                    return Ok(empty_page());
+                };
+                let page = self.repos.search.search_tweets(&filter, query).await?;
+                let mut highlights = filter.terms;
+                highlights.extend(filter.hashtags.iter().map(|tag| format!("#{}", tag)));
+                Ok(hits(page, |tweet| SearchHit {
+                    snippet: snippet(&tweet.content, &highlights),
+                    item: SearchItem::Tweet(tweet),
+                }))
+            }
+            SearchType::Comments => {
+                let Some(mut filter) = self.filter(parsed, viewer).await? else {
+                    return Ok(empty_page());
+                };
+                // コメントにはハッシュタグの関連付けがないため、本文の語として探す
+                let tags = std::mem::take(&mut filter.hashtags);
+                filter
+                    .terms
+                    .extend(tags.into_iter().map(|tag| format!("#{}", tag)));
+                let page = self.repos.search.search_comments(&filter, query).await?;
+                Ok(hits(page, |comment| SearchHit {
+                    snippet: snippet(&comment.content, &filter.terms),
+                    item: SearchItem::Comment(comment),
+                }))
+            }
+            SearchType::Users => {
+                if parsed.has_filters() {
+                    return Err(AppError::validation(
+                        "query",
+                        "Filters are not supported when searching users",
+                    ));
+                }
+                let page = self.repos.search.search_users(&parsed.terms, query).await?;
+                Ok(hits(page, |user| SearchHit {
+                    snippet: snippet(&user.username, &parsed.terms),
+                    item: SearchItem::User(user),
+                }))
+            }
+        }
+    }
+
+    /// from: のユーザー名を ID にして検索条件を作る（存在しないユーザーなら None）
+    async fn filter(
+        &self,
+        parsed: SearchQuery,
+        viewer: Option<Uuid>,
+    ) -> Result<Option<SearchFilter>> {
+        let author_id = match &parsed.from {
+            Some(username) => match self.repos.users.find_by_username(username).await? {
+                Some(user) => Some(user.id),
+                None => return Ok(None),
+            },
+            None => None,
+        };
+
+        Ok(Some(SearchFilter {
+            terms: parsed.terms,
+            author_id,
+            hashtags: parsed.hashtags,
+            since: parsed.since,
+            until: parsed.until,
+            viewer,
+        }))
+    }
+}
+
+fn hits<T, F>(page: Page<T>, to_hit: F) -> Page<SearchHit>
+where
+    F: Fn(T) -> SearchHit,
+{
+    Page {
+        items: page
+            .items
+            .into_iter()
+            .map(|(cursor, item)| (cursor, to_hit(item)))
+            .collect(),
+        has_previous_page: page.has_previous_page,
+        has_next_page: page.has_next_page,
+    }
+}
+
+fn empty_page() -> Page<SearchHit> {
+    Page {
+        items: Vec::new(),
+        has_previous_page: false,
+        has_next_page: false,
+    }
+}
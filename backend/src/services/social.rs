@@ -0,0 +1,189 @@
+use chrono::Utc;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::models::{NotificationKind, User};
+use crate::pagination::{Page, PageQuery};
+use crate::repository::{FollowCounts, Repositories};
+use crate::services::notification::notify;
+
+type Result<T> = std::result::Result<T, AppError>;
+
+/// フォロー・ブロック・ミュートに関するビジネスルール
+///
+/// ブロックはお互いのフォローを解除し、どちらからもフォロー・いいね・コメントができなくなる。
+/// ミュートは自分のタイムライン・コメント・検索・通知から相手を隠すだけで、相手からは分からない
+#[derive(Clone)]
+pub struct SocialGraphService {
+    repos: Repositories,
+}
+
+impl SocialGraphService {
+    pub fn new(repos: Repositories) -> Self {
+        Self { repos }
+    }
+
+    pub async fn follow(&self, follower_id: Uuid, target_id: Uuid) -> Result<()> {
+        if follower_id == target_id {
+            return Err(AppError::BadRequest("Cannot follow yourself".to_string()));
+        }
+
+        self.find_user(target_id).await?;
+        ensure_not_blocked(
+            &self.repos,
+            follower_id,
+            target_id,
+            "Cannot follow this user",
+        )
+        .await?;
+
+        let created_at = Utc::now().to_rfc3339();
+        if !self
+            .repos
+            .follows
+            .insert(follower_id, target_id, &created_at)
+            .await?
+        {
+            return Err(AppError::Conflict(
+                "Already following this user".to_string(),
+            ));
+        }
+        notify(
+            &self.repos,
+            NotificationKind::Follow,
+            target_id,
+            follower_id,
+            None,
+            None,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn unfollow(&self, follower_id: Uuid, target_id: Uuid) -> Result<()> {
+        if !self.repos.follows.delete(follower_id, target_id).await? {
+            return Err(AppError::NotFound("Not following this user".to_string()));
+        }
+        Ok(())
+    }
+
+    /// ブロックする（お互いのフォローと、2人の間の通知は削除する）
+    pub async fn block(&self, blocker_id: Uuid, target_id: Uuid) -> Result<()> {
+        if blocker_id == target_id {
+            return Err(AppError::BadRequest("Cannot block yourself".to_string()));
+        }
+        self.find_user(target_id).await?;
+
+        let created_at = Utc::now().to_rfc3339();
+        if !self
+            .repos
+            .blocks
+            .block(blocker_id, target_id, &created_at)
+            .await?
+        {
+            return Err(AppError::Conflict("Already blocking this user".to_string()));
+        }
+        Ok(())
+    }
+
+    pub async fn unblock(&self, blocker_id: Uuid, target_id: Uuid) -> Result<()> {
+        if !self.repos.blocks.unblock(blocker_id, target_id).await? {
+            return Err(AppError::NotFound("Not blocking this user".to_string()));
+        }
+        Ok(())
+    }
+
+    /// ミュートする（相手からのそれまでの通知は削除する）
+    pub async fn mute(&self, muter_id: Uuid, target_id: Uuid) -> Result<()> {
+        if muter_id == target_id {
+            return Err(AppError::BadRequest("Cannot mute yourself".to_string()));
+        }
+        self.find_user(target_id).await?;
+
+        let created_at = Utc::now().to_rfc3339();
+        if !self
+            .repos
+            .blocks
+            .mute(muter_id, target_id, &created_at)
+            .await?
+        {
+            return Err(AppError::Conflict("Already muting this user".to_string()));
+        }
+        Ok(())
+    }
+
+    pub async fn unmute(&self, muter_id: Uuid, target_id: Uuid) -> Result<()> {
+        if !self.repos.blocks.unmute(muter_id, target_id).await? {
+            return Err(AppError::NotFound("Not muting this user".to_string()));
+        }
+        Ok(())
+    }
+
+    /// user_id をフォローしているユーザー一覧の1ページ（フォローの新しい順）
+    pub async fn followers(&self, user_id: Uuid, query: &PageQuery) -> Result<Page<User>> {
+        self.repos.follows.followers(user_id, query).await
+    }
+
+    /// user_id がフォローしているユーザー一覧の1ページ（フォローの新しい順）
+    pub async fn following(&self, user_id: Uuid, query: &PageQuery) -> Result<Page<User>> {
+        self.repos.follows.following(user_id, query).await
+    }
+
+    /// follower_id が target_id をフォローしているか
+    pub async fn is_following(&self, follower_id: Uuid, target_id: Uuid) -> Result<bool> {
+        Ok(self
+            .following_set(follower_id, &[target_id])
+            .await?
+            .contains(&target_id))
+    }
+
+    /// target_ids のうち follower_id がフォローしているユーザー
+    pub async fn following_set(
+        &self,
+        follower_id: Uuid,
+        target_ids: &[Uuid],
+    ) -> Result<HashSet<Uuid>> {
+        self.repos
+            .follows
+            .following_set(follower_id, target_ids)
+            .await
+    }
+
+    /// user_id が viewer から隠されているか（ブロック・ミュートした相手、ブロックされた相手）
+    pub async fn is_hidden(&self, viewer: Uuid, user_id: Uuid) -> Result<bool> {
+        Ok(!self
+            .repos
+            .blocks
+            .hidden_set(viewer, &[user_id])
+            .await?
+            .is_empty())
+    }
+
+    /// 各ユーザーのフォロワー数とフォロー中の数
+    pub async fn counts(&self, user_ids: &[Uuid]) -> Result<HashMap<Uuid, FollowCounts>> {
+        self.repos.follows.counts(user_ids).await
+    }
+
+    async fn find_user(&self, id: Uuid) -> Result<User> {
+        self.repos
+            .users
+            .find_by_id(id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))
+    }
+}
+
+/// どちらかがもう一方をブロックしていれば Forbidden にする
+pub(super) async fn ensure_not_blocked(
+    repos: &Repositories,
+    user_id: Uuid,
+    other_id: Uuid,
+    message: &str,
+) -> Result<()> {
+    if repos.blocks.is_blocked(user_id, other_id).await? {
+        return Err(AppError::Forbidden(message.to_string()));
+    }
+    Ok(())
+}
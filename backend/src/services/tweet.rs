@@ -0,0 +1,584 @@
+use chrono::Utc;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::events::{Event, EventBus};
+use crate::models::{Comment, ModerationActionKind, NotificationKind, TimelineEntry, Tweet};
+use crate::pagination::{Page, PageQuery};
+use crate::repository::Repositories;
+use crate::roles::{Actor, Permission};
+use crate::services::moderation::record;
+use crate::services::notification::notify;
+use crate::services::social::ensure_not_blocked;
+use crate::utils::{extract_hashtags, extract_mentions};
+
+type Result<T> = std::result::Result<T, AppError>;
+
+/// ツイート本文・コメントの最大文字数
+const MAX_CONTENT_LENGTH: usize = 280;
+/// 返信スレッドを一度に取得できる最大の深さ
+pub const MAX_THREAD_DEPTH: i64 = 10;
+/// 返信スレッドの既定の深さ
+pub const DEFAULT_THREAD_DEPTH: i64 = 3;
+
+/// いいね数・いいね状態・ハッシュタグを含むツイート
+pub struct TweetDetails {
+    pub tweet: Tweet,
+    pub like_count: i64,
+    pub is_liked: bool,
+    pub hashtags: Vec<String>,
+}
+
+/// ツイートへのコメントのスレッド（comment を指定すればその返信スレッド）
+pub struct Conversation {
+    pub tweet: Tweet,
+    pub comment: Option<Comment>,
+    /// comment の返信先をたどったコメント（ツイートへの直接のコメントから順）
+    pub ancestors: Vec<Comment>,
+}
+
+/// ツイート・いいね・コメントに関するビジネスルール
+#[derive(Clone)]
+pub struct TweetService {
+    repos: Repositories,
+    events: EventBus,
+}
+
+impl TweetService {
+    pub fn new(repos: Repositories, events: EventBus) -> Self {
+        Self { repos, events }
+    }
+
+    /// ツイートを投稿し、本文中のハッシュタグを登録する
+    pub async fn create(&self, user_id: Uuid, content: &str) -> Result<TweetDetails> {
+        validate_content("Tweet", content)?;
+        self.post(user_id, content, None).await
+    }
+
+    /// tweet_id を引用してコメント付きで投稿する
+    pub async fn quote(
+        &self,
+        user_id: Uuid,
+        tweet_id: Uuid,
+        content: &str,
+    ) -> Result<TweetDetails> {
+        validate_content("Tweet", content)?;
+        let quoted = self.find_existing(tweet_id).await?;
+        ensure_not_blocked(
+            &self.repos,
+            user_id,
+            quoted.user_id,
+            "Cannot quote this tweet",
+        )
+        .await?;
+
+        self.post(user_id, content, Some(tweet_id)).await
+    }
+
+    async fn post(
+        &self,
+        user_id: Uuid,
+        content: &str,
+        quoted_tweet_id: Option<Uuid>,
+    ) -> Result<TweetDetails> {
+        let tweet = Tweet {
+            id: Uuid::new_v4(),
+            user_id,
+            content: content.to_string(),
+            created_at: Utc::now().to_rfc3339(),
+            quoted_tweet_id,
+        };
+        let hashtags = extract_hashtags(content);
+        let mentions = self.resolve_mentions(content).await?;
+
+        self.repos
+            .tweets
+            .insert(&tweet, &hashtags, &mentions)
+            .await?;
+        for &user_id in &mentions {
+            notify(
+                &self.repos,
+                NotificationKind::Mention,
+                user_id,
+                tweet.user_id,
+                Some(tweet.id),
+                None,
+            )
+            .await?;
+        }
+        self.events.publish(Event::TweetPosted(tweet.clone()));
+
+        Ok(TweetDetails {
+            tweet,
+            like_count: 0,
+            is_liked: false,
+            hashtags,
+        })
+    }
+
+    pub async fn find(&self, id: Uuid) -> Result<Option<Tweet>> {
+        self.repos.tweets.find_by_id(id).await
+    }
+
+    pub async fn find_many(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, Tweet>> {
+        self.repos.tweets.find_many(ids).await
+    }
+
+    /// ツイートを取得する（viewer は閲覧中のユーザー）
+    pub async fn get(&self, id: Uuid, viewer: Option<Uuid>) -> Result<Option<TweetDetails>> {
+        let Some(tweet) = self.repos.tweets.find_by_id(id).await? else {
+            return Ok(None);
+        };
+        Ok(self.with_details(vec![tweet], viewer).await?.pop())
+    }
+
+    /// ツイートを削除する（他のユーザーのツイートは DeleteAnyContent 権限があれば削除でき、監査ログに残す）
+    pub async fn delete(&self, actor: Actor, id: Uuid) -> Result<()> {
+        let not_found = || AppError::NotFound("Tweet not found or not authorized".to_string());
+
+        let tweet = self
+            .repos
+            .tweets
+            .find_by_id(id)
+            .await?
+            .ok_or_else(not_found)?;
+        let own = tweet.user_id == actor.user_id;
+        if !own && !actor.role.can(Permission::DeleteAnyContent) {
+            return Err(not_found());
+        }
+        if !self
+            .repos
+            .tweets
+            .delete(id, own.then_some(actor.user_id))
+            .await?
+        {
+            return Err(not_found());
+        }
+
+        if !own {
+            record(
+                &self.repos,
+                actor,
+                ModerationActionKind::DeleteTweet,
+                id,
+                None,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// タイムライン（自分 + フォロー中のユーザーのツイート、新しい順）
+    pub async fn timeline(&self, user_id: Uuid) -> Result<Vec<TweetDetails>> {
+        let tweets = self.repos.tweets.timeline(user_id).await?;
+        self.with_details(tweets, Some(user_id)).await
+    }
+
+    /// タイムラインの1ページ（自分 + フォロー中のユーザーの投稿とリツイート、載った日時の新しい順）
+    pub async fn timeline_page(
+        &self,
+        user_id: Uuid,
+        query: &PageQuery,
+    ) -> Result<Page<TimelineEntry>> {
+        self.repos.tweets.timeline_page(user_id, query).await
+    }
+
+    /// タイムラインの総件数
+    pub async fn timeline_count(&self, user_id: Uuid) -> Result<i64> {
+        self.repos.tweets.timeline_count(user_id).await
+    }
+
+    /// 各ツイートのいいね数（いいねのないツイートは含まれない）
+    pub async fn like_counts(&self, tweet_ids: &[Uuid]) -> Result<HashMap<Uuid, i64>> {
+        self.repos.likes.counts(tweet_ids).await
+    }
+
+    /// tweet_ids のうち user_id がいいねしているツイート
+    pub async fn liked_by(&self, user_id: Uuid, tweet_ids: &[Uuid]) -> Result<HashSet<Uuid>> {
+        self.repos.likes.liked_by(user_id, tweet_ids).await
+    }
+
+    /// 各ツイートのリツイート数（リツイートのないツイートは含まれない）
+    pub async fn retweet_counts(&self, tweet_ids: &[Uuid]) -> Result<HashMap<Uuid, i64>> {
+        self.repos.retweets.counts(tweet_ids).await
+    }
+
+    /// 各ツイートの引用数（引用されていないツイートは含まれない）
+    pub async fn quote_counts(&self, tweet_ids: &[Uuid]) -> Result<HashMap<Uuid, i64>> {
+        self.repos.tweets.quote_counts(tweet_ids).await
+    }
+
+    /// tweet_ids のうち user_id がリツイートしているツイート
+    pub async fn retweeted_by(&self, user_id: Uuid, tweet_ids: &[Uuid]) -> Result<HashSet<Uuid>> {
+        self.repos.retweets.retweeted_by(user_id, tweet_ids).await
+    }
+
+    /// 各ツイートで言及されたユーザーID（言及のないツイートは含まれない）
+    pub async fn tweet_mentions(&self, tweet_ids: &[Uuid]) -> Result<HashMap<Uuid, Vec<Uuid>>> {
+        self.repos.mentions.for_tweets(tweet_ids).await
+    }
+
+    /// 各コメントで言及されたユーザーID（言及のないコメントは含まれない）
+    pub async fn comment_mentions(&self, comment_ids: &[Uuid]) -> Result<HashMap<Uuid, Vec<Uuid>>> {
+        self.repos.mentions.for_comments(comment_ids).await
+    }
+
+    /// user_id を言及したツイートの1ページ（新しい順）
+    pub async fn mentions_page(&self, user_id: Uuid, query: &PageQuery) -> Result<Page<Tweet>> {
+        self.repos.mentions.tweets_page(user_id, query).await
+    }
+
+    pub async fn mention_count(&self, user_id: Uuid) -> Result<i64> {
+        self.repos.mentions.tweet_count(user_id).await
+    }
+
+    /// 各ツイートのハッシュタグ（ハッシュタグのないツイートは含まれない）
+    pub async fn hashtags(&self, tweet_ids: &[Uuid]) -> Result<HashMap<Uuid, Vec<String>>> {
+        self.repos.hashtags.for_tweets(tweet_ids).await
+    }
+
+    /// いいね数・いいね状態・ハッシュタグをまとめて取得して付与する
+    async fn with_details(
+        &self,
+        tweets: Vec<Tweet>,
+        viewer: Option<Uuid>,
+    ) -> Result<Vec<TweetDetails>> {
+        if tweets.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let tweet_ids: Vec<Uuid> = tweets.iter().map(|t| t.id).collect();
+        let like_counts = self.repos.likes.counts(&tweet_ids).await?;
+        let liked = match viewer {
+            Some(viewer) => self.repos.likes.liked_by(viewer, &tweet_ids).await?,
+            None => HashSet::new(),
+        };
+        let mut hashtag_map = self.repos.hashtags.for_tweets(&tweet_ids).await?;
+
+        Ok(tweets
+            .into_iter()
+            .map(|tweet| TweetDetails {
+                like_count: *like_counts.get(&tweet.id).unwrap_or(&0),
+                is_liked: liked.contains(&tweet.id),
+                hashtags: hashtag_map.remove(&tweet.id).unwrap_or_default(),
+                tweet,
+            })
+            .collect())
+    }
+
+    pub async fn like(&self, user_id: Uuid, tweet_id: Uuid) -> Result<()> {
+        let tweet = self.find_existing(tweet_id).await?;
+        ensure_not_blocked(
+            &self.repos,
+            user_id,
+            tweet.user_id,
+            "Cannot like this tweet",
+        )
+        .await?;
+
+        let created_at = Utc::now().to_rfc3339();
+        if !self
+            .repos
+            .likes
+            .insert(user_id, tweet_id, &created_at)
+            .await?
+        {
+            return Err(AppError::Conflict("Already liked".to_string()));
+        }
+        notify(
+            &self.repos,
+            NotificationKind::Like,
+            tweet.user_id,
+            user_id,
+            Some(tweet_id),
+            None,
+        )
+        .await?;
+        self.events.publish(Event::TweetLiked {
+            tweet_id,
+            author_id: tweet.user_id,
+        });
+
+        Ok(())
+    }
+
+    pub async fn unlike(&self, user_id: Uuid, tweet_id: Uuid) -> Result<()> {
+        if !self.repos.likes.delete(user_id, tweet_id).await? {
+            return Err(AppError::NotFound("Like not found".to_string()));
+        }
+        Ok(())
+    }
+
+    /// リツイートする（フォロワーのタイムラインに載る）
+    pub async fn retweet(&self, user_id: Uuid, tweet_id: Uuid) -> Result<()> {
+        let tweet = self.find_existing(tweet_id).await?;
+        ensure_not_blocked(
+            &self.repos,
+            user_id,
+            tweet.user_id,
+            "Cannot retweet this tweet",
+        )
+        .await?;
+
+        let created_at = Utc::now().to_rfc3339();
+        if !self
+            .repos
+            .retweets
+            .insert(user_id, tweet_id, &created_at)
+            .await?
+        {
+            return Err(AppError::Conflict("Already retweeted".to_string()));
+        }
+        Ok(())
+    }
+
+    pub async fn undo_retweet(&self, user_id: Uuid, tweet_id: Uuid) -> Result<()> {
+        if !self.repos.retweets.delete(user_id, tweet_id).await? {
+            return Err(AppError::NotFound("Retweet not found".to_string()));
+        }
+        Ok(())
+    }
+
+    pub async fn find_comments(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, Comment>> {
+        self.repos.comments.find_many(ids).await
+    }
+
+    /// ツイートへのコメント一覧の1ページ（古い順、viewer がブロック・ミュートしたユーザーのものは除く）
+    pub async fn comments(
+        &self,
+        tweet_id: Uuid,
+        viewer: Option<Uuid>,
+        query: &PageQuery,
+    ) -> Result<Page<Comment>> {
+        self.repos
+            .comments
+            .list_for_tweet(tweet_id, viewer, query)
+            .await
+    }
+
+    pub async fn comment_count(&self, tweet_id: Uuid, viewer: Option<Uuid>) -> Result<i64> {
+        self.repos.comments.count_for_tweet(tweet_id, viewer).await
+    }
+
+    /// 各ツイートへの直接のコメント数（コメントのないツイートは含まれない）
+    pub async fn tweet_reply_counts(
+        &self,
+        tweet_ids: &[Uuid],
+        viewer: Option<Uuid>,
+    ) -> Result<HashMap<Uuid, i64>> {
+        self.repos
+            .comments
+            .top_level_counts(tweet_ids, viewer)
+            .await
+    }
+
+    /// 各コメントへの直接の返信数（返信のないコメントは含まれない）
+    pub async fn comment_reply_counts(
+        &self,
+        comment_ids: &[Uuid],
+        viewer: Option<Uuid>,
+    ) -> Result<HashMap<Uuid, i64>> {
+        self.repos.comments.reply_counts(comment_ids, viewer).await
+    }
+
+    /// ツイート（comment_id を指定すればそのコメント）を起点にしたスレッド
+    ///
+    /// ツイートがない場合・コメントがそのツイートのものでない場合は None
+    pub async fn conversation(
+        &self,
+        tweet_id: Uuid,
+        comment_id: Option<Uuid>,
+    ) -> Result<Option<Conversation>> {
+        let Some(tweet) = self.repos.tweets.find_by_id(tweet_id).await? else {
+            return Ok(None);
+        };
+        let Some(comment_id) = comment_id else {
+            return Ok(Some(Conversation {
+                tweet,
+                comment: None,
+                ancestors: Vec::new(),
+            }));
+        };
+
+        let comment = self.repos.comments.find_by_id(comment_id).await?;
+        let Some(comment) = comment.filter(|c| c.tweet_id == tweet_id) else {
+            return Ok(None);
+        };
+        let ancestors = self.repos.comments.ancestors(comment.id).await?;
+        Ok(Some(Conversation {
+            tweet,
+            comment: Some(comment),
+            ancestors,
+        }))
+    }
+
+    /// parent_id（None ならツイート）から depth 階層までの返信の1ページ（古い順）
+    pub async fn thread(
+        &self,
+        tweet_id: Uuid,
+        parent_id: Option<Uuid>,
+        depth: i64,
+        viewer: Option<Uuid>,
+        query: &PageQuery,
+    ) -> Result<Page<Comment>> {
+        validate_depth(depth)?;
+        self.repos
+            .comments
+            .thread_page(tweet_id, parent_id, depth, viewer, query)
+            .await
+    }
+
+    pub async fn thread_count(
+        &self,
+        tweet_id: Uuid,
+        parent_id: Option<Uuid>,
+        depth: i64,
+        viewer: Option<Uuid>,
+    ) -> Result<i64> {
+        validate_depth(depth)?;
+        self.repos
+            .comments
+            .thread_count(tweet_id, parent_id, depth, viewer)
+            .await
+    }
+
+    /// コメントする（parent_id を指定すれば同じツイートのコメントへの返信になる）
+    pub async fn create_comment(
+        &self,
+        user_id: Uuid,
+        tweet_id: Uuid,
+        parent_id: Option<Uuid>,
+        content: &str,
+    ) -> Result<Comment> {
+        validate_content("Comment", content)?;
+        let tweet = self.find_existing(tweet_id).await?;
+        ensure_not_blocked(
+            &self.repos,
+            user_id,
+            tweet.user_id,
+            "Cannot comment on this tweet",
+        )
+        .await?;
+        if let Some(parent_id) = parent_id {
+            let parent = self.repos.comments.find_by_id(parent_id).await?;
+            if parent.is_none_or(|p| p.tweet_id != tweet_id) {
+                return Err(AppError::NotFound("Parent comment not found".to_string()));
+            }
+        }
+
+        let comment = Comment {
+            id: Uuid::new_v4(),
+            tweet_id,
+            user_id,
+            content: content.to_string(),
+            created_at: Utc::now().to_rfc3339(),
+            parent_id,
+        };
+
+        let mentions = self.resolve_mentions(content).await?;
+        self.repos.comments.insert(&comment, &mentions).await?;
+        // 投稿者が本文で言及されていれば、コメントの通知ではなく言及の通知だけを送る
+        if !mentions.contains(&tweet.user_id) {
+            notify(
+                &self.repos,
+                NotificationKind::Comment,
+                tweet.user_id,
+                user_id,
+                Some(tweet_id),
+                Some(comment.id),
+            )
+            .await?;
+        }
+        for &mentioned in &mentions {
+            notify(
+                &self.repos,
+                NotificationKind::Mention,
+                mentioned,
+                user_id,
+                Some(tweet_id),
+                Some(comment.id),
+            )
+            .await?;
+        }
+        self.events.publish(Event::CommentAdded(comment.clone()));
+
+        Ok(comment)
+    }
+
+    /// コメントを返信ごと削除する（他のユーザーのコメントは DeleteAnyContent 権限があれば削除でき、監査ログに残す）
+    pub async fn delete_comment(&self, actor: Actor, id: Uuid) -> Result<()> {
+        let not_found = || AppError::NotFound("Comment not found or not authorized".to_string());
+
+        let comment = self
+            .repos
+            .comments
+            .find_by_id(id)
+            .await?
+            .ok_or_else(not_found)?;
+        let own = comment.user_id == actor.user_id;
+        if !own && !actor.role.can(Permission::DeleteAnyContent) {
+            return Err(not_found());
+        }
+        if !self
+            .repos
+            .comments
+            .delete(id, own.then_some(actor.user_id))
+            .await?
+        {
+            return Err(not_found());
+        }
+
+        if !own {
+            record(
+                &self.repos,
+                actor,
+                ModerationActionKind::DeleteComment,
+                id,
+                None,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// 本文の @username を投稿時点で存在するユーザーのIDにする（存在しないユーザー名は無視する）
+    async fn resolve_mentions(&self, content: &str) -> Result<Vec<Uuid>> {
+        let usernames = extract_mentions(content);
+        let users = self.repos.users.find_by_usernames(&usernames).await?;
+        Ok(users.into_iter().map(|u| u.id).collect())
+    }
+
+    async fn find_existing(&self, tweet_id: Uuid) -> Result<Tweet> {
+        self.repos
+            .tweets
+            .find_by_id(tweet_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Tweet not found".to_string()))
+    }
+}
+
+/// 本文が1〜280文字であることを確認する
+fn validate_content(kind: &str, content: &str) -> Result<()> {
+    if content.is_empty() || content.len() > MAX_CONTENT_LENGTH {
+        return Err(AppError::validation(
+            "content",
+            format!(
+                "{} content must be between 1 and {} characters",
+                kind, MAX_CONTENT_LENGTH
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// 返信スレッドの深さが 1〜MAX_THREAD_DEPTH であることを確認する
+fn validate_depth(depth: i64) -> Result<()> {
+    if !(1..=MAX_THREAD_DEPTH).contains(&depth) {
+        return Err(AppError::validation(
+            "depth",
+            format!("Depth must be between 1 and {}", MAX_THREAD_DEPTH),
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use chrono::Utc;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::models::{Role, Session, User};
+use crate::repository::Repositories;
+use crate::sessions::{
+    ClientInfo, ensure_active, list_sessions, revoke_other_sessions, revoke_session,
+};
+use crate::tokens::{revoke_refresh_token, rotate_refresh_token, start_session};
+use crate::utils::{create_jwt, hash_password, verify_password};
+
+type Result<T> = std::result::Result<T, AppError>;
+
+/// ログイン・登録・トークン更新の結果
+pub struct AuthSession {
+    pub token: String,
+    pub refresh_token: String,
+    pub user: User,
+}
+
+/// アカウントと認証に関するビジネスルール
+#[derive(Clone)]
+pub struct UserService {
+    repos: Repositories,
+}
+
+impl UserService {
+    pub fn new(repos: Repositories) -> Self {
+        Self { repos }
+    }
+
+    /// ユーザーを登録し、ログインセッションを開始する
+    pub async fn register(
+        &self,
+        username: &str,
+        email: &str,
+        password: &str,
+        client: &ClientInfo,
+    ) -> Result<AuthSession> {
+        if self.repos.users.find_by_email(email).await?.is_some() {
+            return Err(AppError::Conflict("Email already exists".to_string()));
+        }
+        if self.repos.users.find_by_username(username).await?.is_some() {
+            return Err(AppError::Conflict("Username already exists".to_string()));
+        }
+
+        let user = User {
+            id: Uuid::new_v4(),
+            username: username.to_string(),
+            email: email.to_string(),
+            password_hash: hash_password(password)?,
+            created_at: Utc::now().to_rfc3339(),
+            role: Role::User,
+            suspended_at: None,
+            allow_open_dms: false,
+        };
+
+        self.repos.users.insert(&user).await?;
+
+        self.start(user, client).await
+    }
+
+    /// メールアドレスとパスワードで認証し、ログインセッションを開始する
+    pub async fn login(
+        &self,
+        email: &str,
+        password: &str,
+        client: &ClientInfo,
+    ) -> Result<AuthSession> {
+        let user = self
+            .repos
+            .users
+            .find_by_email(email)
+            .await?
+            .ok_or_else(|| AppError::Unauthorized("Invalid email or password".to_string()))?;
+
+        if !verify_password(password, &user.password_hash)? {
+            return Err(AppError::Unauthorized(
+                "Invalid email or password".to_string(),
+            ));
+        }
+        if user.suspended_at.is_some() {
+            return Err(AppError::Forbidden("Account is suspended".to_string()));
+        }
+
+        self.start(user, client).await
+    }
+
+    async fn start(&self, user: User, client: &ClientInfo) -> Result<AuthSession> {
+        let (token, refresh_token) = start_session(&self.repos, user.id, user.role, client).await?;
+        Ok(AuthSession {
+            token,
+            refresh_token,
+            user,
+        })
+    }
+
+    /// リフレッシュトークンをローテーションし、新しいトークンの組を発行する（ロールの変更はここで反映される）
+    pub async fn refresh(&self, refresh_token: &str) -> Result<AuthSession> {
+        let (user_id, session_id, refresh_token) =
+            rotate_refresh_token(&self.repos, refresh_token).await?;
+
+        let user = self
+            .find(user_id)
+            .await?
+            .ok_or_else(|| AppError::Unauthorized("User no longer exists".to_string()))?;
+
+        Ok(AuthSession {
+            token: create_jwt(user.id, session_id, user.role)?,
+            refresh_token,
+            user,
+        })
+    }
+
+    /// ログアウト: リフレッシュトークンのファミリーとセッションを失効させる
+    pub async fn logout(&self, refresh_token: &str) -> Result<()> {
+        revoke_refresh_token(&self.repos, refresh_token).await
+    }
+
+    pub async fn find(&self, id: Uuid) -> Result<Option<User>> {
+        self.repos.users.find_by_id(id).await
+    }
+
+    /// 複数のユーザーをまとめて取得する
+    pub async fn find_many(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, User>> {
+        let users = self.repos.users.find_by_ids(ids).await?;
+        Ok(users.into_iter().map(|user| (user.id, user)).collect())
+    }
+
+    /// ログイン中のセッション一覧
+    pub async fn sessions(&self, user_id: Uuid) -> Result<Vec<Session>> {
+        list_sessions(&self.repos, user_id).await
+    }
+
+    /// セッションがまだ有効か確認する（失効していれば Unauthorized）
+    pub async fn ensure_session(&self, user_id: Uuid, session_id: Uuid) -> Result<()> {
+        ensure_active(&self.repos, session_id, user_id).await
+    }
+
+    /// 指定したセッションを失効させる
+    pub async fn revoke_session(&self, user_id: Uuid, session_id: Uuid) -> Result<()> {
+        revoke_session(&self.repos, user_id, session_id).await
+    }
+
+    /// 現在のセッション以外をすべて失効させ、失効させた件数を返す
+    pub async fn revoke_other_sessions(&self, user_id: Uuid, current: Uuid) -> Result<u64> {
+        revoke_other_sessions(&self.repos, user_id, current).await
+    }
+}
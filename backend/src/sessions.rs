@@ -0,0 +1,118 @@
+use actix_web::HttpRequest;
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::models::Session;
+use crate::repository::Repositories;
+
+type Result<T> = std::result::Result<T, AppError>;
+
+/// last_seen_at を更新する最小間隔（リクエストごとの書き込みを避ける）
+const TOUCH_INTERVAL_SECONDS: i64 = 60;
+
+/// ログイン元の端末情報
+#[derive(Debug, Clone, Default)]
+pub struct ClientInfo {
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+impl ClientInfo {
+    pub fn from_request(req: &HttpRequest) -> Self {
+        Self {
+            user_agent: req
+                .headers()
+                .get(actix_web::http::header::USER_AGENT)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string),
+            ip_address: req.peer_addr().map(|addr| addr.ip().to_string()),
+        }
+    }
+}
+
+/// GraphQLコンテキストに載せる現在のセッションID
+#[derive(Debug, Clone, Copy)]
+pub struct CurrentSession(pub Uuid);
+
+/// ログインごとにセッションを作成する
+pub async fn create_session(
+    repos: &Repositories,
+    user_id: Uuid,
+    client: &ClientInfo,
+) -> Result<Uuid> {
+    let now = Utc::now().to_rfc3339();
+    let session = Session {
+        id: Uuid::new_v4(),
+        user_id,
+        user_agent: client.user_agent.clone(),
+        ip_address: client.ip_address.clone(),
+        created_at: now.clone(),
+        last_seen_at: now,
+        revoked_at: None,
+    };
+
+    repos.sessions.insert(&session).await?;
+
+    Ok(session.id)
+}
+
+/// セッションが有効か確認し、最終アクセス日時を更新する
+pub async fn ensure_active(repos: &Repositories, session_id: Uuid, user_id: Uuid) -> Result<()> {
+    let session = repos
+        .sessions
+        .find(session_id, user_id)
+        .await?
+        .ok_or_else(|| AppError::Unauthorized("Session not found".to_string()))?;
+
+    if session.revoked_at.is_some() {
+        return Err(AppError::Unauthorized(
+            "Session has been revoked".to_string(),
+        ));
+    }
+
+    let now = Utc::now();
+    let threshold = (now - Duration::seconds(TOUCH_INTERVAL_SECONDS)).to_rfc3339();
+    if session.last_seen_at < threshold {
+        repos.sessions.touch(session_id, &now.to_rfc3339()).await?;
+    }
+
+    Ok(())
+}
+
+/// ユーザーの有効なセッション一覧（最終アクセスの新しい順）
+pub async fn list_sessions(repos: &Repositories, user_id: Uuid) -> Result<Vec<Session>> {
+    repos.sessions.list_active(user_id).await
+}
+
+/// セッションと、それに紐づくリフレッシュトークンを失効させる
+pub async fn revoke_session(repos: &Repositories, user_id: Uuid, session_id: Uuid) -> Result<()> {
+    let now = Utc::now().to_rfc3339();
+
+    if !repos
+        .sessions
+        .revoke(session_id, Some(user_id), &now)
+        .await?
+    {
+        return Err(AppError::NotFound("Session not found".to_string()));
+    }
+
+    repos.refresh_tokens.revoke_family(session_id, &now).await
+}
+
+/// 現在のセッション以外をすべて失効させ、失効させた件数を返す
+pub async fn revoke_other_sessions(
+    repos: &Repositories,
+    user_id: Uuid,
+    current: Uuid,
+) -> Result<u64> {
+    let now = Utc::now().to_rfc3339();
+
+    let revoked = repos.sessions.revoke_others(user_id, current, &now).await?;
+    repos
+        .refresh_tokens
+        .revoke_other_families(user_id, current, &now)
+        .await?;
+
+    Ok(revoked)
+}
@@ -0,0 +1,366 @@
+use serde_json::{Value, json};
+
+use super::{TestApp, TestUser, contents, data, error_extensions};
+
+const LIKE: &str = "mutation($id: UUID!) { likeTweet(tweetId: $id) }";
+
+/// targetId を受け取るミューテーションを実行する
+async fn act(app: &TestApp, user: &TestUser, mutation: &str, target: &TestUser) -> Value {
+    app.execute(
+        &format!("mutation($id: UUID!) {{ {}(targetId: $id) }}", mutation),
+        json!({ "id": target.id }),
+        Some(user),
+    )
+    .await
+}
+
+async fn comments(app: &TestApp, user: Option<&TestUser>, tweet_id: &str) -> Value {
+    let resp = app
+        .execute(
+            "query($id: UUID!) { comments(tweetId: $id) { totalCount nodes { content } } }",
+            json!({ "id": tweet_id }),
+            user,
+        )
+        .await;
+    data(&resp)["comments"].clone()
+}
+
+async fn search(app: &TestApp, user: Option<&TestUser>, query: &str, search_type: &str) -> Value {
+    let resp = app
+        .execute(
+            r#"
+            query($q: String!, $type: SearchType!) {
+                search(query: $q, type: $type) {
+                    nodes {
+                        ... on TweetType { content }
+                        ... on CommentType { content }
+                    }
+                }
+            }
+            "#,
+            json!({ "q": query, "type": search_type }),
+            user,
+        )
+        .await;
+    data(&resp)["search"].clone()
+}
+
+async fn notification_count(app: &TestApp, user: &TestUser) -> Value {
+    let resp = app
+        .execute("{ notifications { totalCount } }", json!({}), Some(user))
+        .await;
+    data(&resp)["notifications"]["totalCount"].clone()
+}
+
+async fn follow_counts(app: &TestApp, user: &TestUser) -> Value {
+    let resp = app
+        .execute(
+            "query($id: UUID!) { user(id: $id) { followersCount followingCount } }",
+            json!({ "id": user.id }),
+            None,
+        )
+        .await;
+    data(&resp)["user"].clone()
+}
+
+#[actix_rt::test]
+async fn blocking_removes_follows_and_prevents_interaction() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let tweet_id = app.tweet(&alice, "hello").await;
+
+    act(&app, &alice, "followUser", &bob).await;
+    act(&app, &bob, "followUser", &alice).await;
+    let retweet = "mutation($id: UUID!) { retweet(tweetId: $id) }";
+    data(
+        &app.execute(retweet, json!({ "id": tweet_id }), Some(&bob))
+            .await,
+    );
+    let resp = act(&app, &alice, "blockUser", &bob).await;
+    assert_eq!(data(&resp)["blockUser"], json!(bob.id));
+
+    // 相手のツイートのリツイートも取り消される
+    let resp = app
+        .execute(
+            "query($id: UUID!) { tweet(id: $id) { retweetCount } }",
+            json!({ "id": tweet_id }),
+            None,
+        )
+        .await;
+    assert_eq!(data(&resp)["tweet"]["retweetCount"], 0);
+
+    // フォローはどちらの向きも解除される
+    assert_eq!(
+        follow_counts(&app, &alice).await,
+        json!({ "followersCount": 0, "followingCount": 0 })
+    );
+
+    // ブロックされた側からもブロックした側からもフォローできない
+    for (user, target) in [(&bob, &alice), (&alice, &bob)] {
+        let resp = act(&app, user, "followUser", target).await;
+        assert_eq!(error_extensions(&resp)["code"], "FORBIDDEN");
+    }
+    let resp = app
+        .execute(LIKE, json!({ "id": tweet_id }), Some(&bob))
+        .await;
+    assert_eq!(error_extensions(&resp)["code"], "FORBIDDEN");
+    let resp = app
+        .execute(
+            "mutation($id: UUID!) { createComment(tweetId: $id, content: \"hi\") { id } }",
+            json!({ "id": tweet_id }),
+            Some(&bob),
+        )
+        .await;
+    assert_eq!(error_extensions(&resp)["code"], "FORBIDDEN");
+    let resp = app
+        .execute(retweet, json!({ "id": tweet_id }), Some(&bob))
+        .await;
+    assert_eq!(error_extensions(&resp)["code"], "FORBIDDEN");
+    let resp = app
+        .execute(
+            "mutation($id: UUID!) { quoteTweet(tweetId: $id, content: \"look\") { id } }",
+            json!({ "id": tweet_id }),
+            Some(&bob),
+        )
+        .await;
+    assert_eq!(error_extensions(&resp)["code"], "FORBIDDEN");
+
+    // 誰からでも受け取る設定でもメッセージは送れない
+    app.execute(
+        "mutation { setAllowOpenDirectMessages(allow: true) }",
+        json!({}),
+        Some(&alice),
+    )
+    .await;
+    let resp = app
+        .execute(
+            "mutation($id: UUID!) { sendDirectMessage(recipientIds: [$id], content: \"hi\") { id } }",
+            json!({ "id": alice.id }),
+            Some(&bob),
+        )
+        .await;
+    assert_eq!(error_extensions(&resp)["code"], "FORBIDDEN");
+
+    let resp = act(&app, &alice, "blockUser", &bob).await;
+    assert_eq!(error_extensions(&resp)["code"], "CONFLICT");
+
+    // 解除すればまたやりとりできる（フォローは戻らない）
+    let resp = act(&app, &alice, "unblockUser", &bob).await;
+    assert_eq!(data(&resp)["unblockUser"], json!(bob.id));
+    assert_eq!(follow_counts(&app, &bob).await["followersCount"], 0);
+    app.like(&bob, &tweet_id).await;
+    let resp = act(&app, &bob, "followUser", &alice).await;
+    assert_eq!(data(&resp)["followUser"], json!(alice.id));
+}
+
+#[actix_rt::test]
+async fn blocking_stops_messages_in_existing_conversations() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    act(&app, &alice, "followUser", &bob).await;
+    act(&app, &bob, "followUser", &alice).await;
+
+    let resp = app
+        .execute(
+            "mutation($id: UUID!) { sendDirectMessage(recipientIds: [$id], content: \"hi\") { conversationId } }",
+            json!({ "id": alice.id }),
+            Some(&bob),
+        )
+        .await;
+    let conversation_id = data(&resp)["sendDirectMessage"]["conversationId"].clone();
+
+    act(&app, &alice, "blockUser", &bob).await;
+    for user in [&bob, &alice] {
+        let resp = app
+            .execute(
+                "mutation($id: UUID!) { sendDirectMessage(conversationId: $id, content: \"hi\") { id } }",
+                json!({ "id": conversation_id }),
+                Some(user),
+            )
+            .await;
+        assert_eq!(error_extensions(&resp)["code"], "FORBIDDEN");
+    }
+}
+
+#[actix_rt::test]
+async fn muted_and_blocking_users_are_hidden_from_timeline_comments_and_search() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let carol = app.register("carol").await;
+    let dave = app.register("dave").await;
+    act(&app, &alice, "followUser", &bob).await;
+    act(&app, &alice, "followUser", &carol).await;
+
+    let own_id = app.tweet(&alice, "alice topic").await;
+    let bob_tweet = app.tweet(&bob, "bob topic").await;
+    app.tweet(&carol, "carol topic").await;
+    app.execute(
+        "mutation($id: UUID!) { retweet(tweetId: $id) }",
+        json!({ "id": bob_tweet }),
+        Some(&carol),
+    )
+    .await;
+    for (user, content) in [
+        (&bob, "bob says"),
+        (&carol, "carol says"),
+        (&dave, "dave says"),
+    ] {
+        app.comment(user, &own_id, None, content).await;
+    }
+
+    act(&app, &alice, "muteUser", &bob).await;
+    // dave が alice をブロックすると、alice からも dave は見えない
+    act(&app, &dave, "blockUser", &alice).await;
+
+    // ミュートした相手の投稿も、そのリツイートも載らない
+    let result = app.timeline(&alice).await;
+    assert_eq!(contents(&result), vec!["carol topic", "alice topic"]);
+    assert_eq!(result["totalCount"], 2);
+
+    let result = comments(&app, Some(&alice), &own_id).await;
+    assert_eq!(contents(&result), vec!["carol says"]);
+    assert_eq!(result["totalCount"], 1);
+    // 他のユーザーと未ログインには見える
+    let result = comments(&app, Some(&carol), &own_id).await;
+    assert_eq!(result["totalCount"], 3);
+    assert_eq!(comments(&app, None, &own_id).await["totalCount"], 3);
+
+    let result = search(&app, Some(&alice), "topic", "TWEETS").await;
+    assert_eq!(contents(&result), vec!["carol topic", "alice topic"]);
+    let result = search(&app, Some(&alice), "says", "COMMENTS").await;
+    assert_eq!(contents(&result), vec!["carol says"]);
+    let result = search(&app, None, "topic", "TWEETS").await;
+    assert_eq!(result["nodes"].as_array().unwrap().len(), 3);
+
+    // ミュートではフォローは解除されず、解除すれば元に戻る
+    let resp = act(&app, &alice, "unmuteUser", &bob).await;
+    assert_eq!(data(&resp)["unmuteUser"], json!(bob.id));
+    assert_eq!(
+        contents(&app.timeline(&alice).await),
+        vec!["bob topic", "carol topic", "alice topic"]
+    );
+    assert_eq!(comments(&app, Some(&alice), &own_id).await["totalCount"], 2);
+}
+
+#[actix_rt::test]
+async fn muted_users_are_hidden_from_threads_and_reply_counts() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let carol = app.register("carol").await;
+    let tweet_id = app.tweet(&alice, "hello").await;
+
+    let bob_comment = app.comment(&bob, &tweet_id, None, "bob says").await;
+    app.comment(&carol, &tweet_id, None, "carol says").await;
+    let carol_reply = app
+        .comment(&carol, &tweet_id, Some(&bob_comment), "re")
+        .await;
+    app.comment(&bob, &tweet_id, Some(&carol_reply), "re").await;
+    act(&app, &alice, "muteUser", &carol).await;
+
+    let query = r#"
+        query($id: UUID!) {
+            tweet(id: $id) { replyCount }
+            conversation(tweetId: $id) {
+                replies(first: 10) { totalCount nodes { user { username } replyCount } }
+            }
+        }
+    "#;
+    // ミュートした相手のコメントは、その下の返信ごと数えない
+    let resp = app
+        .execute(query, json!({ "id": tweet_id }), Some(&alice))
+        .await;
+    assert_eq!(data(&resp)["tweet"]["replyCount"], 1);
+    assert_eq!(
+        data(&resp)["conversation"]["replies"],
+        json!({ "totalCount": 1, "nodes": [{ "user": { "username": "bob" }, "replyCount": 0 }] })
+    );
+
+    let resp = app.execute(query, json!({ "id": tweet_id }), None).await;
+    assert_eq!(data(&resp)["tweet"]["replyCount"], 2);
+    assert_eq!(data(&resp)["conversation"]["replies"]["totalCount"], 4);
+    assert_eq!(
+        data(&resp)["conversation"]["replies"]["nodes"][0]["replyCount"],
+        1
+    );
+}
+
+#[actix_rt::test]
+async fn muted_and_blocked_users_do_not_notify() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let carol = app.register("carol").await;
+    let tweet_id = app.tweet(&alice, "hello").await;
+
+    app.like(&bob, &tweet_id).await;
+    app.like(&carol, &tweet_id).await;
+    act(&app, &carol, "followUser", &alice).await;
+    assert_eq!(notification_count(&app, &alice).await, 2);
+
+    // ミュートするとそれまでの通知も消え、まとまりの人数も減る
+    act(&app, &alice, "muteUser", &bob).await;
+    let resp = app
+        .execute(
+            "{ notifications { nodes { summary } } }",
+            json!({}),
+            Some(&alice),
+        )
+        .await;
+    assert_eq!(
+        data(&resp)["notifications"]["nodes"],
+        json!([
+            { "summary": "carol followed you" },
+            { "summary": "carol liked your tweet" },
+        ])
+    );
+    app.comment(&bob, &tweet_id, None, "@alice hi").await;
+    app.tweet(&bob, "@alice hey").await;
+    assert_eq!(notification_count(&app, &alice).await, 2);
+
+    act(&app, &alice, "blockUser", &carol).await;
+    assert_eq!(notification_count(&app, &alice).await, 0);
+
+    // ミュートを解除すれば以降の通知は届く
+    act(&app, &alice, "unmuteUser", &bob).await;
+    app.tweet(&bob, "@alice again").await;
+    assert_eq!(notification_count(&app, &alice).await, 1);
+}
+
+#[actix_rt::test]
+async fn invalid_blocks_and_mutes_are_rejected() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+
+    for mutation in ["blockUser", "muteUser"] {
+        let resp = act(&app, &alice, mutation, &alice).await;
+        assert_eq!(
+            error_extensions(&resp)["code"],
+            "BAD_REQUEST",
+            "{}",
+            mutation
+        );
+
+        let resp = app
+            .execute(
+                &format!("mutation($id: UUID!) {{ {}(targetId: $id) }}", mutation),
+                json!({ "id": uuid::Uuid::new_v4() }),
+                Some(&alice),
+            )
+            .await;
+        assert_eq!(error_extensions(&resp)["code"], "NOT_FOUND", "{}", mutation);
+    }
+
+    for mutation in ["unblockUser", "unmuteUser"] {
+        let resp = act(&app, &alice, mutation, &bob).await;
+        assert_eq!(error_extensions(&resp)["code"], "NOT_FOUND", "{}", mutation);
+    }
+
+    act(&app, &alice, "muteUser", &bob).await;
+    let resp = act(&app, &alice, "muteUser", &bob).await;
+    assert_eq!(error_extensions(&resp)["code"], "CONFLICT");
+}
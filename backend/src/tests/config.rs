@@ -0,0 +1,60 @@
+use crate::config::{Config, Environment, VerificationKeyConfig};
+use crate::keys::DEV_SECRET;
+use jsonwebtoken::Algorithm;
+
+const SECRET: &str = "a-random-secret-that-is-at-least-32-bytes";
+
+fn hs256_key(kid: &str, secret: Option<&str>) -> VerificationKeyConfig {
+    VerificationKeyConfig {
+        kid: kid.to_string(),
+        algorithm: Algorithm::HS256,
+        secret: secret.map(str::to_string),
+        public_key_path: None,
+    }
+}
+
+#[test]
+fn example_jwt_secret_is_rejected_in_production() {
+    let mut config = Config::default();
+    config.jwt.secret = Some("change-me-to-a-random-string-of-32-bytes".to_string());
+    let err = config.validate().err().unwrap();
+    assert!(err.to_string().contains("example value"), "{}", err);
+
+    // 開発モードでは使える
+    config.server.environment = Environment::Development;
+    assert!(config.validate().is_ok());
+
+    config.server.environment = Environment::Production;
+    config.jwt.secret = Some(SECRET.to_string());
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn verification_key_secrets_are_checked_in_production() {
+    let mut config = Config::default();
+    config.jwt.secret = Some(SECRET.to_string());
+
+    for (secret, message) in [
+        (None, "needs a secret"),
+        (Some(""), "must not be empty"),
+        (Some(DEV_SECRET), "only allowed in development"),
+        (
+            Some("change-me-to-a-random-string-of-32-bytes"),
+            "example value",
+        ),
+        (Some("short"), "at least 32 bytes"),
+    ] {
+        config.jwt.verification_keys = vec![hs256_key("old", secret)];
+        let err = config.validate().err().unwrap();
+        assert!(err.to_string().contains(message), "{}", err);
+    }
+
+    // 開発モードでは既定のシークレットも使える
+    config.server.environment = Environment::Development;
+    config.jwt.verification_keys = vec![hs256_key("old", Some(DEV_SECRET))];
+    assert!(config.validate().is_ok());
+
+    config.server.environment = Environment::Production;
+    config.jwt.verification_keys = vec![hs256_key("old", Some(SECRET))];
+    assert!(config.validate().is_ok());
+}
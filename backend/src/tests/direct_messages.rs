@@ -0,0 +1,363 @@
+use serde_json::{Value, json};
+
+use super::{TestApp, TestUser, contents, data, error_extensions};
+
+const SEND: &str = r#"
+    mutation($conversationId: UUID, $recipientIds: [UUID!], $content: String!) {
+        sendDirectMessage(
+            conversationId: $conversationId, recipientIds: $recipientIds, content: $content
+        ) { id conversationId content sender { username } }
+    }
+"#;
+
+const CONVERSATIONS: &str = r#"
+    query($first: Int, $after: String) {
+        conversations(first: $first, after: $after) {
+            totalCount
+            pageInfo { hasNextPage endCursor }
+            nodes {
+                id unreadCount
+                members { username }
+                lastMessage { content }
+            }
+        }
+    }
+"#;
+
+const MESSAGES: &str = r#"
+    query($id: UUID!, $first: Int, $after: String) {
+        messages(conversationId: $id, first: $first, after: $after) {
+            totalCount
+            pageInfo { hasNextPage endCursor }
+            nodes { content sender { username } readBy { username } }
+        }
+    }
+"#;
+
+async fn follow_each_other(app: &TestApp, a: &TestUser, b: &TestUser) {
+    app.follow(a, b).await;
+    app.follow(b, a).await;
+}
+
+async fn send_to(app: &TestApp, user: &TestUser, recipients: &[&TestUser], content: &str) -> Value {
+    let ids: Vec<_> = recipients.iter().map(|r| r.id).collect();
+    app.execute(
+        SEND,
+        json!({ "recipientIds": ids, "content": content }),
+        Some(user),
+    )
+    .await
+}
+
+/// 会話にメッセージを送り、会話の ID を返す
+async fn send(app: &TestApp, user: &TestUser, conversation_id: &str, content: &str) -> String {
+    let resp = app
+        .execute(
+            SEND,
+            json!({ "conversationId": conversation_id, "content": content }),
+            Some(user),
+        )
+        .await;
+    data(&resp)["sendDirectMessage"]["conversationId"]
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+async fn start(app: &TestApp, user: &TestUser, recipients: &[&TestUser], content: &str) -> String {
+    let resp = send_to(app, user, recipients, content).await;
+    data(&resp)["sendDirectMessage"]["conversationId"]
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+async fn conversations(app: &TestApp, user: &TestUser) -> Value {
+    let resp = app.execute(CONVERSATIONS, json!({}), Some(user)).await;
+    data(&resp)["conversations"].clone()
+}
+
+async fn messages(app: &TestApp, user: &TestUser, conversation_id: &str) -> Value {
+    let resp = app
+        .execute(MESSAGES, json!({ "id": conversation_id }), Some(user))
+        .await;
+    data(&resp)["messages"].clone()
+}
+
+#[actix_rt::test]
+async fn only_mutual_followers_can_start_a_conversation() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+
+    // 片方だけのフォローでは始められない
+    app.follow(&alice, &bob).await;
+    let resp = send_to(&app, &alice, &[&bob], "hi").await;
+    assert_eq!(error_extensions(&resp)["code"], "FORBIDDEN");
+
+    app.follow(&bob, &alice).await;
+    let resp = send_to(&app, &alice, &[&bob], "hi").await;
+    let message = &data(&resp)["sendDirectMessage"];
+    assert_eq!(message["content"], "hi");
+    assert_eq!(message["sender"]["username"], "alice");
+
+    // 同じ相手には同じ会話を使う
+    let conversation_id = message["conversationId"].as_str().unwrap();
+    assert_eq!(start(&app, &bob, &[&alice], "hey").await, conversation_id);
+    let result = conversations(&app, &alice).await;
+    assert_eq!(result["totalCount"], 1);
+    let mut members: Vec<&str> = result["nodes"][0]["members"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["username"].as_str().unwrap())
+        .collect();
+    members.sort();
+    assert_eq!(members, vec!["alice", "bob"]);
+    assert_eq!(result["nodes"][0]["lastMessage"]["content"], "hey");
+}
+
+#[actix_rt::test]
+async fn users_who_allow_open_messages_can_be_messaged_by_anyone() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+
+    let resp = app
+        .execute(
+            "mutation { setAllowOpenDirectMessages(allow: true) }",
+            json!({}),
+            Some(&bob),
+        )
+        .await;
+    assert_eq!(data(&resp)["setAllowOpenDirectMessages"], true);
+    let resp = app
+        .execute(
+            "query($id: UUID!) { user(id: $id) { allowsOpenDirectMessages } }",
+            json!({ "id": bob.id }),
+            None,
+        )
+        .await;
+    assert_eq!(data(&resp)["user"]["allowsOpenDirectMessages"], true);
+
+    let conversation_id = start(&app, &alice, &[&bob], "hello").await;
+
+    // 会話が始まれば、設定を戻してもやりとりを続けられる
+    app.execute(
+        "mutation { setAllowOpenDirectMessages(allow: false) }",
+        json!({}),
+        Some(&bob),
+    )
+    .await;
+    send(&app, &alice, &conversation_id, "still there?").await;
+    send(&app, &bob, &conversation_id, "yes").await;
+    assert_eq!(
+        contents(&messages(&app, &alice, &conversation_id).await),
+        vec!["yes", "still there?", "hello"]
+    );
+
+    // 新しい会話は始められない
+    let carol = app.register("carol").await;
+    let resp = send_to(&app, &alice, &[&bob, &carol], "group").await;
+    assert_eq!(error_extensions(&resp)["code"], "FORBIDDEN");
+}
+
+#[actix_rt::test]
+async fn group_conversations_are_only_visible_to_members() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let carol = app.register("carol").await;
+    let dave = app.register("dave").await;
+    follow_each_other(&app, &alice, &bob).await;
+    follow_each_other(&app, &alice, &carol).await;
+
+    let group_id = start(&app, &alice, &[&bob, &carol], "hi all").await;
+    let pair_id = start(&app, &alice, &[&bob], "just you").await;
+    assert_ne!(group_id, pair_id);
+
+    // 参加者なら誰でも送れる
+    send(&app, &carol, &group_id, "hi alice").await;
+    assert_eq!(conversations(&app, &bob).await["totalCount"], 2);
+    assert_eq!(conversations(&app, &carol).await["totalCount"], 1);
+
+    // 参加者以外には会話がないものとして扱う
+    assert_eq!(conversations(&app, &dave).await["totalCount"], 0);
+    let resp = app
+        .execute(MESSAGES, json!({ "id": group_id }), Some(&dave))
+        .await;
+    assert_eq!(error_extensions(&resp)["code"], "NOT_FOUND");
+    let resp = app
+        .execute(
+            SEND,
+            json!({ "conversationId": group_id, "content": "let me in" }),
+            Some(&dave),
+        )
+        .await;
+    assert_eq!(error_extensions(&resp)["code"], "NOT_FOUND");
+    let resp = app
+        .execute(
+            "mutation($id: UUID!) { markConversationRead(conversationId: $id) }",
+            json!({ "id": group_id }),
+            Some(&dave),
+        )
+        .await;
+    assert_eq!(error_extensions(&resp)["code"], "NOT_FOUND");
+}
+
+#[actix_rt::test]
+async fn conversations_and_messages_are_paginated() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let carol = app.register("carol").await;
+    follow_each_other(&app, &alice, &bob).await;
+    follow_each_other(&app, &alice, &carol).await;
+
+    let with_bob = start(&app, &alice, &[&bob], "message 0").await;
+    for i in 1..3 {
+        send(&app, &alice, &with_bob, &format!("message {}", i)).await;
+    }
+
+    let resp = app
+        .execute(
+            MESSAGES,
+            json!({ "id": with_bob, "first": 2 }),
+            Some(&alice),
+        )
+        .await;
+    let page = &data(&resp)["messages"];
+    assert_eq!(contents(page), vec!["message 2", "message 1"]);
+    assert_eq!(page["totalCount"], 3);
+    assert_eq!(page["pageInfo"]["hasNextPage"], true);
+
+    let resp = app
+        .execute(
+            MESSAGES,
+            json!({ "id": with_bob, "first": 2, "after": page["pageInfo"]["endCursor"] }),
+            Some(&alice),
+        )
+        .await;
+    let page = &data(&resp)["messages"];
+    assert_eq!(contents(page), vec!["message 0"]);
+    assert_eq!(page["pageInfo"]["hasNextPage"], false);
+
+    // 会話は最後のメッセージの新しい順
+    let with_carol = start(&app, &alice, &[&carol], "to carol").await;
+    let resp = app
+        .execute(CONVERSATIONS, json!({ "first": 1 }), Some(&alice))
+        .await;
+    let page = &data(&resp)["conversations"];
+    assert_eq!(page["nodes"][0]["id"], with_carol);
+    assert_eq!(page["totalCount"], 2);
+    assert_eq!(page["pageInfo"]["hasNextPage"], true);
+
+    send(&app, &bob, &with_bob, "back to bob").await;
+    let result = conversations(&app, &alice).await;
+    assert_eq!(result["nodes"][0]["id"], with_bob);
+    assert_eq!(result["nodes"][1]["id"], with_carol);
+}
+
+#[actix_rt::test]
+async fn read_receipts_track_what_each_member_has_read() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    follow_each_other(&app, &alice, &bob).await;
+
+    let conversation_id = start(&app, &alice, &[&bob], "one").await;
+    send(&app, &alice, &conversation_id, "two").await;
+
+    // 自分が送ったメッセージは未読に数えない
+    assert_eq!(
+        conversations(&app, &alice).await["nodes"][0]["unreadCount"],
+        0
+    );
+    assert_eq!(
+        conversations(&app, &bob).await["nodes"][0]["unreadCount"],
+        2
+    );
+    let result = messages(&app, &alice, &conversation_id).await;
+    assert_eq!(result["nodes"][0]["readBy"], json!([]));
+
+    let resp = app
+        .execute(
+            "mutation($id: UUID!) { markConversationRead(conversationId: $id) }",
+            json!({ "id": conversation_id }),
+            Some(&bob),
+        )
+        .await;
+    assert_eq!(data(&resp)["markConversationRead"], true);
+    assert_eq!(
+        conversations(&app, &bob).await["nodes"][0]["unreadCount"],
+        0
+    );
+    let result = messages(&app, &alice, &conversation_id).await;
+    assert_eq!(result["nodes"][0]["readBy"], json!([{ "username": "bob" }]));
+    assert_eq!(result["nodes"][1]["readBy"], json!([{ "username": "bob" }]));
+
+    // 返信すると相手のメッセージも読んだことになる
+    send(&app, &alice, &conversation_id, "three").await;
+    send(&app, &bob, &conversation_id, "reply").await;
+    let result = messages(&app, &alice, &conversation_id).await;
+    assert_eq!(contents(&result), vec!["reply", "three", "two", "one"]);
+    assert_eq!(result["nodes"][0]["readBy"], json!([]));
+    assert_eq!(result["nodes"][1]["readBy"], json!([{ "username": "bob" }]));
+    assert_eq!(
+        conversations(&app, &alice).await["nodes"][0]["unreadCount"],
+        1
+    );
+}
+
+#[actix_rt::test]
+async fn invalid_messages_are_rejected() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    follow_each_other(&app, &alice, &bob).await;
+
+    let resp = send_to(&app, &alice, &[&bob], "").await;
+    assert_eq!(
+        error_extensions(&resp),
+        &json!({ "code": "VALIDATION_FAILED", "field": "content" })
+    );
+
+    // 文字数はバイト数ではなく文字で数える
+    let resp = send_to(&app, &alice, &[&bob], &"あ".repeat(1000)).await;
+    assert_eq!(
+        data(&resp)["sendDirectMessage"]["content"],
+        "あ".repeat(1000)
+    );
+    let resp = send_to(&app, &alice, &[&bob], &"あ".repeat(1001)).await;
+    assert_eq!(error_extensions(&resp)["field"], "content");
+
+    let resp = send_to(&app, &alice, &[&alice], "note to self").await;
+    assert_eq!(
+        error_extensions(&resp),
+        &json!({ "code": "VALIDATION_FAILED", "field": "recipientIds" })
+    );
+
+    // 会話と相手の両方、またはどちらも指定しないとエラー
+    let conversation_id = start(&app, &alice, &[&bob], "hi").await;
+    let resp = app
+        .execute(
+            SEND,
+            json!({ "conversationId": conversation_id, "recipientIds": [bob.id], "content": "hi" }),
+            Some(&alice),
+        )
+        .await;
+    assert_eq!(error_extensions(&resp)["code"], "BAD_REQUEST");
+    let resp = app
+        .execute(SEND, json!({ "content": "hi" }), Some(&alice))
+        .await;
+    assert_eq!(error_extensions(&resp)["code"], "BAD_REQUEST");
+
+    let resp = app
+        .execute(
+            SEND,
+            json!({ "recipientIds": [uuid::Uuid::new_v4()], "content": "hi" }),
+            Some(&alice),
+        )
+        .await;
+    assert_eq!(error_extensions(&resp)["code"], "NOT_FOUND");
+}
@@ -0,0 +1,528 @@
+use actix_web::ResponseError;
+use actix_web::body::to_bytes;
+use actix_web::http::StatusCode;
+use actix_web::http::header::CONTENT_TYPE;
+use async_graphql::ErrorExtensions;
+use serde_json::{Value, json};
+
+use crate::error::AppError;
+
+use super::{PASSWORD, TestApp, data, error_extensions, error_message};
+
+#[actix_rt::test]
+async fn register_and_login() {
+    let app = TestApp::new().await;
+
+    let register = "mutation($input: RegisterInput!) {
+        register(input: $input) { token refreshToken user { username email } }
+    }";
+    let input = json!({ "input": {
+        "username": "alice",
+        "email": "alice@example.com",
+        "password": PASSWORD,
+    }});
+
+    let resp = app.execute(register, input.clone(), None).await;
+    let payload = &data(&resp)["register"];
+    assert_eq!(payload["user"]["username"], "alice");
+    assert!(payload["token"].as_str().is_some_and(|t| !t.is_empty()));
+
+    let resp = app.execute(register, input, None).await;
+    assert_eq!(error_message(&resp), "Email already exists");
+
+    let login = "mutation($input: LoginInput!) { login(input: $input) { user { username } } }";
+    let resp = app
+        .execute(
+            login,
+            json!({ "input": { "email": "alice@example.com", "password": PASSWORD } }),
+            None,
+        )
+        .await;
+    assert_eq!(data(&resp)["login"]["user"]["username"], "alice");
+
+    let resp = app
+        .execute(
+            login,
+            json!({ "input": { "email": "alice@example.com", "password": "wrong" } }),
+            None,
+        )
+        .await;
+    assert_eq!(error_message(&resp), "Invalid email or password");
+}
+
+#[actix_rt::test]
+async fn refresh_token_and_logout() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+
+    let refresh = "mutation($t: String!) { refreshToken(refreshToken: $t) { refreshToken } }";
+    let resp = app
+        .execute(refresh, json!({ "t": alice.refresh_token }), None)
+        .await;
+    let rotated = data(&resp)["refreshToken"]["refreshToken"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let resp = app
+        .execute(refresh, json!({ "t": alice.refresh_token }), None)
+        .await;
+    assert_eq!(error_message(&resp), "Refresh token reuse detected");
+
+    let logout = "mutation($t: String!) { logout(refreshToken: $t) }";
+    let other = app.login(&alice).await;
+    let resp = app
+        .execute(logout, json!({ "t": other.refresh_token }), None)
+        .await;
+    assert_eq!(data(&resp)["logout"], true);
+
+    let resp = app
+        .execute(refresh, json!({ "t": other.refresh_token }), None)
+        .await;
+    assert_eq!(error_message(&resp), "Refresh token revoked");
+
+    let resp = app.execute(logout, json!({ "t": "unknown" }), None).await;
+    assert_eq!(error_message(&resp), "Invalid refresh token");
+    assert!(!rotated.is_empty());
+}
+
+#[actix_rt::test]
+async fn me_and_user() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+
+    let resp = app.execute("{ me { id } }", json!({}), None).await;
+    assert_eq!(data(&resp)["me"], Value::Null);
+
+    let resp = app
+        .execute("{ me { id username email } }", json!({}), Some(&alice))
+        .await;
+    assert_eq!(
+        data(&resp)["me"],
+        json!({ "id": alice.id, "username": "alice", "email": "alice@example.com" })
+    );
+
+    app.execute(
+        "mutation($id: UUID!) { followUser(targetId: $id) }",
+        json!({ "id": bob.id }),
+        Some(&alice),
+    )
+    .await;
+
+    let query = "query($id: UUID!) {
+        user(id: $id) { username followersCount followingCount isFollowing }
+    }";
+    let resp = app
+        .execute(query, json!({ "id": bob.id }), Some(&alice))
+        .await;
+    assert_eq!(
+        data(&resp)["user"],
+        json!({ "username": "bob", "followersCount": 1, "followingCount": 0, "isFollowing": true })
+    );
+
+    // 未ログインでは isFollowing は常に false
+    let resp = app.execute(query, json!({ "id": bob.id }), None).await;
+    assert_eq!(data(&resp)["user"]["isFollowing"], false);
+
+    let resp = app
+        .execute(query, json!({ "id": uuid::Uuid::new_v4() }), None)
+        .await;
+    assert_eq!(data(&resp)["user"], Value::Null);
+}
+
+#[actix_rt::test]
+async fn tweets_and_timeline() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+
+    let resp = app
+        .execute(
+            "mutation { createTweet(content: \"hi\") { id } }",
+            json!({}),
+            None,
+        )
+        .await;
+    assert!(resp["errors"].is_array());
+
+    let id = app.tweet(&alice, "Hello #GraphQL #rust").await;
+
+    let query = "query($id: UUID!) {
+        tweet(id: $id) { content likeCount isLiked hashtags user { username } }
+    }";
+    let resp = app.execute(query, json!({ "id": id }), None).await;
+    let tweet = &data(&resp)["tweet"];
+    assert_eq!(tweet["content"], "Hello #GraphQL #rust");
+    assert_eq!(tweet["user"]["username"], "alice");
+    let mut hashtags: Vec<&str> = tweet["hashtags"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|h| h.as_str().unwrap())
+        .collect();
+    hashtags.sort();
+    assert_eq!(hashtags, ["graphql", "rust"]);
+
+    let resp = app
+        .execute(query, json!({ "id": uuid::Uuid::new_v4() }), None)
+        .await;
+    assert_eq!(data(&resp)["tweet"], Value::Null);
+
+    // タイムラインは認証が必要で、フォローしていないユーザーのツイートは含まない
+    let resp = app
+        .execute("{ timeline { nodes { id } } }", json!({}), None)
+        .await;
+    assert!(resp["errors"].is_array());
+
+    let resp = app
+        .execute("{ timeline { nodes { id } } }", json!({}), Some(&bob))
+        .await;
+    assert_eq!(data(&resp)["timeline"]["nodes"], json!([]));
+
+    app.execute(
+        "mutation($id: UUID!) { followUser(targetId: $id) }",
+        json!({ "id": alice.id }),
+        Some(&bob),
+    )
+    .await;
+    let resp = app
+        .execute("{ timeline { nodes { id } } }", json!({}), Some(&bob))
+        .await;
+    assert_eq!(data(&resp)["timeline"]["nodes"], json!([{ "id": id }]));
+
+    // 削除は投稿者本人のみ
+    let delete = "mutation($id: UUID!) { deleteTweet(id: $id) }";
+    let resp = app.execute(delete, json!({ "id": id }), Some(&bob)).await;
+    assert_eq!(error_message(&resp), "Tweet not found or not authorized");
+
+    let resp = app.execute(delete, json!({ "id": id }), None).await;
+    assert!(resp["errors"].is_array());
+
+    let resp = app.execute(delete, json!({ "id": id }), Some(&alice)).await;
+    assert_eq!(data(&resp)["deleteTweet"], true);
+
+    let resp = app.execute(query, json!({ "id": id }), None).await;
+    assert_eq!(data(&resp)["tweet"], Value::Null);
+}
+
+#[actix_rt::test]
+async fn like_and_unlike() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let id = app.tweet(&alice, "like me").await;
+
+    let like = "mutation($id: UUID!) { likeTweet(tweetId: $id) }";
+    let unlike = "mutation($id: UUID!) { unlikeTweet(tweetId: $id) }";
+    let query = "query($id: UUID!) { tweet(id: $id) { likeCount isLiked } }";
+
+    let resp = app.execute(like, json!({ "id": id }), None).await;
+    assert!(resp["errors"].is_array());
+
+    let resp = app.execute(like, json!({ "id": id }), Some(&bob)).await;
+    assert_eq!(data(&resp)["likeTweet"], true);
+
+    let resp = app.execute(like, json!({ "id": id }), Some(&bob)).await;
+    assert_eq!(error_message(&resp), "Already liked");
+
+    let resp = app.execute(query, json!({ "id": id }), Some(&bob)).await;
+    assert_eq!(
+        data(&resp)["tweet"],
+        json!({ "likeCount": 1, "isLiked": true })
+    );
+    let resp = app.execute(query, json!({ "id": id }), Some(&alice)).await;
+    assert_eq!(data(&resp)["tweet"]["isLiked"], false);
+
+    let resp = app
+        .execute(like, json!({ "id": uuid::Uuid::new_v4() }), Some(&bob))
+        .await;
+    assert_eq!(error_message(&resp), "Tweet not found");
+
+    let resp = app.execute(unlike, json!({ "id": id }), Some(&bob)).await;
+    assert_eq!(data(&resp)["unlikeTweet"], true);
+
+    let resp = app.execute(unlike, json!({ "id": id }), Some(&bob)).await;
+    assert_eq!(error_message(&resp), "Like not found");
+
+    let resp = app.execute(unlike, json!({ "id": id }), None).await;
+    assert!(resp["errors"].is_array());
+}
+
+#[actix_rt::test]
+async fn comments() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let tweet_id = app.tweet(&alice, "comment on me").await;
+
+    let create = "mutation($id: UUID!, $content: String!) {
+        createComment(tweetId: $id, content: $content) { id tweetId content user { username } }
+    }";
+    let resp = app
+        .execute(create, json!({ "id": tweet_id, "content": "nice" }), None)
+        .await;
+    assert!(resp["errors"].is_array());
+
+    let resp = app
+        .execute(
+            create,
+            json!({ "id": tweet_id, "content": "nice" }),
+            Some(&bob),
+        )
+        .await;
+    let comment = &data(&resp)["createComment"];
+    assert_eq!(comment["tweetId"], tweet_id);
+    assert_eq!(comment["user"]["username"], "bob");
+    let comment_id = comment["id"].clone();
+
+    let resp = app
+        .execute(create, json!({ "id": tweet_id, "content": "" }), Some(&bob))
+        .await;
+    assert_eq!(
+        error_message(&resp),
+        "Comment content must be between 1 and 280 characters"
+    );
+
+    let resp = app
+        .execute(
+            create,
+            json!({ "id": uuid::Uuid::new_v4(), "content": "lost" }),
+            Some(&bob),
+        )
+        .await;
+    assert_eq!(error_message(&resp), "Tweet not found");
+
+    let list = "query($id: UUID!) { comments(tweetId: $id) { nodes { content } } }";
+    let resp = app.execute(list, json!({ "id": tweet_id }), None).await;
+    assert_eq!(
+        data(&resp)["comments"]["nodes"],
+        json!([{ "content": "nice" }])
+    );
+
+    // 削除はコメントした本人のみ
+    let delete = "mutation($id: UUID!) { deleteComment(id: $id) }";
+    let resp = app
+        .execute(delete, json!({ "id": comment_id }), Some(&alice))
+        .await;
+    assert_eq!(error_message(&resp), "Comment not found or not authorized");
+
+    let resp = app
+        .execute(delete, json!({ "id": comment_id }), Some(&bob))
+        .await;
+    assert_eq!(data(&resp)["deleteComment"], true);
+
+    let resp = app.execute(list, json!({ "id": tweet_id }), None).await;
+    assert_eq!(data(&resp)["comments"]["nodes"], json!([]));
+}
+
+#[actix_rt::test]
+async fn follow_and_unfollow() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+
+    let follow = "mutation($id: UUID!) { followUser(targetId: $id) }";
+    let unfollow = "mutation($id: UUID!) { unfollowUser(targetId: $id) }";
+
+    let resp = app.execute(follow, json!({ "id": bob.id }), None).await;
+    assert!(resp["errors"].is_array());
+
+    let resp = app
+        .execute(follow, json!({ "id": bob.id }), Some(&alice))
+        .await;
+    assert_eq!(data(&resp)["followUser"], bob.id.to_string());
+
+    let resp = app
+        .execute(follow, json!({ "id": bob.id }), Some(&alice))
+        .await;
+    assert_eq!(error_message(&resp), "Already following this user");
+
+    let resp = app
+        .execute(follow, json!({ "id": alice.id }), Some(&alice))
+        .await;
+    assert_eq!(error_message(&resp), "Cannot follow yourself");
+
+    let resp = app
+        .execute(follow, json!({ "id": uuid::Uuid::new_v4() }), Some(&alice))
+        .await;
+    assert_eq!(error_message(&resp), "User not found");
+
+    let followers =
+        "query($id: UUID!) { followers(userId: $id) { nodes { username isFollowing } } }";
+    let following =
+        "query($id: UUID!) { following(userId: $id) { nodes { username isFollowing } } }";
+
+    let resp = app.execute(followers, json!({ "id": bob.id }), None).await;
+    assert!(resp["errors"].is_array());
+
+    let resp = app
+        .execute(followers, json!({ "id": bob.id }), Some(&bob))
+        .await;
+    assert_eq!(
+        data(&resp)["followers"]["nodes"],
+        json!([{ "username": "alice", "isFollowing": false }])
+    );
+
+    let resp = app
+        .execute(following, json!({ "id": alice.id }), Some(&alice))
+        .await;
+    assert_eq!(
+        data(&resp)["following"]["nodes"],
+        json!([{ "username": "bob", "isFollowing": true }])
+    );
+
+    let resp = app
+        .execute(following, json!({ "id": alice.id }), None)
+        .await;
+    assert!(resp["errors"].is_array());
+
+    let resp = app
+        .execute(unfollow, json!({ "id": bob.id }), Some(&alice))
+        .await;
+    assert_eq!(data(&resp)["unfollowUser"], bob.id.to_string());
+
+    let resp = app
+        .execute(unfollow, json!({ "id": bob.id }), Some(&alice))
+        .await;
+    assert_eq!(error_message(&resp), "Not following this user");
+
+    let resp = app.execute(unfollow, json!({ "id": bob.id }), None).await;
+    assert!(resp["errors"].is_array());
+}
+
+#[actix_rt::test]
+async fn sessions_can_be_listed_and_revoked() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let laptop = app.login(&alice).await;
+    let phone = app.login(&alice).await;
+    let bob = app.register("bob").await;
+
+    let resp = app.execute("{ sessions { id } }", json!({}), None).await;
+    assert!(resp["errors"].is_array());
+
+    let resp = app
+        .execute("{ sessions { id isCurrent } }", json!({}), Some(&laptop))
+        .await;
+    let sessions = data(&resp)["sessions"].as_array().unwrap().clone();
+    assert_eq!(sessions.len(), 3);
+    assert_eq!(
+        sessions.iter().filter(|s| s["isCurrent"] == true).count(),
+        1
+    );
+    let current = sessions.iter().find(|s| s["isCurrent"] == true).unwrap()["id"].clone();
+    let other = sessions.iter().find(|s| s["isCurrent"] == false).unwrap()["id"].clone();
+
+    // 他人のセッションは失効させられない
+    let revoke = "mutation($id: UUID!) { revokeSession(id: $id) }";
+    let resp = app
+        .execute(revoke, json!({ "id": other }), Some(&bob))
+        .await;
+    assert_eq!(error_message(&resp), "Session not found");
+
+    let resp = app.execute(revoke, json!({ "id": other }), None).await;
+    assert!(resp["errors"].is_array());
+
+    let resp = app
+        .execute(revoke, json!({ "id": other }), Some(&laptop))
+        .await;
+    assert_eq!(data(&resp)["revokeSession"], true);
+
+    let revoke_others = "mutation { revokeAllOtherSessions }";
+    let resp = app.execute(revoke_others, json!({}), None).await;
+    assert!(resp["errors"].is_array());
+
+    let resp = app.execute(revoke_others, json!({}), Some(&laptop)).await;
+    assert_eq!(data(&resp)["revokeAllOtherSessions"], 1);
+
+    let resp = app
+        .execute("{ sessions { id } }", json!({}), Some(&laptop))
+        .await;
+    assert_eq!(data(&resp)["sessions"], json!([{ "id": current }]));
+
+    // 失効したセッションのトークンは検証に失敗する
+    for user in [&alice, &phone] {
+        assert!(
+            crate::utils::verify_jwt(&app.repos, &user.token)
+                .await
+                .is_err()
+        );
+    }
+}
+
+#[actix_rt::test]
+async fn errors_carry_codes() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let tweet_id = app.tweet(&alice, "hello").await;
+    let like = "mutation($id: UUID!) { likeTweet(tweetId: $id) }";
+    app.execute(like, json!({ "id": tweet_id }), Some(&alice))
+        .await;
+
+    for (query, variables, user, extensions) in [
+        (
+            "mutation { createTweet(content: \"hello\") { id } }",
+            json!({}),
+            None,
+            json!({ "code": "UNAUTHENTICATED" }),
+        ),
+        (
+            "mutation($id: UUID!) { deleteTweet(id: $id) }",
+            json!({ "id": uuid::Uuid::new_v4() }),
+            Some(&alice),
+            json!({ "code": "NOT_FOUND" }),
+        ),
+        (
+            like,
+            json!({ "id": tweet_id }),
+            Some(&alice),
+            json!({ "code": "CONFLICT" }),
+        ),
+        (
+            "mutation($id: UUID!) { createComment(tweetId: $id, content: \"\") { id } }",
+            json!({ "id": tweet_id }),
+            Some(&alice),
+            json!({ "code": "VALIDATION_FAILED", "field": "content" }),
+        ),
+        (
+            "{ timeline(last: 101) { nodes { id } } }",
+            json!({}),
+            Some(&alice),
+            json!({ "code": "VALIDATION_FAILED", "field": "last" }),
+        ),
+        // 構文エラーなどサービス層以外のエラー
+        (
+            "{ timeline { nodes { id }",
+            json!({}),
+            Some(&alice),
+            json!({ "code": "BAD_REQUEST" }),
+        ),
+        (
+            "{ unknownField }",
+            json!({}),
+            None,
+            json!({ "code": "BAD_REQUEST" }),
+        ),
+    ] {
+        let resp = app.execute(query, variables, user).await;
+        assert_eq!(error_extensions(&resp), &extensions, "{}", query);
+    }
+
+    // GraphQL から直接は起きないエラーも同じ code を持つ
+    let error = AppError::RateLimited("slow down".into());
+    assert_eq!(
+        error.extend().extensions.unwrap().get("code"),
+        Some(&async_graphql::Value::from("RATE_LIMITED"))
+    );
+    let resp = error.error_response();
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(
+        resp.headers().get(CONTENT_TYPE).unwrap(),
+        "application/problem+json"
+    );
+    let body = to_bytes(resp.into_body()).await.unwrap();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["code"], "RATE_LIMITED");
+    assert_eq!(body["status"], 429);
+}
@@ -0,0 +1,126 @@
+use chrono::{Duration, Utc};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use super::{TestApp, data};
+use crate::models::Tweet;
+
+/// minutes_ago 分前に投稿されたツイートを直接保存する
+async fn insert_tweet(app: &TestApp, user_id: Uuid, tag: &str, minutes_ago: i64) -> Uuid {
+    let tweet = Tweet {
+        id: Uuid::new_v4(),
+        user_id,
+        content: format!("#{}", tag),
+        created_at: (Utc::now() - Duration::minutes(minutes_ago)).to_rfc3339(),
+        quoted_tweet_id: None,
+    };
+    app.repos
+        .tweets
+        .insert(&tweet, &[tag.to_string()], &[])
+        .await
+        .unwrap();
+    tweet.id
+}
+
+async fn trending(app: &TestApp, window: &str) -> Vec<String> {
+    let resp = app
+        .execute(
+            "query($w: TrendWindow!) { trendingHashtags(window: $w) { name } }",
+            json!({ "w": window }),
+            None,
+        )
+        .await;
+    data(&resp)["trendingHashtags"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["name"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[actix_rt::test]
+async fn hashtag_lists_tagged_tweets_with_usage() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let oldest = insert_tweet(&app, alice.id, "rust", 30).await;
+    let newest = insert_tweet(&app, alice.id, "rust", 10).await;
+    insert_tweet(&app, alice.id, "go", 20).await;
+
+    let query = r#"
+        query($name: String!, $after: String) {
+            hashtag(name: $name) {
+                name tweetCount firstUsedAt lastUsedAt
+                tweets(first: 1, after: $after) {
+                    totalCount
+                    edges { cursor node { id } }
+                    pageInfo { hasNextPage }
+                }
+            }
+        }
+    "#;
+    let resp = app.execute(query, json!({ "name": "#Rust" }), None).await;
+    let hashtag = &data(&resp)["hashtag"];
+    assert_eq!(hashtag["name"], "rust");
+    assert_eq!(hashtag["tweetCount"], 2);
+    assert!(hashtag["firstUsedAt"].as_str() < hashtag["lastUsedAt"].as_str());
+    assert_eq!(hashtag["tweets"]["totalCount"], 2);
+    assert_eq!(
+        hashtag["tweets"]["edges"][0]["node"]["id"],
+        newest.to_string()
+    );
+    assert_eq!(hashtag["tweets"]["pageInfo"]["hasNextPage"], true);
+
+    let cursor = hashtag["tweets"]["edges"][0]["cursor"].clone();
+    let resp = app
+        .execute(query, json!({ "name": "rust", "after": cursor }), None)
+        .await;
+    let tweets = &data(&resp)["hashtag"]["tweets"];
+    assert_eq!(tweets["edges"][0]["node"]["id"], oldest.to_string());
+    assert_eq!(tweets["pageInfo"]["hasNextPage"], false);
+
+    let resp = app.execute(query, json!({ "name": "python" }), None).await;
+    assert_eq!(data(&resp)["hashtag"], Value::Null);
+}
+
+#[actix_rt::test]
+async fn trending_ranks_hashtags_by_velocity() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+
+    // 毎時1件ずつ使われ続けている（直近1時間は基準どおり）
+    for hour in 0..8 {
+        insert_tweet(&app, alice.id, "steady", hour * 60 + 30).await;
+    }
+    // 直近1時間で急に使われ始めた
+    for minutes in [5, 10, 15] {
+        insert_tweet(&app, alice.id, "rising", minutes).await;
+    }
+    insert_tweet(&app, alice.id, "fresh", 20).await;
+    // 直近1時間には使われていない
+    insert_tweet(&app, alice.id, "stale", 180).await;
+
+    assert_eq!(trending(&app, "HOUR").await, ["rising", "fresh"]);
+    // 24時間では基準の期間に使われていないため、件数の多い順になる
+    assert_eq!(
+        trending(&app, "DAY").await,
+        ["steady", "rising", "fresh", "stale"]
+    );
+
+    let resp = app
+        .execute(
+            "{ trendingHashtags(window: HOUR, limit: 1) { name recentCount baseline score hashtag { tweetCount } } }",
+            json!({}),
+            None,
+        )
+        .await;
+    assert_eq!(
+        data(&resp)["trendingHashtags"],
+        json!([{
+            "name": "rising",
+            "recentCount": 3,
+            "baseline": 0.0,
+            "score": 3.0,
+            "hashtag": { "tweetCount": 3 },
+        }])
+    );
+}
@@ -0,0 +1,322 @@
+use actix_web::http::StatusCode;
+use actix_web::test::TestRequest;
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use super::{TestApp, data, error_extensions, error_message, test_repositories, urlencode};
+use crate::config::{Config, Environment, GraphqlConfig};
+use crate::graphql::PersistedQueries;
+
+/// GraphiQL などが送る標準的なイントロスペクションクエリ
+const INTROSPECTION_QUERY: &str = "
+query IntrospectionQuery {
+  __schema {
+    queryType { name }
+    mutationType { name }
+    subscriptionType { name }
+    types { ...FullType }
+    directives { name description locations args { ...InputValue } }
+  }
+}
+fragment FullType on __Type {
+  kind name description
+  fields(includeDeprecated: true) {
+    name description
+    args { ...InputValue }
+    type { ...TypeRef }
+    isDeprecated deprecationReason
+  }
+  inputFields { ...InputValue }
+  interfaces { ...TypeRef }
+  enumValues(includeDeprecated: true) { name description isDeprecated deprecationReason }
+  possibleTypes { ...TypeRef }
+}
+fragment InputValue on __InputValue {
+  name description type { ...TypeRef } defaultValue
+}
+fragment TypeRef on __Type {
+  kind name
+  ofType { kind name ofType { kind name ofType { kind name ofType { kind name
+    ofType { kind name ofType { kind name ofType { kind name } } } } } } }
+}";
+
+const ME_QUERY: &str = "{ me { username } }";
+
+fn sha256(query: &str) -> String {
+    format!("{:x}", Sha256::digest(query.as_bytes()))
+}
+
+fn persisted_query(hash: &str) -> Value {
+    json!({ "persistedQuery": { "version": 1, "sha256Hash": hash } })
+}
+
+async fn post(app: &TestApp, body: Value) -> Value {
+    let (status, _, body) = app
+        .call(TestRequest::post().uri("/graphql").set_json(body))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    body
+}
+
+async fn app_with(graphql: GraphqlConfig, allowlist: HashMap<String, String>) -> TestApp {
+    let config = Config {
+        graphql,
+        ..Config::default()
+    };
+    let persisted = PersistedQueries::new(allowlist, &config.graphql);
+    TestApp::with_config(test_repositories().await, config, persisted)
+}
+
+#[actix_rt::test]
+async fn deep_queries_are_rejected() {
+    let app = app_with(
+        GraphqlConfig {
+            max_depth: 3,
+            ..GraphqlConfig::default()
+        },
+        HashMap::new(),
+    )
+    .await;
+    let alice = app.register("alice").await;
+
+    let resp = app
+        .execute("{ timeline { nodes { id } } }", json!({}), Some(&alice))
+        .await;
+    data(&resp);
+
+    let resp = app
+        .execute(
+            "{ timeline { nodes { user { username } } } }",
+            json!({}),
+            Some(&alice),
+        )
+        .await;
+    assert_eq!(error_message(&resp), "Query is nested too deep.");
+}
+
+#[actix_rt::test]
+async fn complex_queries_are_rejected() {
+    let app = app_with(
+        GraphqlConfig {
+            max_complexity: 100,
+            ..GraphqlConfig::default()
+        },
+        HashMap::new(),
+    )
+    .await;
+    let alice = app.register("alice").await;
+
+    let resp = app
+        .execute(
+            "{ timeline(first: 5) { nodes { id likeCount } } }",
+            json!({}),
+            Some(&alice),
+        )
+        .await;
+    data(&resp);
+
+    // 取得件数がコストに掛かる
+    let resp = app
+        .execute(
+            "{ timeline(first: 50) { nodes { id likeCount } } }",
+            json!({}),
+            Some(&alice),
+        )
+        .await;
+    assert_eq!(error_message(&resp), "Query is too complex.");
+}
+
+#[actix_rt::test]
+async fn introspection_fits_default_limits() {
+    let app = TestApp::new().await;
+
+    let resp = app.execute(INTROSPECTION_QUERY, json!({}), None).await;
+    assert_eq!(data(&resp)["__schema"]["queryType"]["name"], "QueryRoot");
+}
+
+#[actix_rt::test]
+async fn automatic_persisted_queries() {
+    let app = TestApp::new().await;
+    let hash = sha256(ME_QUERY);
+
+    // 未登録のハッシュ
+    let body = post(&app, json!({ "extensions": persisted_query(&hash) })).await;
+    assert_eq!(error_message(&body), "PersistedQueryNotFound");
+    assert_eq!(error_extensions(&body)["code"], "PERSISTED_QUERY_NOT_FOUND");
+
+    // ハッシュと本文が一致しなければ登録しない
+    let body = post(
+        &app,
+        json!({ "query": "{ me { id } }", "extensions": persisted_query(&hash) }),
+    )
+    .await;
+    assert_eq!(error_message(&body), "provided sha does not match query");
+
+    // 本文付きで送り直すと登録され、以降はハッシュだけで実行できる
+    let body = post(
+        &app,
+        json!({ "query": ME_QUERY, "extensions": persisted_query(&hash) }),
+    )
+    .await;
+    assert_eq!(data(&body)["me"], json!(null));
+
+    let body = post(&app, json!({ "extensions": persisted_query(&hash) })).await;
+    assert_eq!(data(&body)["me"], json!(null));
+
+    // GET でもハッシュだけで実行できる
+    let uri = format!(
+        "/graphql?extensions={}",
+        urlencode(&persisted_query(&hash).to_string())
+    );
+    let (status, _, body) = app.call(TestRequest::get().uri(&uri)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(data(&body)["me"], json!(null));
+
+    let (_, _, body) = app
+        .call(TestRequest::get().uri("/graphql?extensions=oops"))
+        .await;
+    assert!(error_message(&body).starts_with("Failed to parse GraphQL extensions"));
+}
+
+#[actix_rt::test]
+async fn failed_queries_are_not_persisted() {
+    let app = TestApp::new().await;
+
+    // 検証エラーや実行時のエラーになったクエリは登録されない
+    for query in ["{ unknownField }", "{ notifications { totalCount } }"] {
+        let hash = sha256(query);
+        let body = post(
+            &app,
+            json!({ "query": query, "extensions": persisted_query(&hash) }),
+        )
+        .await;
+        error_message(&body);
+
+        let body = post(&app, json!({ "extensions": persisted_query(&hash) })).await;
+        assert_eq!(error_message(&body), "PersistedQueryNotFound", "{}", query);
+    }
+}
+
+#[actix_rt::test]
+async fn least_recently_used_persisted_queries_are_evicted() {
+    let app = app_with(
+        GraphqlConfig {
+            apq_cache_size: 2,
+            ..GraphqlConfig::default()
+        },
+        HashMap::new(),
+    )
+    .await;
+    let queries = ["{ me { id } }", "{ me { username } }", "{ me { email } }"];
+    let hashes: Vec<String> = queries.iter().map(|q| sha256(q)).collect();
+
+    for (query, hash) in queries.iter().zip(&hashes).take(2) {
+        post(
+            &app,
+            json!({ "query": query, "extensions": persisted_query(hash) }),
+        )
+        .await;
+    }
+    // 1つ目を使ってから3つ目を登録すると、使われていない2つ目が消える
+    let body = post(&app, json!({ "extensions": persisted_query(&hashes[0]) })).await;
+    data(&body);
+    post(
+        &app,
+        json!({ "query": queries[2], "extensions": persisted_query(&hashes[2]) }),
+    )
+    .await;
+
+    for (hash, registered) in hashes.iter().zip([true, false, true]) {
+        let body = post(&app, json!({ "extensions": persisted_query(hash) })).await;
+        if registered {
+            data(&body);
+        } else {
+            assert_eq!(error_message(&body), "PersistedQueryNotFound");
+        }
+    }
+}
+
+#[actix_rt::test]
+async fn persisted_only_mode_accepts_allowlisted_queries() {
+    let hash = sha256(ME_QUERY);
+    let app = app_with(
+        GraphqlConfig {
+            persisted_only: true,
+            ..GraphqlConfig::default()
+        },
+        HashMap::from([(hash.clone(), ME_QUERY.to_string())]),
+    )
+    .await;
+
+    let body = post(&app, json!({ "extensions": persisted_query(&hash) })).await;
+    assert_eq!(data(&body)["me"], json!(null));
+
+    // 許可リストと同じ本文はそのまま実行できる
+    let body = post(&app, json!({ "query": ME_QUERY })).await;
+    assert_eq!(data(&body)["me"], json!(null));
+
+    // 許可リストにないクエリは登録もされない
+    let query = "{ me { id } }";
+    let body = post(&app, json!({ "query": query })).await;
+    assert_eq!(error_message(&body), "Only persisted queries are allowed");
+
+    let extensions = persisted_query(&sha256(query));
+    let body = post(&app, json!({ "query": query, "extensions": extensions })).await;
+    assert_eq!(error_message(&body), "PersistedQueryNotFound");
+    let body = post(&app, json!({ "extensions": extensions })).await;
+    assert_eq!(error_message(&body), "PersistedQueryNotFound");
+
+    // 許可リストにあってもイントロスペクションは無効
+    let query = "{ __schema { queryType { name } } }";
+    let app = app_with(
+        GraphqlConfig {
+            persisted_only: true,
+            ..GraphqlConfig::default()
+        },
+        HashMap::from([(sha256(query), query.to_string())]),
+    )
+    .await;
+    let body = post(&app, json!({ "query": query })).await;
+    assert_eq!(data(&body)["__schema"], json!(null));
+
+    let (status, _, _) = app.call(TestRequest::get().uri("/graphiql")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[actix_rt::test]
+async fn persisted_queries_config_is_validated() {
+    let mut config = Config::default();
+    config.server.environment = Environment::Development;
+    config.graphql.persisted_only = true;
+    let err = config.validate().err().unwrap();
+    assert!(
+        err.to_string().contains("persisted_queries_path"),
+        "{}",
+        err
+    );
+
+    let path = std::env::temp_dir().join(format!("persisted-{}.json", Uuid::new_v4()));
+    config.graphql.persisted_queries_path = Some(path.to_string_lossy().into_owned());
+    assert!(config.validate().is_ok());
+
+    // ファイルがない
+    assert!(PersistedQueries::from_config(&config.graphql).is_err());
+
+    // ハッシュと本文が一致しない
+    std::fs::write(
+        &path,
+        json!({ sha256("{ me { id } }"): ME_QUERY }).to_string(),
+    )
+    .unwrap();
+    let err = PersistedQueries::from_config(&config.graphql)
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("does not match"), "{}", err);
+
+    std::fs::write(&path, json!({ sha256(ME_QUERY): ME_QUERY }).to_string()).unwrap();
+    let result = PersistedQueries::from_config(&config.graphql);
+    std::fs::remove_file(&path).unwrap();
+    assert!(result.is_ok());
+}
@@ -0,0 +1,240 @@
+use async_trait::async_trait;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use uuid::Uuid;
+
+use super::{PASSWORD, TestApp, data, test_repositories};
+use crate::error::AppError;
+use crate::models::{Role, User};
+use crate::pagination::{Page, PageQuery};
+use crate::repository::{FollowCounts, FollowRepository, LikeRepository, UserRepository};
+
+type Result<T> = std::result::Result<T, AppError>;
+
+/// リポジトリの呼び出し回数を数えるラッパー
+struct Counting<T: ?Sized> {
+    inner: Arc<T>,
+    calls: Arc<AtomicUsize>,
+}
+
+impl<T: ?Sized> Counting<T> {
+    fn wrap(inner: Arc<T>) -> (Arc<Self>, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counting = Arc::new(Self {
+            inner,
+            calls: calls.clone(),
+        });
+        (counting, calls)
+    }
+
+    fn count(&self) {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[async_trait]
+impl UserRepository for Counting<dyn UserRepository> {
+    async fn insert(&self, user: &User) -> Result<()> {
+        self.inner.insert(user).await
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<User>> {
+        self.count();
+        self.inner.find_by_id(id).await
+    }
+
+    async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<User>> {
+        self.count();
+        self.inner.find_by_ids(ids).await
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<User>> {
+        self.inner.find_by_email(email).await
+    }
+
+    async fn find_by_username(&self, username: &str) -> Result<Option<User>> {
+        self.inner.find_by_username(username).await
+    }
+
+    async fn find_by_usernames(&self, usernames: &[String]) -> Result<Vec<User>> {
+        self.inner.find_by_usernames(usernames).await
+    }
+
+    async fn set_role(&self, id: Uuid, role: Role) -> Result<bool> {
+        self.inner.set_role(id, role).await
+    }
+
+    async fn set_suspended_at(&self, id: Uuid, at: Option<&str>) -> Result<bool> {
+        self.inner.set_suspended_at(id, at).await
+    }
+
+    async fn set_allow_open_dms(&self, id: Uuid, allow: bool) -> Result<bool> {
+        self.inner.set_allow_open_dms(id, allow).await
+    }
+}
+
+#[async_trait]
+impl LikeRepository for Counting<dyn LikeRepository> {
+    async fn insert(&self, user_id: Uuid, tweet_id: Uuid, created_at: &str) -> Result<bool> {
+        self.inner.insert(user_id, tweet_id, created_at).await
+    }
+
+    async fn delete(&self, user_id: Uuid, tweet_id: Uuid) -> Result<bool> {
+        self.inner.delete(user_id, tweet_id).await
+    }
+
+    async fn counts(&self, tweet_ids: &[Uuid]) -> Result<HashMap<Uuid, i64>> {
+        self.count();
+        self.inner.counts(tweet_ids).await
+    }
+
+    async fn liked_by(&self, user_id: Uuid, tweet_ids: &[Uuid]) -> Result<HashSet<Uuid>> {
+        self.count();
+        self.inner.liked_by(user_id, tweet_ids).await
+    }
+}
+
+#[async_trait]
+impl FollowRepository for Counting<dyn FollowRepository> {
+    async fn insert(
+        &self,
+        follower_id: Uuid,
+        following_id: Uuid,
+        created_at: &str,
+    ) -> Result<bool> {
+        self.inner
+            .insert(follower_id, following_id, created_at)
+            .await
+    }
+
+    async fn delete(&self, follower_id: Uuid, following_id: Uuid) -> Result<bool> {
+        self.inner.delete(follower_id, following_id).await
+    }
+
+    async fn counts(&self, user_ids: &[Uuid]) -> Result<HashMap<Uuid, FollowCounts>> {
+        self.count();
+        self.inner.counts(user_ids).await
+    }
+
+    async fn following_set(&self, follower_id: Uuid, target_ids: &[Uuid]) -> Result<HashSet<Uuid>> {
+        self.count();
+        self.inner.following_set(follower_id, target_ids).await
+    }
+
+    async fn followers(&self, user_id: Uuid, query: &PageQuery) -> Result<Page<User>> {
+        self.inner.followers(user_id, query).await
+    }
+
+    async fn following(&self, user_id: Uuid, query: &PageQuery) -> Result<Page<User>> {
+        self.inner.following(user_id, query).await
+    }
+}
+
+#[actix_rt::test]
+async fn nested_fields_are_batched() {
+    let mut repos = test_repositories().await;
+    let (users, user_calls) = Counting::wrap(repos.users.clone());
+    let (likes, like_calls) = Counting::wrap(repos.likes.clone());
+    let (follows, follow_calls) = Counting::wrap(repos.follows.clone());
+    repos.users = users;
+    repos.likes = likes;
+    repos.follows = follows;
+
+    let app = TestApp::with_repositories(repos);
+    let alice = app.register("alice").await;
+    for name in ["bob", "carol", "dave"] {
+        let user = app.register(name).await;
+        app.follow(&alice, &user).await;
+        for i in 0..3 {
+            app.tweet(&user, &format!("{} {}", name, i)).await;
+        }
+    }
+
+    for calls in [&user_calls, &like_calls, &follow_calls] {
+        calls.store(0, Ordering::SeqCst);
+    }
+    let resp = app
+        .execute(
+            "{
+                timeline(first: 9) {
+                    nodes {
+                        likeCount isLiked hashtags
+                        user { username followersCount followingCount isFollowing }
+                    }
+                }
+            }",
+            json!({}),
+            Some(&alice),
+        )
+        .await;
+    let nodes = data(&resp)["timeline"]["nodes"].as_array().unwrap().clone();
+    assert_eq!(nodes.len(), 9);
+    assert_eq!(
+        nodes[0]["user"],
+        json!({ "username": "dave", "followersCount": 1, "followingCount": 0, "isFollowing": true })
+    );
+
+    // 投稿者・フォロー数・フォロー状態・いいね数・いいね状態がそれぞれ1回の取得にまとまる
+    assert_eq!(user_calls.load(Ordering::SeqCst), 1);
+    assert_eq!(follow_calls.load(Ordering::SeqCst), 2);
+    assert_eq!(like_calls.load(Ordering::SeqCst), 2);
+}
+
+#[actix_rt::test]
+async fn user_counters_are_resolved_everywhere() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    app.follow(&bob, &alice).await;
+
+    // ミューテーションの結果やコメントの投稿者でもフォロー数が正しい
+    let resp = app
+        .execute(
+            "mutation { createTweet(content: \"hello\") { id user { followersCount } } }",
+            json!({}),
+            Some(&alice),
+        )
+        .await;
+    let tweet = &data(&resp)["createTweet"];
+    assert_eq!(tweet["user"]["followersCount"], 1);
+
+    let resp = app
+        .execute(
+            "mutation($id: UUID!) {
+                createComment(tweetId: $id, content: \"hi\") {
+                    user { followingCount isFollowing }
+                }
+            }",
+            json!({ "id": tweet["id"] }),
+            Some(&bob),
+        )
+        .await;
+    assert_eq!(
+        data(&resp)["createComment"]["user"],
+        json!({ "followingCount": 1, "isFollowing": false })
+    );
+
+    let resp = app
+        .execute(
+            "mutation($input: LoginInput!) { login(input: $input) { user { followersCount } } }",
+            json!({ "input": { "email": alice.email, "password": PASSWORD } }),
+            None,
+        )
+        .await;
+    assert_eq!(data(&resp)["login"]["user"]["followersCount"], 1);
+
+    // 未ログインではフォロー状態・いいね状態は false
+    let resp = app
+        .execute(
+            "query($id: UUID!) { tweet(id: $id) { isLiked user { isFollowing } } }",
+            json!({ "id": tweet["id"] }),
+            None,
+        )
+        .await;
+    assert_eq!(
+        data(&resp)["tweet"],
+        json!({ "isLiked": false, "user": { "isFollowing": false } })
+    );
+}
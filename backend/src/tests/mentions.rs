@@ -0,0 +1,121 @@
+use serde_json::{Value, json};
+
+use super::{TestApp, data};
+use crate::utils::extract_mentions;
+
+fn usernames(users: &Value) -> Vec<&str> {
+    users
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|u| u["username"].as_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn extracts_mentions_in_order_without_duplicates() {
+    assert_eq!(
+        extract_mentions("Hi @bob and @alice, cc @bob"),
+        vec!["bob", "alice"]
+    );
+    assert_eq!(extract_mentions("@carol: thanks"), vec!["carol"]);
+    // メールアドレスや @@ は言及ではない
+    assert!(extract_mentions("mail alice@example.com or @@bob").is_empty());
+}
+
+#[actix_rt::test]
+async fn tweets_and_comments_expose_resolved_mentions() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    app.register("bob").await;
+    app.register("carol").await;
+
+    // 存在しないユーザー名は無視され、本文での出現順に並ぶ
+    let tweet_id = app.tweet(&alice, "hey @carol and @bob, not @nobody").await;
+    let resp = app
+        .execute(
+            "query($id: UUID!) { tweet(id: $id) { mentions { username } } }",
+            json!({ "id": tweet_id }),
+            None,
+        )
+        .await;
+    assert_eq!(
+        usernames(&data(&resp)["tweet"]["mentions"]),
+        vec!["carol", "bob"]
+    );
+
+    let resp = app
+        .execute(
+            r#"mutation($id: UUID!) {
+                createComment(tweetId: $id, content: "@alice agreed") { mentions { username } }
+            }"#,
+            json!({ "id": tweet_id }),
+            Some(&alice),
+        )
+        .await;
+    assert_eq!(
+        usernames(&data(&resp)["createComment"]["mentions"]),
+        vec!["alice"]
+    );
+
+    let resp = app
+        .execute(
+            r#"query($id: UUID!) {
+                comments(tweetId: $id) { nodes { mentions { username } } }
+            }"#,
+            json!({ "id": tweet_id }),
+            None,
+        )
+        .await;
+    assert_eq!(
+        usernames(&data(&resp)["comments"]["nodes"][0]["mentions"]),
+        vec!["alice"]
+    );
+}
+
+#[actix_rt::test]
+async fn mentions_timeline_lists_tweets_mentioning_the_viewer() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+
+    let first = app.tweet(&alice, "@bob first").await;
+    app.tweet(&alice, "no mention").await;
+    let second = app.tweet(&alice, "second for @bob").await;
+    app.tweet(&alice, "@Bob is someone else").await;
+
+    let query = r#"
+        query($after: String) {
+            mentionsTimeline(first: 1, after: $after) {
+                totalCount
+                nodes { id }
+                pageInfo { hasNextPage endCursor }
+            }
+        }
+    "#;
+    let resp = app.execute(query, json!({}), Some(&bob)).await;
+    let mentions = &data(&resp)["mentionsTimeline"];
+    assert_eq!(mentions["totalCount"], 2);
+    assert_eq!(mentions["nodes"], json!([{ "id": second }]));
+    assert_eq!(mentions["pageInfo"]["hasNextPage"], true);
+
+    let cursor = mentions["pageInfo"]["endCursor"].clone();
+    let resp = app
+        .execute(query, json!({ "after": cursor }), Some(&bob))
+        .await;
+    let mentions = &data(&resp)["mentionsTimeline"];
+    assert_eq!(mentions["nodes"], json!([{ "id": first }]));
+    assert_eq!(mentions["pageInfo"]["hasNextPage"], false);
+
+    // 削除されたツイートは載らない
+    let resp = app
+        .execute(
+            "mutation($id: UUID!) { deleteTweet(id: $id) }",
+            json!({ "id": first }),
+            Some(&alice),
+        )
+        .await;
+    data(&resp);
+    let resp = app.execute(query, json!({}), Some(&bob)).await;
+    assert_eq!(data(&resp)["mentionsTimeline"]["totalCount"], 1);
+}
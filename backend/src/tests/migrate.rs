@@ -0,0 +1,91 @@
+use crate::config::DatabaseConfig;
+use crate::migrate::{MIGRATIONS, MigrateError, migrate, rollback, status};
+use crate::store::{Db, connect};
+
+/// マイグレーション未適用のインメモリSQLite（接続ごとに別のデータベースになるため1本に限る）
+async fn empty_database() -> Db {
+    let config = DatabaseConfig {
+        url: "sqlite::memory:".to_string(),
+        max_connections: 1,
+    };
+    connect(&config).await.expect("in-memory database")
+}
+
+/// schema_migrations 以外のテーブル名
+async fn tables(db: &Db) -> Vec<String> {
+    sqlx::query_scalar(
+        "SELECT name FROM sqlite_master
+         WHERE type = 'table' AND name <> 'schema_migrations' AND name NOT LIKE 'sqlite_%'
+         ORDER BY name",
+    )
+    .fetch_all(db)
+    .await
+    .unwrap()
+}
+
+#[actix_rt::test]
+async fn migrations_roll_back_and_reapply() {
+    let db = empty_database().await;
+    let versions: Vec<i64> = MIGRATIONS.iter().map(|m| m.version).collect();
+
+    assert_eq!(migrate(&db).await.unwrap(), versions);
+    assert!(migrate(&db).await.unwrap().is_empty());
+    let created = tables(&db).await;
+    assert!(created.contains(&"users".to_string()));
+
+    // すべて戻すと、新しい順にロールバックされてテーブルが残らない
+    let rolled_back = rollback(&db, MIGRATIONS.len()).await.unwrap();
+    assert_eq!(
+        rolled_back,
+        versions.iter().rev().copied().collect::<Vec<_>>()
+    );
+    assert!(tables(&db).await.is_empty());
+    assert!(
+        status(&db)
+            .await
+            .unwrap()
+            .iter()
+            .all(|m| m.applied_at.is_none())
+    );
+
+    // もう一度適用できる
+    assert_eq!(migrate(&db).await.unwrap(), versions);
+    assert_eq!(tables(&db).await, created);
+}
+
+#[actix_rt::test]
+async fn tampered_migrations_are_rejected() {
+    let db = empty_database().await;
+    migrate(&db).await.unwrap();
+
+    sqlx::query("UPDATE schema_migrations SET checksum = 'tampered' WHERE version = 1")
+        .execute(&db)
+        .await
+        .unwrap();
+    match migrate(&db).await {
+        Err(MigrateError::ChecksumMismatch { version, name }) => {
+            assert_eq!((version, name.as_str()), (1, MIGRATIONS[0].name));
+        }
+        other => panic!("expected a checksum mismatch: {:?}", other),
+    }
+}
+
+#[actix_rt::test]
+async fn unknown_migrations_are_rejected() {
+    let db = empty_database().await;
+    migrate(&db).await.unwrap();
+
+    sqlx::query(
+        "INSERT INTO schema_migrations (version, name, checksum, applied_at)
+         VALUES (9999, 'from_the_future', '', '')",
+    )
+    .execute(&db)
+    .await
+    .unwrap();
+    match rollback(&db, 1).await {
+        Err(MigrateError::Unknown { version, name }) => {
+            assert_eq!((version, name.as_str()), (9999, "from_the_future"));
+        }
+        other => panic!("expected an unknown migration: {:?}", other),
+    }
+}
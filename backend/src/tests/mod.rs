@@ -0,0 +1,414 @@
+//! HTTP・GraphQLの結合テスト
+//!
+//! `TestApp` は main.rs と同じ構成のアプリをインメモリのデータベースで起動する。
+//! `postgres` フィーチャーでは環境変数 `TEST_DATABASE_URL` のPostgreSQLを、テストごとのスキーマで使う
+
+mod blocks;
+mod config;
+mod direct_messages;
+mod graphql;
+mod hashtags;
+mod limits;
+mod loaders;
+mod mentions;
+// マイグレーションのSQLは方言ごとに違うため、SQLiteのものだけを確かめる
+#[cfg(not(feature = "postgres"))]
+mod migrate;
+mod moderation;
+mod notifications;
+mod pagination;
+mod privacy;
+mod rest;
+mod retweets;
+mod search;
+mod subscription;
+mod threads;
+
+use actix_web::http::{StatusCode, header};
+use actix_web::test::{self, TestRequest};
+use actix_web::{App, body};
+use async_graphql::Variables;
+use async_graphql::futures_util::stream::BoxStream;
+use async_graphql::futures_util::{FutureExt, StreamExt};
+use serde_json::{Value, json};
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::config::{Config, Environment, JwtConfig};
+use crate::configure_app;
+use crate::graphql::{AppSchema, PersistedQueries, create_schema};
+use crate::keys::JwtKeys;
+use crate::models::Role;
+use crate::repository::Repositories;
+use crate::services::Services;
+use crate::sessions::{ClientInfo, CurrentSession};
+use crate::utils::{init_jwt, verify_jwt};
+
+/// テストユーザーのパスワード
+pub const PASSWORD: &str = "password123";
+
+/// 登録済みのテストユーザーとそのトークン
+#[derive(Debug, Clone)]
+pub struct TestUser {
+    pub id: Uuid,
+    pub email: String,
+    pub token: String,
+    pub refresh_token: String,
+}
+
+/// テストごとに独立したデータベースを持つアプリ
+pub struct TestApp {
+    pub repos: Repositories,
+    schema: AppSchema,
+    config: Config,
+    persisted: PersistedQueries,
+}
+
+impl TestApp {
+    /// インメモリのSQLite（`postgres` フィーチャーでは `test_repositories` を参照）で起動する
+    pub async fn new() -> Self {
+        Self::with_repositories(test_repositories().await)
+    }
+
+    /// 指定したリポジトリで起動する
+    pub fn with_repositories(repos: Repositories) -> Self {
+        let config = Config::default();
+        let persisted = PersistedQueries::new(Default::default(), &config.graphql);
+        Self::with_config(repos, config, persisted)
+    }
+
+    /// 指定したリポジトリと設定で起動する
+    pub fn with_config(repos: Repositories, config: Config, persisted: PersistedQueries) -> Self {
+        let keys = JwtKeys::from_config(&JwtConfig::default(), Environment::Development)
+            .expect("development JWT keys");
+        init_jwt(keys);
+
+        let schema = create_schema(
+            Services::new(repos.clone()),
+            &config.graphql,
+            persisted.clone(),
+        );
+        Self {
+            repos,
+            schema,
+            config,
+            persisted,
+        }
+    }
+
+    /// main.rs と同じルート・状態でリクエストを処理する
+    pub async fn call(&self, req: TestRequest) -> (StatusCode, header::HeaderMap, Value) {
+        let app = test::init_service(App::new().configure(configure_app(
+            self.repos.clone(),
+            &self.config,
+            self.persisted.clone(),
+        )))
+        .await;
+        let resp = test::call_service(&app, req.to_request()).await;
+
+        let status = resp.status();
+        let headers = resp.headers().clone();
+        let bytes = body::to_bytes(resp.into_body())
+            .await
+            .expect("response body");
+        let json = serde_json::from_slice(&bytes)
+            .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&bytes).into_owned()));
+        (status, headers, json)
+    }
+
+    /// REST API でユーザーを登録する
+    pub async fn register(&self, username: &str) -> TestUser {
+        let email = format!("{}@example.com", username);
+        let (status, _, body) = self
+            .call(TestRequest::post().uri("/api/register").set_json(json!({
+                "username": username,
+                "email": email,
+                "password": PASSWORD,
+            })))
+            .await;
+        assert_eq!(status, StatusCode::OK, "register failed: {}", body);
+
+        TestUser {
+            id: body["user"]["id"].as_str().unwrap().parse().unwrap(),
+            email,
+            token: body["token"].as_str().unwrap().to_string(),
+            refresh_token: body["refresh_token"].as_str().unwrap().to_string(),
+        }
+    }
+
+    /// 同じユーザーで別のセッションにログインする
+    pub async fn login(&self, user: &TestUser) -> TestUser {
+        let (status, _, body) = self
+            .call(TestRequest::post().uri("/api/login").set_json(json!({
+                "email": user.email,
+                "password": PASSWORD,
+            })))
+            .await;
+        assert_eq!(status, StatusCode::OK, "login failed: {}", body);
+
+        TestUser {
+            token: body["token"].as_str().unwrap().to_string(),
+            refresh_token: body["refresh_token"].as_str().unwrap().to_string(),
+            ..user.clone()
+        }
+    }
+
+    /// ロールを設定し、そのロールのトークンでログインし直す
+    pub async fn grant(&self, user: &TestUser, role: Role) -> TestUser {
+        assert!(self.repos.users.set_role(user.id, role).await.unwrap());
+        self.login(user).await
+    }
+
+    /// ツイートを投稿して ID を返す
+    pub async fn tweet(&self, user: &TestUser, content: &str) -> String {
+        let resp = self
+            .execute(
+                "mutation($c: String!) { createTweet(content: $c) { id } }",
+                json!({ "c": content }),
+                Some(user),
+            )
+            .await;
+        data(&resp)["createTweet"]["id"]
+            .as_str()
+            .unwrap()
+            .to_string()
+    }
+
+    /// コメント（parent_id を指定すれば返信）を投稿して ID を返す
+    pub async fn comment(
+        &self,
+        user: &TestUser,
+        tweet_id: &str,
+        parent_id: Option<&str>,
+        content: &str,
+    ) -> String {
+        let resp = self
+            .execute(
+                r#"
+                mutation($id: UUID!, $parentId: UUID, $c: String!) {
+                    createComment(tweetId: $id, parentId: $parentId, content: $c) { id }
+                }
+                "#,
+                json!({ "id": tweet_id, "parentId": parent_id, "c": content }),
+                Some(user),
+            )
+            .await;
+        data(&resp)["createComment"]["id"]
+            .as_str()
+            .unwrap()
+            .to_string()
+    }
+
+    /// target をフォローする
+    pub async fn follow(&self, user: &TestUser, target: &TestUser) {
+        let resp = self
+            .execute(
+                "mutation($id: UUID!) { followUser(targetId: $id) }",
+                json!({ "id": target.id }),
+                Some(user),
+            )
+            .await;
+        data(&resp);
+    }
+
+    /// ツイートにいいねする
+    pub async fn like(&self, user: &TestUser, tweet_id: &str) {
+        let resp = self
+            .execute(
+                "mutation($id: UUID!) { likeTweet(tweetId: $id) }",
+                json!({ "id": tweet_id }),
+                Some(user),
+            )
+            .await;
+        assert_eq!(data(&resp)["likeTweet"], true);
+    }
+
+    /// タイムラインの先頭10件（totalCount と nodes の content）
+    pub async fn timeline(&self, user: &TestUser) -> Value {
+        let resp = self
+            .execute(
+                "{ timeline(first: 10) { totalCount nodes { content } } }",
+                json!({}),
+                Some(user),
+            )
+            .await;
+        data(&resp)["timeline"].clone()
+    }
+
+    /// GraphQL操作をスキーマで直接実行する（user を指定すればそのトークンで認証する）
+    pub async fn execute(&self, query: &str, variables: Value, user: Option<&TestUser>) -> Value {
+        let request = self.request(query, variables, user).await;
+        serde_json::to_value(self.schema.execute(request).await).unwrap()
+    }
+
+    /// サブスクリプションを開始する（以降に発行されたイベントを受け取れる状態まで進める）
+    pub async fn subscribe(
+        &self,
+        query: &str,
+        variables: Value,
+        user: Option<&TestUser>,
+    ) -> TestSubscription<'_> {
+        let request = self.request(query, variables, user).await;
+        let mut stream = self
+            .schema
+            .execute_stream(request)
+            .map(|resp| serde_json::to_value(resp).unwrap())
+            .boxed();
+        let ready = stream.next().now_or_never().flatten();
+        TestSubscription { stream, ready }
+    }
+
+    async fn request(
+        &self,
+        query: &str,
+        variables: Value,
+        user: Option<&TestUser>,
+    ) -> async_graphql::Request {
+        let mut request = async_graphql::Request::new(query)
+            .variables(Variables::from_json(variables))
+            .data(ClientInfo::default());
+
+        if let Some(user) = user {
+            let claims = verify_jwt(&self.repos, &user.token)
+                .await
+                .expect("valid token");
+            request = request
+                .data(claims.user_id)
+                .data(claims.role)
+                .data(CurrentSession(claims.sid));
+        }
+        request
+    }
+}
+
+/// 購読中のサブスクリプション
+pub struct TestSubscription<'a> {
+    stream: BoxStream<'a, Value>,
+    /// 開始時点で届いていたレスポンス（エラーなど）
+    ready: Option<Value>,
+}
+
+impl TestSubscription<'_> {
+    /// 次のレスポンスを待つ
+    pub async fn next(&mut self) -> Value {
+        if let Some(resp) = self.ready.take() {
+            return resp;
+        }
+        actix_rt::time::timeout(Duration::from_secs(5), self.stream.next())
+            .await
+            .expect("timed out waiting for a subscription event")
+            .expect("subscription ended")
+    }
+
+    /// ストリームが終わったか（次のイベントが届く代わりに終わるのを待つ）
+    pub async fn ended(&mut self) -> bool {
+        actix_rt::time::timeout(Duration::from_secs(5), self.stream.next())
+            .await
+            .expect("timed out waiting for the subscription to end")
+            .is_none()
+    }
+}
+
+#[cfg(not(feature = "postgres"))]
+pub async fn test_repositories() -> Repositories {
+    use crate::config::DatabaseConfig;
+    use crate::store::init_db;
+
+    // インメモリのSQLiteは接続ごとに別のデータベースになるため接続は1本に限る
+    let config = DatabaseConfig {
+        url: "sqlite::memory:".to_string(),
+        max_connections: 1,
+    };
+    Repositories::database(init_db(&config).await.expect("in-memory database"))
+}
+
+/// TEST_DATABASE_URL のデータベースにテスト用のスキーマを作ってマイグレーションを適用する
+///
+/// 未設定ならインメモリのリポジトリを使う。スキーマは調査できるようテスト後も残す
+#[cfg(feature = "postgres")]
+pub async fn test_repositories() -> Repositories {
+    use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+    use sqlx::{Connection, PgConnection};
+    use tokio::sync::OnceCell;
+
+    // 拡張はデータベースに1つしか作れないため、最初のテストで public に作っておく
+    static EXTENSIONS: OnceCell<()> = OnceCell::const_new();
+
+    let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+        return Repositories::in_memory();
+    };
+    let options: PgConnectOptions = url.parse().expect("valid TEST_DATABASE_URL");
+
+    let schema = format!("test_{}", Uuid::new_v4().simple());
+    let mut conn = PgConnection::connect_with(&options)
+        .await
+        .expect("test database");
+    EXTENSIONS
+        .get_or_init(|| async {
+            sqlx::query("CREATE EXTENSION IF NOT EXISTS pg_trgm SCHEMA public")
+                .execute(&mut conn)
+                .await
+                .expect("pg_trgm extension");
+        })
+        .await;
+    sqlx::query(&format!("CREATE SCHEMA {}", schema))
+        .execute(&mut conn)
+        .await
+        .expect("test schema");
+    conn.close().await.expect("close connection");
+
+    let search_path = format!("{},public", schema);
+    let db = PgPoolOptions::new()
+        .max_connections(2)
+        .connect_with(options.options([("search_path", search_path.as_str())]))
+        .await
+        .expect("test database");
+    crate::migrate::migrate(&db).await.expect("migrations");
+    Repositories::database(db)
+}
+
+/// Bearerトークンの Authorization ヘッダー
+pub fn bearer(user: &TestUser) -> (header::HeaderName, String) {
+    (header::AUTHORIZATION, format!("Bearer {}", user.token))
+}
+
+/// GraphQLレスポンスの最初のエラーメッセージ
+pub fn error_message(resp: &Value) -> &str {
+    resp["errors"][0]["message"]
+        .as_str()
+        .unwrap_or_else(|| panic!("expected an error: {}", resp))
+}
+
+/// GraphQLレスポンスの最初のエラーの extensions
+pub fn error_extensions(resp: &Value) -> &Value {
+    error_message(resp);
+    &resp["errors"][0]["extensions"]
+}
+
+/// GraphQLレスポンスにエラーがないことを確認し、data を返す
+pub fn data(resp: &Value) -> &Value {
+    assert!(resp.get("errors").is_none(), "unexpected errors: {}", resp);
+    &resp["data"]
+}
+
+/// コネクションの nodes の content
+pub fn contents(connection: &Value) -> Vec<&str> {
+    connection["nodes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|n| n["content"].as_str().unwrap())
+        .collect()
+}
+
+/// クエリ文字列用の最小限のパーセントエンコード
+pub fn urlencode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
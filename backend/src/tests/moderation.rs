@@ -0,0 +1,470 @@
+use actix_web::http::StatusCode;
+use actix_web::test::TestRequest;
+use serde_json::{Value, json};
+
+use super::{PASSWORD, TestApp, TestUser, bearer, data, error_extensions, error_message};
+use crate::models::Role;
+
+/// モデレーター以上の権限が必要な操作
+const MODERATOR_ONLY: &[&str] = &[
+    "{ reports { id } }",
+    "mutation($id: UUID!) { resolveReport(id: $id) }",
+    "mutation($id: UUID!) { suspendUser(userId: $id) }",
+    "mutation($id: UUID!) { unsuspendUser(userId: $id) }",
+];
+
+/// 管理者のみの操作
+const ADMIN_ONLY: &[&str] = &[
+    "{ moderationLog { id } }",
+    "mutation($id: UUID!) { setUserRole(userId: $id, role: MODERATOR) { id } }",
+];
+
+/// 監査ログ（古い順）
+async fn audit_log(app: &TestApp, admin: &TestUser) -> Vec<Value> {
+    let resp = app
+        .execute(
+            "{ moderationLog { action targetId details actor { id } } }",
+            json!({}),
+            Some(admin),
+        )
+        .await;
+    let mut entries = data(&resp)["moderationLog"].as_array().unwrap().clone();
+    entries.reverse();
+    entries
+}
+
+#[actix_rt::test]
+async fn role_guard_rejects_users_without_permission() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let moderator = app.register("mod").await;
+    let moderator = app.grant(&moderator, Role::Moderator).await;
+    let variables = json!({ "id": alice.id });
+
+    for query in MODERATOR_ONLY.iter().chain(ADMIN_ONLY) {
+        let resp = app.execute(query, variables.clone(), None).await;
+        assert_eq!(
+            error_extensions(&resp)["code"],
+            "UNAUTHENTICATED",
+            "{}",
+            query
+        );
+
+        let resp = app.execute(query, variables.clone(), Some(&alice)).await;
+        assert_eq!(error_extensions(&resp)["code"], "FORBIDDEN", "{}", query);
+        assert_eq!(error_message(&resp), "Insufficient permissions");
+    }
+
+    // モデレーターは管理者のみの操作はできない
+    for query in ADMIN_ONLY {
+        let resp = app
+            .execute(query, variables.clone(), Some(&moderator))
+            .await;
+        assert_eq!(error_extensions(&resp)["code"], "FORBIDDEN", "{}", query);
+    }
+
+    // REST でも同じガードで拒否する
+    let (status, _, body) = app
+        .call(
+            TestRequest::get()
+                .uri("/api/reports")
+                .insert_header(bearer(&alice)),
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["code"], "FORBIDDEN");
+
+    let (status, _, _) = app
+        .call(
+            TestRequest::get()
+                .uri("/api/reports")
+                .insert_header(bearer(&moderator)),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[actix_rt::test]
+async fn role_changes_revoke_existing_sessions() {
+    let app = TestApp::new().await;
+    let admin = app.register("admin").await;
+    let admin = app.grant(&admin, Role::Admin).await;
+    let bob = app.register("bob").await;
+
+    let resp = app
+        .execute(
+            "mutation($id: UUID!) { setUserRole(userId: $id, role: MODERATOR) { role } }",
+            json!({ "id": bob.id }),
+            Some(&admin),
+        )
+        .await;
+    assert_eq!(data(&resp)["setUserRole"]["role"], "MODERATOR");
+
+    // 古いロールのトークン・リフレッシュトークンは失効する
+    let (status, _, _) = app
+        .call(
+            TestRequest::get()
+                .uri("/api/timeline")
+                .insert_header(bearer(&bob)),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _, _) = app
+        .call(
+            TestRequest::post()
+                .uri("/api/token/refresh")
+                .set_json(json!({ "refresh_token": bob.refresh_token })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // ログインし直すと新しいロールがトークンに入る
+    let bob = app.login(&bob).await;
+    let resp = app
+        .execute("{ reports { id } }", json!({}), Some(&bob))
+        .await;
+    assert_eq!(data(&resp)["reports"], json!([]));
+
+    // 自分のロールは変更できない
+    let resp = app
+        .execute(
+            "mutation($id: UUID!) { setUserRole(userId: $id, role: USER) { id } }",
+            json!({ "id": admin.id }),
+            Some(&admin),
+        )
+        .await;
+    assert_eq!(error_message(&resp), "Cannot change your own role");
+
+    // 管理者には他のユーザーの email も見える
+    let resp = app
+        .execute(
+            "query($id: UUID!) { user(id: $id) { email role } }",
+            json!({ "id": bob.id }),
+            Some(&admin),
+        )
+        .await;
+    assert_eq!(
+        data(&resp)["user"],
+        json!({ "email": bob.email, "role": "MODERATOR" })
+    );
+}
+
+#[actix_rt::test]
+async fn admins_cannot_change_the_role_of_other_admins() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let alice = app.grant(&alice, Role::Admin).await;
+    let bob = app.register("bob").await;
+    let bob = app.grant(&bob, Role::Admin).await;
+
+    let resp = app
+        .execute(
+            "mutation($id: UUID!) { setUserRole(userId: $id, role: USER) { id } }",
+            json!({ "id": bob.id }),
+            Some(&alice),
+        )
+        .await;
+    assert_eq!(error_extensions(&resp)["code"], "FORBIDDEN");
+    assert_eq!(
+        error_message(&resp),
+        "Cannot moderate a user with an equal or higher role"
+    );
+
+    let resp = app
+        .execute(
+            "query($id: UUID!) { user(id: $id) { role } }",
+            json!({ "id": bob.id }),
+            Some(&alice),
+        )
+        .await;
+    assert_eq!(data(&resp)["user"]["role"], "ADMIN");
+    assert!(audit_log(&app, &alice).await.is_empty());
+}
+
+#[actix_rt::test]
+async fn demoted_users_lose_their_permissions_immediately() {
+    let app = TestApp::new().await;
+    let admin = app.register("admin").await;
+    let admin = app.grant(&admin, Role::Admin).await;
+    let moderator = app.register("mod").await;
+    let moderator = app.grant(&moderator, Role::Moderator).await;
+
+    let (status, _, _) = app
+        .call(
+            TestRequest::get()
+                .uri("/api/reports")
+                .insert_header(bearer(&moderator)),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let resp = app
+        .execute(
+            "mutation($id: UUID!) { setUserRole(userId: $id, role: USER) { role } }",
+            json!({ "id": moderator.id }),
+            Some(&admin),
+        )
+        .await;
+    assert_eq!(data(&resp)["setUserRole"]["role"], "USER");
+
+    // 降格前のトークンではモデレーター用の API を使えない
+    let (status, _, body) = app
+        .call(
+            TestRequest::get()
+                .uri("/api/reports")
+                .insert_header(bearer(&moderator)),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["code"], "UNAUTHENTICATED");
+
+    let moderator = app.login(&moderator).await;
+    let resp = app
+        .execute("{ reports { id } }", json!({}), Some(&moderator))
+        .await;
+    assert_eq!(error_extensions(&resp)["code"], "FORBIDDEN");
+}
+
+#[actix_rt::test]
+async fn moderators_can_delete_any_content() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let moderator = app.register("mod").await;
+    let moderator = app.grant(&moderator, Role::Moderator).await;
+    let admin = app.register("admin").await;
+    let admin = app.grant(&admin, Role::Admin).await;
+
+    let tweet_id = app.tweet(&alice, "hello").await;
+    let comment_id = app.comment(&alice, &tweet_id, None, "hi").await;
+
+    // 一般ユーザーは他人のツイート・コメントを削除できない
+    let resp = app
+        .execute(
+            "mutation($id: UUID!) { deleteComment(id: $id) }",
+            json!({ "id": comment_id }),
+            Some(&bob),
+        )
+        .await;
+    assert_eq!(error_message(&resp), "Comment not found or not authorized");
+    let (status, _, _) = app
+        .call(
+            TestRequest::delete()
+                .uri(&format!("/api/tweets/{}", tweet_id))
+                .insert_header(bearer(&bob)),
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // モデレーターは削除でき、監査ログに残る
+    let resp = app
+        .execute(
+            "mutation($id: UUID!) { deleteComment(id: $id) }",
+            json!({ "id": comment_id }),
+            Some(&moderator),
+        )
+        .await;
+    assert_eq!(data(&resp)["deleteComment"], true);
+    let (status, _, _) = app
+        .call(
+            TestRequest::delete()
+                .uri(&format!("/api/tweets/{}", tweet_id))
+                .insert_header(bearer(&moderator)),
+        )
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    // 本人による削除は記録しない
+    let own_tweet = app.tweet(&moderator, "hello").await;
+    let resp = app
+        .execute(
+            "mutation($id: UUID!) { deleteTweet(id: $id) }",
+            json!({ "id": own_tweet }),
+            Some(&moderator),
+        )
+        .await;
+    assert_eq!(data(&resp)["deleteTweet"], true);
+
+    let actor = json!({ "id": moderator.id });
+    assert_eq!(
+        audit_log(&app, &admin).await,
+        vec![
+            json!({ "action": "DELETE_COMMENT", "targetId": comment_id, "details": null, "actor": actor }),
+            json!({ "action": "DELETE_TWEET", "targetId": tweet_id, "details": null, "actor": actor }),
+        ]
+    );
+}
+
+#[actix_rt::test]
+async fn suspended_users_are_logged_out_and_cannot_log_in() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let moderator = app.register("mod").await;
+    let moderator = app.grant(&moderator, Role::Moderator).await;
+    let admin = app.register("admin").await;
+    let admin = app.grant(&admin, Role::Admin).await;
+
+    let (status, _, _) = app
+        .call(
+            TestRequest::post()
+                .uri(&format!("/api/users/{}/suspend", alice.id))
+                .insert_header(bearer(&moderator))
+                .set_json(json!({ "reason": "spam" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    // 既存のトークン・リフレッシュトークンは失効し、ログインもできない
+    let (status, _, _) = app
+        .call(
+            TestRequest::get()
+                .uri("/api/timeline")
+                .insert_header(bearer(&alice)),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _, _) = app
+        .call(
+            TestRequest::post()
+                .uri("/api/token/refresh")
+                .set_json(json!({ "refresh_token": alice.refresh_token })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _, body) = app
+        .call(TestRequest::post().uri("/api/login").set_json(json!({
+            "email": alice.email,
+            "password": PASSWORD,
+        })))
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["detail"], "Account is suspended");
+
+    // 利用停止の状態はモデレーターにだけ見える
+    let query = "query($id: UUID!) { user(id: $id) { suspendedAt } }";
+    let variables = json!({ "id": alice.id });
+    let resp = app
+        .execute(query, variables.clone(), Some(&moderator))
+        .await;
+    assert!(data(&resp)["user"]["suspendedAt"].is_string());
+    let resp = app.execute(query, variables.clone(), None).await;
+    assert_eq!(data(&resp)["user"]["suspendedAt"], Value::Null);
+
+    // 同格以上のユーザーは利用停止にできない
+    for target in [&admin, &moderator] {
+        let resp = app
+            .execute(
+                "mutation($id: UUID!) { suspendUser(userId: $id) }",
+                json!({ "id": target.id }),
+                Some(&moderator),
+            )
+            .await;
+        assert_eq!(error_extensions(&resp)["code"], "FORBIDDEN");
+    }
+
+    let resp = app
+        .execute(
+            "mutation($id: UUID!) { unsuspendUser(userId: $id) }",
+            variables.clone(),
+            Some(&moderator),
+        )
+        .await;
+    assert_eq!(data(&resp)["unsuspendUser"], true);
+    app.login(&alice).await;
+
+    let actions: Vec<Value> = audit_log(&app, &admin)
+        .await
+        .into_iter()
+        .map(|entry| json!([entry["action"], entry["details"]]))
+        .collect();
+    assert_eq!(
+        actions,
+        vec![
+            json!(["SUSPEND_USER", "spam"]),
+            json!(["UNSUSPEND_USER", null])
+        ]
+    );
+}
+
+#[actix_rt::test]
+async fn reports_are_listed_and_resolved_by_moderators() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let moderator = app.register("mod").await;
+    let moderator = app.grant(&moderator, Role::Moderator).await;
+
+    let tweet_id = app.tweet(&alice, "hello").await;
+    let report = "mutation($input: ReportInput!) {
+        reportContent(input: $input) { id targetType targetId reason reporter { id } }
+    }";
+
+    let resp = app
+        .execute(
+            report,
+            json!({ "input": { "targetType": "TWEET", "targetId": tweet_id, "reason": "spam" } }),
+            Some(&bob),
+        )
+        .await;
+    let report_id = data(&resp)["reportContent"]["id"].clone();
+    assert_eq!(
+        data(&resp)["reportContent"]["reporter"]["id"],
+        json!(bob.id)
+    );
+
+    // 存在しない対象・空の理由は通報できない
+    let resp = app
+        .execute(
+            report,
+            json!({ "input": { "targetType": "COMMENT", "targetId": tweet_id, "reason": "spam" } }),
+            Some(&bob),
+        )
+        .await;
+    assert_eq!(error_extensions(&resp)["code"], "NOT_FOUND");
+    let resp = app
+        .execute(
+            report,
+            json!({ "input": { "targetType": "USER", "targetId": alice.id, "reason": "" } }),
+            Some(&bob),
+        )
+        .await;
+    assert_eq!(error_extensions(&resp)["field"], "reason");
+
+    let (status, _, body) = app
+        .call(
+            TestRequest::get()
+                .uri("/api/reports")
+                .insert_header(bearer(&moderator)),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body[0]["id"], report_id);
+    assert_eq!(body[0]["target_type"], "tweet");
+    assert_eq!(body[0]["reporter_id"], json!(bob.id));
+
+    let resolve = "mutation($id: UUID!) { resolveReport(id: $id) }";
+    let resp = app
+        .execute(resolve, json!({ "id": report_id }), Some(&moderator))
+        .await;
+    assert_eq!(data(&resp)["resolveReport"], true);
+
+    // 対応済みの通報は一覧から消え、再度は対応できない
+    let resp = app
+        .execute("{ reports { id } }", json!({}), Some(&moderator))
+        .await;
+    assert_eq!(data(&resp)["reports"], json!([]));
+    let resp = app
+        .execute(resolve, json!({ "id": report_id }), Some(&moderator))
+        .await;
+    assert_eq!(error_extensions(&resp)["code"], "NOT_FOUND");
+
+    // 理由の長さはバイト数ではなく文字で数える
+    let resp = app
+        .execute(
+            report,
+            json!({ "input": { "targetType": "USER", "targetId": alice.id, "reason": "迷".repeat(500) } }),
+            Some(&bob),
+        )
+        .await;
+    assert_eq!(data(&resp)["reportContent"]["targetType"], "USER");
+}
@@ -0,0 +1,290 @@
+use serde_json::{Value, json};
+
+use super::{TestApp, TestUser, data};
+
+const NOTIFICATIONS: &str = r#"
+    query($first: Int, $after: String) {
+        notifications(first: $first, after: $after) {
+            totalCount unreadCount
+            pageInfo { hasNextPage endCursor }
+            nodes {
+                kind summary isRead actorCount
+                actors { username }
+                tweet { content }
+                comment { content }
+            }
+        }
+    }
+"#;
+
+async fn notifications(app: &TestApp, user: &TestUser) -> Value {
+    let resp = app.execute(NOTIFICATIONS, json!({}), Some(user)).await;
+    data(&resp)["notifications"].clone()
+}
+
+fn summaries(notifications: &Value) -> Vec<&str> {
+    notifications["nodes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|n| n["summary"].as_str().unwrap())
+        .collect()
+}
+
+#[actix_rt::test]
+async fn likes_follows_comments_and_mentions_notify() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+
+    let tweet_id = app.tweet(&alice, "hello").await;
+    app.like(&bob, &tweet_id).await;
+    app.follow(&bob, &alice).await;
+    app.comment(&bob, &tweet_id, None, "nice").await;
+    app.tweet(&bob, "hi @alice").await;
+
+    // 新しい順に並ぶ
+    let result = notifications(&app, &alice).await;
+    assert_eq!(
+        summaries(&result),
+        vec![
+            "bob mentioned you",
+            "bob commented on your tweet",
+            "bob followed you",
+            "bob liked your tweet",
+        ]
+    );
+    let nodes = &result["nodes"];
+    assert_eq!(nodes[0]["kind"], "MENTION");
+    assert_eq!(nodes[0]["tweet"]["content"], "hi @alice");
+    assert_eq!(nodes[1]["comment"]["content"], "nice");
+    assert_eq!(nodes[1]["tweet"]["content"], "hello");
+    assert_eq!(nodes[2]["tweet"], Value::Null);
+    assert_eq!(nodes[3]["actors"], json!([{ "username": "bob" }]));
+    assert_eq!(result["totalCount"], 4);
+    assert_eq!(result["unreadCount"], 4);
+
+    // bob には何も届いていない
+    let result = notifications(&app, &bob).await;
+    assert_eq!(result["totalCount"], 0);
+}
+
+#[actix_rt::test]
+async fn notifications_for_the_same_target_are_grouped() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let tweet_id = app.tweet(&alice, "hello").await;
+    let other_id = app.tweet(&alice, "another").await;
+
+    let mut users = Vec::new();
+    for name in ["bob", "carol", "dave", "erin"] {
+        let user = app.register(name).await;
+        app.like(&user, &tweet_id).await;
+        users.push(user);
+    }
+    app.like(&users[0], &other_id).await;
+
+    let result = notifications(&app, &alice).await;
+    assert_eq!(
+        summaries(&result),
+        vec!["bob liked your tweet", "erin and 3 others liked your tweet"]
+    );
+    // 最近操作したユーザーは新しい順に最大3人
+    let group = &result["nodes"][1];
+    assert_eq!(group["actorCount"], 4);
+    assert_eq!(
+        group["actors"],
+        json!([{ "username": "erin" }, { "username": "dave" }, { "username": "carol" }])
+    );
+    assert_eq!(result["totalCount"], 2);
+
+    // 取り消して再びいいねしても同じユーザーは1人として数え、まとまりが先頭に来る
+    app.execute(
+        "mutation($id: UUID!) { unlikeTweet(tweetId: $id) }",
+        json!({ "id": tweet_id }),
+        Some(&users[1]),
+    )
+    .await;
+    app.like(&users[1], &tweet_id).await;
+    let result = notifications(&app, &alice).await;
+    assert_eq!(
+        summaries(&result),
+        vec![
+            "carol and 3 others liked your tweet",
+            "bob liked your tweet"
+        ]
+    );
+
+    // 言及はツイートごとにまとまる
+    app.tweet(&users[0], "@alice one").await;
+    app.tweet(&users[0], "@alice two").await;
+    let result = notifications(&app, &alice).await;
+    assert_eq!(result["totalCount"], 4);
+    assert_eq!(summaries(&result)[0], "bob mentioned you");
+    assert_eq!(result["nodes"][0]["tweet"]["content"], "@alice two");
+}
+
+#[actix_rt::test]
+async fn own_actions_do_not_notify() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+
+    let tweet_id = app.tweet(&alice, "hello @alice").await;
+    app.like(&alice, &tweet_id).await;
+    app.comment(&alice, &tweet_id, None, "me again").await;
+    assert_eq!(notifications(&app, &alice).await["totalCount"], 0);
+
+    // 投稿者を言及したコメントは言及の通知だけになる
+    app.comment(&bob, &tweet_id, None, "@alice hi").await;
+    let result = notifications(&app, &alice).await;
+    assert_eq!(summaries(&result), vec!["bob mentioned you"]);
+    assert_eq!(result["nodes"][0]["comment"]["content"], "@alice hi");
+}
+
+#[actix_rt::test]
+async fn marking_as_read_resets_unread_count() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let carol = app.register("carol").await;
+
+    let tweet_id = app.tweet(&alice, "hello").await;
+    app.like(&bob, &tweet_id).await;
+    app.follow(&bob, &alice).await;
+
+    let resp = app
+        .execute(
+            "mutation { markNotificationsRead }",
+            json!({}),
+            Some(&alice),
+        )
+        .await;
+    assert_eq!(data(&resp)["markNotificationsRead"], 2);
+    let result = notifications(&app, &alice).await;
+    assert_eq!(result["unreadCount"], 0);
+    assert_eq!(result["nodes"][0]["isRead"], true);
+
+    // 既読のまとまりに新しい通知が来ると未読に戻る
+    app.like(&carol, &tweet_id).await;
+    let result = notifications(&app, &alice).await;
+    assert_eq!(result["unreadCount"], 1);
+    assert_eq!(result["totalCount"], 2);
+    let first = &result["nodes"][0];
+    assert_eq!(first["summary"], "carol and 1 other liked your tweet");
+    assert_eq!(first["isRead"], false);
+    assert_eq!(result["nodes"][1]["isRead"], true);
+
+    let resp = app
+        .execute(
+            "mutation { markNotificationsRead }",
+            json!({}),
+            Some(&alice),
+        )
+        .await;
+    assert_eq!(data(&resp)["markNotificationsRead"], 1);
+}
+
+#[actix_rt::test]
+async fn disabled_kinds_are_not_delivered() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+
+    let resp = app
+        .execute(
+            "mutation { setNotificationPreference(kind: LIKE, enabled: false) { kind enabled } }",
+            json!({}),
+            Some(&alice),
+        )
+        .await;
+    assert_eq!(
+        data(&resp)["setNotificationPreference"],
+        json!({ "kind": "LIKE", "enabled": false })
+    );
+    let resp = app
+        .execute(
+            "{ notificationPreferences { kind enabled } }",
+            json!({}),
+            Some(&alice),
+        )
+        .await;
+    assert_eq!(
+        data(&resp)["notificationPreferences"],
+        json!([
+            { "kind": "LIKE", "enabled": false },
+            { "kind": "FOLLOW", "enabled": true },
+            { "kind": "COMMENT", "enabled": true },
+            { "kind": "MENTION", "enabled": true },
+        ])
+    );
+
+    let tweet_id = app.tweet(&alice, "hello").await;
+    app.like(&bob, &tweet_id).await;
+    app.follow(&bob, &alice).await;
+    assert_eq!(
+        summaries(&notifications(&app, &alice).await),
+        vec!["bob followed you"]
+    );
+
+    // 有効に戻すと以降の通知は届く
+    app.execute(
+        "mutation { setNotificationPreference(kind: LIKE, enabled: true) { enabled } }",
+        json!({}),
+        Some(&alice),
+    )
+    .await;
+    let other_id = app.tweet(&alice, "again").await;
+    app.like(&bob, &other_id).await;
+    assert_eq!(
+        summaries(&notifications(&app, &alice).await),
+        vec!["bob liked your tweet", "bob followed you"]
+    );
+}
+
+#[actix_rt::test]
+async fn notifications_are_paginated_and_removed_with_their_target() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+
+    let mut tweet_ids = Vec::new();
+    for i in 0..3 {
+        let tweet_id = app.tweet(&alice, &format!("tweet {}", i)).await;
+        app.like(&bob, &tweet_id).await;
+        tweet_ids.push(tweet_id);
+    }
+
+    let resp = app
+        .execute(NOTIFICATIONS, json!({ "first": 2 }), Some(&alice))
+        .await;
+    let page = &data(&resp)["notifications"];
+    let contents: Vec<&Value> = page["nodes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|n| &n["tweet"]["content"])
+        .collect();
+    assert_eq!(contents, vec!["tweet 2", "tweet 1"]);
+    assert_eq!(page["pageInfo"]["hasNextPage"], true);
+
+    let resp = app
+        .execute(
+            NOTIFICATIONS,
+            json!({ "first": 2, "after": page["pageInfo"]["endCursor"] }),
+            Some(&alice),
+        )
+        .await;
+    let page = &data(&resp)["notifications"];
+    assert_eq!(page["nodes"][0]["tweet"]["content"], "tweet 0");
+    assert_eq!(page["pageInfo"]["hasNextPage"], false);
+
+    // ツイートを削除すると通知も消える
+    app.execute(
+        "mutation($id: UUID!) { deleteTweet(id: $id) }",
+        json!({ "id": tweet_ids[0] }),
+        Some(&alice),
+    )
+    .await;
+    assert_eq!(notifications(&app, &alice).await["totalCount"], 2);
+}
@@ -0,0 +1,252 @@
+use chrono::Utc;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use super::{TestApp, TestUser, data, error_message};
+use crate::models::Tweet;
+
+const TIMELINE: &str = "query($first: Int, $after: String, $last: Int, $before: String) {
+    timeline(first: $first, after: $after, last: $last, before: $before) {
+        totalCount
+        pageInfo { hasPreviousPage hasNextPage startCursor endCursor }
+        edges { cursor node { content } }
+    }
+}";
+
+/// ページ内のツイート本文
+fn contents(connection: &Value) -> Vec<String> {
+    connection["edges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["node"]["content"].as_str().unwrap().to_string())
+        .collect()
+}
+
+async fn post_tweets(app: &TestApp, user: &TestUser, count: usize) {
+    for i in 0..count {
+        app.execute(
+            "mutation($content: String!) { createTweet(content: $content) { id } }",
+            json!({ "content": format!("tweet {}", i) }),
+            Some(user),
+        )
+        .await;
+    }
+}
+
+async fn timeline(app: &TestApp, user: &TestUser, variables: Value) -> Value {
+    let resp = app.execute(TIMELINE, variables, Some(user)).await;
+    data(&resp)["timeline"].clone()
+}
+
+#[actix_rt::test]
+async fn timeline_pages_forward() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    post_tweets(&app, &alice, 5).await;
+
+    let page = timeline(&app, &alice, json!({ "first": 2 })).await;
+    assert_eq!(page["totalCount"], 5);
+    assert_eq!(contents(&page), ["tweet 4", "tweet 3"]);
+    assert_eq!(page["pageInfo"]["hasPreviousPage"], false);
+    assert_eq!(page["pageInfo"]["hasNextPage"], true);
+    assert_eq!(page["pageInfo"]["endCursor"], page["edges"][1]["cursor"]);
+
+    let page = timeline(
+        &app,
+        &alice,
+        json!({ "first": 2, "after": page["pageInfo"]["endCursor"] }),
+    )
+    .await;
+    assert_eq!(contents(&page), ["tweet 2", "tweet 1"]);
+    assert_eq!(page["pageInfo"]["hasPreviousPage"], true);
+    assert_eq!(page["pageInfo"]["hasNextPage"], true);
+
+    let page = timeline(
+        &app,
+        &alice,
+        json!({ "first": 2, "after": page["pageInfo"]["endCursor"] }),
+    )
+    .await;
+    assert_eq!(contents(&page), ["tweet 0"]);
+    assert_eq!(page["pageInfo"]["hasNextPage"], false);
+
+    // 省略時は先頭から既定の件数
+    let page = timeline(&app, &alice, json!({})).await;
+    assert_eq!(contents(&page).len(), 5);
+    assert_eq!(page["pageInfo"]["hasNextPage"], false);
+}
+
+#[actix_rt::test]
+async fn timeline_pages_backward() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    post_tweets(&app, &alice, 5).await;
+
+    let page = timeline(&app, &alice, json!({ "last": 2 })).await;
+    assert_eq!(contents(&page), ["tweet 1", "tweet 0"]);
+    assert_eq!(page["pageInfo"]["hasPreviousPage"], true);
+    assert_eq!(page["pageInfo"]["hasNextPage"], false);
+
+    let page = timeline(
+        &app,
+        &alice,
+        json!({ "last": 2, "before": page["pageInfo"]["startCursor"] }),
+    )
+    .await;
+    assert_eq!(contents(&page), ["tweet 3", "tweet 2"]);
+    assert_eq!(page["pageInfo"]["hasPreviousPage"], true);
+    assert_eq!(page["pageInfo"]["hasNextPage"], true);
+
+    let page = timeline(
+        &app,
+        &alice,
+        json!({ "last": 2, "before": page["pageInfo"]["startCursor"] }),
+    )
+    .await;
+    assert_eq!(contents(&page), ["tweet 4"]);
+    assert_eq!(page["pageInfo"]["hasPreviousPage"], false);
+}
+
+#[actix_rt::test]
+async fn cursors_are_stable_for_equal_timestamps() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+
+    // 同じ作成日時のツイートも ID で順序が決まり、重複・欠落なくたどれる
+    let created_at = Utc::now().to_rfc3339();
+    for i in 0..5 {
+        let tweet = Tweet {
+            id: Uuid::new_v4(),
+            user_id: alice.id,
+            content: format!("same {}", i),
+            created_at: created_at.clone(),
+            quoted_tweet_id: None,
+        };
+        app.repos.tweets.insert(&tweet, &[], &[]).await.unwrap();
+    }
+
+    let mut seen = Vec::new();
+    let mut after = Value::Null;
+    loop {
+        let page = timeline(&app, &alice, json!({ "first": 2, "after": after })).await;
+        seen.extend(contents(&page));
+        if page["pageInfo"]["hasNextPage"] == false {
+            break;
+        }
+        after = page["pageInfo"]["endCursor"].clone();
+    }
+
+    seen.sort();
+    assert_eq!(seen, ["same 0", "same 1", "same 2", "same 3", "same 4"]);
+}
+
+#[actix_rt::test]
+async fn comments_and_follows_are_paginated() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let resp = app
+        .execute(
+            "mutation { createTweet(content: \"hello\") { id } }",
+            json!({}),
+            Some(&alice),
+        )
+        .await;
+    let tweet_id = data(&resp)["createTweet"]["id"].clone();
+
+    let mut fans = Vec::new();
+    for name in ["bob", "carol", "dave"] {
+        let fan = app.register(name).await;
+        app.execute(
+            "mutation($id: UUID!, $content: String!) {
+                createComment(tweetId: $id, content: $content) { id }
+            }",
+            json!({ "id": tweet_id, "content": format!("from {}", name) }),
+            Some(&fan),
+        )
+        .await;
+        app.execute(
+            "mutation($id: UUID!) { followUser(targetId: $id) }",
+            json!({ "id": alice.id }),
+            Some(&fan),
+        )
+        .await;
+        fans.push(fan);
+    }
+
+    // コメントは古い順
+    let resp = app
+        .execute(
+            "query($id: UUID!) {
+                comments(tweetId: $id, first: 2) {
+                    totalCount pageInfo { hasNextPage } nodes { content }
+                }
+            }",
+            json!({ "id": tweet_id }),
+            None,
+        )
+        .await;
+    assert_eq!(
+        data(&resp)["comments"],
+        json!({
+            "totalCount": 3,
+            "pageInfo": { "hasNextPage": true },
+            "nodes": [{ "content": "from bob" }, { "content": "from carol" }],
+        })
+    );
+
+    // フォロワーはフォローの新しい順
+    let resp = app
+        .execute(
+            "query($id: UUID!) {
+                followers(userId: $id, last: 1) {
+                    totalCount pageInfo { hasPreviousPage } nodes { username }
+                }
+            }",
+            json!({ "id": alice.id }),
+            Some(&alice),
+        )
+        .await;
+    assert_eq!(
+        data(&resp)["followers"],
+        json!({
+            "totalCount": 3,
+            "pageInfo": { "hasPreviousPage": true },
+            "nodes": [{ "username": "bob" }],
+        })
+    );
+
+    let resp = app
+        .execute(
+            "query($id: UUID!) { following(userId: $id, first: 5) { totalCount nodes { username } } }",
+            json!({ "id": fans[0].id }),
+            Some(&alice),
+        )
+        .await;
+    assert_eq!(
+        data(&resp)["following"],
+        json!({ "totalCount": 1, "nodes": [{ "username": "alice" }] })
+    );
+}
+
+#[actix_rt::test]
+async fn invalid_page_arguments() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+
+    for (variables, message) in [
+        (
+            json!({ "first": 1, "last": 1 }),
+            "Passing both first and last is not supported",
+        ),
+        (json!({ "first": 101 }), "first/last must be at most 100"),
+        (json!({ "after": "not-a-cursor" }), "Invalid cursor"),
+        (
+            json!({ "first": -1 }),
+            "The \"first\" parameter must be a non-negative number",
+        ),
+    ] {
+        let resp = app.execute(TIMELINE, variables, Some(&alice)).await;
+        assert_eq!(error_message(&resp), message);
+    }
+}
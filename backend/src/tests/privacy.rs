@@ -0,0 +1,156 @@
+use serde_json::{Value, json};
+
+use super::{PASSWORD, TestApp, TestUser, data, error_extensions};
+
+/// ログインユーザーのみが使えるフィールド
+const LOGIN_REQUIRED: &[&str] = &[
+    "{ timeline { totalCount } }",
+    "{ mentionsTimeline { totalCount } }",
+    "{ sessions { id } }",
+    "{ notifications { totalCount unreadCount } }",
+    "{ notificationPreferences { kind } }",
+    "{ conversations { totalCount } }",
+    "query($id: UUID!) { messages(conversationId: $id) { totalCount } }",
+    "query($id: UUID!) { followers(userId: $id) { totalCount } }",
+    "query($id: UUID!) { following(userId: $id) { totalCount } }",
+    "mutation { createTweet(content: \"hello\") { id } }",
+    "mutation($id: UUID!) { deleteTweet(id: $id) }",
+    "mutation($id: UUID!) { likeTweet(tweetId: $id) }",
+    "mutation($id: UUID!) { unlikeTweet(tweetId: $id) }",
+    "mutation($id: UUID!) { retweet(tweetId: $id) }",
+    "mutation($id: UUID!) { undoRetweet(tweetId: $id) }",
+    "mutation($id: UUID!) { quoteTweet(tweetId: $id, content: \"hi\") { id } }",
+    "mutation($id: UUID!) { createComment(tweetId: $id, content: \"hi\") { id } }",
+    "mutation($id: UUID!) { deleteComment(id: $id) }",
+    "mutation($id: UUID!) { followUser(targetId: $id) }",
+    "mutation($id: UUID!) { unfollowUser(targetId: $id) }",
+    "mutation($id: UUID!) { blockUser(targetId: $id) }",
+    "mutation($id: UUID!) { unblockUser(targetId: $id) }",
+    "mutation($id: UUID!) { muteUser(targetId: $id) }",
+    "mutation($id: UUID!) { unmuteUser(targetId: $id) }",
+    "mutation($id: UUID!) { revokeSession(id: $id) }",
+    "mutation { revokeAllOtherSessions }",
+    "mutation { markNotificationsRead }",
+    "mutation { setNotificationPreference(kind: LIKE, enabled: false) { enabled } }",
+    "mutation($id: UUID!) { sendDirectMessage(recipientIds: [$id], content: \"hi\") { id } }",
+    "mutation($id: UUID!) { markConversationRead(conversationId: $id) }",
+    "mutation { setAllowOpenDirectMessages(allow: true) }",
+    "mutation($id: UUID!) { reportContent(input: { targetType: TWEET, targetId: $id, reason: \"spam\" }) { id } }",
+];
+
+/// ユーザー・ツイートの投稿者・コメントの投稿者の email
+fn emails(resp: &Value) -> Vec<Value> {
+    let data = &resp["data"];
+    vec![
+        data["user"]["email"].clone(),
+        data["tweet"]["user"]["email"].clone(),
+        data["comments"]["nodes"][0]["user"]["email"].clone(),
+    ]
+}
+
+async fn setup() -> (TestApp, TestUser, TestUser, String) {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+
+    let tweet_id = app.tweet(&alice, "hello").await;
+    app.comment(&alice, &tweet_id, None, "hi").await;
+    app.follow(&bob, &alice).await;
+
+    (app, alice, bob, tweet_id)
+}
+
+#[actix_rt::test]
+async fn email_is_redacted_for_other_users() {
+    let (app, alice, bob, tweet_id) = setup().await;
+    let query = "query($user: UUID!, $tweet: UUID!) {
+        user(id: $user) { email }
+        tweet(id: $tweet) { user { email } }
+        comments(tweetId: $tweet) { nodes { user { email } } }
+    }";
+    let variables = json!({ "user": alice.id, "tweet": tweet_id });
+
+    // 本人には見える
+    let resp = app.execute(query, variables.clone(), Some(&alice)).await;
+    let email = json!(alice.email);
+    data(&resp);
+    assert_eq!(emails(&resp), vec![email; 3]);
+
+    // 他のユーザーや未ログインでは null になる（ユーザー自体は返る）
+    for user in [Some(&bob), None] {
+        let resp = app.execute(query, variables.clone(), user).await;
+        data(&resp);
+        assert_eq!(emails(&resp), vec![Value::Null; 3]);
+        assert_eq!(resp["data"]["user"], json!({ "email": null }));
+    }
+
+    // フォロー一覧では自分以外の email は見えない
+    let resp = app
+        .execute(
+            "query($id: UUID!) {
+                followers(userId: $id) { nodes { id email } }
+            }",
+            json!({ "id": alice.id }),
+            Some(&alice),
+        )
+        .await;
+    assert_eq!(
+        data(&resp)["followers"]["nodes"],
+        json!([{ "id": bob.id, "email": null }])
+    );
+}
+
+#[actix_rt::test]
+async fn own_email_is_visible_after_login() {
+    let (app, alice, _, _) = setup().await;
+
+    let resp = app
+        .execute("{ me { email } }", json!({}), Some(&alice))
+        .await;
+    assert_eq!(data(&resp)["me"]["email"], alice.email);
+
+    // トークン発行前のログイン結果にも本人の email を返す
+    let resp = app
+        .execute(
+            "mutation($input: LoginInput!) { login(input: $input) { user { email } } }",
+            json!({ "input": { "email": alice.email, "password": PASSWORD } }),
+            None,
+        )
+        .await;
+    assert_eq!(data(&resp)["login"]["user"]["email"], alice.email);
+}
+
+#[actix_rt::test]
+async fn guarded_fields_require_login() {
+    let (app, alice, _, tweet_id) = setup().await;
+
+    for query in LOGIN_REQUIRED {
+        let resp = app.execute(query, json!({ "id": tweet_id }), None).await;
+        assert_eq!(
+            error_extensions(&resp)["code"],
+            "UNAUTHENTICATED",
+            "{}",
+            query
+        );
+    }
+
+    // 公開フィールドは未ログインでも読める
+    let resp = app
+        .execute(
+            "query($id: UUID!) { user(id: $id) { username followersCount } }",
+            json!({ "id": alice.id }),
+            None,
+        )
+        .await;
+    assert_eq!(
+        data(&resp)["user"],
+        json!({ "username": "alice", "followersCount": 1 })
+    );
+}
+
+#[actix_rt::test]
+async fn private_fields_are_nullable_in_schema() {
+    // ガードで拒否されたときに親のオブジェクトまで null にならないよう nullable にする
+    let sdl = TestApp::new().await.schema.sdl();
+    assert!(sdl.contains("\temail: String\n"), "{}", sdl);
+}
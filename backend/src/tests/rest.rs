@@ -0,0 +1,435 @@
+use actix_web::http::{StatusCode, header};
+use actix_web::test::TestRequest;
+use serde_json::json;
+
+use super::{PASSWORD, TestApp, bearer, urlencode};
+
+#[actix_rt::test]
+async fn register_returns_tokens_and_user() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+
+    assert!(!alice.token.is_empty());
+    assert!(!alice.refresh_token.is_empty());
+
+    // 同じメールアドレス・ユーザー名は登録できない
+    for (username, email, message) in [
+        ("alice2", "alice@example.com", "Email already exists"),
+        ("alice", "other@example.com", "Username already exists"),
+    ] {
+        let (status, _, body) = app
+            .call(TestRequest::post().uri("/api/register").set_json(json!({
+                "username": username,
+                "email": email,
+                "password": PASSWORD,
+            })))
+            .await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["detail"], message);
+        assert_eq!(body["code"], "CONFLICT");
+    }
+}
+
+#[actix_rt::test]
+async fn login_checks_password() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+
+    let again = app.login(&alice).await;
+    assert_ne!(again.refresh_token, alice.refresh_token);
+
+    let (status, _, body) = app
+        .call(TestRequest::post().uri("/api/login").set_json(json!({
+            "email": alice.email,
+            "password": "wrong",
+        })))
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["detail"], "Invalid email or password");
+}
+
+#[actix_rt::test]
+async fn refresh_rotates_and_detects_reuse() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+
+    let refresh = |token: &str| {
+        TestRequest::post()
+            .uri("/api/token/refresh")
+            .set_json(json!({ "refresh_token": token }))
+    };
+
+    let (status, _, body) = app.call(refresh(&alice.refresh_token)).await;
+    assert_eq!(status, StatusCode::OK);
+    let rotated = body["refresh_token"].as_str().unwrap().to_string();
+    assert_ne!(rotated, alice.refresh_token);
+
+    // 使用済みのトークンを再利用するとファミリーごと失効する
+    let (status, _, body) = app.call(refresh(&alice.refresh_token)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["detail"], "Refresh token reuse detected");
+
+    let (status, _, _) = app.call(refresh(&rotated)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, _, _) = app.call(refresh("not-a-token")).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[actix_rt::test]
+async fn logout_revokes_session() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+
+    let (status, _, _) = app
+        .call(
+            TestRequest::post()
+                .uri("/api/logout")
+                .set_json(json!({ "refresh_token": alice.refresh_token })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // ログアウトしたセッションのアクセストークンは使えない
+    let (status, headers, body) = app
+        .call(
+            TestRequest::get()
+                .uri("/api/timeline")
+                .insert_header(bearer(&alice)),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["detail"], "Session has been revoked");
+    assert!(
+        headers
+            .get(header::WWW_AUTHENTICATE)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .contains("error=\"invalid_token\"")
+    );
+
+    let (status, _, _) = app
+        .call(
+            TestRequest::post()
+                .uri("/api/token/refresh")
+                .set_json(json!({ "refresh_token": alice.refresh_token })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[actix_rt::test]
+async fn logout_without_a_body_revokes_the_current_session() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let other = app.login(&alice).await;
+
+    // 本文もトークンもなければ 401
+    let (status, _, _) = app.call(TestRequest::post().uri("/api/logout")).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, _, _) = app
+        .call(
+            TestRequest::post()
+                .uri("/api/logout")
+                .insert_header(bearer(&alice)),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // 失効するのはそのセッションとリフレッシュトークンだけ
+    let (status, _, _) = app
+        .call(
+            TestRequest::get()
+                .uri("/api/timeline")
+                .insert_header(bearer(&alice)),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _, _) = app
+        .call(
+            TestRequest::post()
+                .uri("/api/token/refresh")
+                .set_json(json!({ "refresh_token": alice.refresh_token })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _, _) = app
+        .call(
+            TestRequest::get()
+                .uri("/api/timeline")
+                .insert_header(bearer(&other)),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[actix_rt::test]
+async fn tweet_routes_require_authentication() {
+    let app = TestApp::new().await;
+    let id = uuid::Uuid::new_v4();
+
+    for req in [
+        TestRequest::post()
+            .uri("/api/tweets")
+            .set_json(json!({ "content": "hello" })),
+        TestRequest::delete().uri(&format!("/api/tweets/{}", id)),
+        TestRequest::get().uri("/api/timeline"),
+    ] {
+        let (status, headers, body) = app.call(req).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["detail"], "Missing authorization header");
+        assert_eq!(body["code"], "UNAUTHENTICATED");
+        assert_eq!(
+            headers.get(header::WWW_AUTHENTICATE).unwrap(),
+            "Bearer realm=\"play-with-actix-web\""
+        );
+    }
+
+    // 不正なトークンは任意認証のルートでも拒否する
+    let (status, _, body) = app
+        .call(
+            TestRequest::get()
+                .uri(&format!("/api/tweets/{}", id))
+                .insert_header((header::AUTHORIZATION, "Bearer invalid")),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["detail"], "Invalid token");
+
+    let (status, _, body) = app
+        .call(
+            TestRequest::get()
+                .uri("/api/timeline")
+                .insert_header((header::AUTHORIZATION, "Basic abc")),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["detail"], "Invalid authorization format");
+}
+
+#[actix_rt::test]
+async fn create_get_and_delete_tweet() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+
+    let (status, _, tweet) = app
+        .call(
+            TestRequest::post()
+                .uri("/api/tweets")
+                .insert_header(bearer(&alice))
+                .set_json(json!({ "content": "Hello #Rust" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(tweet["content"], "Hello #Rust");
+    assert_eq!(tweet["user_id"], alice.id.to_string());
+    assert_eq!(tweet["hashtags"], json!(["rust"]));
+    let uri = format!("/api/tweets/{}", tweet["id"].as_str().unwrap());
+
+    // 取得は認証なしでもできる
+    let (status, _, body) = app.call(TestRequest::get().uri(&uri)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["id"], tweet["id"]);
+    assert_eq!(body["like_count"], 0);
+
+    // 他人のツイートは削除できない
+    let (status, _, body) = app
+        .call(TestRequest::delete().uri(&uri).insert_header(bearer(&bob)))
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["detail"], "Tweet not found or not authorized");
+
+    let (status, _, _) = app
+        .call(
+            TestRequest::delete()
+                .uri(&uri)
+                .insert_header(bearer(&alice)),
+        )
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, _, body) = app.call(TestRequest::get().uri(&uri)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["detail"], "Tweet not found");
+    assert_eq!(body["code"], "NOT_FOUND");
+}
+
+#[actix_rt::test]
+async fn create_tweet_validates_content() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+
+    for content in [String::new(), "a".repeat(281)] {
+        let (status, _, body) = app
+            .call(
+                TestRequest::post()
+                    .uri("/api/tweets")
+                    .insert_header(bearer(&alice))
+                    .set_json(json!({ "content": content })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body,
+            json!({
+                "type": "about:blank",
+                "title": "Bad Request",
+                "status": 400,
+                "detail": "Tweet content must be between 1 and 280 characters",
+                "code": "VALIDATION_FAILED",
+                "field": "content",
+            })
+        );
+    }
+
+    // 上限を超えるボディは拒否する
+    let (status, headers, body) = app
+        .call(
+            TestRequest::post()
+                .uri("/api/tweets")
+                .insert_header(bearer(&alice))
+                .set_json(json!({ "content": "a".repeat(5000) })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(
+        headers.get(header::CONTENT_TYPE).unwrap(),
+        "application/problem+json"
+    );
+    assert_eq!(body["code"], "BAD_REQUEST");
+
+    // パスの ID が不正
+    let (status, _, body) = app
+        .call(TestRequest::get().uri("/api/tweets/not-a-uuid"))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "BAD_REQUEST");
+}
+
+#[actix_rt::test]
+async fn timeline_includes_followed_users() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let carol = app.register("carol").await;
+
+    for (user, content) in [
+        (&alice, "from alice"),
+        (&bob, "from bob"),
+        (&carol, "from carol"),
+    ] {
+        let (status, _, _) = app
+            .call(
+                TestRequest::post()
+                    .uri("/api/tweets")
+                    .insert_header(bearer(user))
+                    .set_json(json!({ "content": content })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+    app.execute(
+        "mutation($id: UUID!) { followUser(targetId: $id) }",
+        json!({ "id": bob.id }),
+        Some(&alice),
+    )
+    .await;
+
+    let (status, _, body) = app
+        .call(
+            TestRequest::get()
+                .uri("/api/timeline")
+                .insert_header(bearer(&alice)),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let contents: Vec<&str> = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["content"].as_str().unwrap())
+        .collect();
+    assert_eq!(contents, ["from bob", "from alice"]);
+}
+
+#[actix_rt::test]
+async fn jwks_and_graphiql() {
+    let app = TestApp::new().await;
+
+    // HS256 の鍵は公開しない
+    let (status, _, body) = app
+        .call(TestRequest::get().uri("/.well-known/jwks.json"))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!({ "keys": [] }));
+
+    let (status, headers, body) = app.call(TestRequest::get().uri("/graphiql")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        headers.get(header::CONTENT_TYPE).unwrap(),
+        "text/html; charset=utf-8"
+    );
+    assert!(body.as_str().unwrap().contains("/graphql"));
+}
+
+#[actix_rt::test]
+async fn graphql_over_http() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+
+    let (status, _, body) = app
+        .call(
+            TestRequest::post()
+                .uri("/graphql")
+                .insert_header(bearer(&alice))
+                .set_json(json!({ "query": "{ me { username } }" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["me"]["username"], "alice");
+
+    // トークンなしは未ログインとして扱う
+    let (_, _, body) = app
+        .call(
+            TestRequest::post()
+                .uri("/graphql")
+                .set_json(json!({ "query": "{ me { username } }" })),
+        )
+        .await;
+    assert_eq!(body["data"]["me"], json!(null));
+
+    // 不正なトークンは 401
+    let (status, _, _) = app
+        .call(
+            TestRequest::post()
+                .uri("/graphql")
+                .insert_header((header::AUTHORIZATION, "Bearer invalid"))
+                .set_json(json!({ "query": "{ me { username } }" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // GET はクエリパラメータで変数を受け取る
+    let query = "query($id: UUID!) { user(id: $id) { username } }";
+    let variables = json!({ "id": alice.id }).to_string();
+    let uri = format!(
+        "/graphql?query={}&variables={}",
+        urlencode(query),
+        urlencode(&variables)
+    );
+    let (status, _, body) = app.call(TestRequest::get().uri(&uri)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["user"]["username"], "alice");
+
+    let uri = format!("/graphql?query={}&variables=oops", urlencode(query));
+    let (_, _, body) = app.call(TestRequest::get().uri(&uri)).await;
+    assert!(
+        body["errors"][0]["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("Failed to parse GraphQL variables")
+    );
+}
@@ -0,0 +1,192 @@
+use serde_json::{Value, json};
+
+use super::{TestApp, TestUser, data, error_extensions};
+
+async fn retweet(app: &TestApp, user: &TestUser, tweet_id: &str) -> Value {
+    app.execute(
+        "mutation($id: UUID!) { retweet(tweetId: $id) }",
+        json!({ "id": tweet_id }),
+        Some(user),
+    )
+    .await
+}
+
+async fn timeline(app: &TestApp, user: &TestUser) -> Value {
+    let resp = app
+        .execute(
+            r#"{
+                timeline(first: 10) {
+                    totalCount
+                    edges { node { id } retweetedBy { username } retweetedAt }
+                }
+            }"#,
+            json!({}),
+            Some(user),
+        )
+        .await;
+    data(&resp)["timeline"].clone()
+}
+
+#[actix_rt::test]
+async fn retweet_updates_counts_and_can_be_undone() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let tweet_id = app.tweet(&alice, "hello").await;
+
+    data(&retweet(&app, &bob, &tweet_id).await);
+    let resp = retweet(&app, &bob, &tweet_id).await;
+    assert_eq!(error_extensions(&resp)["code"], "CONFLICT");
+
+    let query = "query($id: UUID!) { tweet(id: $id) { retweetCount isRetweeted } }";
+    let resp = app
+        .execute(query, json!({ "id": tweet_id }), Some(&bob))
+        .await;
+    assert_eq!(
+        data(&resp)["tweet"],
+        json!({ "retweetCount": 1, "isRetweeted": true })
+    );
+    let resp = app.execute(query, json!({ "id": tweet_id }), None).await;
+    assert_eq!(data(&resp)["tweet"]["isRetweeted"], false);
+
+    let undo = "mutation($id: UUID!) { undoRetweet(tweetId: $id) }";
+    let resp = app
+        .execute(undo, json!({ "id": tweet_id }), Some(&bob))
+        .await;
+    assert_eq!(data(&resp)["undoRetweet"], true);
+    let resp = app
+        .execute(undo, json!({ "id": tweet_id }), Some(&bob))
+        .await;
+    assert_eq!(error_extensions(&resp)["code"], "NOT_FOUND");
+
+    let resp = app
+        .execute(query, json!({ "id": tweet_id }), Some(&bob))
+        .await;
+    assert_eq!(
+        data(&resp)["tweet"],
+        json!({ "retweetCount": 0, "isRetweeted": false })
+    );
+
+    let resp = retweet(&app, &bob, &uuid::Uuid::new_v4().to_string()).await;
+    assert_eq!(error_extensions(&resp)["code"], "NOT_FOUND");
+}
+
+#[actix_rt::test]
+async fn quote_tweet_embeds_the_quoted_tweet() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let tweet_id = app.tweet(&alice, "original").await;
+
+    let resp = app
+        .execute(
+            r#"mutation($id: UUID!) {
+                quoteTweet(tweetId: $id, content: "so true") {
+                    id content quotedTweet { id content user { username } }
+                }
+            }"#,
+            json!({ "id": tweet_id }),
+            Some(&bob),
+        )
+        .await;
+    let quote = &data(&resp)["quoteTweet"];
+    assert_eq!(quote["content"], "so true");
+    assert_eq!(
+        quote["quotedTweet"],
+        json!({ "id": tweet_id, "content": "original", "user": { "username": "alice" } })
+    );
+    let quote_id = quote["id"].as_str().unwrap().to_string();
+
+    let resp = app
+        .execute(
+            "query($id: UUID!) { tweet(id: $id) { quoteCount quotedTweet { id } } }",
+            json!({ "id": tweet_id }),
+            None,
+        )
+        .await;
+    assert_eq!(
+        data(&resp)["tweet"],
+        json!({ "quoteCount": 1, "quotedTweet": null })
+    );
+
+    // 引用元が削除されると quotedTweet は null になる
+    let resp = app
+        .execute(
+            "mutation($id: UUID!) { deleteTweet(id: $id) }",
+            json!({ "id": tweet_id }),
+            Some(&alice),
+        )
+        .await;
+    data(&resp);
+    let resp = app
+        .execute(
+            "query($id: UUID!) { tweet(id: $id) { content quotedTweet { id } } }",
+            json!({ "id": quote_id }),
+            None,
+        )
+        .await;
+    assert_eq!(
+        data(&resp)["tweet"],
+        json!({ "content": "so true", "quotedTweet": null })
+    );
+
+    let resp = app
+        .execute(
+            r#"mutation($id: UUID!) { quoteTweet(tweetId: $id, content: "gone") { id } }"#,
+            json!({ "id": tweet_id }),
+            Some(&bob),
+        )
+        .await;
+    assert_eq!(error_extensions(&resp)["code"], "NOT_FOUND");
+}
+
+#[actix_rt::test]
+async fn timeline_shows_retweets_once_with_attribution() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let carol = app.register("carol").await;
+    let dave = app.register("dave").await;
+    app.follow(&alice, &bob).await;
+    app.follow(&alice, &carol).await;
+
+    // フォローしていない dave のツイートがリツイートで載る
+    let daves = app.tweet(&dave, "from dave").await;
+    let bobs = app.tweet(&bob, "from bob").await;
+    data(&retweet(&app, &carol, &daves).await);
+
+    let timeline_now = timeline(&app, &alice).await;
+    assert_eq!(timeline_now["totalCount"], 2);
+    let edges = timeline_now["edges"].as_array().unwrap();
+    assert_eq!(edges[0]["node"]["id"], daves);
+    assert_eq!(edges[0]["retweetedBy"]["username"], "carol");
+    assert!(edges[0]["retweetedAt"].is_string());
+    assert_eq!(edges[1]["node"]["id"], bobs);
+    assert_eq!(edges[1]["retweetedBy"], Value::Null);
+    assert_eq!(edges[1]["retweetedAt"], Value::Null);
+
+    // 投稿とリツイートの両方で載るツイートは最新の1件だけになる
+    data(&retweet(&app, &carol, &bobs).await);
+    data(&retweet(&app, &bob, &daves).await);
+    let timeline_now = timeline(&app, &alice).await;
+    assert_eq!(timeline_now["totalCount"], 2);
+    let edges = timeline_now["edges"].as_array().unwrap();
+    assert_eq!(edges.len(), 2);
+    assert_eq!(edges[0]["node"]["id"], daves);
+    assert_eq!(edges[0]["retweetedBy"]["username"], "bob");
+    assert_eq!(edges[1]["node"]["id"], bobs);
+    assert_eq!(edges[1]["retweetedBy"]["username"], "carol");
+
+    // リツイートを取り消すと元の投稿として載る
+    let resp = app
+        .execute(
+            "mutation($id: UUID!) { undoRetweet(tweetId: $id) }",
+            json!({ "id": bobs }),
+            Some(&carol),
+        )
+        .await;
+    data(&resp);
+    let edges = timeline(&app, &alice).await["edges"].clone();
+    assert_eq!(edges[1]["node"]["id"], bobs);
+    assert_eq!(edges[1]["retweetedBy"], Value::Null);
+}
@@ -0,0 +1,254 @@
+use chrono::{Days, Utc};
+use serde_json::{Value, json};
+
+use super::{TestApp, data, error_extensions};
+use crate::search::{Fragment, SearchQuery, snippet};
+
+const SEARCH: &str = r#"
+    query($q: String!, $type: SearchType, $first: Int, $after: String) {
+        search(query: $q, type: $type, first: $first, after: $after) {
+            edges {
+                cursor
+                snippet { text highlighted }
+                node {
+                    ... on TweetType { id content }
+                    ... on CommentType { id content }
+                    ... on UserType { id username }
+                }
+            }
+            pageInfo { hasNextPage }
+        }
+    }
+"#;
+
+async fn search(app: &TestApp, query: &str, search_type: &str) -> Value {
+    app.execute(SEARCH, json!({ "q": query, "type": search_type }), None)
+        .await
+}
+
+/// 検索結果のノードの ID（新しい順）
+async fn search_ids(app: &TestApp, query: &str, search_type: &str) -> Vec<String> {
+    let resp = search(app, query, search_type).await;
+    data(&resp)["search"]["edges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|edge| edge["node"]["id"].as_str().unwrap().to_string())
+        .collect()
+}
+
+fn fragment(text: &str, highlighted: bool) -> Fragment {
+    Fragment {
+        text: text.to_string(),
+        highlighted,
+    }
+}
+
+#[test]
+fn query_parser_extracts_filters() {
+    let query = SearchQuery::parse(
+        r#"rust "hello world" from:alice #Tokyo since:2024-01-01 until:2024-01-31"#,
+    )
+    .unwrap();
+
+    assert_eq!(query.terms, ["rust", "hello world"]);
+    assert_eq!(query.from.as_deref(), Some("alice"));
+    assert_eq!(query.hashtags, ["tokyo"]);
+    assert_eq!(query.since.as_deref(), Some("2024-01-01T00:00:00+00:00"));
+    // until の日付を含むよう翌日の0時より前とする
+    assert_eq!(query.until.as_deref(), Some("2024-02-01T00:00:00+00:00"));
+
+    // 引用符で囲めば絞り込みの書式も語として扱う
+    let query = SearchQuery::parse(r#""from:alice""#).unwrap();
+    assert_eq!(query.terms, ["from:alice"]);
+    assert_eq!(query.from, None);
+
+    assert!(SearchQuery::parse("   ").is_err());
+    assert!(SearchQuery::parse("since:2024-13-01").is_err());
+}
+
+#[test]
+fn snippet_highlights_terms_around_first_match() {
+    assert_eq!(
+        snippet("今日は東京で寿司を食べた", &["東京".to_string()]),
+        [
+            fragment("今日は", false),
+            fragment("東京", true),
+            fragment("で寿司を食べた", false),
+        ]
+    );
+
+    // 大文字小文字を区別せず、重なる一致はまとめる
+    assert_eq!(
+        snippet("Learning Rust", &["rust".to_string(), "ust".to_string()]),
+        [fragment("Learning ", false), fragment("Rust", true)]
+    );
+
+    // 長い本文は一致の周辺だけを切り出す
+    let text = format!("{}needle{}", "a".repeat(200), "b".repeat(200));
+    let fragments = snippet(&text, &["needle".to_string()]);
+    assert_eq!(fragments.first(), Some(&fragment("…", false)));
+    assert_eq!(fragments.last(), Some(&fragment("…", false)));
+    assert!(fragments.contains(&fragment("needle", true)));
+}
+
+#[actix_rt::test]
+async fn japanese_text_is_searchable_without_spaces() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let sushi = app.tweet(&alice, "今日は東京で寿司を食べた").await;
+    app.tweet(&alice, "大阪でたこ焼きを食べた").await;
+
+    // trigram で一致する3文字以上の語と、短い語の両方で見つかる
+    assert_eq!(
+        search_ids(&app, "寿司を食", "TWEETS").await,
+        vec![sushi.clone()]
+    );
+    assert_eq!(
+        search_ids(&app, "東京", "TWEETS").await,
+        vec![sushi.clone()]
+    );
+    assert_eq!(search_ids(&app, "食べた 東京", "TWEETS").await, [sushi]);
+    assert_eq!(
+        search_ids(&app, "名古屋", "TWEETS").await,
+        Vec::<String>::new()
+    );
+
+    let resp = search(&app, "東京", "TWEETS").await;
+    assert_eq!(
+        data(&resp)["search"]["edges"][0]["snippet"],
+        json!([
+            { "text": "今日は", "highlighted": false },
+            { "text": "東京", "highlighted": true },
+            { "text": "で寿司を食べた", "highlighted": false },
+        ])
+    );
+}
+
+#[actix_rt::test]
+async fn tweet_search_applies_filters() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let tagged = app.tweet(&alice, "Learning #Rust today").await;
+    let plain = app.tweet(&alice, "Learning Go today").await;
+    let bobs = app.tweet(&bob, "Learning #rust too").await;
+
+    assert_eq!(
+        search_ids(&app, "learning from:alice", "TWEETS").await,
+        [plain.clone(), tagged.clone()]
+    );
+    assert_eq!(
+        search_ids(&app, "#RUST", "TWEETS").await,
+        [bobs.clone(), tagged.clone()]
+    );
+    assert_eq!(
+        search_ids(&app, "#rust from:alice", "TWEETS").await,
+        vec![tagged.clone()]
+    );
+    // 存在しないユーザーの投稿は1件もない
+    assert!(search_ids(&app, "from:nobody", "TWEETS").await.is_empty());
+
+    let today = Utc::now().date_naive();
+    let tomorrow = today.checked_add_days(Days::new(1)).unwrap();
+    assert_eq!(
+        search_ids(&app, &format!("learning until:{}", today), "TWEETS")
+            .await
+            .len(),
+        3
+    );
+    assert!(
+        search_ids(&app, &format!("learning since:{}", tomorrow), "TWEETS")
+            .await
+            .is_empty()
+    );
+
+    // ハッシュタグの絞り込みはスニペットでもハイライトする
+    let resp = search(&app, "#rust from:alice", "TWEETS").await;
+    assert_eq!(
+        data(&resp)["search"]["edges"][0]["snippet"][1],
+        json!({ "text": "#Rust", "highlighted": true })
+    );
+}
+
+#[actix_rt::test]
+async fn comments_and_users_are_searchable() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let alicia = app.register("alicia").await;
+    app.register("bob").await;
+
+    let tweet_id = app.tweet(&alice, "hello").await;
+    let resp = app
+        .execute(
+            "mutation($id: UUID!) { createComment(tweetId: $id, content: \"素敵な写真ですね\") { id } }",
+            json!({ "id": tweet_id }),
+            Some(&alicia),
+        )
+        .await;
+    let comment_id = data(&resp)["createComment"]["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    assert_eq!(
+        search_ids(&app, "写真 from:alicia", "COMMENTS").await,
+        [comment_id]
+    );
+    assert!(
+        search_ids(&app, "写真 from:alice", "COMMENTS")
+            .await
+            .is_empty()
+    );
+
+    assert_eq!(
+        search_ids(&app, "ALI", "USERS").await,
+        [alicia.id.to_string(), alice.id.to_string()]
+    );
+
+    let resp = search(&app, "ali from:bob", "USERS").await;
+    assert_eq!(error_extensions(&resp)["code"], "VALIDATION_FAILED");
+    assert_eq!(error_extensions(&resp)["field"], "query");
+}
+
+#[actix_rt::test]
+async fn search_paginates_and_follows_deletes() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let first = app.tweet(&alice, "ページ送りのテスト 1").await;
+    let second = app.tweet(&alice, "ページ送りのテスト 2").await;
+
+    let resp = app
+        .execute(
+            SEARCH,
+            json!({ "q": "ページ送り", "type": "TWEETS", "first": 1 }),
+            None,
+        )
+        .await;
+    let page = &data(&resp)["search"];
+    assert_eq!(page["edges"][0]["node"]["id"], second.as_str());
+    assert_eq!(page["pageInfo"]["hasNextPage"], true);
+
+    let cursor = page["edges"][0]["cursor"].as_str().unwrap();
+    let resp = app
+        .execute(
+            SEARCH,
+            json!({ "q": "ページ送り", "type": "TWEETS", "first": 1, "after": cursor }),
+            None,
+        )
+        .await;
+    let page = &data(&resp)["search"];
+    assert_eq!(page["edges"][0]["node"]["id"], first.as_str());
+    assert_eq!(page["pageInfo"]["hasNextPage"], false);
+
+    // 削除したツイートは索引からも消える
+    let resp = app
+        .execute(
+            "mutation($id: UUID!) { deleteTweet(id: $id) }",
+            json!({ "id": second }),
+            Some(&alice),
+        )
+        .await;
+    data(&resp);
+    assert_eq!(search_ids(&app, "ページ送り", "TWEETS").await, [first]);
+}
@@ -0,0 +1,300 @@
+use async_graphql::ErrorExtensions;
+use serde_json::{Value, json};
+
+use super::{TestApp, data, error_message};
+use crate::auth::{AuthError, authenticate_connection};
+use crate::models::Role;
+
+#[actix_rt::test]
+async fn tweet_posted_delivers_timeline_tweets() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let carol = app.register("carol").await;
+    app.follow(&alice, &bob).await;
+
+    let mut sub = app
+        .subscribe(
+            "subscription { tweetPosted { content hashtags user { username } } }",
+            json!({}),
+            Some(&alice),
+        )
+        .await;
+
+    // フォローしていないユーザーのツイートは届かない
+    app.tweet(&carol, "from carol").await;
+    app.tweet(&bob, "from bob #rust").await;
+    app.tweet(&alice, "from alice").await;
+
+    assert_eq!(
+        data(&sub.next().await)["tweetPosted"],
+        json!({ "content": "from bob #rust", "hashtags": ["rust"], "user": { "username": "bob" } })
+    );
+    assert_eq!(
+        data(&sub.next().await)["tweetPosted"]["content"],
+        "from alice"
+    );
+}
+
+#[actix_rt::test]
+async fn tweet_posted_requires_login() {
+    let app = TestApp::new().await;
+
+    let mut sub = app
+        .subscribe("subscription { tweetPosted { id } }", json!({}), None)
+        .await;
+    assert_eq!(error_message(&sub.next().await), "Authentication required");
+}
+
+#[actix_rt::test]
+async fn tweet_liked_delivers_updated_counts() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let carol = app.register("carol").await;
+    app.follow(&alice, &bob).await;
+    let bobs = app.tweet(&bob, "from bob").await;
+    let carols = app.tweet(&carol, "from carol").await;
+    let alices = app.tweet(&alice, "from alice").await;
+
+    let mut all = app
+        .subscribe(
+            "subscription { tweetLiked { id likeCount isLiked } }",
+            json!({}),
+            Some(&alice),
+        )
+        .await;
+    let mut one = app
+        .subscribe(
+            "subscription($id: UUID) { tweetLiked(tweetId: $id) { id likeCount } }",
+            json!({ "id": alices }),
+            Some(&alice),
+        )
+        .await;
+
+    // タイムライン外のツイートへのいいねは届かない
+    app.like(&bob, &carols).await;
+    for (user, likes, is_liked) in [(&carol, 1, false), (&alice, 2, true)] {
+        app.like(user, &bobs).await;
+        assert_eq!(
+            data(&all.next().await)["tweetLiked"],
+            json!({ "id": bobs, "likeCount": likes, "isLiked": is_liked })
+        );
+    }
+
+    app.like(&bob, &alices).await;
+    assert_eq!(data(&all.next().await)["tweetLiked"]["id"], alices);
+    assert_eq!(
+        data(&one.next().await)["tweetLiked"],
+        json!({ "id": alices, "likeCount": 1 })
+    );
+}
+
+#[actix_rt::test]
+async fn comment_added_is_filtered_by_tweet() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let first = app.tweet(&alice, "first").await;
+    let second = app.tweet(&alice, "second").await;
+
+    // 未ログインでも購読できる
+    let mut sub = app
+        .subscribe(
+            "subscription($id: UUID!) { commentAdded(tweetId: $id) { content user { username } } }",
+            json!({ "id": second }),
+            None,
+        )
+        .await;
+
+    for (tweet_id, content) in [(&first, "on first"), (&second, "on second")] {
+        let resp = app
+            .execute(
+                "mutation($id: UUID!, $content: String!) {
+                    createComment(tweetId: $id, content: $content) { id }
+                }",
+                json!({ "id": tweet_id, "content": content }),
+                Some(&bob),
+            )
+            .await;
+        data(&resp);
+    }
+
+    assert_eq!(
+        data(&sub.next().await)["commentAdded"],
+        json!({ "content": "on second", "user": { "username": "bob" } })
+    );
+}
+
+#[actix_rt::test]
+async fn connection_init_payload_is_authenticated() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+
+    for key in ["Authorization", "authorization"] {
+        let payload = json!({ key: format!("Bearer {}", alice.token) });
+        let user = authenticate_connection(&app.repos, &payload)
+            .await
+            .unwrap()
+            .expect("authenticated user");
+        assert_eq!(user.user_id, alice.id);
+    }
+
+    // トークンがなければ未ログインとして扱う
+    for payload in [json!({}), Value::Null] {
+        let user = authenticate_connection(&app.repos, &payload).await.unwrap();
+        assert!(user.is_none());
+    }
+
+    for (payload, message) in [
+        (
+            json!({ "Authorization": alice.token }),
+            "Invalid authorization format",
+        ),
+        (
+            json!({ "Authorization": "Bearer invalid" }),
+            "Invalid token",
+        ),
+    ] {
+        match authenticate_connection(&app.repos, &payload).await {
+            Err(err @ AuthError::InvalidToken(_)) => {
+                assert_eq!(err.to_string(), message);
+                let error = err.extend();
+                assert_eq!(error.message, message);
+                assert_eq!(
+                    error.extensions.unwrap().get("code"),
+                    Some(&async_graphql::Value::from("UNAUTHENTICATED"))
+                );
+            }
+            other => panic!("expected an invalid token error: {:?}", other.map(|_| ())),
+        }
+    }
+}
+
+#[actix_rt::test]
+async fn muted_users_are_not_delivered() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    app.follow(&alice, &bob).await;
+    let bobs = app.tweet(&bob, "from bob").await;
+    let alices = app.tweet(&alice, "from alice").await;
+    // ミュートではフォローは外れないが、配信はされない
+    let resp = app
+        .execute(
+            "mutation($id: UUID!) { muteUser(targetId: $id) }",
+            json!({ "id": bob.id }),
+            Some(&alice),
+        )
+        .await;
+    data(&resp);
+
+    let mut posted = app
+        .subscribe(
+            "subscription { tweetPosted { content } }",
+            json!({}),
+            Some(&alice),
+        )
+        .await;
+    let mut liked = app
+        .subscribe(
+            "subscription { tweetLiked { id } }",
+            json!({}),
+            Some(&alice),
+        )
+        .await;
+    let mut comments = app
+        .subscribe(
+            "subscription($id: UUID!) { commentAdded(tweetId: $id) { content } }",
+            json!({ "id": alices }),
+            Some(&alice),
+        )
+        .await;
+
+    app.tweet(&bob, "muted").await;
+    app.tweet(&alice, "own").await;
+    assert_eq!(data(&posted.next().await)["tweetPosted"]["content"], "own");
+
+    app.like(&alice, &bobs).await;
+    app.like(&bob, &alices).await;
+    assert_eq!(data(&liked.next().await)["tweetLiked"]["id"], alices);
+
+    for (user, content) in [(&bob, "muted"), (&alice, "own")] {
+        let resp = app
+            .execute(
+                "mutation($id: UUID!, $content: String!) {
+                    createComment(tweetId: $id, content: $content) { id }
+                }",
+                json!({ "id": alices, "content": content }),
+                Some(user),
+            )
+            .await;
+        data(&resp);
+    }
+    assert_eq!(
+        data(&comments.next().await)["commentAdded"]["content"],
+        "own"
+    );
+}
+
+#[actix_rt::test]
+async fn subscriptions_end_after_logout() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    app.follow(&alice, &bob).await;
+
+    let mut sub = app
+        .subscribe(
+            "subscription { tweetPosted { content } }",
+            json!({}),
+            Some(&alice),
+        )
+        .await;
+    app.tweet(&bob, "before").await;
+    assert_eq!(data(&sub.next().await)["tweetPosted"]["content"], "before");
+
+    // セッションが失効すると、次の配信の前に購読が終わる
+    let resp = app
+        .execute(
+            "mutation($token: String!) { logout(refreshToken: $token) }",
+            json!({ "token": alice.refresh_token }),
+            None,
+        )
+        .await;
+    assert_eq!(data(&resp)["logout"], true);
+    app.tweet(&bob, "after").await;
+    assert!(sub.ended().await);
+}
+
+#[actix_rt::test]
+async fn subscriptions_end_when_the_user_is_suspended() {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let moderator = app.register("mod").await;
+    let moderator = app.grant(&moderator, Role::Moderator).await;
+    app.follow(&alice, &bob).await;
+
+    let mut sub = app
+        .subscribe(
+            "subscription { tweetPosted { content } }",
+            json!({}),
+            Some(&alice),
+        )
+        .await;
+    app.tweet(&bob, "before").await;
+    assert_eq!(data(&sub.next().await)["tweetPosted"]["content"], "before");
+
+    // 利用停止でセッションが失効すると、次の配信の前に購読が終わる
+    let resp = app
+        .execute(
+            "mutation($id: UUID!) { suspendUser(userId: $id) }",
+            json!({ "id": alice.id }),
+            Some(&moderator),
+        )
+        .await;
+    assert_eq!(data(&resp)["suspendUser"], true);
+    app.tweet(&bob, "after").await;
+    assert!(sub.ended().await);
+}
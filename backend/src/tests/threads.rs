@@ -0,0 +1,198 @@
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use super::{TestApp, TestUser, contents, data, error_extensions};
+
+async fn reply(
+    app: &TestApp,
+    user: &TestUser,
+    tweet_id: &str,
+    parent_id: Option<&str>,
+    content: &str,
+) -> Value {
+    app.execute(
+        r#"
+        mutation($tweetId: UUID!, $parentId: UUID, $content: String!) {
+            createComment(tweetId: $tweetId, parentId: $parentId, content: $content) { id }
+        }
+        "#,
+        json!({ "tweetId": tweet_id, "parentId": parent_id, "content": content }),
+        Some(user),
+    )
+    .await
+}
+
+const CONVERSATION: &str = r#"
+    query($tweetId: UUID!, $commentId: UUID, $depth: Int! = 3, $after: String) {
+        conversation(tweetId: $tweetId, commentId: $commentId) {
+            tweet { id replyCount }
+            comment { content }
+            ancestors { content }
+            replies(depth: $depth, first: 10, after: $after) {
+                totalCount
+                nodes { content parentId replyCount }
+            }
+        }
+    }
+"#;
+
+/// root の下に a → a1 → a1x → a1xy の鎖と b を作る
+async fn setup() -> (TestApp, TestUser, String, Vec<String>) {
+    let app = TestApp::new().await;
+    let alice = app.register("alice").await;
+    let tweet_id = app.tweet(&alice, "root").await;
+
+    let a = app.comment(&alice, &tweet_id, None, "a").await;
+    let b = app.comment(&alice, &tweet_id, None, "b").await;
+    let a1 = app.comment(&alice, &tweet_id, Some(&a), "a1").await;
+    let a1x = app.comment(&alice, &tweet_id, Some(&a1), "a1x").await;
+    let a1xy = app.comment(&alice, &tweet_id, Some(&a1x), "a1xy").await;
+    (app, alice, tweet_id, vec![a, b, a1, a1x, a1xy])
+}
+
+#[actix_rt::test]
+async fn conversation_returns_replies_up_to_depth() {
+    let (app, _, tweet_id, ids) = setup().await;
+
+    let resp = app
+        .execute(CONVERSATION, json!({ "tweetId": tweet_id }), None)
+        .await;
+    let conversation = &data(&resp)["conversation"];
+    assert_eq!(conversation["tweet"]["replyCount"], 2);
+    assert_eq!(conversation["comment"], Value::Null);
+    assert_eq!(conversation["ancestors"], json!([]));
+
+    // 既定の深さ 3 では a1xy は含まれず、返信先は返信より前に並ぶ
+    let replies = &conversation["replies"];
+    assert_eq!(replies["totalCount"], 4);
+    assert_eq!(contents(replies), vec!["a", "b", "a1", "a1x"]);
+    assert_eq!(replies["nodes"][0]["replyCount"], 1);
+    assert_eq!(replies["nodes"][1]["replyCount"], 0);
+    assert_eq!(replies["nodes"][2]["parentId"], ids[0]);
+
+    let resp = app
+        .execute(
+            CONVERSATION,
+            json!({ "tweetId": tweet_id, "depth": 1 }),
+            None,
+        )
+        .await;
+    let replies = &data(&resp)["conversation"]["replies"];
+    assert_eq!(replies["totalCount"], 2);
+    assert_eq!(contents(replies), vec!["a", "b"]);
+
+    for depth in [0, 11] {
+        let resp = app
+            .execute(
+                CONVERSATION,
+                json!({ "tweetId": tweet_id, "depth": depth }),
+                None,
+            )
+            .await;
+        assert_eq!(error_extensions(&resp)["code"], "VALIDATION_FAILED");
+    }
+
+    let resp = app
+        .execute(CONVERSATION, json!({ "tweetId": Uuid::new_v4() }), None)
+        .await;
+    assert_eq!(data(&resp)["conversation"], Value::Null);
+}
+
+#[actix_rt::test]
+async fn conversation_from_a_comment_includes_ancestors() {
+    let (app, _, tweet_id, ids) = setup().await;
+
+    let resp = app
+        .execute(
+            CONVERSATION,
+            json!({ "tweetId": tweet_id, "commentId": ids[3] }),
+            None,
+        )
+        .await;
+    let conversation = &data(&resp)["conversation"];
+    assert_eq!(conversation["comment"]["content"], "a1x");
+    assert_eq!(
+        conversation["ancestors"],
+        json!([{ "content": "a" }, { "content": "a1" }])
+    );
+    assert_eq!(contents(&conversation["replies"]), vec!["a1xy"]);
+
+    // 別のツイートのコメントは起点にできない
+    let other = app.tweet(&app.register("bob").await, "root").await;
+    let resp = app
+        .execute(
+            CONVERSATION,
+            json!({ "tweetId": other, "commentId": ids[3] }),
+            None,
+        )
+        .await;
+    assert_eq!(data(&resp)["conversation"], Value::Null);
+}
+
+#[actix_rt::test]
+async fn replies_are_paginated() {
+    let (app, _, tweet_id, _) = setup().await;
+    let query = r#"
+        query($tweetId: UUID!, $after: String) {
+            conversation(tweetId: $tweetId) {
+                replies(depth: 10, first: 3, after: $after) {
+                    nodes { content }
+                    pageInfo { hasNextPage endCursor }
+                }
+            }
+        }
+    "#;
+
+    let resp = app
+        .execute(query, json!({ "tweetId": tweet_id }), None)
+        .await;
+    let replies = &data(&resp)["conversation"]["replies"];
+    assert_eq!(contents(replies), vec!["a", "b", "a1"]);
+    assert_eq!(replies["pageInfo"]["hasNextPage"], true);
+
+    let cursor = replies["pageInfo"]["endCursor"].clone();
+    let resp = app
+        .execute(query, json!({ "tweetId": tweet_id, "after": cursor }), None)
+        .await;
+    let replies = &data(&resp)["conversation"]["replies"];
+    assert_eq!(contents(replies), vec!["a1x", "a1xy"]);
+    assert_eq!(replies["pageInfo"]["hasNextPage"], false);
+}
+
+#[actix_rt::test]
+async fn reply_parent_must_belong_to_the_tweet() {
+    let (app, alice, tweet_id, ids) = setup().await;
+    let other = app.tweet(&alice, "root").await;
+
+    let resp = reply(&app, &alice, &other, Some(&ids[0]), "wrong tweet").await;
+    assert_eq!(error_extensions(&resp)["code"], "NOT_FOUND");
+    let missing = Uuid::new_v4().to_string();
+    let resp = reply(&app, &alice, &tweet_id, Some(&missing), "missing").await;
+    assert_eq!(error_extensions(&resp)["code"], "NOT_FOUND");
+}
+
+#[actix_rt::test]
+async fn deleting_a_comment_deletes_its_replies() {
+    let (app, alice, tweet_id, ids) = setup().await;
+
+    let resp = app
+        .execute(
+            "mutation($id: UUID!) { deleteComment(id: $id) }",
+            json!({ "id": ids[2] }),
+            Some(&alice),
+        )
+        .await;
+    data(&resp);
+
+    let resp = app
+        .execute(
+            CONVERSATION,
+            json!({ "tweetId": tweet_id, "depth": 10 }),
+            None,
+        )
+        .await;
+    let replies = &data(&resp)["conversation"]["replies"];
+    assert_eq!(replies["totalCount"], 2);
+    assert_eq!(contents(replies), vec!["a", "b"]);
+    assert_eq!(replies["nodes"][0]["replyCount"], 0);
+}
@@ -0,0 +1,135 @@
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::models::{RefreshToken, Role};
+use crate::repository::Repositories;
+use crate::sessions::{ClientInfo, create_session, ensure_active};
+use crate::utils::{create_jwt, refresh_token_ttl};
+
+type Result<T> = std::result::Result<T, AppError>;
+
+/// リフレッシュトークンのハッシュ（DBにはこちらのみ保存する）
+fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// 推測不可能なトークン文字列を生成する
+fn generate_token() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// ログインセッションを開始し、(アクセストークン, リフレッシュトークン) を発行する
+///
+/// リフレッシュトークンのファミリーIDにはセッションIDを使う
+pub async fn start_session(
+    repos: &Repositories,
+    user_id: Uuid,
+    role: Role,
+    client: &ClientInfo,
+) -> Result<(String, String)> {
+    let session_id = create_session(repos, user_id, client).await?;
+    let access_token = create_jwt(user_id, session_id, role)?;
+    let (_, refresh_token) = insert_refresh_token(repos, user_id, session_id).await?;
+    Ok((access_token, refresh_token))
+}
+
+/// トークンを保存し、(行ID, トークン文字列) を返す
+async fn insert_refresh_token(
+    repos: &Repositories,
+    user_id: Uuid,
+    family_id: Uuid,
+) -> Result<(Uuid, String)> {
+    let token = generate_token();
+    let now = Utc::now();
+    let row = RefreshToken {
+        id: Uuid::new_v4(),
+        user_id,
+        family_id,
+        token_hash: hash_token(&token),
+        created_at: now.to_rfc3339(),
+        expires_at: (now + refresh_token_ttl()).to_rfc3339(),
+        revoked_at: None,
+        replaced_by: None,
+    };
+
+    repos.refresh_tokens.insert(&row).await?;
+
+    Ok((row.id, token))
+}
+
+async fn find_refresh_token(repos: &Repositories, token: &str) -> Result<RefreshToken> {
+    repos
+        .refresh_tokens
+        .find_by_hash(&hash_token(token))
+        .await?
+        .ok_or_else(|| AppError::Unauthorized("Invalid refresh token".to_string()))
+}
+
+/// 同じファミリーのトークンをすべて失効させる
+async fn revoke_family(repos: &Repositories, family_id: Uuid) -> Result<()> {
+    repos
+        .refresh_tokens
+        .revoke_family(family_id, &Utc::now().to_rfc3339())
+        .await
+}
+
+/// リフレッシュトークンをローテーションし、(ユーザーID, セッションID, 新しいリフレッシュトークン) を返す
+///
+/// ローテーション済みのトークンが再利用された場合は漏洩とみなし、ファミリー全体を失効させる
+pub async fn rotate_refresh_token(
+    repos: &Repositories,
+    token: &str,
+) -> Result<(Uuid, Uuid, String)> {
+    let current = find_refresh_token(repos, token).await?;
+
+    if current.revoked_at.is_some() && current.replaced_by.is_none() {
+        return Err(AppError::Unauthorized("Refresh token revoked".to_string()));
+    }
+    if current.revoked_at.is_some() {
+        revoke_family(repos, current.family_id).await?;
+        return Err(AppError::Unauthorized(
+            "Refresh token reuse detected".to_string(),
+        ));
+    }
+
+    let expires_at = DateTime::parse_from_rfc3339(&current.expires_at)
+        .map_err(|_| AppError::Internal("Invalid refresh token expiry".to_string()))?;
+    if expires_at < Utc::now() {
+        return Err(AppError::Unauthorized("Refresh token expired".to_string()));
+    }
+
+    // セッションが失効していればリフレッシュも拒否する
+    ensure_active(repos, current.family_id, current.user_id).await?;
+
+    let (next_id, next) = insert_refresh_token(repos, current.user_id, current.family_id).await?;
+
+    // 同時に同じトークンでローテーションされた場合も再利用として扱う
+    let replaced = repos
+        .refresh_tokens
+        .mark_replaced(current.id, next_id, &Utc::now().to_rfc3339())
+        .await?;
+
+    if !replaced {
+        revoke_family(repos, current.family_id).await?;
+        return Err(AppError::Unauthorized(
+            "Refresh token reuse detected".to_string(),
+        ));
+    }
+
+    Ok((current.user_id, current.family_id, next))
+}
+
+/// ログアウト: トークンのファミリーとセッションを失効させる（失効済みでも成功扱い）
+pub async fn revoke_refresh_token(repos: &Repositories, token: &str) -> Result<()> {
+    let current = find_refresh_token(repos, token).await?;
+    revoke_family(repos, current.family_id).await?;
+
+    repos
+        .sessions
+        .revoke(current.family_id, None, &Utc::now().to_rfc3339())
+        .await?;
+
+    Ok(())
+}
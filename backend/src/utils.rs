@@ -1,21 +1,39 @@
+use crate::auth::AuthError;
 use crate::error::AppError;
-use actix_web::HttpRequest;
+use crate::keys::JwtKeys;
+use crate::models::Role;
+use crate::repository::Repositories;
+use crate::sessions::ensure_active;
 use bcrypt::{DEFAULT_COST, hash, verify};
 use chrono::{Duration, Utc};
-use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Validation, decode, decode_header, encode};
 use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
 use uuid::Uuid;
 
+/// bcryptのコスト（テストでは速度のため最小値を使う）
+const BCRYPT_COST: u32 = if cfg!(test) { 4 } else { DEFAULT_COST };
+
+/// 起動時に設定されるJWT鍵
+static JWT_KEYS: OnceLock<JwtKeys> = OnceLock::new();
+
 /// JWTのペイロード（クレーム）を表す構造体
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
     pub user_id: Uuid,
+    /// ログインセッションID（失効したセッションのトークンは拒否する）
+    pub sid: Uuid,
+    /// 発行時点のロール（変更はトークンの再発行で反映される）
+    #[serde(default)]
+    pub role: Role,
     pub exp: usize,
 }
 
 /// パスワードをハッシュ化する
 pub fn hash_password(password: &str) -> Result<String, AppError> {
-    hash(password, DEFAULT_COST)
+    hash(password, BCRYPT_COST)
         .map_err(|_| AppError::Internal("Failed to hash password".to_string()))
 }
 
@@ -24,64 +42,68 @@ pub fn verify_password(password: &str, hash: &str) -> Result<bool, AppError> {
     verify(password, hash).map_err(|_| AppError::Internal("Failed to verify password".to_string()))
 }
 
-/// JWTシークレットキーを取得する
-fn get_jwt_secret() -> String {
-    std::env::var("JWT_SECRET").unwrap_or_else(|_| "your-secret-key".to_string())
+/// JWT鍵を登録する（起動時に一度だけ呼ぶ）
+pub fn init_jwt(keys: JwtKeys) {
+    let _ = JWT_KEYS.set(keys);
+}
+
+fn jwt_keys() -> &'static JwtKeys {
+    JWT_KEYS
+        .get()
+        .expect("JWT keys must be initialized at startup")
+}
+
+/// 公開検証鍵のJWKセットを取得する
+pub fn jwks() -> JwkSet {
+    jwt_keys().jwks()
+}
+
+/// リフレッシュトークンの有効期間
+pub fn refresh_token_ttl() -> Duration {
+    Duration::days(jwt_keys().refresh_token_days)
 }
 
 /// JWTトークンを生成する
-pub fn create_jwt(user_id: Uuid) -> Result<String, AppError> {
+pub fn create_jwt(user_id: Uuid, session_id: Uuid, role: Role) -> Result<String, AppError> {
+    let keys = jwt_keys();
     let expiration = Utc::now()
-        .checked_add_signed(Duration::hours(24))
+        .checked_add_signed(Duration::minutes(keys.access_token_minutes))
         .ok_or_else(|| AppError::Internal("Failed to calculate token expiration".to_string()))?
         .timestamp() as usize;
 
     let claims = Claims {
         user_id,
+        sid: session_id,
+        role,
         exp: expiration,
     };
 
-    let secret = get_jwt_secret();
-
-    encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(secret.as_ref()),
-    )
-    .map_err(|_| AppError::Internal("Failed to create token".to_string()))
+    encode(&keys.header(), &claims, keys.encoding_key())
+        .map_err(|_| AppError::Internal("Failed to create token".to_string()))
 }
 
-/// JWTトークンを検証してユーザーIDを取得する
-pub fn verify_jwt(token: &str) -> Result<Uuid, AppError> {
-    let secret = get_jwt_secret();
+/// JWTトークンを検証し、セッションが有効であればクレームを返す
+pub async fn verify_jwt(repos: &Repositories, token: &str) -> Result<Claims, AuthError> {
+    let header =
+        decode_header(token).map_err(|_| AuthError::InvalidToken("Invalid token".to_string()))?;
 
-    let token_data = decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(secret.as_ref()),
-        &Validation::new(Algorithm::HS256),
-    )
-    .map_err(|_| AppError::Unauthorized("Invalid token".to_string()))?;
+    // kidに対応する鍵で、その鍵のアルゴリズムのみを受け付ける
+    let key = jwt_keys()
+        .find(header.kid.as_deref())
+        .ok_or_else(|| AuthError::InvalidToken("Unknown signing key".to_string()))?;
 
-    Ok(token_data.claims.user_id)
-}
+    let token_data =
+        decode::<Claims>(token, &key.key, &Validation::new(key.algorithm)).map_err(|e| match e
+            .kind()
+        {
+            ErrorKind::ExpiredSignature => AuthError::ExpiredToken,
+            _ => AuthError::InvalidToken("Invalid token".to_string()),
+        })?;
 
-/// AuthorizationヘッダーからBearerトークンを抽出する
-fn extract_bearer_token(req: &HttpRequest) -> Result<&str, AppError> {
-    let header = req
-        .headers()
-        .get(actix_web::http::header::AUTHORIZATION)
-        .and_then(|v| v.to_str().ok())
-        .ok_or_else(|| AppError::Unauthorized("Missing authorization header".to_string()))?;
-
-    header
-        .strip_prefix("Bearer ")
-        .ok_or_else(|| AppError::Unauthorized("Invalid authorization format".to_string()))
-}
+    let claims = token_data.claims;
+    ensure_active(repos, claims.sid, claims.user_id).await?;
 
-/// リクエストからユーザーIDを認証して取得する
-pub fn authenticate(req: &HttpRequest) -> Result<Uuid, AppError> {
-    let token = extract_bearer_token(req)?;
-    verify_jwt(token)
+    Ok(claims)
 }
 
 /// ツイート本文からハッシュタグを抽出する
@@ -101,3 +123,21 @@ pub fn extract_hashtags(content: &str) -> Vec<String> {
 
     unique_tags.into_iter().collect()
 }
+
+/// 本文から @username の言及を抽出する（出現順、重複なし）
+/// 例: "Hi @alice and @bob, cc @alice" → ["alice", "bob"]
+///
+/// メールアドレスのように直前が英数字の @ は言及とみなさない
+pub fn extract_mentions(content: &str) -> Vec<String> {
+    use regex::Regex;
+
+    let re = Regex::new(r"(?:^|[^\w@])@(\w+)").unwrap();
+
+    let mut usernames: Vec<String> = Vec::new();
+    for cap in re.captures_iter(content) {
+        if !usernames.iter().any(|u| u == &cap[1]) {
+            usernames.push(cap[1].to_string());
+        }
+    }
+    usernames
+}
@@ -50,7 +50,7 @@ export function Comments({ tweetId, currentUserId, onClose }: Props) {
     [deleteComment, reexecuteQuery]
   );
 
-  const comments = data?.comments ?? [];
+  const comments = data?.comments.nodes ?? [];
   const error = queryError || createError || deleteError;
 
   return (
@@ -40,7 +40,7 @@ export function Timeline({ userId }: Props) {
     [content, createTweet, refetch]
   );
 
-  const tweets = data?.timeline ?? [];
+  const tweets = data?.timeline.nodes ?? [];
   const error = queryError || createError;
 
   return (
@@ -128,7 +128,7 @@ function FollowersTab({
     return <p className="p-6 text-center text-muted">読み込み中...</p>;
   }
 
-  const followers = data?.followers ?? [];
+  const followers = data?.followers.nodes ?? [];
 
   if (followers.length === 0) {
     return (
@@ -169,7 +169,7 @@ function FollowingTab({
     return <p className="p-6 text-center text-muted">読み込み中...</p>;
   }
 
-  const following = data?.following ?? [];
+  const following = data?.following.nodes ?? [];
 
   if (following.length === 0) {
     return (
@@ -237,7 +237,13 @@ function ProfileCard({
   );
 }
 
-function UserHeader({ username, email }: { username: string; email: string }) {
+function UserHeader({
+  username,
+  email,
+}: {
+  username: string;
+  email?: string | null;
+}) {
   return (
     <div>
       <h1 className="text-2xl font-bold">@{username}</h1>
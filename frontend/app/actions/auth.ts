@@ -3,13 +3,22 @@
 import { createClient } from "@/lib/graphql/client";
 import {
   LoginDocument,
+  LogoutDocument,
   RegisterDocument,
   type LoginMutation,
   type LoginMutationVariables,
+  type LogoutMutation,
+  type LogoutMutationVariables,
   type RegisterMutation,
   type RegisterMutationVariables,
 } from "@/lib/graphql/generated/graphql";
-import { createSession, deleteSession, verifySession } from "@/lib/session";
+import {
+  createSession,
+  deleteSession,
+  needsRefresh,
+  refreshTokens,
+  verifySession,
+} from "@/lib/session";
 import { redirect } from "next/navigation";
 
 export interface AuthState {
@@ -46,10 +55,15 @@ async function registerUser(
     return { error: "登録に失敗しました" };
   }
 
-  const { token, user } = result.data.register;
+  const { token, refreshToken, user } = result.data.register;
   await createSession(
-    { id: user.id, username: user.username, email: user.email },
-    token
+    {
+      id: user.id,
+      username: user.username,
+      email: user.email ?? input.email,
+    },
+    token,
+    refreshToken
   );
 
   return undefined;
@@ -70,10 +84,15 @@ async function loginUser(input: LoginInput): Promise<AuthState | undefined> {
     return { error: "ログインに失敗しました" };
   }
 
-  const { token, user } = result.data.login;
+  const { token, refreshToken, user } = result.data.login;
   await createSession(
-    { id: user.id, username: user.username, email: user.email },
-    token
+    {
+      id: user.id,
+      username: user.username,
+      email: user.email ?? input.email,
+    },
+    token,
+    refreshToken
   );
 
   return undefined;
@@ -120,10 +139,44 @@ export async function login(
   redirect("/");
 }
 
+/**
+ * アクセストークンを更新して返す（Client Components 用）
+ * 拒否されたトークンより新しいトークンがCookieにあればそれを返し、更新できなければセッションを削除して null を返す
+ */
+export async function refreshSession(
+  rejectedToken: string
+): Promise<string | null> {
+  const session = await verifySession();
+
+  if (!session) {
+    return null;
+  }
+
+  if (session.token !== rejectedToken && !needsRefresh(session)) {
+    return session.token;
+  }
+
+  const refreshed = await refreshTokens(session);
+
+  if (!refreshed) {
+    await deleteSession();
+    return null;
+  }
+
+  await createSession(refreshed.user, refreshed.token, refreshed.refreshToken);
+  return refreshed.token;
+}
+
 export async function logout() {
   const session = await verifySession();
 
   if (session) {
+    // サーバー側でもリフレッシュトークンを失効させる（失敗してもCookieは削除する）
+    await createClient()
+      .mutation<LogoutMutation, LogoutMutationVariables>(LogoutDocument, {
+        refreshToken: session.refreshToken,
+      })
+      .toPromise();
     await deleteSession();
   }
 
@@ -20,6 +20,7 @@ export type Scalars = {
 
 export type AuthPayload = {
   __typename?: 'AuthPayload';
+  refreshToken: Scalars['String']['output'];
   token: Scalars['String']['output'];
   user: UserType;
 };
@@ -49,6 +50,8 @@ export type MutationRoot = {
   followUser: Scalars['UUID']['output'];
   likeTweet: Scalars['Boolean']['output'];
   login: AuthPayload;
+  logout: Scalars['Boolean']['output'];
+  refreshToken: AuthPayload;
   register: AuthPayload;
   unfollowUser: Scalars['UUID']['output'];
   unlikeTweet: Scalars['Boolean']['output'];
@@ -91,6 +94,16 @@ export type MutationRootLoginArgs = {
 };
 
 
+export type MutationRootLogoutArgs = {
+  refreshToken: Scalars['String']['input'];
+};
+
+
+export type MutationRootRefreshTokenArgs = {
+  refreshToken: Scalars['String']['input'];
+};
+
+
 export type MutationRootRegisterArgs = {
   input: RegisterInput;
 };
@@ -165,7 +178,7 @@ export type TweetType = {
 
 export type UserType = {
   __typename?: 'UserType';
-  email: Scalars['String']['output'];
+  email?: Maybe<Scalars['String']['output']>;
   followersCount: Scalars['Int']['output'];
   followingCount: Scalars['Int']['output'];
   id: Scalars['UUID']['output'];
@@ -173,7 +186,7 @@ export type UserType = {
   username: Scalars['String']['output'];
 };
 
-export type UserFieldsFragment = { __typename?: 'UserType', id: string, username: string, email: string, followersCount: number, followingCount: number, isFollowing: boolean };
+export type UserFieldsFragment = { __typename?: 'UserType', id: string, username: string, email?: string | null, followersCount: number, followingCount: number, isFollowing: boolean };
 
 export type TweetFieldsFragment = { __typename?: 'TweetType', id: string, userId: string, content: string, createdAt: string, likeCount: number, isLiked: boolean, hashtags: Array<string>, user?: { __typename?: 'UserType', id: string, username: string } | null };
 
@@ -184,14 +197,28 @@ export type RegisterMutationVariables = Exact<{
 }>;
 
 
-export type RegisterMutation = { __typename?: 'MutationRoot', register: { __typename?: 'AuthPayload', token: string, user: { __typename?: 'UserType', id: string, username: string, email: string, followersCount: number, followingCount: number, isFollowing: boolean } } };
+export type RegisterMutation = { __typename?: 'MutationRoot', register: { __typename?: 'AuthPayload', token: string, refreshToken: string, user: { __typename?: 'UserType', id: string, username: string, email?: string | null, followersCount: number, followingCount: number, isFollowing: boolean } } };
 
 export type LoginMutationVariables = Exact<{
   input: LoginInput;
 }>;
 
 
-export type LoginMutation = { __typename?: 'MutationRoot', login: { __typename?: 'AuthPayload', token: string, user: { __typename?: 'UserType', id: string, username: string, email: string, followersCount: number, followingCount: number, isFollowing: boolean } } };
+export type LoginMutation = { __typename?: 'MutationRoot', login: { __typename?: 'AuthPayload', token: string, refreshToken: string, user: { __typename?: 'UserType', id: string, username: string, email?: string | null, followersCount: number, followingCount: number, isFollowing: boolean } } };
+
+export type RefreshTokenMutationVariables = Exact<{
+  refreshToken: Scalars['String']['input'];
+}>;
+
+
+export type RefreshTokenMutation = { __typename?: 'MutationRoot', refreshToken: { __typename?: 'AuthPayload', token: string, refreshToken: string, user: { __typename?: 'UserType', id: string, username: string, email?: string | null, followersCount: number, followingCount: number, isFollowing: boolean } } };
+
+export type LogoutMutationVariables = Exact<{
+  refreshToken: Scalars['String']['input'];
+}>;
+
+
+export type LogoutMutation = { __typename?: 'MutationRoot', logout: boolean };
 
 export type CreateTweetMutationVariables = Exact<{
   content: Scalars['String']['input'];
@@ -253,12 +280,12 @@ export type UnfollowUserMutation = { __typename?: 'MutationRoot', unfollowUser:
 export type MeQueryVariables = Exact<{ [key: string]: never; }>;
 
 
-export type MeQuery = { __typename?: 'QueryRoot', me?: { __typename?: 'UserType', id: string, username: string, email: string, followersCount: number, followingCount: number, isFollowing: boolean } | null };
+export type MeQuery = { __typename?: 'QueryRoot', me?: { __typename?: 'UserType', id: string, username: string, email?: string | null, followersCount: number, followingCount: number, isFollowing: boolean } | null };
 
 export type TimelineQueryVariables = Exact<{ [key: string]: never; }>;
 
 
-export type TimelineQuery = { __typename?: 'QueryRoot', timeline: Array<{ __typename?: 'TweetType', id: string, userId: string, content: string, createdAt: string, likeCount: number, isLiked: boolean, hashtags: Array<string>, user?: { __typename?: 'UserType', id: string, username: string } | null }> };
+export type TimelineQuery = { __typename?: 'QueryRoot', timeline: { __typename?: 'TweetTypeConnection', nodes: Array<{ __typename?: 'TweetType', id: string, userId: string, content: string, createdAt: string, likeCount: number, isLiked: boolean, hashtags: Array<string>, user?: { __typename?: 'UserType', id: string, username: string } | null }> } };
 
 export type TweetQueryVariables = Exact<{
   id: Scalars['UUID']['input'];
@@ -272,28 +299,28 @@ export type CommentsQueryVariables = Exact<{
 }>;
 
 
-export type CommentsQuery = { __typename?: 'QueryRoot', comments: Array<{ __typename?: 'CommentType', id: string, tweetId: string, userId: string, content: string, createdAt: string, user?: { __typename?: 'UserType', id: string, username: string } | null }> };
+export type CommentsQuery = { __typename?: 'QueryRoot', comments: { __typename?: 'CommentTypeConnection', nodes: Array<{ __typename?: 'CommentType', id: string, tweetId: string, userId: string, content: string, createdAt: string, user?: { __typename?: 'UserType', id: string, username: string } | null }> } };
 
 export type UserQueryVariables = Exact<{
   id: Scalars['UUID']['input'];
 }>;
 
 
-export type UserQuery = { __typename?: 'QueryRoot', user?: { __typename?: 'UserType', id: string, username: string, email: string, followersCount: number, followingCount: number, isFollowing: boolean } | null };
+export type UserQuery = { __typename?: 'QueryRoot', user?: { __typename?: 'UserType', id: string, username: string, email?: string | null, followersCount: number, followingCount: number, isFollowing: boolean } | null };
 
 export type FollowersQueryVariables = Exact<{
   userId: Scalars['UUID']['input'];
 }>;
 
 
-export type FollowersQuery = { __typename?: 'QueryRoot', followers: Array<{ __typename?: 'UserType', id: string, username: string, email: string, followersCount: number, followingCount: number, isFollowing: boolean }> };
+export type FollowersQuery = { __typename?: 'QueryRoot', followers: { __typename?: 'UserTypeConnection', nodes: Array<{ __typename?: 'UserType', id: string, username: string, email?: string | null, followersCount: number, followingCount: number, isFollowing: boolean }> } };
 
 export type FollowingQueryVariables = Exact<{
   userId: Scalars['UUID']['input'];
 }>;
 
 
-export type FollowingQuery = { __typename?: 'QueryRoot', following: Array<{ __typename?: 'UserType', id: string, username: string, email: string, followersCount: number, followingCount: number, isFollowing: boolean }> };
+export type FollowingQuery = { __typename?: 'QueryRoot', following: { __typename?: 'UserTypeConnection', nodes: Array<{ __typename?: 'UserType', id: string, username: string, email?: string | null, followersCount: number, followingCount: number, isFollowing: boolean }> } };
 
 export const UserFieldsFragmentDoc = gql`
     fragment UserFields on UserType {
@@ -337,6 +364,7 @@ export const RegisterDocument = gql`
     mutation Register($input: RegisterInput!) {
   register(input: $input) {
     token
+    refreshToken
     user {
       ...UserFields
     }
@@ -351,6 +379,7 @@ export const LoginDocument = gql`
     mutation Login($input: LoginInput!) {
   login(input: $input) {
     token
+    refreshToken
     user {
       ...UserFields
     }
@@ -361,6 +390,30 @@ export const LoginDocument = gql`
 export function useLoginMutation() {
   return Urql.useMutation<LoginMutation, LoginMutationVariables>(LoginDocument);
 };
+export const RefreshTokenDocument = gql`
+    mutation RefreshToken($refreshToken: String!) {
+  refreshToken(refreshToken: $refreshToken) {
+    token
+    refreshToken
+    user {
+      ...UserFields
+    }
+  }
+}
+    ${UserFieldsFragmentDoc}`;
+
+export function useRefreshTokenMutation() {
+  return Urql.useMutation<RefreshTokenMutation, RefreshTokenMutationVariables>(RefreshTokenDocument);
+};
+export const LogoutDocument = gql`
+    mutation Logout($refreshToken: String!) {
+  logout(refreshToken: $refreshToken)
+}
+    `;
+
+export function useLogoutMutation() {
+  return Urql.useMutation<LogoutMutation, LogoutMutationVariables>(LogoutDocument);
+};
 export const CreateTweetDocument = gql`
     mutation CreateTweet($content: String!) {
   createTweet(content: $content) {
@@ -451,7 +504,9 @@ export function useMeQuery(options?: Omit<Urql.UseQueryArgs<MeQueryVariables>, '
 export const TimelineDocument = gql`
     query Timeline {
   timeline {
-    ...TweetFields
+    nodes {
+      ...TweetFields
+    }
   }
 }
     ${TweetFieldsFragmentDoc}`;
@@ -473,7 +528,9 @@ export function useTweetQuery(options: Omit<Urql.UseQueryArgs<TweetQueryVariable
 export const CommentsDocument = gql`
     query Comments($tweetId: UUID!) {
   comments(tweetId: $tweetId) {
-    ...CommentFields
+    nodes {
+      ...CommentFields
+    }
   }
 }
     ${CommentFieldsFragmentDoc}`;
@@ -495,7 +552,9 @@ export function useUserQuery(options: Omit<Urql.UseQueryArgs<UserQueryVariables>
 export const FollowersDocument = gql`
     query Followers($userId: UUID!) {
   followers(userId: $userId) {
-    ...UserFields
+    nodes {
+      ...UserFields
+    }
   }
 }
     ${UserFieldsFragmentDoc}`;
@@ -506,7 +565,9 @@ export function useFollowersQuery(options: Omit<Urql.UseQueryArgs<FollowersQuery
 export const FollowingDocument = gql`
     query Following($userId: UUID!) {
   following(userId: $userId) {
-    ...UserFields
+    nodes {
+      ...UserFields
+    }
   }
 }
     ${UserFieldsFragmentDoc}`;
@@ -1,7 +1,8 @@
 "use client";
 
-import { useMemo } from "react";
+import { useEffect, useMemo, useRef } from "react";
 import { Provider, cacheExchange, createClient, fetchExchange } from "urql";
+import { refreshSession } from "@/app/actions/auth";
 
 const API_URL = process.env.NEXT_PUBLIC_API_URL || "http://localhost:8080";
 
@@ -10,22 +11,84 @@ interface GraphQLProviderProps {
   token?: string | null;
 }
 
+// 同時に届いた UNAUTHENTICATED で何度も更新しないよう、実行中の更新を共有する
+// （リフレッシュトークンは使い捨てで、再利用するとセッションごと失効する）
+let pendingRefresh: Promise<string | null> | null = null;
+
+function refreshOnce(rejectedToken: string): Promise<string | null> {
+  pendingRefresh ??= refreshSession(rejectedToken).finally(() => {
+    pendingRefresh = null;
+  });
+  return pendingRefresh;
+}
+
+/** 認証エラー（HTTP 401 または UNAUTHENTICATED）のレスポンスか */
+async function isUnauthenticated(response: Response): Promise<boolean> {
+  if (response.status === 401) {
+    return true;
+  }
+
+  try {
+    const body = await response.clone().json();
+    return (body.errors ?? []).some(
+      (error: { extensions?: { code?: string } }) =>
+        error.extensions?.code === "UNAUTHENTICATED"
+    );
+  } catch {
+    return false;
+  }
+}
+
 export function GraphQLProvider({ children, token }: GraphQLProviderProps) {
+  // 更新したトークンはページを再読み込みするまでここに保持する
+  const tokenRef = useRef(token);
+
+  useEffect(() => {
+    tokenRef.current = token;
+  }, [token]);
+
   const client = useMemo(() => {
-    const headers: Record<string, string> = {
-      "Content-Type": "application/json",
+    const send = (
+      input: RequestInfo | URL,
+      init: RequestInit | undefined,
+      bearer: string | null | undefined
+    ) => {
+      const headers = new Headers(init?.headers);
+      headers.set("Content-Type", "application/json");
+      if (bearer) {
+        headers.set("Authorization", `Bearer ${bearer}`);
+      }
+      return fetch(input, { ...init, headers });
     };
 
-    if (token) {
-      headers["Authorization"] = `Bearer ${token}`;
-    }
+    // 認証エラーならアクセストークンを更新して一度だけ再送する
+    const fetchWithRefresh = async (
+      input: RequestInfo | URL,
+      init?: RequestInit
+    ) => {
+      const sent = tokenRef.current;
+      const response = await send(input, init, sent);
+
+      if (!sent || !(await isUnauthenticated(response))) {
+        return response;
+      }
+
+      const refreshed = await refreshOnce(sent);
+      if (!refreshed) {
+        window.location.assign("/login");
+        return response;
+      }
+
+      tokenRef.current = refreshed;
+      return send(input, init, refreshed);
+    };
 
     return createClient({
       url: `${API_URL}/graphql`,
       exchanges: [cacheExchange, fetchExchange],
-      fetchOptions: { headers },
+      fetch: fetchWithRefresh,
     });
-  }, [token]);
+  }, []);
 
   return <Provider value={client}>{children}</Provider>;
 }
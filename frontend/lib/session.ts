@@ -1,18 +1,26 @@
 import "server-only";
 
-import { SignJWT, jwtVerify } from "jose";
+import { SignJWT, decodeJwt, jwtVerify } from "jose";
 import { cookies } from "next/headers";
+import { createClient } from "./graphql/client";
+import {
+  RefreshTokenDocument,
+  type RefreshTokenMutation,
+  type RefreshTokenMutationVariables,
+} from "./graphql/generated/graphql";
 import type { SessionUser } from "./types";
 
 const SECRET_KEY =
   process.env.SESSION_SECRET || "your-secret-key-min-32-chars!!";
 const ENCODED_KEY = new TextEncoder().encode(SECRET_KEY);
 const COOKIE_NAME = "session";
-const SESSION_DURATION = 24 * 60 * 60 * 1000; // 24時間
+const SESSION_DURATION = 30 * 24 * 60 * 60 * 1000; // 30日（リフレッシュトークンの有効期限）
+const REFRESH_MARGIN = 60 * 1000; // アクセストークンの期限切れ1分前から更新
 
-interface SessionPayload {
+export interface SessionPayload {
   user: SessionUser;
   token: string;
+  refreshToken: string;
   expiresAt: string;
 }
 
@@ -21,7 +29,7 @@ async function encrypt(payload: SessionPayload): Promise<string> {
   return new SignJWT({ ...payload })
     .setProtectedHeader({ alg: "HS256" })
     .setIssuedAt()
-    .setExpirationTime("1d") // 1 day
+    .setExpirationTime("30d") // 30 days
     .sign(ENCODED_KEY);
 }
 
@@ -37,26 +45,69 @@ async function decrypt(token: string): Promise<SessionPayload | null> {
   }
 }
 
-/** セッションを作成してCookieに保存 */
-export async function createSession(
+/** セッションCookieの値と属性を生成（Middleware からも使う） */
+export async function createSessionCookie(
   user: SessionUser,
-  token: string
-): Promise<void> {
+  token: string,
+  refreshToken: string
+) {
   const expiresAt = new Date(Date.now() + SESSION_DURATION);
-  const session = await encrypt({
+  const value = await encrypt({
     user,
     token,
+    refreshToken,
     expiresAt: expiresAt.toISOString(),
   });
 
-  const cookieStore = await cookies();
-  cookieStore.set(COOKIE_NAME, session, {
+  return {
+    name: COOKIE_NAME,
+    value,
     httpOnly: true,
     secure: process.env.NODE_ENV === "production",
     expires: expiresAt,
-    sameSite: "lax",
+    sameSite: "lax" as const,
     path: "/",
-  });
+  };
+}
+
+export type SessionCookie = Awaited<ReturnType<typeof createSessionCookie>>;
+
+/** セッションを作成してCookieに保存 */
+export async function createSession(
+  user: SessionUser,
+  token: string,
+  refreshToken: string
+): Promise<void> {
+  const cookieStore = await cookies();
+  cookieStore.set(await createSessionCookie(user, token, refreshToken));
+}
+
+/** アクセストークンの期限が切れている、または間もなく切れるか */
+export function needsRefresh(session: SessionPayload): boolean {
+  const { exp } = decodeJwt(session.token);
+  return exp === undefined || exp * 1000 - REFRESH_MARGIN < Date.now();
+}
+
+/**
+ * リフレッシュトークンでアクセストークンを更新する
+ * リフレッシュトークンは使い捨てなので、返された新しいものを必ず保存すること
+ */
+export async function refreshTokens(
+  session: SessionPayload
+): Promise<SessionPayload | null> {
+  const result = await createClient()
+    .mutation<RefreshTokenMutation, RefreshTokenMutationVariables>(
+      RefreshTokenDocument,
+      { refreshToken: session.refreshToken }
+    )
+    .toPromise();
+
+  if (!result.data) {
+    return null;
+  }
+
+  const { token, refreshToken } = result.data.refreshToken;
+  return { ...session, token, refreshToken };
 }
 
 /** セッションを削除 */
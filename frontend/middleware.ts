@@ -1,5 +1,12 @@
 import { NextRequest, NextResponse } from "next/server";
-import { verifySessionFromCookie, SESSION_COOKIE_NAME } from "@/lib/session";
+import {
+  needsRefresh,
+  refreshTokens,
+  createSessionCookie,
+  verifySessionFromCookie,
+  SESSION_COOKIE_NAME,
+  type SessionCookie,
+} from "@/lib/session";
 
 const PUBLIC_ROUTES = ["/login", "/register"];
 const PROTECTED_ROUTES = ["/"];
@@ -32,7 +39,25 @@ export async function middleware(request: NextRequest) {
 
   // セッションCookieを取得して検証
   const sessionCookie = request.cookies.get(SESSION_COOKIE_NAME)?.value;
-  const session = await verifySessionFromCookie(sessionCookie);
+  let session = await verifySessionFromCookie(sessionCookie);
+
+  // アクセストークンの期限が近ければ更新し、このリクエストとレスポンスの両方のCookieを差し替える
+  let refreshedCookie: SessionCookie | null | undefined;
+  if (session && needsRefresh(session)) {
+    session = await refreshTokens(session);
+    if (session) {
+      refreshedCookie = await createSessionCookie(
+        session.user,
+        session.token,
+        session.refreshToken
+      );
+      request.cookies.set(SESSION_COOKIE_NAME, refreshedCookie.value);
+    } else {
+      // 更新できなければ（失効・期限切れ）未認証として扱う
+      refreshedCookie = null;
+      request.cookies.delete(SESSION_COOKIE_NAME);
+    }
+  }
   const isAuthenticated = session !== null;
 
   // 保護されたルートへの未認証アクセス → ログインへリダイレクト
@@ -42,15 +67,35 @@ export async function middleware(request: NextRequest) {
     if (pathname !== "/") {
       loginUrl.searchParams.set("redirect", pathname);
     }
-    return NextResponse.redirect(loginUrl);
+    return withSessionCookie(NextResponse.redirect(loginUrl), refreshedCookie);
   }
 
   // 公開ルートへの認証済みアクセス → ホームへリダイレクト
   if (isPublicRoute(pathname) && isAuthenticated) {
-    return NextResponse.redirect(new URL("/", request.url));
+    return withSessionCookie(
+      NextResponse.redirect(new URL("/", request.url)),
+      refreshedCookie
+    );
   }
 
-  return NextResponse.next();
+  // 更新したCookieをこのリクエストの Server Components にも渡す
+  return withSessionCookie(
+    NextResponse.next({ request: { headers: request.headers } }),
+    refreshedCookie
+  );
+}
+
+/** 更新したセッションCookieをレスポンスに反映（null なら削除、undefined なら何もしない） */
+function withSessionCookie(
+  response: NextResponse,
+  cookie: SessionCookie | null | undefined
+): NextResponse {
+  if (cookie) {
+    response.cookies.set(cookie);
+  } else if (cookie === null) {
+    response.cookies.delete(SESSION_COOKIE_NAME);
+  }
+  return response;
 }
 
 // Middlewareを適用するパスを設定